@@ -5,7 +5,9 @@
 //! Internal implementation details for eventheader macros and eventheader_dynamic.
 //! Contents subject to change without notice.
 
+use core::ffi::CStr;
 use core::mem;
+use core::pin::Pin;
 use core::ptr;
 use core::time::Duration;
 
@@ -21,13 +23,16 @@ pub use tracepoint::TracepointState;
 pub use crate::provider::provider_new;
 pub use crate::provider::CommandString;
 pub use crate::provider::EventHeaderTracepoint;
+pub use crate::provider::ProviderDescriptor;
+pub use crate::suppression::event_suppressed;
+pub use crate::suppression::read_env_suppression_policy;
 
 /// Type string for use in the DIAG_IOCSREG command string.
 pub const EVENTHEADER_COMMAND_TYPES: &str =
     "u8 eventheader_flags; u8 version; u16 id; u16 tag; u8 opcode; u8 level";
 
 /// Maximum length of a Tracepoint name "ProviderName_Attributes\0" (includes nul).
-pub const EVENTHEADER_NAME_MAX: usize = 256;
+pub const EVENTHEADER_NAME_MAX: usize = eventheader_types::TracepointName::NAME_MAX;
 
 /// Maximum length needed for a DIAG_IOCSREG command "ProviderName_Attributes CommandTypes\0".
 pub const EVENTHEADER_COMMAND_MAX: usize =
@@ -51,6 +56,13 @@ pub fn slice_count<T>(value: &[T]) -> u16 {
     return if 65535 < len { 65535 } else { len as u16 };
 }
 
+/// Returns the header for a provider-defined extension block containing `data`.
+/// The block's size is the smaller of `data.len()` and `65535`.
+pub fn extension_header(kind: ExtensionKind, data: &[u8], chain: bool) -> EventHeaderExtension {
+    debug_assert!(kind.is_custom(), "extension kind must be provider-defined");
+    return EventHeaderExtension::from_parts(slice_count(data), kind, chain);
+}
+
 /// Returns the time_t corresponding to a duration returned by a successful call to
 /// `systemtime.duration_since(SystemTime::UNIX_EPOCH)`.
 /// ```
@@ -94,6 +106,26 @@ pub const fn time_from_duration_before_1970(duration: Duration) -> i64 {
     }
 }
 
+/// Registers a provider's tracepoint. Used by `Provider::register` and by
+/// `eventheader_dynamic`.
+///
+/// If the `testing` feature is enabled and `testing::enable_capture_mode`
+/// has been called, registers a local tracepoint so that events are recorded
+/// in-process instead of being sent to the kernel.
+///
+/// # Safety
+///
+/// Same requirements as [`TracepointState::register`].
+pub unsafe fn register_tracepoint(state: Pin<&TracepointState>, name_args: &CStr) -> i32 {
+    #[cfg(feature = "testing")]
+    if crate::testing::capture_mode_enabled() {
+        crate::testing::register_local(state, name_args);
+        return 0;
+    }
+
+    return unsafe { state.register(name_args) };
+}
+
 /// Copies the specified value to the specified location.
 /// Returns the pointer after the end of the copy.
 ///
@@ -108,43 +140,50 @@ unsafe fn append_bytes<T: Sized>(dst: *mut u8, src: &T) -> *mut u8 {
     }
 }
 
-/// Fills in `data[0]` with the event's write_index, event_header,
-/// activity extension block (if an activity id is provided), and
-/// metadata extension block header (if meta_len != 0), then sends
-/// the event to the `user_events_data` file.
+/// Maximum size of an event's metadata + data. The remainder of the 64KB tracepoint
+/// size limit is reserved for the largest possible EventHeader headers.
+pub const EVENTHEADER_PAYLOAD_MAX: usize = 65535 - (52 + 16);
+
+/// Maximum size of the headers written by [`encode_headers`]: event_header,
+/// activity extension block with activity_id and related_id, and metadata
+/// extension block header.
+pub const EVENTHEADER_HEADERS_MAX: usize = mem::size_of::<EventHeader>() // event_header
+    + mem::size_of::<EventHeaderExtension>() + 16 + 16 // activity header + activity_id + related_id
+    + mem::size_of::<EventHeaderExtension>(); // metadata header (last because metadata follows)
+
+/// Writes the event's event_header, activity extension block (if an activity id
+/// is provided), and metadata extension block header (if meta_len != 0) to the
+/// start of `headers`. Returns the number of bytes written.
+///
+/// If `extensions_follow` is true, the last block written gets the chain flag,
+/// i.e. the caller will write more extension blocks after the metadata.
 ///
 /// Requires:
-/// - `data[0].is_empty()` since it will be used for the headers.
+/// - `headers.len() >= EVENTHEADER_HEADERS_MAX`.
 /// - related_id may only be present if activity_id is present.
-/// - if activity_id.is_some() || meta_len != 0 then event_header.flags
-///   must equal DefaultWithExtension.
-/// - If meta_len != 0 then `data[1]` starts with metadata extension
-///   block data.
-pub fn write_eventheader(
-    state: &TracepointState,
+/// - if activity_id.is_some() || meta_len != 0 || extensions_follow then
+///   event_header.flags must equal DefaultWithExtension.
+pub fn encode_headers(
+    headers: &mut [u8],
     event_header: &EventHeader,
     activity_id: Option<&[u8; 16]>,
     related_id: Option<&[u8; 16]>,
     meta_len: u16,
-    data: &mut [EventDataDescriptor],
-) -> i32 {
-    debug_assert!(data[0].is_empty());
+    extensions_follow: bool,
+) -> usize {
+    assert!(headers.len() >= EVENTHEADER_HEADERS_MAX);
     debug_assert!(related_id.is_none() || activity_id.is_some());
     debug_assert!(
-        (activity_id.is_none() && meta_len == 0)
+        (activity_id.is_none() && meta_len == 0 && !extensions_follow)
             || event_header.flags == HeaderFlags::DefaultWithExtension
     );
 
-    let mut extension_count = (activity_id.is_some() as u8) + ((meta_len != 0) as u8);
+    let mut extension_count =
+        (activity_id.is_some() as u8) + ((meta_len != 0) as u8) + (extensions_follow as u8);
 
-    const HEADERS_SIZE_MAX: usize = mem::size_of::<u32>() // write_index
-        + mem::size_of::<EventHeader>() // event_header
-        + mem::size_of::<EventHeaderExtension>() + 16 + 16 // activity header + activity_id + related_id
-        + mem::size_of::<EventHeaderExtension>(); // metadata header (last because data[1] has the metadata)
-    let mut headers: [u8; HEADERS_SIZE_MAX] = [0; HEADERS_SIZE_MAX];
     let headers_len;
     unsafe {
-        let mut headers_ptr = headers.as_mut_ptr().add(mem::size_of::<u32>()); // write_index
+        let mut headers_ptr = headers.as_mut_ptr();
         headers_ptr = append_bytes(headers_ptr, event_header);
 
         match activity_id {
@@ -193,9 +232,90 @@ pub fn write_eventheader(
         headers_len = headers_ptr.offset_from(headers.as_mut_ptr()) as usize;
     }
 
-    debug_assert!(headers_len <= headers.len());
-    debug_assert!(extension_count == 0);
+    debug_assert!(headers_len <= EVENTHEADER_HEADERS_MAX);
+    debug_assert!(extension_count == extensions_follow as u8);
+    return headers_len;
+}
+
+/// Fills in `data[0]` with the event's write_index, event_header,
+/// activity extension block (if an activity id is provided), and
+/// metadata extension block header (if meta_len != 0), then sends
+/// the event to the `user_events_data` file.
+///
+/// Requires:
+/// - `data[0].is_empty()` since it will be used for the headers.
+/// - related_id may only be present if activity_id is present.
+/// - if activity_id.is_some() || meta_len != 0 || extensions_follow then
+///   event_header.flags must equal DefaultWithExtension.
+/// - If meta_len != 0 then `data[1]` starts with metadata extension
+///   block data.
+/// - If extensions_follow then the metadata is followed by one or more
+///   complete extension blocks (see [`extension_header`]), the last of which
+///   does not have the chain flag.
+pub fn write_eventheader(
+    state: &TracepointState,
+    event_header: &EventHeader,
+    activity_id: Option<&[u8; 16]>,
+    related_id: Option<&[u8; 16]>,
+    meta_len: u16,
+    extensions_follow: bool,
+    data: &mut [EventDataDescriptor],
+) -> i32 {
+    debug_assert!(data[0].is_empty());
+
+    const HEADERS_SIZE_MAX: usize = mem::size_of::<u32>() // write_index
+        + EVENTHEADER_HEADERS_MAX;
+    let mut headers: [u8; HEADERS_SIZE_MAX] = [0; HEADERS_SIZE_MAX];
+    let headers_len = mem::size_of::<u32>()
+        + encode_headers(
+            &mut headers[mem::size_of::<u32>()..],
+            event_header,
+            activity_id,
+            related_id,
+            meta_len,
+            extensions_follow,
+        );
+
+    #[cfg(feature = "testing")]
+    if state.is_local() {
+        return crate::testing::write_local(
+            state,
+            &headers[mem::size_of::<u32>()..headers_len],
+            &data[1..],
+        );
+    }
 
     let writev_result = state.write_with_headers(data, &mut headers[0..headers_len]);
     return writev_result;
 }
+
+/// Sends a pre-encoded event (header, metadata, and data) to the `user_events_data`
+/// file. Used by implementations of [`EventProvider::write_encoded`](crate::EventProvider).
+///
+/// Returns `ERANGE` (34) if the event is too large.
+pub fn write_encoded(
+    state: &TracepointState,
+    event_header: &EventHeader,
+    activity_id: Option<&[u8; 16]>,
+    related_id: Option<&[u8; 16]>,
+    metadata: &[u8],
+    data: &[u8],
+) -> i32 {
+    return if metadata.len() + data.len() > EVENTHEADER_PAYLOAD_MAX {
+        34 // libc::ERANGE
+    } else {
+        write_eventheader(
+            state,
+            event_header,
+            activity_id,
+            related_id,
+            metadata.len() as u16,
+            false,
+            &mut [
+                EventDataDescriptor::zero(),
+                EventDataDescriptor::from_bytes(metadata),
+                EventDataDescriptor::from_bytes(data),
+            ],
+        )
+    };
+}
@@ -6,6 +6,59 @@
 #[allow(unused_imports)]
 use crate::*; // For docs
 
+/// # v0.6.0 (TBD)
+/// - Optional build-time export of a JSON event manifest from `define_provider!`
+///   and `write_event!`, enabled by the `EVENTHEADER_MANIFEST_DIR` environment
+///   variable.
+/// - On Linux, `define_provider!` places a descriptor for each provider in the
+///   `_eh_providers` linker section, and `EventHeaderTracepoint` now uses
+///   `#[repr(C)]` layout, so that event schemas can be extracted from compiled
+///   binaries.
+/// - New `rate_limit(N per second)` and `sample(1 in N)` options for `write_event!`,
+///   backed by the new [`EventThrottle`] type. Throttled events include a
+///   `_suppressed` field with the number of dropped occurrences, and `write_event!`
+///   returns `EAGAIN` for dropped occurrences.
+/// - New [`EventProvider`] trait, implemented by [`Provider`] and by
+///   `eventheader_dynamic::Provider`, for code that writes pre-encoded events
+///   without depending on a specific provider type.
+/// - Export `EventHeader` and `HeaderFlags` from the crate root.
+/// - New `testing` feature and `testing` module for capturing events in-process
+///   and asserting on their provider, name, level, keyword, and field values in
+///   unit tests.
+/// - New `EventProvider::for_each_tracepoint` method and [`TracepointStatus`] type
+///   for reporting the name, level, keyword, registration errno, and enabled state
+///   of a provider's tracepoints, and new [`for_each_provider`] function for
+///   visiting all registered static providers.
+/// - New `serde` feature that enables `serde` support in `eventheader_types`, e.g.
+///   deserializing a [`Level`] from `"Warning"`.
+/// - Re-export [`TracepointName`] and [`TracepointNameError`] from
+///   `eventheader_types`. New `TracepointStatus::tracepoint_name` method. Tracepoint
+///   names are now formatted using `TracepointName`.
+/// - New `extension(KIND, &data)` option for `write_event!` that adds a
+///   provider-defined header extension block to the event. Re-export
+///   [`ExtensionKind`] from `eventheader_types`. New
+///   `testing::CapturedEvent::extensions` and `extension` methods.
+/// - New `Provider::for_each_schema_conflict` method and [`SchemaConflict`] type for
+///   finding events of a provider that use the same name, or the same id and
+///   version, with different schemas. `Provider::register` performs this check when
+///   enabled via [`set_schema_check`] (enabled by default in debug builds) and calls
+///   the handler set by [`set_schema_conflict_handler`] (none by default) for each
+///   conflict.
+/// - New `keywords(Name = value, ...)` option for `define_provider!` that declares
+///   named keyword bits. The names can be used with `keyword(...)` in `write_event!`
+///   and with `provider_enabled!`, are exported as constants in a module named after
+///   the provider symbol, and are recorded in the event manifest. New
+///   `Provider::keywords` method.
+/// - New process-local [`SuppressionPolicy`], read from the `EVENTHEADER_SUPPRESS`
+///   environment variable or set with [`set_suppression_policy`], that suppresses
+///   enabled events by provider, event name, maximum level, or keyword mask.
+///   `write_event!` returns `EBADF` for suppressed events and `provider_enabled!`
+///   returns false.
+/// - `write_event!` validates event metadata at compile time and reports errors for
+///   structs nested more than 8 levels deep, raw struct field counts outside 1..=127,
+///   invalid encodings, and formats that are not usable with the field's encoding.
+pub mod v0_6_0 {}
+
 /// # v0.5.0 (2025-11-26)
 /// - Fix linker issues with newer Rust versions.
 /// - Fix clippy issues for Rust 1.91.
@@ -0,0 +1,160 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use core::fmt;
+
+use eventheader_types::EventHeader;
+
+use crate::Level;
+use crate::TracepointName;
+
+#[allow(unused_imports)] // For docs
+use crate::Provider;
+
+/// Common interface for writing eventheader events, implemented by the static
+/// [`Provider`] and by `eventheader_dynamic::Provider`.
+///
+/// A library can accept `&impl EventProvider` (or `&dyn EventProvider`) to write
+/// events through a provider chosen by the application, without needing to know
+/// whether the provider is defined statically with `define_provider!` or created
+/// at runtime with `eventheader_dynamic`.
+///
+/// Events written through this trait are pre-encoded: the caller supplies the event
+/// header, the event metadata (nul-terminated event name followed by field
+/// declarations), and the field data. An event can only be written if the provider
+/// has a registered tracepoint for the event's level and keyword:
+///
+/// - For a static [`Provider`], this means the provider was registered and contains a
+///   `write_event!` or `provider_enabled!` with the same level and keyword.
+/// - For a dynamic provider, this means an event set with the same level and keyword
+///   was registered via `register_set`.
+pub trait EventProvider {
+    /// Returns this provider's name.
+    fn name(&self) -> &str;
+
+    /// Returns this provider's options, e.g. "" or "Gmygroup".
+    fn options(&self) -> &str;
+
+    /// Returns true if any logging session is listening for events with this
+    /// provider, the specified level, and the specified keyword. Returns false if
+    /// the provider does not have a registered tracepoint for the level and keyword.
+    fn enabled(&self, level: Level, keyword: u64) -> bool;
+
+    /// Sends a pre-encoded event to the tracepoint for `header.level` and `keyword`.
+    ///
+    /// - `header` is the event header. If `metadata` is non-empty or `activity_id` is
+    ///   `Some`, `header.flags` must be `HeaderFlags::DefaultWithExtension`.
+    /// - `activity_id` and `related_id` are as for `write_event!`. If `activity_id`
+    ///   is `None`, `related_id` must also be `None`.
+    /// - `metadata` is the event's metadata: nul-terminated event name followed by
+    ///   the field declarations.
+    /// - `data` is the event's field values.
+    ///
+    /// Returns 0 for success. Returns `EBADF` (9) if no consumer is listening to the
+    /// tracepoint or if the provider has no registered tracepoint for the level and
+    /// keyword. Returns `ERANGE` (34) if the event is greater than 64KB. Returns other
+    /// errors as reported by `writev`. The return value is for diagnostic/debugging
+    /// purposes only and should generally be ignored in retail builds.
+    fn write_encoded(
+        &self,
+        header: &EventHeader,
+        keyword: u64,
+        activity_id: Option<&[u8; 16]>,
+        related_id: Option<&[u8; 16]>,
+        metadata: &[u8],
+        data: &[u8],
+    ) -> i32;
+
+    /// Calls `f` with the status of each of this provider's tracepoints, i.e. one
+    /// [`TracepointStatus`] for each level + keyword combination that the provider has
+    /// registered (or attempted to register), in no particular order.
+    ///
+    /// This is intended for diagnostics, e.g. reporting which tracepoints are enabled
+    /// and which failed to register. The default implementation does nothing.
+    fn for_each_tracepoint(&self, f: &mut dyn FnMut(&TracepointStatus)) {
+        _ = f;
+    }
+}
+
+/// The status of one of a provider's tracepoints, i.e. of one level + keyword
+/// combination. Reported by [`EventProvider::for_each_tracepoint`].
+///
+/// The `Display` implementation writes the tracepoint name, e.g.
+/// `MyCompany_MyComponent_L5K1fGmygroup`.
+#[derive(Clone, Copy, Debug)]
+pub struct TracepointStatus<'a> {
+    provider_name: &'a str,
+    options: &'a str,
+    level: Level,
+    keyword: u64,
+    errno: i32,
+    enabled: bool,
+}
+
+impl<'a> TracepointStatus<'a> {
+    /// Creates a TracepointStatus. For use by [`EventProvider`] implementations.
+    pub const fn new(
+        provider_name: &'a str,
+        options: &'a str,
+        level: Level,
+        keyword: u64,
+        errno: i32,
+        enabled: bool,
+    ) -> Self {
+        return Self {
+            provider_name,
+            options,
+            level,
+            keyword,
+            errno,
+            enabled,
+        };
+    }
+
+    /// Returns the name of the tracepoint's provider.
+    pub const fn provider_name(&self) -> &'a str {
+        return self.provider_name;
+    }
+
+    /// Returns the options of the tracepoint's provider, e.g. "" or "Gmygroup".
+    pub const fn options(&self) -> &'a str {
+        return self.options;
+    }
+
+    /// Returns the parts of the tracepoint's name.
+    pub const fn tracepoint_name(&self) -> TracepointName<'a> {
+        return TracepointName::new_unchecked(
+            self.provider_name,
+            self.level,
+            self.keyword,
+            self.options,
+        );
+    }
+
+    /// Returns the tracepoint's level.
+    pub const fn level(&self) -> Level {
+        return self.level;
+    }
+
+    /// Returns the tracepoint's keyword.
+    pub const fn keyword(&self) -> u64 {
+        return self.keyword;
+    }
+
+    /// Returns 0 if the tracepoint was successfully registered (or has not been
+    /// registered yet), or the error code from registration.
+    pub const fn errno(&self) -> i32 {
+        return self.errno;
+    }
+
+    /// Returns true if any logging session is currently listening to the tracepoint.
+    pub const fn enabled(&self) -> bool {
+        return self.enabled;
+    }
+}
+
+impl fmt::Display for TracepointStatus<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return self.tracepoint_name().fmt(f);
+    }
+}
@@ -128,6 +128,51 @@
 //! <Ctrl-C to stop the perf tool>
 //! decode-perf perf.data > perf.json
 //! ```
+//!
+//! # Event manifest
+//!
+//! The macros can optionally export a catalog of the providers and events defined by
+//! your crates. If the `EVENTHEADER_MANIFEST_DIR` environment variable is set during
+//! compilation, each `define_provider!` and `write_event!` writes a JSON entry to
+//! `$EVENTHEADER_MANIFEST_DIR/<crate name>/`. Each event entry includes the provider
+//! symbol, event name, id, version, level, keyword, opcode, tag, and the field names,
+//! encodings, formats, and tags. Use the `merge_manifests` example from the
+//! `eventheader_macros` crate to combine the entries into a single JSON file.
+//!
+//! Entries are keyed by provider symbol and event name, so a changed event overwrites
+//! its previous entry. Entries for renamed or removed events are not deleted, and
+//! Cargo does not track this environment variable, so clear the manifest directory
+//! and run `cargo clean` first to make sure that every crate is re-expanded.
+//!
+//! # Testing
+//!
+//! With the `testing` feature enabled, the `testing` module can capture events
+//! in-process so that unit tests can check which events a component writes, e.g.
+//! "event `MyEvent` from provider `MyCompany_MyComponent` was written at level
+//! `Warning` with field `Field1` == 42". See the `testing` module for details.
+//!
+//! # Schema conflicts
+//!
+//! Consumers treat events from the same provider with the same name (or the same
+//! nonzero `id_version`) as the same event. If two `write_event!` call sites use the
+//! same name with different fields, consumers may decode one event using the other
+//! event's fields. Use [`set_schema_conflict_handler`] to have `register()` report
+//! such conflicts (e.g. log them, or panic so that tests fail), [`set_schema_check`]
+//! to enable or disable the check (enabled by default in debug builds), and
+//! [`Provider::for_each_schema_conflict`] to list the conflicts.
+//!
+//! # Suppressing events
+//!
+//! A [`SuppressionPolicy`] silences events in the current process even when a
+//! logging session has enabled them, e.g. to quiet a noisy provider, event, keyword,
+//! or level on one machine without changing the code. The policy is read from the
+//! `EVENTHEADER_SUPPRESS` environment variable, e.g.
+//! `EVENTHEADER_SUPPRESS="provider=MyCompany_Noisy;keyword=0x10"`, and can be
+//! replaced at runtime with [`set_suppression_policy`]. Suppressed events are
+//! treated like disabled events. The policy is only checked for enabled events.
+
+#[cfg(feature = "testing")]
+extern crate std;
 
 /// Creates a static symbol representing a tracepoint provider.
 ///
@@ -136,6 +181,7 @@
 /// [Options:](#options)
 ///
 /// - `group_name("providergroupname")`
+/// - `keywords(KeywordName = 0x1, ...)`
 ///
 /// # Overview
 ///
@@ -222,6 +268,20 @@
 ///
 ///   Example: `group_name("mycompany")`
 ///
+/// - `keywords(KeywordName = value, ...)`
+///
+///   Declares names for the provider's keyword bits. Each value must be a non-zero
+///   integer literal. The names can be used in the `keyword` option of
+///   [`write_event!`] and in the keyword parameter of [`provider_enabled!`] for this
+///   provider, e.g. `keyword(Networking)` or `keyword(Networking | Storage)`.
+///
+///   The names are also exported as `u64` constants in a module with the same name
+///   as the provider symbol, e.g. `MY_PROVIDER::Networking`, and are recorded in the
+///   provider (see [`Provider::keywords`]) and in the event manifest so that tools
+///   can display keyword names.
+///
+///   Example: `keywords(Networking = 0x2, Storage = 0x4)`
+///
 /// - `debug()`
 ///
 ///   For non-production diagnostics: prints the expanded macro during compilation.
@@ -244,6 +304,7 @@ pub use eventheader_macros::define_provider;
 /// - `related_id(&guid)`
 /// - `tag(0x123)`
 /// - `id_version(23, 0)`
+/// - `extension(KIND, &bytes)`
 /// - `debug()`
 ///
 /// [Fields:](#fields-1)
@@ -298,6 +359,53 @@ pub use eventheader_macros::define_provider;
 ///   macro will generate a compile error if a struct has more than 127 fields. You might
 ///   be able to work around this limitation by using arrays or by logging a series of
 ///   simpler events instead of a single complex event.
+/// - Structs may be nested at most 8 levels deep. Decoders will not decode events with
+///   more deeply-nested structs. The `write_event!` macro will generate a compile error
+///   if structs are nested too deeply.
+/// - The `write_event!` macro will generate a compile error if a field's format is not
+///   usable with the field's encoding (e.g. `u8("Name", &value, format(Float))`). The
+///   usable formats for each encoding are listed in the [`FieldEncoding`] documentation.
+/// - The `write_event!` macro will generate a compile error if a `raw_struct` has a
+///   constant field count of 0 or if a `raw_meta` has a constant encoding that includes
+///   `CArrayFlag` or `ChainFlag`.
+///
+/// For example, each of the following will fail to compile:
+///
+/// ```compile_fail
+/// # use eventheader as eh;
+/// # eh::define_provider!(MY_PROVIDER, "MyCompany_MyComponent");
+/// # let value = 1u8;
+/// // Structs nested 9 levels deep.
+/// eh::write_event!(MY_PROVIDER, "Deep",
+///     struct("s1", { struct("s2", { struct("s3", { struct("s4", { struct("s5", {
+///         struct("s6", { struct("s7", { struct("s8", { struct("s9", {
+///             u8("Value", &value),
+///         }) }) }) })
+///     }) }) }) }) }));
+/// ```
+///
+/// ```compile_fail
+/// # use eventheader as eh;
+/// # eh::define_provider!(MY_PROVIDER, "MyCompany_MyComponent");
+/// # let value = 1u8;
+/// // Float format is not usable with the Value8 encoding.
+/// eh::write_event!(MY_PROVIDER, "BadFormat", u8("Value", &value, format(Float)));
+/// ```
+///
+/// ```compile_fail
+/// # use eventheader as eh;
+/// # eh::define_provider!(MY_PROVIDER, "MyCompany_MyComponent");
+/// // A struct must have at least 1 field.
+/// eh::write_event!(MY_PROVIDER, "EmptyStruct", raw_struct("Empty", 0));
+/// ```
+///
+/// ```compile_fail
+/// # use eventheader as eh;
+/// # eh::define_provider!(MY_PROVIDER, "MyCompany_MyComponent");
+/// // raw_meta encodings must not include CArrayFlag (0x20) or ChainFlag (0x80).
+/// eh::write_event!(MY_PROVIDER, "BadEncoding",
+///     raw_meta("Array", 0x22), raw_data(&[1u8, 2, 3]));
+/// ```
 ///
 /// # Example
 ///
@@ -372,6 +480,9 @@ pub use eventheader_macros::define_provider;
 ///   more than once, in which case all provided keyword values will be OR'ed together in
 ///   the event's keyword.
 ///
+///   The value may use the keyword names declared by the provider's
+///   `keywords(...)` option, e.g. `keyword(Networking)`.
+///
 /// - `opcode(event_opcode)`
 ///
 ///   Specifies the opcode attribute for the event.
@@ -431,6 +542,43 @@ pub use eventheader_macros::define_provider;
 ///   specified, the id must be a constant `u16` value and the version must be a constant
 ///   `u8` value.
 ///
+/// - `extension(extension_kind, &extension_data)`
+///
+///   Adds a provider-defined header extension block to the event, e.g. trace flags, a
+///   W3C `traceparent`, or a tenant id. Decoders can read the block via
+///   `tracepoint_decode::EventHeaderEventInfo::extensions`.
+///
+///   The kind must be a constant [ExtensionKind] value for which
+///   [`ExtensionKind::is_custom`] returns true, e.g. `ExtensionKind::from_int(0x100)`.
+///   The data must be a reference to a value that implements `AsRef<[u8]>`, e.g.
+///   `&[u8]` or `&String`. If the data is larger than 65535 bytes, the event is not
+///   written and the macro returns `ERANGE` (34). This option may be specified more
+///   than once, in which case the blocks are written in the order they are specified.
+///
+/// - `rate_limit(N per second)`
+///
+///   Limits the number of events written by this `write_event!` call site to about
+///   `N` per second, with bursts of at most `N` events. Occurrences beyond the limit
+///   are dropped.
+///
+///   The limit is checked only after the tracepoint is known to be enabled, using
+///   per-call-site atomic state (an [EventThrottle]). If specified, `N` must be a
+///   constant `u32` value.
+///
+/// - `sample(1 in N)`
+///
+///   Writes only 1 of every `N` occurrences of the event from this call site.
+///   If `rate_limit` is also specified, sampling is applied first.
+///
+///   The sample is taken only after the tracepoint is known to be enabled. If
+///   specified, `N` must be a constant `u32` value.
+///
+///   If `rate_limit` or `sample` is specified, a `u32` field named `"_suppressed"` is
+///   added to the end of the event. It contains the number of occurrences that were
+///   dropped since the previous event was written, so consumers can tell that data
+///   was dropped. The `write_event!` macro returns `EAGAIN` (11) if the occurrence
+///   was dropped.
+///
 /// - `debug()`
 ///
 ///   For non-production diagnostics: prints the expanded macro during compilation.
@@ -693,8 +841,9 @@ pub use eventheader_macros::define_provider;
 /// In these cases, you can use the raw field types. These types are harder to use than
 /// the normal field types. Using these types incorrectly can result in events that
 /// cannot be decoded. To use these types correctly, you must understand how
-/// eventheader events are encoded. `write_event!` does not verify that the provided
-/// field types or field data are valid.
+/// eventheader events are encoded. `write_event!` checks the field types that it can
+/// evaluate at compile time (see [Limitations](#limitations)), but it does not verify
+/// that the provided field data matches the field types.
 ///
 /// **Note:** eventheader stores event data tightly-packed with no padding, alignment, or
 /// size. If your field data size does not match up with your field type, the remaining
@@ -745,7 +894,7 @@ pub use eventheader_macros::define_provider;
 ///   of fields in the struct. The struct's member fields are specified separately (e.g.
 ///   via `raw_meta` or `raw_meta_slice`).
 ///
-///   Note that the FIELD_COUNT must be a constant `u8` value in the range 0 to 127. It
+///   Note that the FIELD_COUNT must be a constant `u8` value in the range 1 to 127. It
 ///   indicates the number of subsequent logical fields that will be considered to be
 ///   part of the struct. In cases of nested structs, a struct and its fields count as a
 ///   single logical field.
@@ -759,7 +908,7 @@ pub use eventheader_macros::define_provider;
 ///   The number of elements in the array is specified as a `u16` value immediately
 ///   before the array content.
 ///
-///   Note that the FIELD_COUNT must be a constant `u8` value in the range 0 to 127. It
+///   Note that the FIELD_COUNT must be a constant `u8` value in the range 1 to 127. It
 ///   indicates the number of subsequent logical fields that will be considered to be
 ///   part of the struct. In cases of nested structs, a struct and its fields count as a
 ///   single logical field.
@@ -849,7 +998,9 @@ pub use eventheader_macros::write_event;
 /// The `PROVIDER_SYMBOL` parameter should be the original symbol name created by
 /// [`define_provider!`], not a reference or alias.
 ///
-/// The level and keyword parameters must be compile-time constant expressions.
+/// The level and keyword parameters must be compile-time constant expressions. The
+/// keyword may use the keyword names declared by the provider's `keywords(...)`
+/// option.
 ///
 /// Note that [`write_event!`] already checks whether the tracepoint is enabled -- it
 /// skips evaluating the field value expressions and skips sending the event if the
@@ -863,19 +1014,41 @@ pub use eventheader_macros::write_event;
 pub use eventheader_macros::provider_enabled;
 
 // Re-exports:
+pub use eventheader_types::EventHeader;
+pub use eventheader_types::ExtensionKind;
 pub use eventheader_types::FieldEncoding;
 pub use eventheader_types::FieldFormat;
 pub use eventheader_types::Guid;
+pub use eventheader_types::HeaderFlags;
 pub use eventheader_types::Level;
 pub use eventheader_types::Opcode;
+pub use eventheader_types::TracepointName;
+pub use eventheader_types::TracepointNameError;
 pub use tracepoint::NativeImplementation;
 pub use tracepoint::NATIVE_IMPLEMENTATION;
 
 // Exports from eventheader:
+pub use event_provider::EventProvider;
+pub use event_provider::TracepointStatus;
+pub use provider::for_each_provider;
 pub use provider::Provider;
+pub use schema_conflict::schema_check;
+pub use schema_conflict::set_schema_check;
+pub use schema_conflict::set_schema_conflict_handler;
+pub use schema_conflict::SchemaConflict;
+pub use schema_conflict::SchemaConflictKind;
+pub use suppression::set_suppression_policy;
+pub use suppression::suppression_policy;
+pub use suppression::SuppressionPolicy;
+pub use suppression::SuppressionPolicyError;
+pub use suppression::SUPPRESSION_POLICY_ENV;
+pub use throttle::EventThrottle;
 pub mod _internal;
 pub mod changelog;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
 /// Converts a
 /// [`std::time::SystemTime`](https://doc.rust-lang.org/std/time/struct.SystemTime.html)
 /// into a [`time_t`](https://en.wikipedia.org/wiki/Unix_time) `i64` value.
@@ -907,4 +1080,8 @@ macro_rules! time_from_systemtime {
     };
 }
 
+mod event_provider;
 mod provider;
+mod schema_conflict;
+mod suppression;
+mod throttle;
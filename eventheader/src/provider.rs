@@ -4,16 +4,26 @@
 use core::ffi;
 use core::fmt;
 use core::fmt::Write;
+use core::marker;
+use core::mem;
 use core::ops;
 use core::pin;
 use core::ptr;
+use core::slice;
 use core::str;
 use core::sync::atomic;
 
 use eventheader_types::EventHeader;
 use tracepoint::EventDataDescriptor;
 
+use crate::schema_conflict;
+use crate::suppression;
+use crate::EventProvider;
 use crate::Level;
+use crate::SchemaConflict;
+use crate::SchemaConflictKind;
+use crate::TracepointName;
+use crate::TracepointStatus;
 use crate::_internal;
 
 #[allow(unused_imports)] // For docs
@@ -24,6 +34,12 @@ use crate::define_provider;
 #[cfg(feature = "macros")]
 use crate::write_event;
 
+#[allow(unused_imports)] // For docs
+use crate::set_schema_check;
+
+#[allow(unused_imports)] // For docs
+use crate::set_schema_conflict_handler;
+
 /// A connection for writing compile-time-defined Linux tracepoints.
 ///
 /// # Overview
@@ -37,8 +53,65 @@ use crate::write_event;
 pub struct Provider<'a> {
     name: &'a [u8],
     options: &'a [u8],
-    events: ops::Range<*mut *const EventHeaderTracepoint<'a>>,
-    busy: atomic::AtomicBool,
+    events_start: *mut *const EventHeaderTracepoint<'a>,
+    events_stop: *mut *const EventHeaderTracepoint<'a>,
+    access: atomic::AtomicU32, // ACCESS_BUSY | number of readers of the events list.
+    next: atomic::AtomicPtr<Provider<'static>>, // Process-wide list of registered providers.
+    keywords: &'a [(&'a str, u64)],
+}
+
+/// Set in `Provider::access` while `register` or `unregister` is in progress. The
+/// other bits count the `EventProvider` methods that are reading the events list.
+const ACCESS_BUSY: u32 = 0x8000_0000;
+
+/// Head of the process-wide list of registered static providers. The list is
+/// linked through `Provider::next` and is only accessed while holding PROVIDERS_LOCK.
+static PROVIDERS_HEAD: atomic::AtomicPtr<Provider<'static>> =
+    atomic::AtomicPtr::new(ptr::null_mut());
+static PROVIDERS_LOCK: atomic::AtomicBool = atomic::AtomicBool::new(false);
+
+struct ProvidersLock;
+
+impl ProvidersLock {
+    fn acquire() -> Self {
+        while PROVIDERS_LOCK
+            .compare_exchange_weak(
+                false,
+                true,
+                atomic::Ordering::Acquire,
+                atomic::Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+
+        return Self;
+    }
+}
+
+impl Drop for ProvidersLock {
+    fn drop(&mut self) {
+        PROVIDERS_LOCK.store(false, atomic::Ordering::Release);
+    }
+}
+
+/// Calls `f` for each static [`Provider`] in the process that is currently
+/// registered, i.e. each provider for which `register()` has been called and
+/// `unregister()` has not. Use [`EventProvider::for_each_tracepoint`] to get the
+/// status of each provider's tracepoints.
+///
+/// `f` is called while holding a process-wide lock, so it must not register or
+/// unregister a provider.
+pub fn for_each_provider(mut f: impl FnMut(&Provider)) {
+    let _lock = ProvidersLock::acquire();
+    let mut provider_ptr = PROVIDERS_HEAD.load(atomic::Ordering::Relaxed);
+    while !provider_ptr.is_null() {
+        // Safety: providers unlink themselves (under the lock) before they go away.
+        let provider = unsafe { &*provider_ptr };
+        f(provider);
+        provider_ptr = provider.next.load(atomic::Ordering::Relaxed);
+    }
 }
 
 impl<'a> Provider<'a> {
@@ -52,6 +125,13 @@ impl<'a> Provider<'a> {
         return str::from_utf8(self.options).unwrap();
     }
 
+    /// Returns the named keywords declared by `define_provider!` via the
+    /// `keywords(Name = value, ...)` option, as (name, value) pairs in declaration
+    /// order. Decoders and tools can use these to display keyword names.
+    pub fn keywords(&self) -> &[(&str, u64)] {
+        return self.keywords;
+    }
+
     /// Unregisters all registered tracepoints in the provider.
     ///
     /// Returns 0 for success or an errno if any tracepoints failed to unregister. The
@@ -63,14 +143,11 @@ impl<'a> Provider<'a> {
     pub fn unregister(&self) -> u32 {
         let mut result = 0;
 
-        let was_busy = self.busy.swap(true, atomic::Ordering::Relaxed);
-        if !was_busy {
-            let events_slice = unsafe {
-                &*ptr::slice_from_raw_parts(
-                    self.events.start,
-                    self.events.end.offset_from(self.events.start) as usize,
-                )
-            };
+        self.unlink();
+
+        if self.begin_modify() {
+            let events_slice =
+                unsafe { &*ptr::slice_from_raw_parts(self.events_start, self.events_len()) };
 
             for &event_ptr in events_slice {
                 if event_ptr.is_null() {
@@ -84,7 +161,7 @@ impl<'a> Provider<'a> {
                 }
             }
 
-            self.busy.swap(false, atomic::Ordering::Relaxed);
+            self.end_modify();
         }
 
         return result as u32;
@@ -107,6 +184,12 @@ impl<'a> Provider<'a> {
     ///   method must not occur at the same time as a call to the same provider's
     ///   `register` or `unregister` method on any other thread. Verified at runtime,
     ///   failure = panic.
+    /// - For a given provider object, a call on one thread to the provider's `register`
+    ///   method should not occur at the same time as a call to the provider's
+    ///   [`EventProvider`] methods on any other thread. If it does, the
+    ///   [`EventProvider`] methods may not find the provider's tracepoints. (This is
+    ///   safe: `register` waits for [`EventProvider`] methods that are already
+    ///   reading the provider's list of tracepoints.)
     ///
     /// # Safety
     ///
@@ -139,53 +222,19 @@ impl<'a> Provider<'a> {
     fn register_impl(&self) -> u32 {
         let mut result = 0;
 
-        let was_busy = self.busy.swap(true, atomic::Ordering::Acquire);
-        if was_busy {
+        if !self.begin_modify() {
             panic!("provider.register called simultaneously with another call to register or unregister.");
         }
 
-        if self.events.start < self.events.end {
+        // Read the suppression policy before any of the tracepoints can be enabled.
+        suppression::read_env_suppression_policy();
+
+        if self.events_start < self.events_stop {
             let events_slice = unsafe {
-                &mut *ptr::slice_from_raw_parts_mut(
-                    self.events.start,
-                    self.events.end.offset_from(self.events.start) as usize,
-                )
+                &mut *ptr::slice_from_raw_parts_mut(self.events_start, self.events_len())
             };
 
-            // The list of tracepoints is frequently created using linker tricks.
-            // The linker tricks mean we end up with NULLs and sometimes there are
-            // duplicates, so we need to fix up the list in-place:
-            // - Sort the list so that duplicates are next to each other and NULLs
-            //   are at the end.
-            // - Remove adjacent duplicates, filling the rest of the list with NULL.
-
-            // Sort list. Use reverse sort so that NULL goes at end.
-            events_slice.sort_unstable_by(|a, b| b.cmp(a));
-
-            // Remove adjacent repeated elements.
-            let end_pos = events_slice.len();
-            let mut good_pos = 0;
-            while good_pos != end_pos - 1 {
-                if events_slice[good_pos] == events_slice[good_pos + 1] {
-                    let mut next_pos = good_pos + 2;
-                    while next_pos != end_pos {
-                        if events_slice[good_pos] != events_slice[next_pos] {
-                            good_pos += 1;
-                            events_slice[good_pos] = events_slice[next_pos];
-                        }
-                        next_pos += 1;
-                    }
-                    break;
-                }
-                good_pos += 1;
-            }
-
-            // Fill remaining entries with NULL.
-            let mut next_pos = good_pos + 1;
-            while next_pos != end_pos {
-                events_slice[next_pos] = ptr::null();
-                next_pos += 1;
-            }
+            normalize_events(events_slice);
 
             // Register all of the tracepoints.
             let mut command_string = CommandString::new();
@@ -195,26 +244,412 @@ impl<'a> Provider<'a> {
                 }
 
                 let event = unsafe { &*event_ptr };
-                let name_args = command_string.format(
-                    self.name,
-                    self.options,
+                let name_args = command_string.format(&TracepointName::new_unchecked(
+                    self.name(),
                     event.header.level,
                     event.keyword,
-                );
-                let err = unsafe { pin::Pin::new_unchecked(&event.state).register(name_args) };
+                    self.options(),
+                ));
+                let err = unsafe {
+                    _internal::register_tracepoint(pin::Pin::new_unchecked(&event.state), name_args)
+                };
+                event.errno.store(err, atomic::Ordering::Relaxed);
                 if result == 0 {
                     result = err;
                 }
             }
         }
 
-        self.busy.swap(false, atomic::Ordering::Release);
+        self.end_modify();
+        self.link();
+
+        // Check after end_modify so that the handler can write events using this
+        // provider.
+        if schema_conflict::schema_check() {
+            if let Some(handler) = schema_conflict::schema_conflict_handler() {
+                self.for_each_schema_conflict(handler);
+            }
+        }
+
         return result as u32;
     }
+
+    /// Calls `f` for each [`SchemaConflict`] between the provider's events, i.e. for
+    /// events that have the same name but different fields, or the same nonzero `id`
+    /// and `version` but different names. Consumers treat such events as the same
+    /// event and may decode them incorrectly. Fields are compared by their decoded
+    /// definitions (name, encoding, format, tag, and array length).
+    ///
+    /// Each event whose schema differs from the first event with the same name (or
+    /// the same id and version) is reported as a conflict with that event. Events are
+    /// grouped by comparing each event with the events before it, so the time taken
+    /// grows with the square of the number of events. The provider's list of events
+    /// is not modified.
+    ///
+    /// [`Provider::register`] performs this check (and calls the handler set by
+    /// [`set_schema_conflict_handler`]) when the check is enabled via
+    /// [`set_schema_check`]. The check is enabled by default in debug builds.
+    ///
+    /// Does nothing while the provider is being registered or unregistered on another
+    /// thread. `f` may write events using the provider but must not register or
+    /// unregister the provider.
+    pub fn for_each_schema_conflict(&self, mut f: impl FnMut(&SchemaConflict)) {
+        let events = match self.read_events() {
+            None => return,
+            Some(events) => events,
+        };
+
+        self.report_schema_conflicts(&events, SchemaConflictKind::SameIdVersion, &mut f);
+        self.report_schema_conflicts(&events, SchemaConflictKind::SameName, &mut f);
+    }
+
+    /// Calls `f` for each event whose schema differs from the first event with the
+    /// same key (same name, or same nonzero id and version, depending on `kind`) and
+    /// from each earlier event with the same key. Before `register`, the list may
+    /// contain NULLs and duplicates anywhere.
+    fn report_schema_conflicts(
+        &self,
+        events: &[*const EventHeaderTracepoint<'a>],
+        kind: SchemaConflictKind,
+        f: &mut dyn FnMut(&SchemaConflict),
+    ) {
+        let same_id_version = |a: &EventHeaderTracepoint, b: &EventHeaderTracepoint| {
+            a.header.id != 0 && a.header.id == b.header.id && a.header.version == b.header.version
+        };
+        let same_name = |a: &EventHeaderTracepoint, b: &EventHeaderTracepoint| {
+            schema_conflict::split_metadata(a.metadata()).0
+                == schema_conflict::split_metadata(b.metadata()).0
+        };
+
+        for (pos, event) in events.iter().enumerate() {
+            let event = match unsafe { event.as_ref() } {
+                None => continue,
+                Some(event) => event,
+            };
+            let same_key = |other: &&*const EventHeaderTracepoint| match unsafe { other.as_ref() } {
+                None => false,
+                Some(other) => match kind {
+                    SchemaConflictKind::SameName => same_name(other, event),
+                    SchemaConflictKind::SameIdVersion => same_id_version(other, event),
+                },
+            };
+
+            // The first event with this key defines the expected schema.
+            let first = match events[..pos].iter().find(same_key) {
+                None => continue,
+                Some(first) => unsafe { &**first },
+            };
+
+            // Report each distinct schema once. Events with the same id, version, and
+            // name but different fields are reported as SameName conflicts.
+            if (kind == SchemaConflictKind::SameIdVersion && same_name(first, event))
+                || events[..pos].iter().filter(same_key).any(|other| {
+                    let other = unsafe { &**other };
+                    schema_conflict::compare_metadata(other.metadata(), event.metadata()).is_eq()
+                })
+            {
+                continue;
+            }
+
+            let (id, version) = if same_id_version(first, event) {
+                (event.header.id, event.header.version)
+            } else {
+                (0, 0)
+            };
+            f(&SchemaConflict::new(
+                self.name(),
+                kind,
+                id,
+                version,
+                first.metadata(),
+                event.metadata(),
+            ));
+        }
+    }
+
+    /// Adds this provider to the process-wide list of registered providers.
+    fn link(&self) {
+        let self_ptr = self as *const Self as *mut Provider<'static>;
+        let _lock = ProvidersLock::acquire();
+        let mut provider_ptr = PROVIDERS_HEAD.load(atomic::Ordering::Relaxed);
+        while !provider_ptr.is_null() {
+            if provider_ptr == self_ptr {
+                return; // Already in the list.
+            }
+            provider_ptr = unsafe { &*provider_ptr }
+                .next
+                .load(atomic::Ordering::Relaxed);
+        }
+
+        self.next.store(
+            PROVIDERS_HEAD.load(atomic::Ordering::Relaxed),
+            atomic::Ordering::Relaxed,
+        );
+        PROVIDERS_HEAD.store(self_ptr, atomic::Ordering::Relaxed);
+    }
+
+    /// Removes this provider from the process-wide list of registered providers.
+    fn unlink(&self) {
+        let self_ptr = self as *const Self as *mut Provider<'static>;
+        let _lock = ProvidersLock::acquire();
+        let mut link = &PROVIDERS_HEAD;
+        loop {
+            let provider_ptr = link.load(atomic::Ordering::Relaxed);
+            if provider_ptr.is_null() {
+                return; // Not in the list.
+            } else if provider_ptr == self_ptr {
+                link.store(
+                    self.next.swap(ptr::null_mut(), atomic::Ordering::Relaxed),
+                    atomic::Ordering::Relaxed,
+                );
+                return;
+            }
+            link = &unsafe { &*provider_ptr }.next;
+        }
+    }
+}
+
+impl<'a> EventProvider for Provider<'a> {
+    fn name(&self) -> &str {
+        return Provider::name(self);
+    }
+
+    fn options(&self) -> &str {
+        return Provider::options(self);
+    }
+
+    /// Returns true if the provider has a registered tracepoint with the specified
+    /// level and keyword and the tracepoint is enabled.
+    ///
+    /// This searches the provider's list of tracepoints. For events defined at
+    /// compile time, `write_event!` and `provider_enabled!` are more efficient.
+    fn enabled(&self, level: Level, keyword: u64) -> bool {
+        return match self.find_tracepoint(level, keyword) {
+            Some(tracepoint) => {
+                tracepoint.enabled()
+                    && !suppression::event_suppressed(self.name, &[], level, keyword)
+            }
+            None => false,
+        };
+    }
+
+    fn write_encoded(
+        &self,
+        header: &EventHeader,
+        keyword: u64,
+        activity_id: Option<&[u8; 16]>,
+        related_id: Option<&[u8; 16]>,
+        metadata: &[u8],
+        data: &[u8],
+    ) -> i32 {
+        debug_assert!(related_id.is_none() || activity_id.is_some());
+        return match self.find_tracepoint(header.level, keyword) {
+            Some(tracepoint)
+                if tracepoint.enabled()
+                    && suppression::event_suppressed(
+                        self.name,
+                        metadata,
+                        header.level,
+                        keyword,
+                    ) =>
+            {
+                9 // libc::EBADF
+            }
+            Some(tracepoint) => _internal::write_encoded(
+                &tracepoint.state,
+                header,
+                activity_id,
+                related_id,
+                metadata,
+                data,
+            ),
+            None => 9, // libc::EBADF
+        };
+    }
+
+    /// Reports one status for each distinct level + keyword combination used by the
+    /// provider's `write_event!` and `provider_enabled!` call sites. Reports nothing
+    /// while the provider is being registered or unregistered. `f` must not register
+    /// or unregister the provider.
+    fn for_each_tracepoint(&self, f: &mut dyn FnMut(&TracepointStatus)) {
+        let events = match self.read_events() {
+            None => return,
+            Some(events) => events,
+        };
+
+        for (i, event) in events.iter().enumerate() {
+            let event = match unsafe { event.as_ref() } {
+                None => continue,
+                Some(event) => event,
+            };
+            let same_key = |other: &&*const EventHeaderTracepoint| match unsafe { other.as_ref() } {
+                None => false,
+                Some(other) => {
+                    other.header.level == event.header.level && other.keyword == event.keyword
+                }
+            };
+
+            // Report each level + keyword once, at its first tracepoint.
+            if events[..i].iter().any(|other| same_key(&other)) {
+                continue;
+            }
+
+            let mut errno = 0;
+            let mut enabled = false;
+            for other in events[i..].iter().filter(same_key) {
+                let other = unsafe { &**other };
+                if errno == 0 {
+                    errno = other.errno.load(atomic::Ordering::Relaxed);
+                }
+                enabled |= other.enabled();
+            }
+
+            f(&TracepointStatus::new(
+                self.name(),
+                self.options(),
+                event.header.level,
+                event.keyword,
+                errno,
+                enabled,
+            ));
+        }
+    }
+}
+
+impl<'a> Provider<'a> {
+    /// Returns this provider's list of tracepoints, or None if the provider is being
+    /// registered or unregistered. `register` and `unregister` wait until the
+    /// returned reader is dropped. Before `register`, the list may contain NULLs
+    /// anywhere.
+    fn read_events(&self) -> Option<EventsReader<'_, 'a>> {
+        let mut access = self.access.load(atomic::Ordering::Relaxed);
+        loop {
+            if access & ACCESS_BUSY != 0 {
+                return None;
+            }
+
+            match self.access.compare_exchange_weak(
+                access,
+                access + 1,
+                atomic::Ordering::Acquire,
+                atomic::Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(current) => access = current,
+            }
+        }
+
+        return Some(EventsReader {
+            provider: self,
+            events: unsafe { &*ptr::slice_from_raw_parts(self.events_start, self.events_len()) },
+        });
+    }
+
+    /// Starts `register` or `unregister`: sets ACCESS_BUSY and waits for readers of
+    /// the events list to finish. Returns false if the provider is already busy.
+    fn begin_modify(&self) -> bool {
+        let access = self.access.fetch_or(ACCESS_BUSY, atomic::Ordering::Acquire);
+        if access & ACCESS_BUSY != 0 {
+            return false;
+        }
+
+        while self.access.load(atomic::Ordering::Acquire) != ACCESS_BUSY {
+            core::hint::spin_loop();
+        }
+
+        return true;
+    }
+
+    fn end_modify(&self) {
+        self.access.store(0, atomic::Ordering::Release);
+    }
+
+    fn events_len(&self) -> usize {
+        return unsafe { self.events_stop.offset_from(self.events_start) as usize };
+    }
+
+    /// Returns a tracepoint from this provider's list with the specified level and
+    /// keyword. All such tracepoints share the same kernel tracepoint, so any of them
+    /// can be used. Returns None if there is no such tracepoint or if the list is
+    /// being modified by `register`.
+    fn find_tracepoint(&self, level: Level, keyword: u64) -> Option<&EventHeaderTracepoint<'a>> {
+        // The tracepoints themselves are static. Only the list is modified by register.
+        let events = self.read_events()?;
+        for &event_ptr in events.iter() {
+            // Before register, the list may contain NULLs anywhere.
+            if !event_ptr.is_null() {
+                let event = unsafe { &*event_ptr };
+                if event.header.level == level && event.keyword == keyword {
+                    return Some(event);
+                }
+            }
+        }
+
+        return None;
+    }
 }
 
 unsafe impl Sync for Provider<'_> {}
 
+/// Fixes up a provider's list of tracepoints in-place so that each tracepoint
+/// appears once, followed by NULLs. `events_slice` must not be empty.
+fn normalize_events(events_slice: &mut [*const EventHeaderTracepoint]) {
+    // The list of tracepoints is frequently created using linker tricks.
+    // The linker tricks mean we end up with NULLs and sometimes there are
+    // duplicates, so we need to fix up the list in-place:
+    // - Sort the list so that duplicates are next to each other and NULLs
+    //   are at the end.
+    // - Remove adjacent duplicates, filling the rest of the list with NULL.
+
+    // Sort list. Use reverse sort so that NULL goes at end.
+    events_slice.sort_unstable_by(|a, b| b.cmp(a));
+
+    // Remove adjacent repeated elements.
+    let end_pos = events_slice.len();
+    let mut good_pos = 0;
+    while good_pos != end_pos - 1 {
+        if events_slice[good_pos] == events_slice[good_pos + 1] {
+            let mut next_pos = good_pos + 2;
+            while next_pos != end_pos {
+                if events_slice[good_pos] != events_slice[next_pos] {
+                    good_pos += 1;
+                    events_slice[good_pos] = events_slice[next_pos];
+                }
+                next_pos += 1;
+            }
+            break;
+        }
+        good_pos += 1;
+    }
+
+    // Fill remaining entries with NULL.
+    let mut next_pos = good_pos + 1;
+    while next_pos != end_pos {
+        events_slice[next_pos] = ptr::null();
+        next_pos += 1;
+    }
+}
+
+/// A provider's list of tracepoints. Keeps `register` and `unregister` from
+/// modifying the list until dropped.
+struct EventsReader<'p, 'a> {
+    provider: &'p Provider<'a>,
+    events: &'p [*const EventHeaderTracepoint<'a>],
+}
+
+impl<'p, 'a> ops::Deref for EventsReader<'p, 'a> {
+    type Target = [*const EventHeaderTracepoint<'a>];
+    fn deref(&self) -> &Self::Target {
+        return self.events;
+    }
+}
+
+impl Drop for EventsReader<'_, '_> {
+    fn drop(&mut self) {
+        self.provider.access.fetch_sub(1, atomic::Ordering::Release);
+    }
+}
+
 impl Drop for Provider<'_> {
     fn drop(&mut self) {
         self.unregister();
@@ -232,6 +667,49 @@ impl fmt::Debug for Provider<'_> {
     }
 }
 
+/// For use by the define_provider macro: describes a provider for tools that read
+/// compiled binaries (see the `eventheader_elf` crate). On Linux, `define_provider!`
+/// places one descriptor for each provider in the `_eh_providers` linker section.
+///
+/// Uses `#[repr(C)]` layout with 8 pointer-sized fields: symbol pointer, symbol
+/// length, name pointer, name length, options pointer, options length, and the
+/// start and stop of the provider's `_eh_tracepoints_SYMBOL` section.
+#[repr(C)]
+pub struct ProviderDescriptor {
+    symbol: *const u8,
+    symbol_len: usize,
+    name: *const u8,
+    name_len: usize,
+    options: *const u8,
+    options_len: usize,
+    events_start: *const usize,
+    events_stop: *const usize,
+}
+
+impl ProviderDescriptor {
+    /// For use by the define_provider macro: creates a new provider descriptor.
+    pub const fn new(
+        symbol: &'static [u8],
+        name: &'static [u8],
+        options: &'static [u8],
+        events_start: *const usize,
+        events_stop: *const usize,
+    ) -> Self {
+        return Self {
+            symbol: symbol.as_ptr(),
+            symbol_len: symbol.len(),
+            name: name.as_ptr(),
+            name_len: name.len(),
+            options: options.as_ptr(),
+            options_len: options.len(),
+            events_start,
+            events_stop,
+        };
+    }
+}
+
+unsafe impl Sync for ProviderDescriptor {}
+
 /// For use by the define_provider macro: creates a new provider.
 ///
 /// # Safety
@@ -241,28 +719,49 @@ impl fmt::Debug for Provider<'_> {
 pub const unsafe fn provider_new<'a>(
     name: &'a [u8],
     options: &'a [u8],
+    keywords: &'a [(&'a str, u64)],
     events_start: *const usize,
     events_stop: *const usize,
 ) -> Provider<'a> {
     return Provider {
         name,
         options,
-        events: ops::Range {
-            start: events_start as *mut *const EventHeaderTracepoint,
-            end: events_stop as *mut *const EventHeaderTracepoint,
-        },
-        busy: atomic::AtomicBool::new(false),
+        events_start: events_start as *mut *const EventHeaderTracepoint,
+        events_stop: events_stop as *mut *const EventHeaderTracepoint,
+        access: atomic::AtomicU32::new(0),
+        next: atomic::AtomicPtr::new(ptr::null_mut()),
+        keywords,
     };
 }
 
 /// Stores the information needed for registering and managing a tracepoint.
+///
+/// Uses `#[repr(C)]` layout so that tools can locate the header, keyword, and
+/// metadata in a compiled binary (see the `eventheader_elf` crate): `state` (8
+/// bytes), `header` (8 bytes), `keyword`, metadata pointer, metadata length.
+#[repr(C)]
 pub struct EventHeaderTracepoint<'a> {
     state: _internal::TracepointState,
     header: EventHeader,
     keyword: u64,
-    metadata: &'a [u8],
+    metadata_ptr: *const u8,
+    metadata_len: usize,
+    errno: atomic::AtomicI32, // Result of the most recent registration.
+    metadata_lifetime: marker::PhantomData<&'a [u8]>,
 }
 
+// The eventheader_elf crate reads EventHeaderTracepoint objects from compiled
+// binaries. With `#[repr(C)]`, the header is at offset 8, the keyword is at offset
+// 16, and the metadata pointer and length are at offset 24 as long as these hold.
+const _: () = {
+    assert!(mem::size_of::<_internal::TracepointState>() == 8);
+    assert!(mem::align_of::<_internal::TracepointState>() <= 8);
+    assert!(mem::size_of::<EventHeader>() == 8);
+    assert!(mem::align_of::<EventHeader>() <= 8);
+};
+
+unsafe impl Sync for EventHeaderTracepoint<'_> {}
+
 impl<'a> EventHeaderTracepoint<'a> {
     /// Sets up the data for managing a tracepoint.
     pub const fn new(header: EventHeader, keyword: u64, metadata: &'a [u8]) -> Self {
@@ -270,16 +769,39 @@ impl<'a> EventHeaderTracepoint<'a> {
             state: _internal::TracepointState::new(0),
             header,
             keyword,
-            metadata,
+            metadata_ptr: metadata.as_ptr(),
+            metadata_len: metadata.len(),
+            errno: atomic::AtomicI32::new(0),
+            metadata_lifetime: marker::PhantomData,
         };
     }
 
+    /// Returns the event's metadata: nul-terminated event name followed by the
+    /// field declarations.
+    fn metadata(&self) -> &'a [u8] {
+        return unsafe { slice::from_raw_parts(self.metadata_ptr, self.metadata_len) };
+    }
+
     /// Returns true if this tracepoint is registered and enabled.
     #[inline(always)]
     pub fn enabled(&self) -> bool {
         return self.state.enabled();
     }
 
+    /// Returns true if this tracepoint is registered and enabled and the process's
+    /// [`crate::SuppressionPolicy`] does not suppress it. The policy is checked only if
+    /// the tracepoint is enabled.
+    #[inline(always)]
+    pub fn enabled_unsuppressed(&self, provider: &Provider) -> bool {
+        return self.state.enabled()
+            && !suppression::event_suppressed(
+                provider.name,
+                self.metadata(),
+                self.header.level,
+                self.keyword,
+            );
+    }
+
     /// Fills in `data[0]` with the event's header information,
     /// fills in `data[1]` with the event's metadata, then sends
     /// the event to the `user_events_data` file.
@@ -296,17 +818,48 @@ impl<'a> EventHeaderTracepoint<'a> {
         related_id: Option<&[u8; 16]>,
         data: &mut [EventDataDescriptor<'b>],
     ) -> i32
+    where
+        'a: 'b,
+    {
+        return self.write_eventheader_with_extensions(activity_id, related_id, 0, data);
+    }
+
+    /// Same as [`EventHeaderTracepoint::write_eventheader`], but the metadata is
+    /// followed by `extension_count` provider-defined extension blocks.
+    ///
+    /// Requires:
+    /// - Same as for `write_eventheader`.
+    /// - `data[2..2 + extension_count * 2]` contains one pair of descriptors for
+    ///   each extension block: the block's header (see `_internal::extension_header`)
+    ///   and the block's data. Only the last block's header may omit the chain flag.
+    ///
+    /// Returns `ERANGE` (34) if an extension block's data is larger than 65535 bytes.
+    pub fn write_eventheader_with_extensions<'b>(
+        &self,
+        activity_id: Option<&[u8; 16]>,
+        related_id: Option<&[u8; 16]>,
+        extension_count: usize,
+        data: &mut [EventDataDescriptor<'b>],
+    ) -> i32
     where
         'a: 'b,
     {
         debug_assert!(data[1].is_empty());
-        data[1] = EventDataDescriptor::<'a>::from_bytes(self.metadata);
+        for i in 0..extension_count {
+            if data[3 + i * 2].as_bytes().len() > 65535 {
+                return 34; // libc::ERANGE
+            }
+        }
+
+        let metadata = self.metadata();
+        data[1] = EventDataDescriptor::<'a>::from_bytes(metadata);
         return _internal::write_eventheader(
             &self.state,
             &self.header,
             activity_id,
             related_id,
-            self.metadata.len() as u16,
+            metadata.len() as u16,
+            extension_count != 0,
             data,
         );
     }
@@ -345,19 +898,11 @@ impl CommandString {
         });
     }
 
-    /// Gets the CStr for the specified parameters:
+    /// Gets the CStr for the specified tracepoint name:
     /// `ProviderName_LnnKnn... u8 eventheader_flags; u8 version; u16 id; u16 tag; u8 opcode; u8 level`.
-    pub fn format(
-        &mut self,
-        provider_name: &[u8],
-        provider_options: &[u8],
-        level: Level,
-        keyword: u64,
-    ) -> &ffi::CStr {
+    pub fn format(&mut self, tracepoint_name: &TracepointName) -> &ffi::CStr {
         self.0.pos = 0;
-        self.0.write(provider_name); // "ProviderName"
-        write!(self.0, "_L{:x}K{:x}", level.as_int(), keyword).unwrap(); // "_LxKx"
-        self.0.write(provider_options); // "Options"
+        write!(self.0, "{}", tracepoint_name).unwrap(); // "ProviderName_LxKxOptions"
         write!(self.0, " {}", _internal::EVENTHEADER_COMMAND_TYPES).unwrap(); // " CommandTypes"
         self.0.buf[self.0.pos] = b'\0';
         self.0.pos += 1;
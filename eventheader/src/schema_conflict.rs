@@ -0,0 +1,294 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use core::cmp;
+use core::convert::TryInto;
+use core::fmt;
+use core::mem;
+use core::ptr;
+use core::str;
+use core::sync::atomic;
+
+use crate::FieldEncoding;
+use crate::FieldFormat;
+#[allow(unused_imports)] // For docs
+use crate::Provider;
+
+static SCHEMA_CHECK: atomic::AtomicBool = atomic::AtomicBool::new(cfg!(debug_assertions));
+
+// The handler set by set_schema_conflict_handler, or NULL if no handler is set.
+static SCHEMA_CONFLICT_HANDLER: atomic::AtomicPtr<()> = atomic::AtomicPtr::new(ptr::null_mut());
+
+/// Enables or disables the schema-conflict check performed by [`Provider::register`].
+///
+/// When enabled and a handler has been set by [`set_schema_conflict_handler`],
+/// `register` checks the provider's events for [`SchemaConflict`]s and calls the
+/// handler for each one. No handler is set by default, so conflicts are ignored. The
+/// check is enabled by default in debug builds (i.e. when `debug_assertions` are
+/// enabled for the `eventheader` crate) and disabled by default in release builds.
+pub fn set_schema_check(enabled: bool) {
+    SCHEMA_CHECK.store(enabled, atomic::Ordering::Relaxed);
+}
+
+/// Sets the function that [`Provider::register`] calls for each [`SchemaConflict`]
+/// when the check is enabled (see [`set_schema_check`]), e.g. to log conflicts, or to
+/// panic so that tests fail when a conflict is introduced. `None` removes the handler,
+/// so conflicts are ignored (the default).
+///
+/// The handler runs at the end of `register`, after the provider's tracepoints have
+/// been registered. It may write events using the provider but must not register or
+/// unregister the provider.
+pub fn set_schema_conflict_handler(handler: Option<fn(&SchemaConflict)>) {
+    let handler_ptr = match handler {
+        None => ptr::null_mut(),
+        Some(handler) => handler as *mut (),
+    };
+    SCHEMA_CONFLICT_HANDLER.store(handler_ptr, atomic::Ordering::Release);
+}
+
+/// Returns the handler set by [`set_schema_conflict_handler`], or None.
+pub(crate) fn schema_conflict_handler() -> Option<fn(&SchemaConflict)> {
+    let handler_ptr = SCHEMA_CONFLICT_HANDLER.load(atomic::Ordering::Acquire);
+    return if handler_ptr.is_null() {
+        None
+    } else {
+        // Safety: non-NULL values are stored only by set_schema_conflict_handler.
+        Some(unsafe { mem::transmute::<*mut (), fn(&SchemaConflict)>(handler_ptr) })
+    };
+}
+
+/// Returns true if [`Provider::register`] checks the provider's events for
+/// [`SchemaConflict`]s. See [`set_schema_check`].
+pub fn schema_check() -> bool {
+    return SCHEMA_CHECK.load(atomic::Ordering::Relaxed);
+}
+
+/// The reason two events of a provider conflict. See [`SchemaConflict`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SchemaConflictKind {
+    /// The events have the same name but different fields.
+    SameName,
+
+    /// The events have the same nonzero id and the same version but different names
+    /// or different fields.
+    SameIdVersion,
+}
+
+/// Two events of the same provider that consumers would treat as the same event but
+/// that have different schemas, e.g. two `write_event!` call sites that use the same
+/// event name with different fields. Consumers may decode one of the events using the
+/// other event's schema. Reported by [`Provider::for_each_schema_conflict`].
+///
+/// Two events conflict if:
+///
+/// - they have the same name but different fields ([`SchemaConflictKind::SameName`]), or
+/// - they have the same nonzero `id` and the same `version` but different names or
+///   different fields ([`SchemaConflictKind::SameIdVersion`]).
+///
+/// Events with the same name and fields do not conflict, even if they use different
+/// levels, keywords, opcodes, or tags.
+///
+/// The `Display` implementation writes a description of the conflict, e.g.
+/// `provider "MyProvider": event "MyEvent" is defined with different fields`.
+#[derive(Clone, Copy, Debug)]
+pub struct SchemaConflict<'a> {
+    provider_name: &'a str,
+    kind: SchemaConflictKind,
+    id: u16,
+    version: u8,
+    metadata: &'a [u8],
+    other_metadata: &'a [u8],
+}
+
+impl<'a> SchemaConflict<'a> {
+    /// Creates a SchemaConflict. `metadata` and `other_metadata` are the
+    /// EventHeader metadata of the two events (nul-terminated event name followed by
+    /// field definitions).
+    pub(crate) const fn new(
+        provider_name: &'a str,
+        kind: SchemaConflictKind,
+        id: u16,
+        version: u8,
+        metadata: &'a [u8],
+        other_metadata: &'a [u8],
+    ) -> Self {
+        return Self {
+            provider_name,
+            kind,
+            id,
+            version,
+            metadata,
+            other_metadata,
+        };
+    }
+
+    /// Returns the name of the events' provider.
+    pub const fn provider_name(&self) -> &'a str {
+        return self.provider_name;
+    }
+
+    /// Returns the reason the events conflict.
+    pub const fn kind(&self) -> SchemaConflictKind {
+        return self.kind;
+    }
+
+    /// Returns the events' id (0 for [`SchemaConflictKind::SameName`] conflicts
+    /// between events that have different ids).
+    pub const fn id(&self) -> u16 {
+        return self.id;
+    }
+
+    /// Returns the events' version (0 for [`SchemaConflictKind::SameName`] conflicts
+    /// between events that have different ids).
+    pub const fn version(&self) -> u8 {
+        return self.version;
+    }
+
+    /// Returns the name of the first event.
+    pub fn event_name(&self) -> &'a str {
+        return metadata_name(self.metadata);
+    }
+
+    /// Returns the name of the second event.
+    pub fn other_event_name(&self) -> &'a str {
+        return metadata_name(self.other_metadata);
+    }
+
+    /// Returns the EventHeader metadata of the first event: the nul-terminated event
+    /// name followed by the field definitions.
+    pub const fn metadata(&self) -> &'a [u8] {
+        return self.metadata;
+    }
+
+    /// Returns the EventHeader metadata of the second event.
+    pub const fn other_metadata(&self) -> &'a [u8] {
+        return self.other_metadata;
+    }
+}
+
+impl fmt::Display for SchemaConflict<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self.kind {
+            SchemaConflictKind::SameName => write!(
+                f,
+                "provider \"{}\": event \"{}\" is defined with different fields",
+                self.provider_name,
+                self.event_name(),
+            ),
+            SchemaConflictKind::SameIdVersion => write!(
+                f,
+                "provider \"{}\": events \"{}\" and \"{}\" use id {} version {} with different schemas",
+                self.provider_name,
+                self.event_name(),
+                self.other_event_name(),
+                self.id,
+                self.version,
+            ),
+        };
+    }
+}
+
+/// Compares the schemas in two blocks of EventHeader metadata: first the event names,
+/// then the decoded field definitions. Definitions that decode the same way compare
+/// equal even if their bytes differ, e.g. a field with an explicit `Default` format
+/// and the same field with the format byte omitted.
+pub(crate) fn compare_metadata(metadata: &[u8], other_metadata: &[u8]) -> cmp::Ordering {
+    let (name, fields) = split_metadata(metadata);
+    let (other_name, other_fields) = split_metadata(other_metadata);
+    return name
+        .cmp(other_name)
+        .then_with(|| FieldDefs(fields).cmp(FieldDefs(other_fields)));
+}
+
+/// A decoded field definition: the parts that affect how consumers decode the field.
+#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+enum FieldDef<'a> {
+    Field {
+        name: &'a [u8],
+        encoding: FieldEncoding, // Without ChainFlag.
+        format: FieldFormat,     // Without ChainFlag. Default if not present.
+        tag: [u8; 2],            // 0 if not present.
+        array_count: [u8; 2],    // Element count of a constant-length array, else 0.
+    },
+
+    /// The rest of the metadata, which could not be decoded.
+    Invalid(&'a [u8]),
+}
+
+/// Iterator over the field definitions in EventHeader metadata (after the event name).
+struct FieldDefs<'a>(&'a [u8]);
+
+impl<'a> FieldDefs<'a> {
+    /// Decodes the first field definition. Returns the field and its size in bytes.
+    fn decode(meta: &'a [u8]) -> Option<(FieldDef<'a>, usize)> {
+        let name_len = meta.iter().position(|b| *b == 0)?;
+        let mut pos = name_len + 1;
+
+        let encoding = FieldEncoding::from_int(*meta.get(pos)?);
+        pos += 1;
+
+        let mut format = FieldFormat::Default;
+        let mut tag = [0; 2];
+        if encoding.has_chain_flag() {
+            format = FieldFormat::from_int(*meta.get(pos)?);
+            pos += 1;
+            if format.has_chain_flag() {
+                tag = meta.get(pos..pos + 2)?.try_into().unwrap();
+                pos += 2;
+            }
+        }
+
+        let mut array_count = [0; 2];
+        if encoding.is_constant_length_array() && !encoding.is_variable_length_array() {
+            array_count = meta.get(pos..pos + 2)?.try_into().unwrap();
+            pos += 2;
+        }
+
+        let field = FieldDef::Field {
+            name: &meta[..name_len],
+            encoding: encoding.without_chain_flag(),
+            format: format.without_flags(),
+            tag,
+            array_count,
+        };
+        return Some((field, pos));
+    }
+}
+
+impl<'a> Iterator for FieldDefs<'a> {
+    type Item = FieldDef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        return match Self::decode(self.0) {
+            Some((field, size)) => {
+                self.0 = &self.0[size..];
+                Some(field)
+            }
+            None => {
+                let rest = self.0;
+                self.0 = &[];
+                Some(FieldDef::Invalid(rest))
+            }
+        };
+    }
+}
+
+/// Splits EventHeader metadata into the event name and the field definitions.
+pub(crate) fn split_metadata(metadata: &[u8]) -> (&[u8], &[u8]) {
+    return match metadata.iter().position(|b| *b == 0) {
+        Some(name_len) => (&metadata[..name_len], &metadata[name_len + 1..]),
+        None => (metadata, &[]),
+    };
+}
+
+fn metadata_name(metadata: &[u8]) -> &str {
+    let name = split_metadata(metadata).0;
+    return match str::from_utf8(name) {
+        Ok(name) => name,
+        Err(err) => str::from_utf8(&name[..err.valid_up_to()]).unwrap_or_default(),
+    };
+}
@@ -0,0 +1,419 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use core::cell::UnsafeCell;
+use core::ffi::CStr;
+use core::fmt;
+use core::ptr;
+use core::str;
+use core::sync::atomic;
+
+use crate::Level;
+
+/// Name of the environment variable that supplies the process's initial
+/// [`SuppressionPolicy`]: `"EVENTHEADER_SUPPRESS"`.
+pub const SUPPRESSION_POLICY_ENV: &str = "EVENTHEADER_SUPPRESS";
+
+/// [`SUPPRESSION_POLICY_ENV`] as a nul-terminated string for `getenv`.
+const SUPPRESSION_POLICY_ENV_CSTR: &CStr =
+    unsafe { CStr::from_bytes_with_nul_unchecked(b"EVENTHEADER_SUPPRESS\0") };
+
+/// Maximum length of a policy read from the environment. Longer values are ignored.
+const ENV_POLICY_MAX: usize = 1024;
+
+const ENV_UNREAD: u8 = 0;
+const ENV_READING: u8 = 1;
+const ENV_DONE: u8 = 2;
+
+static POLICY: atomic::AtomicPtr<SuppressionPolicy<'static>> =
+    atomic::AtomicPtr::new(ptr::null_mut());
+static ENV_POLICY: EnvPolicy = EnvPolicy {
+    state: atomic::AtomicU8::new(ENV_UNREAD),
+    policy: UnsafeCell::new(SuppressionPolicy::EMPTY),
+    buffer: UnsafeCell::new([0; ENV_POLICY_MAX]),
+};
+
+/// Storage for the policy read from the environment. `policy` and `buffer` are
+/// written only by the thread that moves `state` from ENV_UNREAD to ENV_READING, and
+/// are never written after that.
+struct EnvPolicy {
+    state: atomic::AtomicU8,
+    policy: UnsafeCell<SuppressionPolicy<'static>>,
+    buffer: UnsafeCell<[u8; ENV_POLICY_MAX]>,
+}
+
+unsafe impl Sync for EnvPolicy {}
+
+/// A process-local policy that silences events even when a logging session has
+/// enabled them, e.g. to quiet a misbehaving provider or event on a production
+/// machine without redeploying.
+///
+/// The policy is a list of rules separated by `;`. Each rule is a list of conditions
+/// separated by `,`. An event is suppressed if it matches all of the conditions of
+/// any rule. The conditions are:
+///
+/// - `provider=NAME`: the event's provider name is `NAME`.
+/// - `event=NAME`: the event's name is `NAME`.
+/// - `maxlevel=LEVEL`: the event's level is greater (more verbose) than `LEVEL`,
+///   e.g. `maxlevel=Warning` or `maxlevel=3` suppresses `Informational` and `Verbose`
+///   events.
+/// - `keyword=MASK`: the event's keyword has any of the bits in `MASK` set, e.g.
+///   `keyword=0x10`.
+///
+/// Example: `provider=MyCompany_Noisy;provider=MyCompany_Net,maxlevel=Warning;keyword=0x10`
+/// suppresses all events from `MyCompany_Noisy`, `Informational` and `Verbose` events
+/// from `MyCompany_Net`, and events from any provider with keyword bit `0x10` set.
+///
+/// The process's policy is read from the `EVENTHEADER_SUPPRESS` environment variable
+/// (see [`SUPPRESSION_POLICY_ENV`]) when the first provider is registered, and can be
+/// replaced at runtime using [`set_suppression_policy`]. The policy is checked only
+/// after the tracepoint's `enabled()` check succeeds, so it does not add cost to events
+/// that no session is listening for. A suppressed event is treated as if no session
+/// were listening for it: its fields are not evaluated and the write returns `EBADF`.
+///
+/// The policy applies to events written by `write_event!` and by
+/// `eventheader_dynamic`. It also applies to `provider_enabled!`, except that `event`
+/// conditions never match in `provider_enabled!`.
+///
+/// The rules are parsed once, by [`SuppressionPolicy::parse`]. Checking an event
+/// only compares the event against the parsed conditions. A policy can contain at
+/// most [`SuppressionPolicy::CONDITIONS_MAX`] conditions.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct SuppressionPolicy<'a> {
+    rules: &'a str,
+    conditions: [RuleCondition<'a>; CONDITIONS_MAX],
+    conditions_len: usize,
+}
+
+const CONDITIONS_MAX: usize = 32;
+
+impl<'a> SuppressionPolicy<'a> {
+    /// The maximum number of conditions in a policy (counting the conditions of all
+    /// rules): 32.
+    pub const CONDITIONS_MAX: usize = CONDITIONS_MAX;
+
+    const EMPTY: SuppressionPolicy<'static> = SuppressionPolicy {
+        rules: "",
+        conditions: [RuleCondition {
+            condition: Condition::Keyword(0),
+            rule_start: false,
+        }; CONDITIONS_MAX],
+        conditions_len: 0,
+    };
+
+    /// Parses and validates a policy, e.g. `"provider=MyCompany_Noisy;keyword=0x10"`.
+    /// An empty string is a valid policy that suppresses nothing.
+    pub fn parse(rules: &'a str) -> Result<Self, SuppressionPolicyError> {
+        let mut policy = Self {
+            rules,
+            ..Self::EMPTY
+        };
+
+        for rule in rules.split(';') {
+            let mut rule_start = true;
+            for condition in rule.split(',') {
+                let condition = condition.trim();
+                if condition.is_empty() {
+                    continue;
+                }
+
+                let condition = parse_condition(condition)?;
+                if policy.conditions_len == CONDITIONS_MAX {
+                    return Err(SuppressionPolicyError::TooManyConditions);
+                }
+
+                policy.conditions[policy.conditions_len] = RuleCondition {
+                    condition,
+                    rule_start,
+                };
+                policy.conditions_len += 1;
+                rule_start = false;
+            }
+
+            if rule_start && !rule.trim().is_empty() {
+                return Err(SuppressionPolicyError::EmptyCondition);
+            }
+        }
+
+        return Ok(policy);
+    }
+
+    /// Returns the policy's rules, e.g. `"provider=MyCompany_Noisy;keyword=0x10"`.
+    pub const fn rules(&self) -> &'a str {
+        return self.rules;
+    }
+
+    /// Returns true if the policy suppresses an event with the specified provider
+    /// name, event name, level, and keyword.
+    pub fn suppresses(
+        &self,
+        provider_name: &str,
+        event_name: &str,
+        level: Level,
+        keyword: u64,
+    ) -> bool {
+        return self.suppresses_bytes(
+            provider_name.as_bytes(),
+            Some(event_name.as_bytes()),
+            level,
+            keyword,
+        );
+    }
+
+    /// Same as `suppresses`, but `event_name` is None if the event name is not known
+    /// (`event` conditions do not match).
+    fn suppresses_bytes(
+        &self,
+        provider_name: &[u8],
+        event_name: Option<&[u8]>,
+        level: Level,
+        keyword: u64,
+    ) -> bool {
+        // True while all of the current rule's conditions (so far) have matched.
+        let mut rule_matched = false;
+        for rule_condition in &self.conditions[..self.conditions_len] {
+            if rule_condition.rule_start {
+                if rule_matched {
+                    return true;
+                }
+                rule_matched = true;
+            }
+
+            if rule_matched {
+                rule_matched = match rule_condition.condition {
+                    Condition::Provider(name) => name.as_bytes() == provider_name,
+                    Condition::Event(name) => Some(name.as_bytes()) == event_name,
+                    Condition::MaxLevel(max_level) => level.as_int() > max_level.as_int(),
+                    Condition::Keyword(mask) => 0 != (keyword & mask),
+                };
+            }
+        }
+
+        return rule_matched;
+    }
+}
+
+impl fmt::Debug for SuppressionPolicy<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "SuppressionPolicy {{ rules: {:?} }}", self.rules);
+    }
+}
+
+/// Error returned by [`SuppressionPolicy::parse`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SuppressionPolicyError {
+    /// A rule contains only empty conditions, e.g. `" , "`.
+    EmptyCondition,
+
+    /// A condition is not of the form `key=value`.
+    MissingValue,
+
+    /// A condition's key is not `provider`, `event`, `maxlevel`, or `keyword`.
+    UnknownKey,
+
+    /// A `provider` or `event` condition has an empty name.
+    InvalidName,
+
+    /// A `maxlevel` condition's value is not a level name or number.
+    InvalidLevel,
+
+    /// A `keyword` condition's value is not a decimal or `0x` hex number.
+    InvalidKeyword,
+
+    /// The policy has more than [`SuppressionPolicy::CONDITIONS_MAX`] conditions.
+    TooManyConditions,
+}
+
+impl fmt::Display for SuppressionPolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(match self {
+            Self::EmptyCondition => "rule must contain at least one condition",
+            Self::MissingValue => "condition must be of the form key=value",
+            Self::UnknownKey => "condition key must be provider, event, maxlevel, or keyword",
+            Self::InvalidName => "provider or event name must not be empty",
+            Self::InvalidLevel => "maxlevel must be a level name or number",
+            Self::InvalidKeyword => "keyword must be a decimal or 0x hex number",
+            Self::TooManyConditions => "policy must not have more than 32 conditions",
+        });
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+struct RuleCondition<'a> {
+    condition: Condition<'a>,
+    rule_start: bool, // True for the first condition of each rule.
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Condition<'a> {
+    Provider(&'a str),
+    Event(&'a str),
+    MaxLevel(Level),
+    Keyword(u64),
+}
+
+fn parse_condition(condition: &str) -> Result<Condition<'_>, SuppressionPolicyError> {
+    let (key, value) = match condition.split_once('=') {
+        Some((key, value)) => (key.trim(), value.trim()),
+        None => return Err(SuppressionPolicyError::MissingValue),
+    };
+
+    return match key {
+        "provider" | "event" if value.is_empty() => Err(SuppressionPolicyError::InvalidName),
+        "provider" => Ok(Condition::Provider(value)),
+        "event" => Ok(Condition::Event(value)),
+        "maxlevel" => match value.parse::<Level>() {
+            Ok(level) => Ok(Condition::MaxLevel(level)),
+            Err(_) => Err(SuppressionPolicyError::InvalidLevel),
+        },
+        "keyword" => {
+            let parsed = match value
+                .strip_prefix("0x")
+                .or_else(|| value.strip_prefix("0X"))
+            {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => value.parse::<u64>(),
+            };
+            match parsed {
+                Ok(mask) => Ok(Condition::Keyword(mask)),
+                Err(_) => Err(SuppressionPolicyError::InvalidKeyword),
+            }
+        }
+        _ => Err(SuppressionPolicyError::UnknownKey),
+    };
+}
+
+/// Sets the process's [`SuppressionPolicy`], replacing the policy from the
+/// `EVENTHEADER_SUPPRESS` environment variable (if any). Use `None` to stop
+/// suppressing events.
+///
+/// The policy must be `'static`, e.g. a `static` variable or a leaked `Box`.
+pub fn set_suppression_policy(policy: Option<&'static SuppressionPolicy<'static>>) {
+    // The environment variable is no longer needed.
+    let _ = ENV_POLICY.state.compare_exchange(
+        ENV_UNREAD,
+        ENV_DONE,
+        atomic::Ordering::Relaxed,
+        atomic::Ordering::Relaxed,
+    );
+
+    let policy_ptr = match policy {
+        Some(policy) => policy as *const SuppressionPolicy as *mut SuppressionPolicy,
+        None => ptr::null_mut(),
+    };
+    POLICY.store(policy_ptr, atomic::Ordering::Release);
+}
+
+/// Returns the process's current [`SuppressionPolicy`], or None if no policy is set.
+/// Reads the `EVENTHEADER_SUPPRESS` environment variable if it has not been read. If
+/// another thread is reading the variable, waits for that thread to finish.
+pub fn suppression_policy() -> Option<&'static SuppressionPolicy<'static>> {
+    read_env_suppression_policy();
+    return current_policy();
+}
+
+/// Returns the current policy without reading the environment.
+#[inline]
+fn current_policy() -> Option<&'static SuppressionPolicy<'static>> {
+    let policy_ptr = POLICY.load(atomic::Ordering::Acquire);
+    return if policy_ptr.is_null() {
+        None
+    } else {
+        Some(unsafe { &*policy_ptr })
+    };
+}
+
+/// Returns true if the process's [`SuppressionPolicy`] suppresses an event. For use
+/// after the event's tracepoint has been found to be enabled. `metadata` is the
+/// event's EventHeader metadata (starting with the nul-terminated event name), or
+/// empty if the event name is not known.
+///
+/// Does not read the environment: events are only enabled after their provider has
+/// been registered, which calls [`read_env_suppression_policy`].
+#[inline]
+pub fn event_suppressed(provider_name: &[u8], metadata: &[u8], level: Level, keyword: u64) -> bool {
+    return match current_policy() {
+        None => false,
+        Some(policy) => {
+            let event_name = if metadata.is_empty() {
+                None
+            } else {
+                Some(match metadata.iter().position(|b| *b == 0) {
+                    Some(name_len) => &metadata[..name_len],
+                    None => metadata,
+                })
+            };
+            policy.suppresses_bytes(provider_name, event_name, level, keyword)
+        }
+    };
+}
+
+/// Reads the `EVENTHEADER_SUPPRESS` environment variable if it has not been read.
+/// Called when a provider is registered so that writing an event never calls
+/// `getenv`. If another thread is reading the variable, waits for that thread to
+/// finish.
+pub fn read_env_suppression_policy() {
+    if ENV_POLICY.state.load(atomic::Ordering::Acquire) != ENV_DONE {
+        read_env_suppression_policy_slow();
+    }
+}
+
+#[cold]
+fn read_env_suppression_policy_slow() {
+    if let Err(state) = ENV_POLICY.state.compare_exchange(
+        ENV_UNREAD,
+        ENV_READING,
+        atomic::Ordering::Acquire,
+        atomic::Ordering::Acquire,
+    ) {
+        // Already read, or being read by another thread. Wait for the other thread
+        // so that the caller sees the policy from the environment.
+        if state == ENV_READING {
+            while ENV_POLICY.state.load(atomic::Ordering::Acquire) != ENV_DONE {
+                core::hint::spin_loop();
+            }
+        }
+        return;
+    }
+
+    // Safety: we own ENV_POLICY.buffer and ENV_POLICY.policy (state is ENV_READING).
+    let buffer = unsafe { &mut *ENV_POLICY.buffer.get() };
+    if let Some(rules) = env_var(SUPPRESSION_POLICY_ENV_CSTR, buffer) {
+        if let Ok(policy) = SuppressionPolicy::parse(rules) {
+            if policy.conditions_len != 0 {
+                unsafe { *ENV_POLICY.policy.get() = policy };
+
+                // Don't replace a policy set via set_suppression_policy.
+                let _ = POLICY.compare_exchange(
+                    ptr::null_mut(),
+                    ENV_POLICY.policy.get(),
+                    atomic::Ordering::Release,
+                    atomic::Ordering::Relaxed,
+                );
+            }
+        }
+    }
+
+    ENV_POLICY.state.store(ENV_DONE, atomic::Ordering::Release);
+}
+
+/// Copies the value of the specified environment variable into `buffer`. Returns
+/// None if the variable is not set, is too long, or is not valid UTF-8.
+#[cfg(target_os = "linux")]
+fn env_var<'b>(name: &CStr, buffer: &'b mut [u8]) -> Option<&'b str> {
+    // Safety: name is nul-terminated. The value is copied immediately.
+    let value_ptr = unsafe { libc::getenv(name.as_ptr() as *const libc::c_char) };
+    if value_ptr.is_null() {
+        return None;
+    }
+
+    let value = unsafe { core::ffi::CStr::from_ptr(value_ptr) }.to_bytes();
+    let value_buffer = buffer.get_mut(..value.len())?;
+    value_buffer.copy_from_slice(value);
+    return str::from_utf8(value_buffer).ok();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn env_var<'b>(_name: &CStr, _buffer: &'b mut [u8]) -> Option<&'b str> {
+    return None;
+}
@@ -0,0 +1,533 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//! In-process capture of eventheader events for use in unit tests.
+//!
+//! Requires the `testing` feature.
+//!
+//! When capture mode is enabled, providers that register afterwards (both static
+//! [`Provider`](crate::Provider)s and `eventheader_dynamic` providers) do not connect
+//! to the kernel. Instead, each of their tracepoints is always enabled and every event
+//! written to it is encoded as usual, then recorded by the [`Capture`] that is active
+//! on the writing thread (if any). Recorded events are decoded with
+//! `tracepoint_decode` so that tests can make assertions about the event's provider,
+//! name, level, keyword, and field values.
+//!
+//! ```
+//! use eventheader as eh;
+//! use eventheader::testing::Capture;
+//!
+//! eh::define_provider!(MY_PROVIDER, "MyCompany_MyComponent");
+//!
+//! // Start capturing before the provider registers.
+//! let capture = Capture::start();
+//! unsafe { MY_PROVIDER.register() };
+//!
+//! eh::write_event!(MY_PROVIDER, "MyEvent", level(Warning), u32("Field1", &42));
+//!
+//! capture
+//!     .assert_written("MyCompany_MyComponent", "MyEvent")
+//!     .assert_level(eh::Level::Warning)
+//!     .assert_field("Field1", 42);
+//!
+//! MY_PROVIDER.unregister();
+//! ```
+//!
+//! Notes:
+//!
+//! - Capture mode is process-wide and cannot be turned off. Tests that use it should
+//!   generally be in their own test binary so that other tests continue to register
+//!   providers normally.
+//! - Capture mode only affects tracepoints that register after it is enabled. A
+//!   provider that was registered before capture mode was enabled must be unregistered
+//!   and registered again.
+//! - Events are recorded by the [`Capture`] that is active on the thread that writes
+//!   the event. Events written on other threads or with no active capture are dropped.
+
+use core::cell::RefCell;
+use core::ffi::CStr;
+use core::fmt;
+use core::fmt::Write;
+use core::pin::Pin;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::Ordering;
+
+use std::format;
+use std::rc::Rc;
+use std::string::String;
+use std::string::ToString;
+use std::sync::Mutex;
+use std::vec::Vec;
+
+use eventheader_types::EventHeader;
+use eventheader_types::ExtensionKind;
+use eventheader_types::FieldEncoding;
+use eventheader_types::Level;
+use tracepoint::EventDataDescriptor;
+use tracepoint::TracepointState;
+use tracepoint_decode::EventHeaderEnumeratorContext;
+use tracepoint_decode::EventHeaderEnumeratorError;
+use tracepoint_decode::EventHeaderEnumeratorState;
+use tracepoint_decode::PerfConvertOptions;
+
+static CAPTURE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Names of local tracepoints, keyed by the address of the tracepoint's state.
+static LOCAL_TRACEPOINTS: Mutex<Vec<(usize, String)>> = Mutex::new(Vec::new());
+
+std::thread_local! {
+    static CURRENT_CAPTURE: RefCell<Option<Rc<RefCell<Vec<CapturedEvent>>>>> =
+        const { RefCell::new(None) };
+}
+
+/// Enables capture mode for the current process. Providers that register after this
+/// call will record their events in-process instead of sending them to the kernel.
+///
+/// Capture mode cannot be disabled. This is called automatically by
+/// [`Capture::start`].
+pub fn enable_capture_mode() {
+    CAPTURE_MODE.store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn capture_mode_enabled() -> bool {
+    return CAPTURE_MODE.load(Ordering::Relaxed);
+}
+
+/// Registers `state` as a local tracepoint with the name from `name_args`, i.e. the
+/// text before the first space.
+pub(crate) fn register_local(state: Pin<&TracepointState>, name_args: &CStr) {
+    let name_args = name_args.to_bytes();
+    let name_len = name_args
+        .iter()
+        .position(|&b| b == b' ')
+        .unwrap_or(name_args.len());
+    let name = String::from_utf8_lossy(&name_args[..name_len]).into_owned();
+    let key = state.get_ref() as *const TracepointState as usize;
+
+    let mut tracepoints = LOCAL_TRACEPOINTS.lock().unwrap();
+    tracepoints.retain(|(k, _)| *k != key);
+    tracepoints.push((key, name));
+    state.register_local();
+}
+
+/// Records an event written to a local tracepoint. `headers` is the event's
+/// eventheader and extension headers (without the `write_index`), and `data` is the
+/// rest of the event.
+///
+/// Returns 0 if the event was recorded or EBADF (9) if there is no active capture.
+pub(crate) fn write_local(
+    state: &TracepointState,
+    headers: &[u8],
+    data: &[EventDataDescriptor],
+) -> i32 {
+    return CURRENT_CAPTURE.with(|current| {
+        let current = current.borrow();
+        let events = match current.as_ref() {
+            None => return 9, // EBADF
+            Some(events) => events,
+        };
+
+        let key = state as *const TracepointState as usize;
+        let tracepoint_name = match LOCAL_TRACEPOINTS
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(k, _)| *k == key)
+        {
+            None => return 9, // EBADF
+            Some((_, name)) => name.clone(),
+        };
+
+        let mut event_data = Vec::from(headers);
+        for descriptor in data {
+            event_data.extend_from_slice(descriptor.as_bytes());
+        }
+
+        events
+            .borrow_mut()
+            .push(CapturedEvent::new(tracepoint_name, event_data));
+        return 0;
+    });
+}
+
+/// Records the events written on the current thread to providers in capture mode.
+///
+/// Creating a `Capture` enables capture mode (see [`enable_capture_mode`]) and makes
+/// the new capture the active capture for the current thread. Dropping it restores the
+/// previously-active capture, if any.
+pub struct Capture {
+    events: Rc<RefCell<Vec<CapturedEvent>>>,
+    previous: Option<Rc<RefCell<Vec<CapturedEvent>>>>,
+}
+
+impl Capture {
+    /// Enables capture mode and starts recording the events written on the current
+    /// thread.
+    pub fn start() -> Self {
+        enable_capture_mode();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let previous = CURRENT_CAPTURE.with(|current| current.replace(Some(events.clone())));
+        return Self { events, previous };
+    }
+
+    /// Returns the events that have been recorded so far.
+    pub fn events(&self) -> Vec<CapturedEvent> {
+        return self.events.borrow().clone();
+    }
+
+    /// Discards the events that have been recorded so far.
+    pub fn clear(&self) {
+        self.events.borrow_mut().clear();
+    }
+
+    /// Returns the first recorded event with the specified provider name and event
+    /// name, or `None` if no such event was recorded.
+    pub fn find(&self, provider_name: &str, event_name: &str) -> Option<CapturedEvent> {
+        return self
+            .events
+            .borrow()
+            .iter()
+            .find(|e| e.provider_name() == provider_name && e.event_name() == event_name)
+            .cloned();
+    }
+
+    /// Returns the first recorded event with the specified provider name and event
+    /// name. Panics (listing the recorded events) if no such event was recorded.
+    #[track_caller]
+    pub fn assert_written(&self, provider_name: &str, event_name: &str) -> CapturedEvent {
+        return match self.find(provider_name, event_name) {
+            Some(event) => event,
+            None => panic!(
+                "event {}:{} was not written. Captured events:{}",
+                provider_name,
+                event_name,
+                EventListDisplay(&self.events.borrow())
+            ),
+        };
+    }
+
+    /// Panics (listing the recorded events) if an event with the specified provider
+    /// name and event name was recorded.
+    #[track_caller]
+    pub fn assert_not_written(&self, provider_name: &str, event_name: &str) {
+        if self.find(provider_name, event_name).is_some() {
+            panic!(
+                "event {}:{} was written. Captured events:{}",
+                provider_name,
+                event_name,
+                EventListDisplay(&self.events.borrow())
+            );
+        }
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_CAPTURE.with(|current| current.replace(previous));
+    }
+}
+
+impl fmt::Debug for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f
+            .debug_struct("Capture")
+            .field("events", &self.events.borrow())
+            .finish();
+    }
+}
+
+/// An event recorded by a [`Capture`], decoded into its provider name, event name,
+/// header, and field values.
+#[derive(Clone, Debug)]
+pub struct CapturedEvent {
+    tracepoint_name: String,
+    data: Vec<u8>,
+    provider_name: String,
+    event_name: String,
+    header: EventHeader,
+    keyword: u64,
+    activity_id: Option<[u8; 16]>,
+    related_id: Option<[u8; 16]>,
+    extensions: Vec<(ExtensionKind, Vec<u8>)>,
+    fields: Vec<(String, String)>,
+    error: EventHeaderEnumeratorError,
+}
+
+impl CapturedEvent {
+    fn new(tracepoint_name: String, data: Vec<u8>) -> Self {
+        let mut event = Self {
+            tracepoint_name,
+            data,
+            provider_name: String::new(),
+            event_name: String::new(),
+            header: EventHeader::new(Level::Invalid, false),
+            keyword: 0,
+            activity_id: None,
+            related_id: None,
+            extensions: Vec::new(),
+            fields: Vec::new(),
+            error: EventHeaderEnumeratorError::Success,
+        };
+        event.error = event.decode();
+        return event;
+    }
+
+    fn decode(&mut self) -> EventHeaderEnumeratorError {
+        let mut context = EventHeaderEnumeratorContext::new();
+        let mut e = match context.enumerate_with_name_and_data(
+            &self.tracepoint_name,
+            &self.data,
+            EventHeaderEnumeratorContext::MOVE_NEXT_LIMIT_DEFAULT,
+        ) {
+            Ok(e) => e,
+            Err(error) => return error,
+        };
+
+        let info = e.event_info();
+        self.provider_name = String::from(info.provider_name());
+        self.event_name = info.name_display().to_string();
+        self.header = info.header();
+        self.keyword = info.keyword();
+        self.activity_id = info.activity_id().copied();
+        self.related_id = info.related_activity_id().copied();
+        self.extensions = info
+            .extensions()
+            .filter(|ext| ext.kind().is_custom())
+            .map(|ext| (ext.kind(), ext.data().to_vec()))
+            .collect();
+
+        // Flatten the fields, naming struct members "Struct.Member". Arrays of values or
+        // strings are formatted like "1, 2, 3" and arrays of structs are formatted as JSON.
+        let json_options = PerfConvertOptions::Default
+            .and_not(PerfConvertOptions::RootName)
+            .and_not(PerfConvertOptions::FieldTag);
+        let mut path: Vec<String> = Vec::new();
+        let mut moved = e.move_next();
+        while moved {
+            let item = e.item_info();
+            let name = item.name_display().to_string();
+            let qualified_name = || {
+                return if path.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", path.join("."), name)
+                };
+            };
+
+            match e.state() {
+                EventHeaderEnumeratorState::Value => {
+                    let value = item.value().display().to_string();
+                    self.fields.push((qualified_name(), value));
+                    moved = e.move_next();
+                }
+                EventHeaderEnumeratorState::ArrayBegin => {
+                    let qualified_name = qualified_name();
+                    let mut value = String::new();
+                    if item.metadata().encoding() == FieldEncoding::Struct {
+                        if e.write_json_item_and_move_next_sibling(&mut value, false, json_options)
+                            .is_err()
+                        {
+                            break;
+                        }
+                        moved = e.state().can_item_info();
+                    } else if item.metadata().type_size() != 0 {
+                        write!(value, "{}", item.value().display()).unwrap();
+                        moved = e.move_next_sibling();
+                    } else {
+                        // Array of strings or blobs: join the element values.
+                        let mut first = true;
+                        moved = e.move_next();
+                        while moved && e.state() == EventHeaderEnumeratorState::Value {
+                            if !first {
+                                value.push_str(", ");
+                            }
+                            first = false;
+                            write!(value, "{}", e.item_info().value().display()).unwrap();
+                            moved = e.move_next();
+                        }
+                        if moved {
+                            moved = e.move_next(); // Skip ArrayEnd.
+                        }
+                    }
+                    self.fields.push((qualified_name, value));
+                }
+                EventHeaderEnumeratorState::StructBegin => {
+                    path.push(name);
+                    moved = e.move_next();
+                }
+                EventHeaderEnumeratorState::StructEnd => {
+                    path.pop();
+                    moved = e.move_next();
+                }
+                _ => {
+                    moved = e.move_next();
+                }
+            }
+        }
+
+        return e.last_error();
+    }
+
+    /// Returns the name of the tracepoint that the event was written to, e.g.
+    /// "MyProvider_L5K1".
+    pub fn tracepoint_name(&self) -> &str {
+        return &self.tracepoint_name;
+    }
+
+    /// Returns the event's encoded bytes, starting with the eventheader, i.e. the
+    /// data that a tracepoint consumer would see as the event's user data.
+    pub fn data(&self) -> &[u8] {
+        return &self.data;
+    }
+
+    /// Returns the name of the provider that wrote the event.
+    pub fn provider_name(&self) -> &str {
+        return &self.provider_name;
+    }
+
+    /// Returns the event's name.
+    pub fn event_name(&self) -> &str {
+        return &self.event_name;
+    }
+
+    /// Returns the event's header (flags, version, id, tag, opcode, level).
+    pub fn header(&self) -> EventHeader {
+        return self.header;
+    }
+
+    /// Returns the event's level.
+    pub fn level(&self) -> Level {
+        return self.header.level;
+    }
+
+    /// Returns the event's keyword.
+    pub fn keyword(&self) -> u64 {
+        return self.keyword;
+    }
+
+    /// Returns the event's activity id, if any.
+    pub fn activity_id(&self) -> Option<&[u8; 16]> {
+        return self.activity_id.as_ref();
+    }
+
+    /// Returns the event's related activity id, if any.
+    pub fn related_id(&self) -> Option<&[u8; 16]> {
+        return self.related_id.as_ref();
+    }
+
+    /// Returns the event's provider-defined extension blocks as (kind, data) pairs,
+    /// in the order they appear in the event.
+    pub fn extensions(&self) -> &[(ExtensionKind, Vec<u8>)] {
+        return &self.extensions;
+    }
+
+    /// Returns the data of the first provider-defined extension block with the
+    /// specified kind, or `None` if the event has no such block.
+    pub fn extension(&self, kind: ExtensionKind) -> Option<&[u8]> {
+        return self
+            .extensions
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, data)| data.as_slice());
+    }
+
+    /// Returns the event's fields as (name, value) pairs, formatted as text.
+    ///
+    /// Members of structs are named "Struct.Member". Arrays of values or strings are
+    /// formatted like "1, 2, 3". Arrays of structs are formatted as JSON.
+    pub fn fields(&self) -> &[(String, String)] {
+        return &self.fields;
+    }
+
+    /// Returns the value of the first field with the specified name, formatted as
+    /// text, or `None` if the event has no such field.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        return self
+            .fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str());
+    }
+
+    /// Returns `Success` if the event was decoded successfully, or the error that
+    /// stopped decoding.
+    pub fn decode_error(&self) -> EventHeaderEnumeratorError {
+        return self.error;
+    }
+
+    /// Panics if the event's level is not `level`.
+    #[track_caller]
+    pub fn assert_level(&self, level: Level) -> &Self {
+        assert!(
+            self.header.level == level,
+            "event {}:{} has level {}, expected {}",
+            self.provider_name,
+            self.event_name,
+            self.header.level,
+            level
+        );
+        return self;
+    }
+
+    /// Panics if the event's keyword is not `keyword`.
+    #[track_caller]
+    pub fn assert_keyword(&self, keyword: u64) -> &Self {
+        assert!(
+            self.keyword == keyword,
+            "event {}:{} has keyword 0x{:x}, expected 0x{:x}",
+            self.provider_name,
+            self.event_name,
+            self.keyword,
+            keyword
+        );
+        return self;
+    }
+
+    /// Panics if the event does not have a field named `name` whose value, formatted
+    /// as text (see [`CapturedEvent::fields`]), equals `expected.to_string()`.
+    #[track_caller]
+    pub fn assert_field(&self, name: &str, expected: impl fmt::Display) -> &Self {
+        let expected = expected.to_string();
+        match self.field(name) {
+            None => panic!(
+                "event {}:{} has no field {}. Fields: {:?}",
+                self.provider_name, self.event_name, name, self.fields
+            ),
+            Some(actual) => assert!(
+                actual == expected,
+                "event {}:{} field {} is {}, expected {}",
+                self.provider_name,
+                self.event_name,
+                name,
+                actual,
+                expected
+            ),
+        }
+        return self;
+    }
+}
+
+struct EventListDisplay<'a>(&'a [CapturedEvent]);
+
+impl<'a> fmt::Display for EventListDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str(" (none)");
+        }
+
+        for event in self.0 {
+            write!(
+                f,
+                "\n  {}:{} level={} keyword=0x{:x} fields={:?}",
+                event.provider_name,
+                event.event_name,
+                event.header.level,
+                event.keyword,
+                event.fields
+            )?;
+        }
+
+        return Ok(());
+    }
+}
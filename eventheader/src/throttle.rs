@@ -0,0 +1,154 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering;
+
+/// Per-call-site rate limiting and sampling state for an event.
+///
+/// An `EventThrottle` is normally stored in a `static` next to the code that writes
+/// the event. It is used automatically by [`write_event!`](crate::write_event) when
+/// the `rate_limit(N per second)` or `sample(1 in N)` options are specified, and can
+/// be used with `eventheader_dynamic::EventBuilder::write_throttled`.
+///
+/// The throttle should be checked only after the event's tracepoint is known to be
+/// enabled so that no state is updated when nobody is listening.
+///
+/// [`EventThrottle::check`] counts the occurrences that are dropped. The count is
+/// returned (and reset to 0) by the next call that allows an event to be written,
+/// so the written event can report how many occurrences were suppressed since the
+/// previous written event.
+///
+/// Rate limiting uses a token bucket that holds up to `N` tokens and is refilled at
+/// `N` tokens per second (measured with a coarse monotonic clock). A burst of at most
+/// `N` events can be written at once, after which events are allowed at a rate of
+/// about `N` per second. If no clock is available (i.e. not Linux or `user_events`
+/// feature disabled), only sampling is applied.
+#[derive(Debug)]
+pub struct EventThrottle {
+    rate_limit_per_second: u32,
+    sample_one_in: u32,
+    sample_counter: AtomicU32,
+    full_time: AtomicU32, // Time (microseconds) at which the bucket will be full.
+    suppressed: AtomicU32,
+}
+
+impl EventThrottle {
+    /// Returns a new throttle.
+    ///
+    /// - `rate_limit_per_second` is the maximum number of events to be written per
+    ///   second, or 0 for no rate limit. Limits greater than 1000000 are not
+    ///   enforced.
+    ///
+    /// - `sample_one_in` is N for writing 1 of every N occurrences, or 0 or 1 to write
+    ///   every occurrence. Sampling is applied before rate limiting.
+    pub const fn new(rate_limit_per_second: u32, sample_one_in: u32) -> Self {
+        return Self {
+            rate_limit_per_second,
+            sample_one_in,
+            sample_counter: AtomicU32::new(0),
+            full_time: AtomicU32::new(0),
+            suppressed: AtomicU32::new(0),
+        };
+    }
+
+    /// Returns the maximum number of events per second, or 0 for no rate limit.
+    pub const fn rate_limit_per_second(&self) -> u32 {
+        return self.rate_limit_per_second;
+    }
+
+    /// Returns N for 1-in-N sampling, or 0 or 1 for no sampling.
+    pub const fn sample_one_in(&self) -> u32 {
+        return self.sample_one_in;
+    }
+
+    /// Returns the number of occurrences that have been suppressed since the last
+    /// occurrence that was allowed.
+    pub fn suppressed(&self) -> u32 {
+        return self.suppressed.load(Ordering::Relaxed);
+    }
+
+    /// Records an occurrence of the event and determines whether it should be written.
+    ///
+    /// Returns `None` if the occurrence should be dropped. Otherwise returns
+    /// `Some(suppressed_count)`, where `suppressed_count` is the number of occurrences
+    /// that were dropped since the previous occurrence that was allowed (saturating at
+    /// `u32::MAX`).
+    pub fn check(&self) -> Option<u32> {
+        let allowed = self.sample_allows() && self.rate_allows();
+        return if allowed {
+            Some(self.suppressed.swap(0, Ordering::Relaxed))
+        } else {
+            // Saturating increment.
+            let _ = self
+                .suppressed
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_add(1));
+            None
+        };
+    }
+
+    fn sample_allows(&self) -> bool {
+        return self.sample_one_in <= 1
+            || self.sample_counter.fetch_add(1, Ordering::Relaxed) % self.sample_one_in == 0;
+    }
+
+    fn rate_allows(&self) -> bool {
+        const MICROS_PER_SECOND: u32 = 1_000_000;
+
+        if self.rate_limit_per_second == 0 || self.rate_limit_per_second > MICROS_PER_SECOND {
+            return true;
+        }
+
+        let now = match monotonic_micros() {
+            Some(now) => now,
+            None => return true,
+        };
+
+        // Each event uses one token, i.e. moves full_time later by `interval`. The
+        // event is allowed if the bucket still has a token, i.e. if full_time would
+        // be at most 1 second (N tokens) in the future.
+        let interval = MICROS_PER_SECOND / self.rate_limit_per_second;
+        let tolerance = interval * (self.rate_limit_per_second - 1);
+        return self
+            .full_time
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |full_time| {
+                // The clock wraps, so compare using the wrapped difference. If the
+                // bucket is already full (full_time is in the past, or is too far in
+                // the future to be valid because it is from before the clock wrapped),
+                // start from now.
+                let ahead = full_time.wrapping_sub(now);
+                if ahead as i32 <= 0 || ahead > tolerance + interval {
+                    Some(now.wrapping_add(interval))
+                } else if ahead <= tolerance {
+                    Some(full_time.wrapping_add(interval))
+                } else {
+                    None // Bucket is empty.
+                }
+            })
+            .is_ok();
+    }
+}
+
+/// Returns the current value of a coarse monotonic clock in microseconds, truncated
+/// to 32 bits (wraps about every 71 minutes).
+#[cfg(all(target_os = "linux", feature = "user_events"))]
+fn monotonic_micros() -> Option<u32> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    return if 0 != unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC_COARSE, &mut ts) } {
+        None
+    } else {
+        Some(
+            (ts.tv_sec as u32)
+                .wrapping_mul(1_000_000)
+                .wrapping_add(ts.tv_nsec as u32 / 1000),
+        )
+    };
+}
+
+#[cfg(not(all(target_os = "linux", feature = "user_events")))]
+fn monotonic_micros() -> Option<u32> {
+    return None;
+}
@@ -0,0 +1,122 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+#![allow(clippy::needless_return)]
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const FIXTURE_MAIN: &str = r#"
+use eventheader as eh;
+
+eh::define_provider!(
+    MANIFEST_PROVIDER,
+    "Manifest_Test",
+    group_name("manifestgroup"),
+    keywords(Alpha = 0x1, Beta = 0x20)
+);
+
+const VALUE_ENCODING: eh::FieldEncoding = eh::FieldEncoding::Value32;
+const VALUE_FORMAT: eh::FieldFormat = eh::FieldFormat::Errno;
+const VALUE_TAG: u16 = 0x12;
+
+fn main() {
+    eh::write_event!(
+        MANIFEST_PROVIDER,
+        "ManifestEvent",
+        id_version(5, 2),
+        level(Warning),
+        keyword(0x10),
+        opcode(Info),
+        tag(3),
+        u32("Plain", &1, format(HexInt)),
+        raw_field("Expr", VALUE_ENCODING, &[0, 0, 0, 0], format(VALUE_FORMAT), tag(VALUE_TAG)),
+        raw_struct("Raw", 2),
+        raw_meta("A", Value8),
+        raw_meta_slice("B", Value16, format(HexInt)),
+        raw_data(&[1, 0, 0]),
+        struct("Nested", { u8("C", &1), str8("D", "d") }),
+    );
+}
+"#;
+
+/// Builds a fixture crate with EVENTHEADER_MANIFEST_DIR set and checks the entries
+/// written by `define_provider!` and `write_event!`.
+///
+/// Ignored by default because it runs a nested `cargo build`, which may need network
+/// access to resolve dependencies. Run with `cargo test --test manifest -- --ignored`.
+#[test]
+#[ignore = "runs a nested cargo build"]
+fn manifest_export() {
+    let tmp_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("manifest_fixture");
+    let crate_dir = tmp_dir.join("crate");
+    let manifest_dir = tmp_dir.join("manifest");
+    let eventheader_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let _ = fs::remove_dir_all(&manifest_dir);
+    fs::create_dir_all(crate_dir.join("src")).unwrap();
+    fs::write(
+        crate_dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"manifest_fixture\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n\
+            [dependencies]\neventheader = {{ path = {:?} }}\n\n[workspace]\n",
+            eventheader_dir
+        ),
+    )
+    .unwrap();
+
+    // Use the workspace's lock file (if any) so the build does not need to resolve
+    // dependencies. Rewriting main.rs makes cargo re-expand the macros.
+    if let Ok(lock) = fs::read(eventheader_dir.join("../Cargo.lock")) {
+        fs::write(crate_dir.join("Cargo.lock"), lock).unwrap();
+    }
+    fs::write(crate_dir.join("src/main.rs"), FIXTURE_MAIN).unwrap();
+
+    let output = Command::new(env!("CARGO"))
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(crate_dir.join("Cargo.toml"))
+        .env("CARGO_TARGET_DIR", tmp_dir.join("target"))
+        .env("EVENTHEADER_MANIFEST_DIR", &manifest_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(manifest_dir.join("manifest_fixture")).unwrap() {
+        entries.push(fs::read_to_string(entry.unwrap().path()).unwrap());
+    }
+    entries.sort();
+
+    assert_eq!(
+        entries,
+        [
+            concat!(
+                r#"{"kind":"event","crate":"manifest_fixture","provider":"MANIFEST_PROVIDER","#,
+                r#""name":"ManifestEvent","id":5,"version":2,"level":"Warning","keyword":16,"#,
+                r#""opcode":"Info","tag":3,"fields":["#,
+                r#"{"name":"Plain","encoding":"Value32","format":"HexInt","tag":0,"array":false},"#,
+                r#"{"name":"Expr","encoding":"VALUE_ENCODING","format":"VALUE_FORMAT","tag":"VALUE_TAG","array":false},"#,
+                r#"{"name":"Raw","encoding":"Struct","field_count":2,"tag":0,"array":false},"#,
+                r#"{"name":"A","encoding":"Value8","format":"Default","tag":0,"array":false},"#,
+                r#"{"name":"B","encoding":"Value16","format":"HexInt","tag":0,"array":true},"#,
+                r#"{"name":"Nested","encoding":"Struct","field_count":2,"tag":0,"array":false},"#,
+                r#"{"name":"C","encoding":"Value8","format":"Default","tag":0,"array":false},"#,
+                r#"{"name":"D","encoding":"StringLength16Char8","format":"Default","tag":0,"array":false}"#,
+                "]}\n",
+            ),
+            concat!(
+                r#"{"kind":"provider","crate":"manifest_fixture","symbol":"MANIFEST_PROVIDER","#,
+                r#""name":"Manifest_Test","group_name":"manifestgroup","#,
+                r#""keywords":{"Alpha":1,"Beta":32}}"#,
+                "\n",
+            ),
+        ]
+    );
+}
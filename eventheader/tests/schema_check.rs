@@ -0,0 +1,63 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+// The schema check settings are process-wide, so this test runs in its own process.
+
+#![allow(clippy::needless_return)]
+
+use std::sync::Mutex;
+
+use eventheader as eh;
+
+struct Unregister(&'static eh::Provider<'static>);
+
+impl Drop for Unregister {
+    fn drop(&mut self) {
+        self.0.unregister();
+    }
+}
+
+#[test]
+fn schema_check() {
+    static REPORTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    fn report(conflict: &eh::SchemaConflict) {
+        REPORTED.lock().unwrap().push(conflict.to_string());
+    }
+    fn fail(conflict: &eh::SchemaConflict) {
+        panic!("{}", conflict);
+    }
+
+    eh::define_provider!(PROV1, "SchemaCheckTest");
+    eh::write_event!(PROV1, "Event", u8("a", &1));
+    eh::write_event!(PROV1, "Event", i8("a", &1));
+
+    let _u = Unregister(&PROV1);
+    eh::set_schema_check(true);
+    assert!(eh::schema_check());
+
+    // Without a handler, conflicts are ignored.
+    unsafe { PROV1.register() };
+    PROV1.unregister();
+
+    // A handler reports conflicts.
+    eh::set_schema_conflict_handler(Some(report));
+    unsafe { PROV1.register() };
+    assert_eq!(
+        *REPORTED.lock().unwrap(),
+        ["provider \"SchemaCheckTest\": event \"Event\" is defined with different fields"]
+    );
+    PROV1.unregister();
+
+    // A panicking handler fails register, and the provider can still be unregistered.
+    eh::set_schema_conflict_handler(Some(fail));
+    let panic = std::panic::catch_unwind(|| unsafe { PROV1.register() });
+    let message = panic.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.ends_with("is defined with different fields"));
+    PROV1.unregister();
+
+    // Disabling the check skips the handler.
+    eh::set_schema_check(false);
+    assert!(!eh::schema_check());
+    unsafe { PROV1.register() };
+    eh::set_schema_conflict_handler(None);
+}
@@ -0,0 +1,239 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+// Capture mode is process-wide, so these tests are in their own test binary.
+#![cfg(all(feature = "testing", feature = "macros"))]
+#![allow(clippy::needless_return)]
+
+use eventheader as eh;
+use eventheader::testing::Capture;
+
+eh::define_provider!(CAPTURE_PROV, "TestCapture_Provider");
+eh::define_provider!(CAPTURE_GROUP, "TestCapture_Group", group_name("mygroup"));
+eh::define_provider!(CAPTURE_EXT, "TestCapture_Extensions");
+eh::define_provider!(CAPTURE_SUPPRESSED, "TestCapture_Suppressed");
+eh::define_provider!(CAPTURE_NESTED, "TestCapture_Nested");
+eh::define_provider!(CAPTURE_THROTTLED, "TestCapture_Throttled");
+
+#[test]
+fn capture() {
+    let capture = Capture::start();
+    unsafe { CAPTURE_PROV.register() };
+    unsafe { CAPTURE_GROUP.register() };
+
+    let aid = [1u8; 16];
+    let result = eh::write_event!(
+        CAPTURE_PROV,
+        "Event1",
+        level(Warning),
+        keyword(0x5),
+        activity_id(&aid),
+        u32("Field1", &42),
+        str8("Field2", "hello"),
+        struct("Field3", { i8("Nested", &-3i8), bool8("Flag", &true) }),
+        u16_slice("Field4", &[1u16, 2, 3][..]),
+    );
+    assert_eq!(result, 0);
+
+    eh::write_event!(CAPTURE_GROUP, "Event2", level(Informational));
+
+    let events = capture.events();
+    assert_eq!(events.len(), 2);
+
+    let event1 = capture
+        .assert_written("TestCapture_Provider", "Event1")
+        .assert_level(eh::Level::Warning)
+        .assert_keyword(0x5)
+        .assert_field("Field1", 42)
+        .assert_field("Field2", "hello")
+        .assert_field("Field3.Nested", -3)
+        .assert_field("Field3.Flag", true)
+        .assert_field("Field4", "1, 2, 3")
+        .clone();
+    assert_eq!(event1.tracepoint_name(), "TestCapture_Provider_L3K5");
+    assert_eq!(event1.activity_id(), Some(&aid));
+    assert_eq!(event1.related_id(), None);
+    assert_eq!(event1.field("Missing"), None);
+    assert_eq!(
+        event1.decode_error(),
+        tracepoint_decode::EventHeaderEnumeratorError::Success
+    );
+
+    let event2 = capture.assert_written("TestCapture_Group", "Event2");
+    assert_eq!(event2.tracepoint_name(), "TestCapture_Group_L4K1Gmygroup");
+    assert!(event2.fields().is_empty());
+
+    capture.assert_not_written("TestCapture_Provider", "Event2");
+
+    capture.clear();
+    assert!(capture.events().is_empty());
+
+    // Nested captures: the innermost capture on the thread records the events.
+    {
+        let inner = Capture::start();
+        eh::write_event!(CAPTURE_PROV, "Inner", level(Error));
+        inner.assert_written("TestCapture_Provider", "Inner");
+    }
+    capture.assert_not_written("TestCapture_Provider", "Inner");
+
+    // Events written on other threads are not recorded.
+    std::thread::spawn(|| {
+        assert_eq!(9, eh::write_event!(CAPTURE_PROV, "OtherThread"));
+    })
+    .join()
+    .unwrap();
+    assert!(capture.events().is_empty());
+
+    CAPTURE_PROV.unregister();
+    CAPTURE_GROUP.unregister();
+    assert_eq!(9, eh::write_event!(CAPTURE_PROV, "Inner", level(Error)));
+}
+
+#[test]
+fn capture_extensions() {
+    const TRACE_FLAGS: eh::ExtensionKind = eh::ExtensionKind::from_int(0x100);
+    const TENANT: eh::ExtensionKind = eh::ExtensionKind::from_int(0x101);
+
+    let capture = Capture::start();
+    unsafe { CAPTURE_EXT.register() };
+
+    let aid = [3u8; 16];
+    let tenant = String::from("contoso");
+    let result = eh::write_event!(
+        CAPTURE_EXT,
+        "Extended",
+        activity_id(&aid),
+        extension(TRACE_FLAGS, &[1u8]),
+        extension(TENANT, &tenant),
+        u32("Field1", &7),
+    );
+    assert_eq!(result, 0);
+
+    let event = capture
+        .assert_written("TestCapture_Extensions", "Extended")
+        .assert_field("Field1", 7)
+        .clone();
+    assert_eq!(event.activity_id(), Some(&aid));
+    assert_eq!(event.extension(TRACE_FLAGS), Some(&[1u8][..]));
+    assert_eq!(event.extension(TENANT), Some(&b"contoso"[..]));
+    assert_eq!(event.extensions().len(), 2);
+
+    eh::write_event!(CAPTURE_EXT, "Plain", u32("Field1", &8));
+    let event = capture.assert_written("TestCapture_Extensions", "Plain");
+    assert!(event.extensions().is_empty());
+    assert_eq!(event.field("Field1"), Some("8"));
+
+    CAPTURE_EXT.unregister();
+}
+
+#[test]
+fn capture_suppressed() {
+    // The policy is process-wide, so only suppress this test's provider.
+    const RULES: &str = "provider=TestCapture_Suppressed, maxlevel=Warning;\
+        provider=TestCapture_Suppressed, event=Quiet";
+
+    let capture = Capture::start();
+    unsafe { CAPTURE_SUPPRESSED.register() };
+
+    let policy = eh::SuppressionPolicy::parse(RULES).unwrap();
+    eh::set_suppression_policy(Some(Box::leak(Box::new(policy))));
+    assert_eq!(eh::suppression_policy(), Some(&policy));
+
+    let loud = eh::write_event!(CAPTURE_SUPPRESSED, "Loud", level(Error));
+    let chatty = eh::write_event!(CAPTURE_SUPPRESSED, "Chatty", level(Verbose));
+    let quiet = eh::write_event!(CAPTURE_SUPPRESSED, "Quiet", level(Error));
+    assert_eq!((loud, chatty, quiet), (0, 9, 9));
+    let error = eh::EventProvider::enabled(&CAPTURE_SUPPRESSED, eh::Level::Error, 1);
+    let verbose = eh::EventProvider::enabled(&CAPTURE_SUPPRESSED, eh::Level::Verbose, 1);
+    assert_eq!((error, verbose), (true, false));
+
+    capture.assert_written("TestCapture_Suppressed", "Loud");
+    capture.assert_not_written("TestCapture_Suppressed", "Chatty");
+    capture.assert_not_written("TestCapture_Suppressed", "Quiet");
+
+    eh::set_suppression_policy(None);
+    let chatty = eh::write_event!(CAPTURE_SUPPRESSED, "Chatty", level(Verbose));
+    assert_eq!(chatty, 0);
+    capture.assert_written("TestCapture_Suppressed", "Chatty");
+
+    CAPTURE_SUPPRESSED.unregister();
+}
+
+#[test]
+fn capture_nested_structs() {
+    let capture = Capture::start();
+    unsafe { CAPTURE_NESTED.register() };
+
+    // write_event! accepts up to 8 levels of nested structs, the decoder's limit.
+    let result = eh::write_event!(
+        CAPTURE_NESTED,
+        "Nested",
+        struct("a", {
+            struct("b", {
+                struct("c", {
+                    struct("d", {
+                        struct("e", {
+                            struct("f", {
+                                struct("g", { struct("h", { u32("x", &7, format(Float)) }) })
+                            })
+                        })
+                    })
+                })
+            })
+        }),
+        u8("Flag", &1, format(Boolean)),
+    );
+    assert_eq!(result, 0);
+
+    let event = capture
+        .assert_written("TestCapture_Nested", "Nested")
+        .clone();
+    assert_eq!(
+        event.decode_error(),
+        tracepoint_decode::EventHeaderEnumeratorError::Success
+    );
+    assert!(event.field("a.b.c.d.e.f.g.h.x").is_some());
+    assert_eq!(event.field("Flag"), Some("true"));
+
+    CAPTURE_NESTED.unregister();
+}
+
+#[test]
+fn capture_throttled() {
+    let capture = Capture::start();
+    unsafe { CAPTURE_THROTTLED.register() };
+
+    // Dropped occurrences return EAGAIN (11).
+    let mut results = Vec::new();
+    for i in 0..3u32 {
+        results.push(eh::write_event!(
+            CAPTURE_THROTTLED,
+            "Sampled",
+            sample(1 in 2),
+            u32("i", &i),
+        ));
+    }
+    assert_eq!(results, [0, 11, 0]);
+
+    let sampled: Vec<_> = capture
+        .events()
+        .into_iter()
+        .filter(|event| event.provider_name() == "TestCapture_Throttled")
+        .collect();
+    assert_eq!(sampled.len(), 2);
+    sampled[0]
+        .assert_field("i", 0)
+        .assert_field("_suppressed", 0);
+    sampled[1]
+        .assert_field("i", 2)
+        .assert_field("_suppressed", 1);
+
+    CAPTURE_THROTTLED.unregister();
+}
+
+#[test]
+#[should_panic(expected = "was not written")]
+fn assert_written_panics() {
+    let capture = Capture::start();
+    capture.assert_written("TestCapture_Provider", "NotWritten");
+}
@@ -105,6 +105,280 @@ fn time_from_duration() {
     );
 }
 
+#[test]
+fn event_throttle() {
+    let unthrottled = eh::EventThrottle::new(0, 0);
+    for _ in 0..5 {
+        assert_eq!(Some(0), unthrottled.check());
+    }
+
+    let sampled = eh::EventThrottle::new(0, 3);
+    assert_eq!(3, sampled.sample_one_in());
+    assert_eq!(Some(0), sampled.check());
+    assert_eq!(None, sampled.check());
+    assert_eq!(None, sampled.check());
+    assert_eq!(2, sampled.suppressed());
+    assert_eq!(Some(2), sampled.check());
+    assert_eq!(0, sampled.suppressed());
+
+    // Without a clock, rate limiting is not applied. With a clock, a burst of 3 is
+    // allowed, plus any tokens that are refilled while the loop runs.
+    let limited = eh::EventThrottle::new(3, 0);
+    assert_eq!(3, limited.rate_limit_per_second());
+    let allowed = (0..100).filter(|_| limited.check().is_some()).count();
+    assert!(allowed == 100 || (3..=6).contains(&allowed));
+}
+
+fn write_via_event_provider(provider: &dyn eh::EventProvider, level: eh::Level) -> i32 {
+    const META: &[u8] = b"Encoded\0value\0\x04"; // name, Value32
+    let header = eh::EventHeader::from_parts(
+        eh::HeaderFlags::DefaultWithExtension,
+        0,
+        0,
+        0,
+        eh::Opcode::Info,
+        level,
+    );
+    _ = provider.enabled(level, 0x5);
+    return provider.write_encoded(&header, 0x5, None, None, META, &123u32.to_ne_bytes());
+}
+
+#[test]
+fn event_provider() {
+    eh::define_provider!(PROV4, "TraceLoggingDynamicTest");
+    let _u = Unregister(&PROV4);
+
+    assert_eq!("TraceLoggingDynamicTest", eh::EventProvider::name(&PROV4));
+    assert_eq!("", eh::EventProvider::options(&PROV4));
+
+    // No tracepoint has been defined for Warning + 0x5.
+    assert!(!eh::EventProvider::enabled(&PROV4, eh::Level::Warning, 0x5));
+    assert_eq!(9, write_via_event_provider(&PROV4, eh::Level::Warning));
+
+    // Tracepoint defined for Verbose + 0x5, but the provider is not registered.
+    eh::write_event!(PROV4, "Static", level(Verbose), keyword(0x5));
+    assert!(!eh::EventProvider::enabled(&PROV4, eh::Level::Verbose, 0x5));
+    assert_eq!(9, write_via_event_provider(&PROV4, eh::Level::Verbose));
+
+    unsafe { PROV4.register() };
+    _ = write_via_event_provider(&PROV4, eh::Level::Verbose);
+}
+
+#[test]
+fn event_provider_during_register() {
+    eh::define_provider!(PROV11, "ConcurrentRegisterTest");
+    let _u = Unregister(&PROV11);
+    eh::write_event!(PROV11, "Event1", level(Verbose), keyword(0x5));
+    eh::write_event!(PROV11, "Event2", level(Warning), keyword(0x5));
+    eh::write_event!(PROV11, "Event3", level(Verbose), keyword(0x5));
+
+    // EventProvider methods may run while register sorts the list of tracepoints.
+    let done = std::sync::atomic::AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                _ = eh::EventProvider::enabled(&PROV11, eh::Level::Verbose, 0x5);
+                _ = write_via_event_provider(&PROV11, eh::Level::Warning);
+                let check = &mut |status: &eh::TracepointStatus| assert_eq!(status.keyword(), 0x5);
+                eh::EventProvider::for_each_tracepoint(&PROV11, check);
+            }
+        });
+
+        for _ in 0..20 {
+            unsafe { PROV11.register() };
+            PROV11.unregister();
+        }
+        done.store(true, std::sync::atomic::Ordering::Relaxed);
+    });
+}
+
+#[test]
+fn provider_tracepoints() {
+    eh::define_provider!(PROV5, "TracepointStatusTest", group_name("mygroup"));
+    let _u = Unregister(&PROV5);
+    eh::write_event!(PROV5, "Event1", level(Verbose), keyword(0x5));
+    eh::write_event!(PROV5, "Event2", level(Verbose), keyword(0x5));
+    eh::write_event!(PROV5, "Event3", level(Warning), keyword(0x1));
+
+    fn statuses(provider: &dyn eh::EventProvider) -> Vec<(String, u8, u64, i32)> {
+        let mut statuses = Vec::new();
+        provider.for_each_tracepoint(&mut |status| {
+            statuses.push((
+                status.to_string(),
+                status.level().as_int(),
+                status.keyword(),
+                status.errno(),
+            ));
+        });
+        statuses.sort();
+        statuses
+    }
+
+    fn is_listed() -> bool {
+        let mut found = false;
+        eh::for_each_provider(|provider| found |= provider.name() == "TracepointStatusTest");
+        found
+    }
+
+    // Each level + keyword is reported once.
+    let before = statuses(&PROV5);
+    assert_eq!(
+        before,
+        [
+            ("TracepointStatusTest_L3K1Gmygroup".to_string(), 3, 1, 0),
+            ("TracepointStatusTest_L5K5Gmygroup".to_string(), 5, 5, 0),
+        ]
+    );
+    assert!(!is_listed());
+
+    let result = unsafe { PROV5.register() };
+    assert!(is_listed());
+    let after = statuses(&PROV5);
+    assert_eq!(after.len(), 2);
+    for status in &after {
+        assert!(result != 0 || status.3 == 0);
+    }
+
+    PROV5.unregister();
+    assert!(!is_listed());
+}
+
+#[test]
+fn schema_conflicts() {
+    eh::define_provider!(PROV6, "SchemaConflictTest");
+    eh::write_event!(PROV6, "Same", level(Verbose), u8("a", &1));
+    eh::write_event!(PROV6, "Same", level(Warning), keyword(0x2), u8("a", &1));
+    eh::write_event!(PROV6, "Fields", u8("a", &1));
+    eh::write_event!(PROV6, "Fields", u16("a", &1));
+    eh::write_event!(PROV6, "Id1", id_version(5, 1), u8("a", &1));
+    eh::write_event!(PROV6, "Id2", id_version(5, 1), u8("a", &1));
+    eh::write_event!(PROV6, "Id3", id_version(5, 2), u8("a", &1));
+
+    let mut conflicts = Vec::new();
+    PROV6.for_each_schema_conflict(|conflict| {
+        let mut names = [
+            conflict.event_name().to_string(),
+            conflict.other_event_name().to_string(),
+        ];
+        names.sort();
+        conflicts.push((
+            names,
+            conflict.kind(),
+            conflict.id(),
+            conflict.version(),
+            conflict.to_string(),
+        ));
+    });
+    conflicts.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(conflicts.len(), 2);
+
+    let (names, kind, id, version, message) = &conflicts[0];
+    assert_eq!(names, &["Fields", "Fields"]);
+    assert_eq!(*kind, eh::SchemaConflictKind::SameName);
+    assert_eq!((*id, *version), (0, 0));
+    assert_eq!(
+        message,
+        "provider \"SchemaConflictTest\": event \"Fields\" is defined with different fields"
+    );
+
+    let (names, kind, id, version, message) = &conflicts[1];
+    assert_eq!(names, &["Id1", "Id2"]);
+    assert_eq!(*kind, eh::SchemaConflictKind::SameIdVersion);
+    assert_eq!((*id, *version), (5, 1));
+    assert!(message.ends_with("use id 5 version 1 with different schemas"));
+
+    eh::define_provider!(PROV7, "SchemaConflictTest2");
+    eh::write_event!(PROV7, "Event", u8("a", &1));
+    eh::write_event!(PROV7, "Event", u8("a", &1));
+    // Same decoded fields: tag(0) adds a format byte and tag to the metadata.
+    eh::write_event!(PROV7, "Tagged", u8("a", &1));
+    eh::write_event!(PROV7, "Tagged", level(Warning), u8("a", &1, tag(0)));
+    let mut count = 0;
+    PROV7.for_each_schema_conflict(|_| count += 1);
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn named_keywords() {
+    const OTHER: u64 = 0x10;
+    eh::define_provider!(
+        PROV9,
+        "NamedKeywordsTest",
+        keywords(Networking = 0x2, Storage = 0x4)
+    );
+    eh::write_event!(PROV9, "Net", level(Verbose), keyword(Networking));
+    eh::write_event!(PROV9, "Both", keyword(Networking), keyword(Storage));
+    eh::write_event!(PROV9, "Or", keyword(Storage | OTHER));
+    eh::write_event!(PROV9, "Raw", keyword(0x8));
+
+    assert_eq!(PROV9::Networking, 0x2);
+    assert_eq!(PROV9::Storage, 0x4);
+    assert_eq!(PROV9.keywords(), [("Networking", 0x2), ("Storage", 0x4)]);
+    assert!(!eh::provider_enabled!(PROV9, eh::Level::Verbose, Storage));
+
+    let mut keywords = Vec::new();
+    eh::EventProvider::for_each_tracepoint(&PROV9, &mut |status| keywords.push(status.keyword()));
+    keywords.sort();
+    assert_eq!(keywords, [0x2, 0x6, 0x8, 0x14]);
+
+    eh::define_provider!(PROV10, "NoKeywordsTest");
+    assert!(PROV10.keywords().is_empty());
+}
+
+#[test]
+fn suppression_policy() {
+    use eh::SuppressionPolicyError as E;
+
+    assert_eq!(eh::SUPPRESSION_POLICY_ENV, "EVENTHEADER_SUPPRESS");
+    assert_eq!(eh::SuppressionPolicy::parse("").unwrap().rules(), "");
+    assert!(eh::SuppressionPolicy::parse(" ; provider=P ;").is_ok());
+    assert_eq!(eh::SuppressionPolicy::parse(" , "), Err(E::EmptyCondition));
+    assert_eq!(
+        eh::SuppressionPolicy::parse("provider"),
+        Err(E::MissingValue)
+    );
+    assert_eq!(
+        eh::SuppressionPolicy::parse("color=red"),
+        Err(E::UnknownKey)
+    );
+    assert_eq!(eh::SuppressionPolicy::parse("event="), Err(E::InvalidName));
+    assert_eq!(
+        eh::SuppressionPolicy::parse("maxlevel=Loud"),
+        Err(E::InvalidLevel)
+    );
+    assert_eq!(
+        eh::SuppressionPolicy::parse("keyword=0xZ"),
+        Err(E::InvalidKeyword)
+    );
+    assert_eq!(
+        E::UnknownKey.to_string(),
+        "condition key must be provider, event, maxlevel, or keyword"
+    );
+
+    let max_rules = vec!["keyword=1"; eh::SuppressionPolicy::CONDITIONS_MAX].join(";");
+    assert!(eh::SuppressionPolicy::parse(&max_rules).is_ok());
+    assert_eq!(
+        eh::SuppressionPolicy::parse(&(max_rules + ",event=E")),
+        Err(E::TooManyConditions)
+    );
+
+    let policy = eh::SuppressionPolicy::parse(
+        "provider=Noisy; provider=Net, maxlevel=Warning; event=Spam, keyword=0x30; maxlevel=5",
+    )
+    .unwrap();
+    let suppresses = |provider, event, level, keyword| {
+        return policy.suppresses(provider, event, level, keyword);
+    };
+    assert!(suppresses("Noisy", "E", eh::Level::CriticalError, 1));
+    assert!(suppresses("Net", "E", eh::Level::Informational, 1));
+    assert!(!suppresses("Net", "E", eh::Level::Warning, 1));
+    assert!(suppresses("Other", "Spam", eh::Level::Error, 0x10));
+    assert!(!suppresses("Other", "Spam", eh::Level::Error, 0x1));
+    assert!(!suppresses("Other", "Ham", eh::Level::Error, 0x10));
+    assert!(suppresses("Other", "E", eh::Level::from_int(6), 0));
+    assert!(!suppresses("Other", "E", eh::Level::Verbose, 0));
+}
+
 struct Unregister(&'static eh::Provider<'static>);
 
 impl Drop for Unregister {
@@ -187,6 +461,18 @@ fn write_event() {
 
     eh::write_event!(PROV, "tag0xFEDC", tag(0xFEDC));
 
+    for i in 0..10u32 {
+        eh::write_event!(PROV, "rate_limit", rate_limit(2 per second), u32("i", &i));
+        eh::write_event!(PROV, "sample", sample(1 in 3), u32("i", &i));
+        eh::write_event!(
+            PROV,
+            "rate_limit_sample",
+            sample(1 in 2),
+            rate_limit(1 + 1 per second),
+            u32("i", &i),
+        );
+    }
+
     eh::write_event!(PROV, "fieldtag", u8("0xFEDC", &0, tag(0xFEDC)),);
 
     eh::write_event!(
@@ -5,7 +5,7 @@ use ehd::FieldFormat;
 use eventheader_dynamic as ehd;
 
 fn main() {
-    let mut prov1 = ehd::Provider::new(
+    let prov1 = ehd::Provider::new(
         "MyProv_1",
         ehd::Provider::new_options().group_name("mygroup"),
     );
@@ -0,0 +1,167 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Storage for the metadata and data of an [`EventBuilder`](crate::EventBuilder).
+///
+/// Implemented by `Vec<u8>` (the default, which grows as needed) and by
+/// [`FixedBuffer`] (fixed capacity, never allocates). This trait is sealed and
+/// cannot be implemented outside of this crate.
+pub trait BuilderBuffer: sealed::Buffer + fmt::Debug {}
+
+impl BuilderBuffer for Vec<u8> {}
+
+impl<const N: usize> BuilderBuffer for FixedBuffer<N> {}
+
+pub(crate) mod sealed {
+    /// Operations used by `EventBuilder` to encode fields.
+    pub trait Buffer {
+        fn bytes(&self) -> &[u8];
+        fn bytes_mut(&mut self) -> &mut [u8];
+        fn len(&self) -> usize;
+        fn clear(&mut self);
+        fn truncate(&mut self, len: usize);
+        fn reserve(&mut self, additional: usize);
+        fn extend_from_slice(&mut self, value: &[u8]);
+
+        /// Returns true if bytes were discarded because the buffer was full.
+        fn overflowed(&self) -> bool;
+
+        fn push(&mut self, value: u8) {
+            self.extend_from_slice(&[value]);
+        }
+    }
+}
+
+impl sealed::Buffer for Vec<u8> {
+    fn bytes(&self) -> &[u8] {
+        return self;
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        return self;
+    }
+
+    fn len(&self) -> usize {
+        return Vec::len(self);
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    fn extend_from_slice(&mut self, value: &[u8]) {
+        Vec::extend_from_slice(self, value);
+    }
+
+    fn overflowed(&self) -> bool {
+        return false;
+    }
+
+    fn push(&mut self, value: u8) {
+        Vec::push(self, value);
+    }
+}
+
+/// A fixed-capacity [`BuilderBuffer`] that stores up to `N` bytes inline and never
+/// allocates. Used by [`FixedEventBuilder`](crate::FixedEventBuilder).
+///
+/// If a field does not fit, the bytes that fit are kept, the remaining bytes are
+/// discarded, and the buffer is marked as overflowed until it is cleared (i.e. until
+/// the builder is reset). An event whose buffers have overflowed is not written.
+pub struct FixedBuffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+    overflowed: bool,
+}
+
+impl<const N: usize> FixedBuffer<N> {
+    /// Returns an empty buffer.
+    pub const fn new() -> Self {
+        return Self {
+            bytes: [0; N],
+            len: 0,
+            overflowed: false,
+        };
+    }
+
+    /// Returns a metadata buffer containing an empty event name (a single nul).
+    pub(crate) const fn new_meta() -> Self {
+        return Self {
+            bytes: [0; N],
+            len: if N == 0 { 0 } else { 1 },
+            overflowed: N == 0,
+        };
+    }
+
+    /// Returns the buffer's capacity, `N`.
+    pub const fn capacity(&self) -> usize {
+        return N;
+    }
+}
+
+impl<const N: usize> Default for FixedBuffer<N> {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedBuffer<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f
+            .debug_struct("FixedBuffer")
+            .field("bytes", &&self.bytes[..self.len])
+            .field("overflowed", &self.overflowed)
+            .finish();
+    }
+}
+
+impl<const N: usize> sealed::Buffer for FixedBuffer<N> {
+    fn bytes(&self) -> &[u8] {
+        return &self.bytes[..self.len];
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        return &mut self.bytes[..self.len];
+    }
+
+    fn len(&self) -> usize {
+        return self.len;
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+        self.overflowed = false;
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+        }
+    }
+
+    fn reserve(&mut self, _additional: usize) {}
+
+    fn extend_from_slice(&mut self, value: &[u8]) {
+        let fit = value.len().min(N - self.len);
+        self.bytes[self.len..self.len + fit].copy_from_slice(&value[..fit]);
+        self.len += fit;
+        if fit != value.len() {
+            self.overflowed = true;
+        }
+    }
+
+    fn overflowed(&self) -> bool {
+        return self.overflowed;
+    }
+}
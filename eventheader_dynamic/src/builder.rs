@@ -1,28 +1,48 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT license.
 
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::string::ToString;
 use alloc::vec::Vec;
 use core::mem;
-use core::ptr::copy_nonoverlapping;
+use core::slice;
 
+use eventheader::EventThrottle;
+use eventheader::ExtensionKind;
 use eventheader::FieldEncoding;
 use eventheader::FieldFormat;
+use eventheader::Level;
 use eventheader::Opcode;
+use eventheader::TracepointName;
 use eventheader::_internal;
 use eventheader::_internal::EventDataDescriptor;
 use eventheader::_internal::EventHeader;
+use eventheader::_internal::EventHeaderExtension;
 use eventheader::_internal::HeaderFlags;
 
+use crate::buffer::BuilderBuffer;
+use crate::buffer::FixedBuffer;
+use crate::overflow;
 use crate::provider::EventSet;
-
-#[allow(unused_imports)] // For docs
+use crate::validation;
+use crate::OverflowPolicy;
 use crate::Provider;
+use crate::ValidationError;
 
-pub trait ValueField: Copy {}
+pub trait ValueField: Copy {
+    /// True for floating-point types. Used by `TemplateWriter` to check values
+    /// against the field's format.
+    const FLOAT: bool = false;
+}
 impl ValueField for bool {}
 impl ValueField for char {}
-impl ValueField for f32 {}
-impl ValueField for f64 {}
+impl ValueField for f32 {
+    const FLOAT: bool = true;
+}
+impl ValueField for f64 {
+    const FLOAT: bool = true;
+}
 impl ValueField for i8 {}
 impl ValueField for i16 {}
 impl ValueField for i32 {}
@@ -52,7 +72,7 @@ pub trait BinaryField: ValueField + Default + Eq {}
 impl BinaryField for i8 {}
 impl BinaryField for u8 {}
 
-trait ValueFieldEncoding: ValueField {
+pub(crate) trait ValueFieldEncoding: ValueField {
     const VALUE_ENCODING: FieldEncoding = match mem::size_of::<Self>() {
         1 => FieldEncoding::Value8,
         2 => FieldEncoding::Value16,
@@ -65,7 +85,7 @@ trait ValueFieldEncoding: ValueField {
 
 impl<T: ValueField> ValueFieldEncoding for T {}
 
-trait StringFieldEncoding: StringField {
+pub(crate) trait StringFieldEncoding: StringField {
     const STRING_ENCODING: FieldEncoding = match mem::size_of::<Self>() {
         1 => FieldEncoding::StringLength16Char8,
         2 => FieldEncoding::StringLength16Char16,
@@ -82,7 +102,7 @@ trait StringFieldEncoding: StringField {
 
 impl<T: StringField> StringFieldEncoding for T {}
 
-trait BinaryFieldEncoding: BinaryField {
+pub(crate) trait BinaryFieldEncoding: BinaryField {
     const BINARY_ENCODING: FieldEncoding = match mem::size_of::<Self>() {
         1 => FieldEncoding::BinaryLength16Char8,
         _ => panic!(),
@@ -142,16 +162,97 @@ impl<T: BinaryField> BinaryFieldEncoding for T {}
 ///
 /// Linux tracepoints have a 64KB size limit. The size includes event headers (timestamp,
 /// level, etc.), metadata (event name, field names, field types), and data (field values).
-/// Events that are too large will cause `builder.write` to return an error.
+/// Events that are too large will cause `builder.write` to return an error, unless
+/// an [OverflowPolicy] has been set via [EventBuilder::overflow_policy].
+/// [EventBuilder::encoded_size] and [EventBuilder::remaining_size] report how much
+/// of the limit the event uses as fields are added.
+///
+/// # Storage
+///
+/// By default, the builder stores the event in two `Vec<u8>` buffers that grow as
+/// needed. A [FixedEventBuilder] (an `EventBuilder<FixedBuffer<N>>`) stores the event
+/// in two inline `N`-byte buffers and never allocates, e.g. for use in a signal
+/// handler. Fields that do not fit in a fixed buffer make the event unwritable (see
+/// [EventBuilder::capacity_exceeded]). With the `pool` feature,
+/// `EventBuilder::pooled` returns a builder from a thread-local pool.
 #[derive(Debug)]
-pub struct EventBuilder {
-    meta: Vec<u8>,
-    data: Vec<u8>,
+pub struct EventBuilder<B: BuilderBuffer = Vec<u8>> {
+    meta: B,
+    data: B,
+    ext: Vec<u8>,    // Extension blocks, each with the chain flag set.
+    ext_last: usize, // Offset of the last extension block in ext.
+    invalid_field_name: bool,
     flags: HeaderFlags,
     version: u8,
     id: u16,
     tag: u16,
     opcode: Opcode,
+    overflow_policy: OverflowPolicy,
+    encode_provider: Option<EncodeProvider>,
+}
+
+/// The provider and keyword set by [`EventBuilder::encode_provider`].
+#[derive(Debug)]
+struct EncodeProvider {
+    name: String,
+    options: String,
+    keyword: u64,
+}
+
+/// The metadata and data to be written for an event, after applying the builder's
+/// overflow policy.
+struct Payload<'a> {
+    meta: &'a [u8],
+    data: Cow<'a, [u8]>,
+    truncated: bool,
+}
+
+impl<'a> Payload<'a> {
+    /// Returns the metadata of the `_truncated` field, or empty if not truncated.
+    fn trailer_meta(&self) -> &'static [u8] {
+        return if self.truncated {
+            overflow::TRUNCATED_META
+        } else {
+            &[]
+        };
+    }
+
+    /// Returns the data of the `_truncated` field, or empty if not truncated.
+    fn trailer_data(&self) -> &'static [u8] {
+        return if self.truncated {
+            overflow::TRUNCATED_DATA
+        } else {
+            &[]
+        };
+    }
+
+    /// Returns the total size of the metadata, including the `_truncated` field.
+    fn meta_len(&self) -> usize {
+        return self.meta.len() + self.trailer_meta().len();
+    }
+}
+
+/// The extension blocks to be written for an event. The blocks are stored with the
+/// chain flag set, so the last block's header is copied with the flag cleared.
+struct Extensions<'a> {
+    head: &'a [u8],
+    last_header: Option<EventHeaderExtension>,
+    tail: &'a [u8],
+}
+
+impl<'a> Extensions<'a> {
+    /// Returns true if there are any extension blocks.
+    fn any(&self) -> bool {
+        return self.last_header.is_some();
+    }
+
+    /// Returns a descriptor for the last block's header, or an empty descriptor.
+    fn last_header_desc(&self) -> EventDataDescriptor<'_> {
+        return match &self.last_header {
+            None => EventDataDescriptor::zero(),
+            Some(header) => EventDataDescriptor::from_value(header),
+        };
+    }
 }
 
 impl EventBuilder {
@@ -173,29 +274,81 @@ impl EventBuilder {
                 meta_capacity as usize
             }),
             data: Vec::with_capacity(data_capacity as usize),
+            ext: Vec::new(),
+            ext_last: 0,
+            invalid_field_name: false,
             flags: HeaderFlags::DefaultWithExtension,
             version: 0,
             id: 0,
             tag: 0,
             opcode: Opcode::Info,
+            overflow_policy: OverflowPolicy::Fail,
+            encode_provider: None,
         };
         b.meta.resize(1, 0); // u8 name_nul_termination = 0;
         return b;
     }
 
+    /// Returns the metadata and data buffers. The metadata starts with the
+    /// nul-terminated event name.
+    #[cfg(feature = "serde")]
+    pub(crate) fn raw_buffers_mut(&mut self) -> (&mut Vec<u8>, &mut Vec<u8>) {
+        return (&mut self.meta, &mut self.data);
+    }
+}
+
+/// An [`EventBuilder`] that stores the event's metadata and data in two inline
+/// `N`-byte buffers instead of allocating. Create one with
+/// [`EventBuilder::new_fixed`], e.g. `FixedEventBuilder::<1024>::new_fixed()`.
+///
+/// `FixedEventBuilder` has the same methods as `EventBuilder`. It does not allocate
+/// when building, writing, or encoding events (except when
+/// [OverflowPolicy::TruncateStrings] shortens an event that exceeds the 64KB limit,
+/// which requires `N` greater than 32KB). If the event's metadata or data does not
+/// fit in `N` bytes, [EventBuilder::capacity_exceeded] returns true and `write`
+/// returns `ERANGE` (34) until the builder is reset.
+pub type FixedEventBuilder<const N: usize> = EventBuilder<FixedBuffer<N>>;
+
+impl<const N: usize> EventBuilder<FixedBuffer<N>> {
+    /// Returns a new fixed-capacity event builder with `N`-byte metadata and data
+    /// buffers.
+    pub const fn new_fixed() -> Self {
+        return EventBuilder {
+            meta: FixedBuffer::new_meta(),
+            data: FixedBuffer::new(),
+            ext: Vec::new(),
+            ext_last: 0,
+            invalid_field_name: false,
+            flags: HeaderFlags::DefaultWithExtension,
+            version: 0,
+            id: 0,
+            tag: 0,
+            opcode: Opcode::Info,
+            overflow_policy: OverflowPolicy::Fail,
+            encode_provider: None,
+        };
+    }
+}
+
+impl<B: BuilderBuffer> EventBuilder<B> {
     /// Clears the previous event (if any) from the builder and starts building a new
-    /// event.
+    /// event. The builder's [OverflowPolicy] is not changed.
     ///
     /// - `name` is the event name. It should be short and unique. It must not contain any
     ///   `'\0'` bytes.
     ///
     /// - `event_tag` is a 16-bit integer that will be recorded in the event and can be
     ///   used for any provider-defined purpose. Use 0 if you are not using event tags.
+    ///
+    /// Use [`EventBuilder::try_reset`] if the name comes from user input.
     pub fn reset(&mut self, name: &str, event_tag: u16) -> &mut Self {
         debug_assert!(!name.contains('\0'), "event name must not contain '\\0'");
 
         self.meta.clear();
         self.data.clear();
+        self.ext.clear();
+        self.ext_last = 0;
+        self.invalid_field_name = false;
         self.flags = HeaderFlags::DefaultWithExtension;
         self.version = 0;
         self.id = 0;
@@ -208,6 +361,41 @@ impl EventBuilder {
         return self;
     }
 
+    /// Same as [`EventBuilder::reset`], but returns
+    /// [`ValidationError::InvalidEventName`] (and leaves the builder unchanged) if
+    /// `name` contains any `'\0'` bytes.
+    pub fn try_reset(&mut self, name: &str, event_tag: u16) -> Result<&mut Self, ValidationError> {
+        if name.contains('\0') {
+            return Err(ValidationError::InvalidEventName);
+        }
+
+        return Ok(self.reset(name, event_tag));
+    }
+
+    /// Checks the current event for problems that would prevent it from decoding
+    /// correctly. Use this before writing an event whose names or structure come from
+    /// user input. Returns:
+    ///
+    /// - [`ValidationError::InvalidFieldName`] if a `try_*` method rejected a field
+    ///   name because it contained `'\0'`.
+    /// - [`ValidationError::InvalidStructFieldCount`] if a struct has 0 fields.
+    /// - [`ValidationError::StructNestingTooDeep`] if structs are nested more than 8
+    ///   levels deep (the limit supported by decoders).
+    ///
+    /// Note that this does not check whether the number of fields added after a struct
+    /// matches the struct's field count.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.invalid_field_name {
+            return Err(ValidationError::InvalidFieldName);
+        }
+
+        let meta = self.meta.bytes();
+        return match meta.iter().position(|b| *b == 0) {
+            Some(name_len) => validation::check_fields(&meta[name_len + 1..]),
+            None => Ok(()),
+        };
+    }
+
     /// Sends the finished event to the kernel with the provider, event level, and event
     /// keyword of the specified event set.
     ///
@@ -226,8 +414,10 @@ impl EventBuilder {
     ///   also be `None`.
     ///
     /// Returns 0 for success. Returns `EBADF` (9) if no consumer is listening to this
-    /// tracepoint. Returns `ERANGE` (34) if the event (headers + metadata + data) is
-    /// greater than 64KB. Returns other errors as reported by `writev`. The return value
+    /// tracepoint or if the process's [`eventheader::SuppressionPolicy`] suppresses the
+    /// event. Returns `ERANGE` (34) if the event (headers + metadata + data) is
+    /// greater than 64KB and the builder's [OverflowPolicy] cannot make it fit. Returns
+    /// other errors as reported by `writev`. The return value
     /// is for diagnostic/debugging purposes only and should generally be ignored in retail
     /// builds.
     pub fn write(
@@ -235,12 +425,28 @@ impl EventBuilder {
         event_set: &EventSet,
         activity_id: Option<&[u8; 16]>,
         related_id: Option<&[u8; 16]>,
+    ) -> i32 {
+        return self.write_with_data(self.data.bytes(), event_set, activity_id, related_id);
+    }
+
+    /// Sends an event with this builder's headers and metadata and with the
+    /// specified data (instead of this builder's data) to the kernel.
+    pub(crate) fn write_with_data(
+        &self,
+        data: &[u8],
+        event_set: &EventSet,
+        activity_id: Option<&[u8; 16]>,
+        related_id: Option<&[u8; 16]>,
     ) -> i32 {
         debug_assert!(related_id.is_none() || activity_id.is_some());
-        return if self.meta.len() + self.data.len() > 65535 - (52 + 16) {
-            34 // libc::ERANGE
-        } else {
-            _internal::write_eventheader(
+        if event_set.enabled() && event_set.suppressed(self.meta.bytes()) {
+            return 9; // libc::EBADF
+        }
+
+        let ext = self.extensions();
+        return match self.payload(data, 0) {
+            Err(error) => error,
+            Ok(payload) => _internal::write_eventheader(
                 event_set.state(),
                 &EventHeader {
                     flags: self.flags,
@@ -252,13 +458,262 @@ impl EventBuilder {
                 },
                 activity_id,
                 related_id,
-                self.meta.len() as u16,
+                payload.meta_len() as u16,
+                ext.any(),
                 &mut [
                     EventDataDescriptor::zero(),
-                    EventDataDescriptor::from_bytes(&self.meta),
-                    EventDataDescriptor::from_bytes(&self.data),
+                    EventDataDescriptor::from_bytes(payload.meta),
+                    EventDataDescriptor::from_bytes(payload.trailer_meta()),
+                    EventDataDescriptor::from_bytes(ext.head),
+                    ext.last_header_desc(),
+                    EventDataDescriptor::from_bytes(ext.tail),
+                    EventDataDescriptor::from_bytes(&payload.data),
+                    EventDataDescriptor::from_bytes(payload.trailer_data()),
                 ],
-            )
+            ),
+        };
+    }
+
+    /// Returns the metadata and data to be written for an event with the specified
+    /// data, applying the overflow policy if the event is too large. `extra_len` is
+    /// the size of any additional metadata and data that will be appended to the event.
+    /// The size of the builder's extension blocks is included automatically.
+    fn payload<'a>(&'a self, data: &'a [u8], extra_len: usize) -> Result<Payload<'a>, i32> {
+        if self.capacity_exceeded() {
+            return Err(34); // libc::ERANGE
+        }
+
+        let extra_len = extra_len + self.ext.len();
+
+        let meta = self.meta.bytes();
+        let len = meta.len() + data.len();
+        if len + extra_len <= _internal::EVENTHEADER_PAYLOAD_MAX {
+            return Ok(Payload {
+                meta,
+                data: Cow::Borrowed(data),
+                truncated: false,
+            });
+        }
+
+        let trailer_len = overflow::TRUNCATED_META.len() + overflow::TRUNCATED_DATA.len();
+        let budget = _internal::EVENTHEADER_PAYLOAD_MAX.checked_sub(extra_len + trailer_len);
+        let payload = match budget {
+            None => None,
+            Some(budget) => match self.overflow_policy {
+                OverflowPolicy::Fail => None,
+                OverflowPolicy::TruncateStrings => {
+                    overflow::truncate_strings(meta, data, len - budget).map(|data| Payload {
+                        meta,
+                        data: Cow::Owned(data),
+                        truncated: true,
+                    })
+                }
+                OverflowPolicy::DropFields => {
+                    overflow::drop_fields(meta, data, budget).map(|(meta_len, data_len)| Payload {
+                        meta: &meta[..meta_len],
+                        data: Cow::Borrowed(&data[..data_len]),
+                        truncated: true,
+                    })
+                }
+            },
+        };
+
+        return payload.ok_or(34); // libc::ERANGE
+    }
+
+    /// Sends the finished event to the kernel if the event set is enabled and the
+    /// specified throttle allows it. This is the equivalent of the `rate_limit` and
+    /// `sample` options of `eventheader::write_event!`.
+    ///
+    /// - `throttle` holds the rate limiting and sampling state for this event, e.g.
+    ///   `static THROTTLE: EventThrottle = EventThrottle::new(100, 0);`. It should
+    ///   normally be used with only one event.
+    ///
+    /// The throttle is checked only if `event_set` is enabled. If the event is written,
+    /// a `u32` field named `"_suppressed"` is appended to it, containing the number of
+    /// events dropped by the throttle since the last event that was written.
+    ///
+    /// Returns 0 for success. Returns `EAGAIN` (11) if the event was dropped by the
+    /// throttle. Returns `EBADF` (9) if no consumer is listening to this tracepoint or
+    /// if the event is suppressed (suppressed events do not count against the
+    /// throttle). Other return values are the same as for [`EventBuilder::write`].
+    pub fn write_throttled(
+        &self,
+        event_set: &EventSet,
+        throttle: &EventThrottle,
+        activity_id: Option<&[u8; 16]>,
+        related_id: Option<&[u8; 16]>,
+    ) -> i32 {
+        const SUPPRESSED_META: &[u8] = b"_suppressed\0\x04"; // name, Value32
+
+        if !event_set.enabled() || event_set.suppressed(self.meta.bytes()) {
+            return 9; // libc::EBADF
+        }
+
+        let suppressed = match throttle.check() {
+            None => return 11, // libc::EAGAIN
+            Some(suppressed) => suppressed,
+        };
+
+        debug_assert!(related_id.is_none() || activity_id.is_some());
+        let ext = self.extensions();
+        return match self.payload(self.data.bytes(), SUPPRESSED_META.len() + 4) {
+            Err(error) => error,
+            Ok(payload) => _internal::write_eventheader(
+                event_set.state(),
+                &EventHeader {
+                    flags: self.flags,
+                    version: self.version,
+                    id: self.id,
+                    tag: self.tag,
+                    opcode: self.opcode,
+                    level: event_set.level(),
+                },
+                activity_id,
+                related_id,
+                (payload.meta_len() + SUPPRESSED_META.len()) as u16,
+                ext.any(),
+                &mut [
+                    EventDataDescriptor::zero(),
+                    EventDataDescriptor::from_bytes(payload.meta),
+                    EventDataDescriptor::from_bytes(payload.trailer_meta()),
+                    EventDataDescriptor::from_bytes(SUPPRESSED_META),
+                    EventDataDescriptor::from_bytes(ext.head),
+                    ext.last_header_desc(),
+                    EventDataDescriptor::from_bytes(ext.tail),
+                    EventDataDescriptor::from_bytes(&payload.data),
+                    EventDataDescriptor::from_bytes(payload.trailer_data()),
+                    EventDataDescriptor::from_value(&suppressed),
+                ],
+            ),
+        };
+    }
+
+    /// Encodes the finished event without sending it to the kernel. Appends the
+    /// event's bytes (EventHeader, extension blocks, metadata, and data) to `buffer`
+    /// and returns the name of the tracepoint that [`EventBuilder::write`] would use,
+    /// e.g. `"MyCompany_MyComponent_L5K1f"`.
+    ///
+    /// - The provider and keyword set by [`EventBuilder::encode_provider`] determine
+    ///   the tracepoint name. The provider does not need to have any registered event
+    ///   sets.
+    ///
+    /// - `level`, `activity_id`, and `related_id` are used as for
+    ///   [`EventBuilder::write`], where the level comes from the event set.
+    ///
+    /// The appended bytes are the same as the event payload that `write` would send
+    /// (not including the kernel-assigned write index), so they can be stored and
+    /// later decoded with
+    /// `tracepoint_decode::EventHeaderEnumeratorContext::enumerate_with_name_and_data`.
+    ///
+    /// Returns `Err(EINVAL)` (22) if `encode_provider` has not been called. Returns
+    /// `Err(ERANGE)` (34) and leaves `buffer` unchanged if the event (headers +
+    /// metadata + data) is greater than 64KB and the builder's [OverflowPolicy]
+    /// cannot make it fit.
+    pub fn encode_to(
+        &self,
+        buffer: &mut Vec<u8>,
+        level: Level,
+        activity_id: Option<&[u8; 16]>,
+        related_id: Option<&[u8; 16]>,
+    ) -> Result<String, i32> {
+        debug_assert!(related_id.is_none() || activity_id.is_some());
+        let target = match &self.encode_provider {
+            None => return Err(22), // libc::EINVAL
+            Some(target) => target,
+        };
+        let payload = self.payload(self.data.bytes(), 0)?;
+        let ext = self.extensions();
+
+        let mut headers = [0u8; _internal::EVENTHEADER_HEADERS_MAX];
+        let headers_len = _internal::encode_headers(
+            &mut headers,
+            &EventHeader {
+                flags: self.flags,
+                version: self.version,
+                id: self.id,
+                tag: self.tag,
+                opcode: self.opcode,
+                level,
+            },
+            activity_id,
+            related_id,
+            payload.meta_len() as u16,
+            ext.any(),
+        );
+
+        buffer.reserve(
+            headers_len
+                + payload.meta_len()
+                + self.ext.len()
+                + payload.data.len()
+                + payload.trailer_data().len(),
+        );
+        buffer.extend_from_slice(&headers[..headers_len]);
+        buffer.extend_from_slice(payload.meta);
+        buffer.extend_from_slice(payload.trailer_meta());
+        buffer.extend_from_slice(ext.head);
+        buffer.extend_from_slice(ext.last_header_desc().as_bytes());
+        buffer.extend_from_slice(ext.tail);
+        buffer.extend_from_slice(&payload.data);
+        buffer.extend_from_slice(payload.trailer_data());
+        return Ok(TracepointName::new_unchecked(
+            &target.name,
+            level,
+            target.keyword,
+            &target.options,
+        )
+        .to_string());
+    }
+
+    /// Adds a provider-defined extension block to the event, e.g. trace flags, a W3C
+    /// `traceparent`, or a tenant id. Extension blocks are written after the event's
+    /// metadata, in the order they were added, and are available to decoders via
+    /// `tracepoint_decode::EventHeaderEventInfo::extensions`.
+    ///
+    /// - `kind` identifies the content of the block. It must be a provider-defined
+    ///   kind, i.e. [`ExtensionKind::is_custom`] must return true.
+    ///
+    /// - `data` is the content of the block. Blocks are limited to 65535 bytes. If
+    ///   `data` is larger, the event cannot be written (`write` returns `ERANGE`).
+    ///
+    /// Extension blocks are part of the event, so they are cleared by `reset`. They
+    /// are stored in a `Vec<u8>` even for a [FixedEventBuilder], so adding an
+    /// extension block may allocate.
+    pub fn add_extension(&mut self, kind: ExtensionKind, data: &[u8]) -> &mut Self {
+        debug_assert!(kind.is_custom(), "extension kind must be provider-defined");
+
+        self.ext_last = self.ext.len();
+        let header = _internal::extension_header(kind, data, true);
+        self.ext.extend_from_slice(&header.size.to_ne_bytes());
+        self.ext
+            .extend_from_slice(&header.kind.as_int().to_ne_bytes());
+        self.ext.extend_from_slice(data);
+        return self;
+    }
+
+    /// Returns the extension blocks, split around the header of the last block.
+    fn extensions(&self) -> Extensions<'_> {
+        return if self.ext.is_empty() {
+            Extensions {
+                head: &[],
+                last_header: None,
+                tail: &[],
+            }
+        } else {
+            let last = self.ext_last;
+            let tail = last + mem::size_of::<EventHeaderExtension>();
+            let size = u16::from_ne_bytes([self.ext[last], self.ext[last + 1]]);
+            let kind = u16::from_ne_bytes([self.ext[last + 2], self.ext[last + 3]]);
+            Extensions {
+                head: &self.ext[..last],
+                last_header: Some(EventHeaderExtension::from_parts(
+                    size,
+                    ExtensionKind::from_int(kind & ExtensionKind::ValueMask),
+                    false,
+                )),
+                tail: &self.ext[tail..],
+            }
         };
     }
 
@@ -298,6 +753,63 @@ impl EventBuilder {
         return self;
     }
 
+    /// Sets the builder's policy for events that are too large to be written.
+    /// Default is [OverflowPolicy::Fail], i.e. `write` returns `ERANGE`.
+    ///
+    /// The policy is applied by `write`, `write_throttled`, and `encode_to`. It is a
+    /// setting of the builder rather than of the current event, so it is not changed
+    /// by `reset`.
+    pub fn overflow_policy(&mut self, policy: OverflowPolicy) -> &mut Self {
+        self.overflow_policy = policy;
+        return self;
+    }
+
+    /// Sets the provider and keyword that [`EventBuilder::encode_to`] uses to
+    /// determine the tracepoint name, i.e. the values that `write` would get from the
+    /// event set. Like the [OverflowPolicy], this is a setting of the builder, so it
+    /// is not changed by `reset`.
+    pub fn encode_provider(&mut self, provider: &Provider, keyword: u64) -> &mut Self {
+        self.encode_provider = Some(EncodeProvider {
+            name: provider.name().to_string(),
+            options: provider.options().to_string(),
+            keyword,
+        });
+        return self;
+    }
+
+    /// Restores the builder's settings (overflow policy, encode provider) to their
+    /// defaults.
+    #[cfg(feature = "pool")]
+    pub(crate) fn reset_settings(&mut self) -> &mut Self {
+        self.overflow_policy = OverflowPolicy::Fail;
+        self.encode_provider = None;
+        return self;
+    }
+
+    /// Returns the current size of the event's metadata + data, in bytes. This
+    /// includes the event name, all fields added so far, and any extension blocks
+    /// added by [EventBuilder::add_extension]. It does not include the event headers.
+    pub fn encoded_size(&self) -> usize {
+        return self.meta.len() + self.ext.len() + self.data.len();
+    }
+
+    /// Returns the number of bytes of metadata + data that can be added to the event
+    /// before it becomes too large to be written. The size limit accounts for the
+    /// largest possible event headers. (`write_throttled` appends a `_suppressed`
+    /// field, so it needs 17 additional bytes: 13 bytes of metadata and 4 bytes of
+    /// data.)
+    pub fn remaining_size(&self) -> usize {
+        return _internal::EVENTHEADER_PAYLOAD_MAX.saturating_sub(self.encoded_size());
+    }
+
+    /// Returns true if a field did not fit in the builder's fixed-capacity buffers
+    /// since the last call to `reset`, i.e. if the current event cannot be written
+    /// (`write` returns `ERANGE`). Always false for a builder that uses `Vec<u8>`
+    /// buffers. See [FixedEventBuilder].
+    pub fn capacity_exceeded(&self) -> bool {
+        return self.meta.overflowed() || self.data.overflowed();
+    }
+
     /// Adds a field containing the specified number of sub-fields.
     ///
     /// A struct is a way to logically group a number of fields. To add a struct to
@@ -336,6 +848,28 @@ impl EventBuilder {
         );
     }
 
+    /// Same as [`EventBuilder::add_struct`], but returns
+    /// [`ValidationError::InvalidStructFieldCount`] (and does not add the field) if
+    /// `struct_field_count` is 0 or greater than 127, or
+    /// [`ValidationError::InvalidFieldName`] if `field_name` contains `'\0'`. A
+    /// rejected field name is also reported by [`EventBuilder::validate`] until the
+    /// next reset, since the fields that follow it would not decode correctly.
+    pub fn try_add_struct(
+        &mut self,
+        field_name: &str,
+        struct_field_count: u8,
+        field_tag: u16,
+    ) -> Result<&mut Self, ValidationError> {
+        if struct_field_count == 0 || struct_field_count > FieldFormat::ValueMask {
+            return Err(ValidationError::InvalidStructFieldCount);
+        } else if field_name.contains('\0') {
+            self.invalid_field_name = true;
+            return Err(ValidationError::InvalidFieldName);
+        }
+
+        return Ok(self.add_struct(field_name, struct_field_count, field_tag));
+    }
+
     /// Advanced: Adds a field containing the specified number of sub-fields, returning
     /// a bookmark that can be used for a subsequent call to
     /// [`EventBuilder::set_struct_field_count`]. This can be used for cases where you do
@@ -407,9 +941,9 @@ impl EventBuilder {
         );
 
         *field_count_bookmark = if field_tag == 0 {
-            self.meta.len() - 1
+            self.meta.len().saturating_sub(1)
         } else {
-            self.meta.len() - 3
+            self.meta.len().saturating_sub(3)
         };
         return self;
     }
@@ -440,8 +974,10 @@ impl EventBuilder {
             masked_field_count != 0,
             "updated_struct_field_count must not be 0"
         );
-        self.meta[field_count_bookmark] =
-            (self.meta[field_count_bookmark] & 0x80) | masked_field_count;
+        match self.meta.bytes_mut().get_mut(field_count_bookmark) {
+            Some(field_count) => *field_count = (*field_count & 0x80) | masked_field_count,
+            None => assert!(self.meta.overflowed(), "invalid field_count_bookmark"),
+        }
         return self;
     }
 
@@ -884,16 +1420,9 @@ impl EventBuilder {
     /// decode properly.
     pub fn raw_add_data_value<T: Copy>(&mut self, value: &T) -> &mut Self {
         let value_size = mem::size_of::<T>();
-        let old_data_size = self.data.len();
-        self.data.reserve(value_size);
-        unsafe {
-            copy_nonoverlapping(
-                value as *const T as *const u8,
-                self.data.as_mut_ptr().add(old_data_size),
-                value_size,
-            );
-            self.data.set_len(old_data_size + value_size);
-        }
+        self.data.extend_from_slice(unsafe {
+            slice::from_raw_parts(value as *const T as *const u8, value_size)
+        });
         return self;
     }
 
@@ -908,19 +1437,36 @@ impl EventBuilder {
     /// decode properly.
     pub fn raw_add_data_slice<T: Copy>(&mut self, value: &[T]) -> &mut Self {
         let value_size = mem::size_of_val(value);
-        let old_data_size = self.data.len();
-        self.data.reserve(value_size);
-        unsafe {
-            copy_nonoverlapping(
-                value.as_ptr() as *const u8,
-                self.data.as_mut_ptr().add(old_data_size),
-                value_size,
-            );
-            self.data.set_len(old_data_size + value_size);
-        }
+        self.data.extend_from_slice(unsafe {
+            slice::from_raw_parts(value.as_ptr() as *const u8, value_size)
+        });
         return self;
     }
 
+    /// Returns the data buffer.
+    pub(crate) fn raw_data(&self) -> &[u8] {
+        return self.data.bytes();
+    }
+
+    /// Clears the data buffer without changing the metadata.
+    pub(crate) fn raw_clear_data(&mut self) {
+        self.data.clear();
+    }
+
+    /// Returns the current sizes of the metadata and data buffers.
+    #[cfg(feature = "serde")]
+    pub(crate) fn raw_lengths(&self) -> (usize, usize) {
+        return (self.meta.len(), self.data.len());
+    }
+
+    /// Shrinks the metadata and data buffers, e.g. to remove fields that were added
+    /// after a call to `raw_lengths`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn raw_truncate(&mut self, meta_len: usize, data_len: usize) {
+        self.meta.truncate(meta_len);
+        self.data.truncate(data_len);
+    }
+
     fn raw_add_meta(
         &mut self,
         field_name: &str,
@@ -952,7 +1498,7 @@ impl EventBuilder {
         return self;
     }
 
-    fn raw_add_data_cstr<T: Copy + Default + Eq>(&mut self, value: &[T]) -> &mut Self {
+    pub(crate) fn raw_add_data_cstr<T: Copy + Default + Eq>(&mut self, value: &[T]) -> &mut Self {
         let zero = T::default();
         let mut nul_pos = 0;
         while nul_pos != value.len() {
@@ -965,7 +1511,7 @@ impl EventBuilder {
         return self.raw_add_data_slice(value).raw_add_data_value(&zero);
     }
 
-    fn raw_add_data_counted<T: Copy>(&mut self, value: &[T]) -> &mut Self {
+    pub(crate) fn raw_add_data_counted<T: Copy>(&mut self, value: &[T]) -> &mut Self {
         if value.len() > 65535 {
             return self
                 .raw_add_data_value(&65535)
@@ -977,7 +1523,7 @@ impl EventBuilder {
         }
     }
 
-    fn raw_add_data_range<T: IntoIterator>(
+    pub(crate) fn raw_add_data_range<T: IntoIterator>(
         &mut self,
         field_values: T,
         add_data: impl Fn(&mut Self, T::Item),
@@ -996,8 +1542,14 @@ impl EventBuilder {
             add_data(self, value);
         }
 
-        // Save actual value of count.
-        self.data[old_data_size..old_data_size + 2].copy_from_slice(&count.to_ne_bytes());
+        // Save actual value of count (unless it did not fit in a fixed buffer).
+        if let Some(count_bytes) = self
+            .data
+            .bytes_mut()
+            .get_mut(old_data_size..old_data_size + 2)
+        {
+            count_bytes.copy_from_slice(&count.to_ne_bytes());
+        }
         return self;
     }
 }
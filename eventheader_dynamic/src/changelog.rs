@@ -6,6 +6,64 @@
 #[allow(unused_imports)]
 use crate::*; // For docs
 
+/// # v0.6.0 (TBD)
+/// - New `EventBuilder::write_throttled` method for rate limiting and sampling
+///   events with an [`EventThrottle`].
+/// - `Provider` implements the new [`EventProvider`] trait.
+/// - New `testing` feature that enables and re-exports `eventheader::testing`.
+/// - New `serde` feature with `EventBuilder::add_serialize`,
+///   `EventBuilder::add_serialize_fields`, and a `FieldSerializer` that writes
+///   `serde::Serialize` values as typed eventheader fields.
+/// - `Provider` is now `Send + Sync` and can be shared via `Arc` or a `static`.
+///   `register_set`, `create_unregistered`, and `unregister` now take `&self`.
+///   Looking up an existing event set no longer requires a lock.
+/// - New `EventBuilder::encode_to` method that appends the encoded event bytes to
+///   a buffer instead of sending them to the kernel, using the provider and keyword
+///   set by the new `EventBuilder::encode_provider` method. New
+///   `Provider::tracepoint_name` method.
+/// - New [`EventTemplate`] and [`TemplateWriter`] types for writing events whose
+///   metadata is built once, with only the field values supplied per event.
+/// - New `json` feature with `EventBuilder::add_json`, `add_json_fields`, and
+///   `add_json_fields_str` for building events from `serde_json::Value` objects,
+///   with `JsonHints` to override the format of specific fields.
+/// - New [`OverflowPolicy`] and `EventBuilder::overflow_policy` method. Events that
+///   are too large can now be written with their last string fields shortened or
+///   their trailing fields dropped, marked by a `_truncated` field. New
+///   `EventBuilder::encoded_size` and `EventBuilder::remaining_size` methods.
+/// - `Provider` implements `EventProvider::for_each_tracepoint`, reporting the status
+///   of each event set. New [`for_each_provider`] function for visiting all providers
+///   in the process, dynamic and static. New `EventSet::level` and
+///   `EventSet::keyword` methods.
+/// - New `config` feature with `ProvidersConfig` for creating providers and
+///   registering event sets from a declarative configuration, with per-set errors
+///   reported in a `ConfigLoad`. New `toml` feature (requires std) with
+///   `ProvidersConfig::from_toml_str`; with the `json` feature,
+///   `ProvidersConfig::from_json_str`.
+/// - `EventBuilder` is now generic over its buffer type, defaulting to `Vec<u8>`.
+///   New [`FixedEventBuilder`] (created by `EventBuilder::new_fixed`) that uses
+///   fixed-size inline [`FixedBuffer`]s and never allocates, and new
+///   `EventBuilder::capacity_exceeded` method. New `pool` feature with
+///   `EventBuilder::pooled`, which returns a builder from a thread-local pool.
+/// - `EventSetConfig::level` is now a [`Level`], so configuration files can use
+///   level names such as `level = "Warning"` as well as numbers.
+/// - Provider and group names are now validated using
+///   `eventheader::TracepointName::validate_provider`, and
+///   `ProviderOptions::is_valid_option_value` uses the same rules.
+/// - New `EventBuilder::add_extension` method that adds a provider-defined header
+///   extension block (e.g. trace flags or a tenant id) to the event. Re-export
+///   [`ExtensionKind`].
+/// - New fallible validation APIs for names that come from user input:
+///   `Provider::try_new`, `EventBuilder::try_reset`, `EventBuilder::try_add_struct`,
+///   and `EventBuilder::validate`, which report a [`ValidationError`] for invalid
+///   provider, group, event, or field names, invalid struct field counts, and structs
+///   nested more than 8 levels deep. Re-export [`TracepointNameError`].
+/// - `ProviderOptions::group_name` no longer asserts that the name is valid. The name
+///   is checked by `Provider::new` (debug assert) or `Provider::try_new` (error).
+/// - Events suppressed by the process's [`SuppressionPolicy`] are not written:
+///   `EventBuilder::write` and `write_throttled` return `EBADF`. Re-export
+///   [`SuppressionPolicy`], [`set_suppression_policy`], and related items.
+pub mod v0_6_0 {}
+
 /// # v0.5.0 (2025-11-26)
 /// - Fix clippy issues for Rust 1.91.
 pub mod v0_5_0 {}
@@ -0,0 +1,283 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use eventheader::Level;
+use eventheader::TracepointName;
+use eventheader::TracepointNameError;
+use serde::Deserialize;
+
+use crate::EventSet;
+use crate::Provider;
+use crate::ProviderOptions;
+
+/// Declarative configuration for a set of providers, e.g. loaded from a TOML or
+/// JSON file. Use [`ProvidersConfig::load`] to create the providers and register
+/// their event sets.
+///
+/// TOML example:
+///
+/// ```toml
+/// [[providers]]
+/// name = "MyCompany_MyComponent"
+/// sets = [
+///     { level = "Informational", keyword = 0x1 },
+///     { level = 5, keyword = 0x1f },
+/// ]
+///
+/// [[providers]]
+/// name = "MyCompany_OtherComponent"
+/// group_name = "mygroup"
+/// sets = [{ level = "Error" }]
+/// ```
+///
+/// The equivalent JSON is
+/// `{"providers":[{"name":"MyCompany_MyComponent","sets":[{"level":"Informational","keyword":1}, ...]}, ...]}`.
+///
+/// Unknown keys are rejected when deserializing.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ProvidersConfig {
+    /// The providers to create.
+    #[serde(default)]
+    pub providers: Vec<ProviderConfig>,
+}
+
+/// Configuration for one provider within a [`ProvidersConfig`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderConfig {
+    /// The provider name. See [`Provider::new`] for the naming rules.
+    pub name: String,
+
+    /// The provider group name, or `""` (the default) for no group. If not empty, it
+    /// must contain only ASCII digits and lowercase ASCII letters.
+    #[serde(default)]
+    pub group_name: String,
+
+    /// The event sets to register for the provider.
+    #[serde(default)]
+    pub sets: Vec<EventSetConfig>,
+}
+
+/// Configuration for one event set within a [`ProviderConfig`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EventSetConfig {
+    /// The event set's level, e.g. [`Level::Informational`]. Must not be 0. In a
+    /// configuration file, this may be a name (e.g. `"Informational"`, case-insensitive)
+    /// or a number (e.g. `4`).
+    pub level: Level,
+
+    /// The event set's keyword (category bits). Defaults to 0.
+    #[serde(default)]
+    pub keyword: u64,
+}
+
+/// The reason that a provider or event set from a [`ProvidersConfig`] was not loaded.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConfigErrorKind {
+    /// The provider name is empty or contains `'\0'`, `' '`, or `':'`.
+    InvalidName,
+
+    /// The group name contains a character other than an ASCII digit or a
+    /// lowercase ASCII letter.
+    InvalidGroupName,
+
+    /// The provider name + group name are 234 or more bytes long.
+    NameTooLong,
+
+    /// An earlier provider in the configuration has the same name and group name.
+    DuplicateProvider,
+
+    /// The event set's level is 0.
+    InvalidLevel,
+
+    /// An earlier event set of the same provider has the same level and keyword.
+    DuplicateSet,
+
+    /// The event set was created, but registering its tracepoint failed with the
+    /// specified errno. The event set will never be enabled.
+    RegisterFailed(i32),
+}
+
+/// An error reported by [`ProvidersConfig::load`] for a provider or event set.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigError {
+    /// Index of the provider within [`ProvidersConfig::providers`].
+    pub provider_index: usize,
+
+    /// Name of the provider.
+    pub provider_name: String,
+
+    /// The event set that failed, or None if the provider itself failed (in which
+    /// case none of its event sets were registered).
+    pub set: Option<EventSetConfig>,
+
+    /// The reason for the failure.
+    pub kind: ConfigErrorKind,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "provider \"{}\"", self.provider_name)?;
+        if let Some(set) = &self.set {
+            write!(f, " set L{:x}K{:x}", set.level.as_int(), set.keyword)?;
+        }
+
+        return match self.kind {
+            ConfigErrorKind::InvalidName => {
+                f.write_str(": name must be non-empty and must not contain '\\0', ' ', or ':'")
+            }
+            ConfigErrorKind::InvalidGroupName => {
+                f.write_str(": group_name must contain only 0..9 and a..z")
+            }
+            ConfigErrorKind::NameTooLong => {
+                f.write_str(": name.len() + group_name.len() must be less than 234")
+            }
+            ConfigErrorKind::DuplicateProvider => f.write_str(": duplicate provider"),
+            ConfigErrorKind::InvalidLevel => f.write_str(": level must not be 0"),
+            ConfigErrorKind::DuplicateSet => f.write_str(": duplicate event set"),
+            ConfigErrorKind::RegisterFailed(errno) => {
+                write!(f, ": registration failed with errno {}", errno)
+            }
+        };
+    }
+}
+
+/// A provider created by [`ProvidersConfig::load`].
+#[derive(Debug)]
+pub struct LoadedProvider {
+    /// Index of the provider's configuration within [`ProvidersConfig::providers`].
+    pub index: usize,
+
+    /// The provider. Dropping it unregisters its event sets.
+    pub provider: Provider,
+
+    /// The provider's event sets, in configuration order. Event sets that failed to
+    /// register are included (they will never be enabled). Duplicate and invalid
+    /// event sets are not included.
+    pub sets: Vec<Arc<EventSet>>,
+}
+
+/// The result of [`ProvidersConfig::load`]: the providers that were created and the
+/// errors that were encountered.
+#[derive(Debug, Default)]
+pub struct ConfigLoad {
+    /// The providers that were created, in configuration order.
+    pub providers: Vec<LoadedProvider>,
+
+    /// The errors that were encountered, in configuration order.
+    pub errors: Vec<ConfigError>,
+}
+
+impl ConfigLoad {
+    /// Returns true if no errors were encountered.
+    pub fn is_ok(&self) -> bool {
+        return self.errors.is_empty();
+    }
+
+    /// Returns the loaded provider with the specified name and group name, if any.
+    pub fn provider(&self, name: &str, group_name: &str) -> Option<&LoadedProvider> {
+        return self.providers.iter().find(|loaded| {
+            loaded.provider.name() == name && group_of(&loaded.provider) == group_name
+        });
+    }
+}
+
+impl ProvidersConfig {
+    /// Parses a configuration from a TOML string. Requires the `toml` feature, which
+    /// requires std.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(text: &str) -> Result<Self, toml::de::Error> {
+        return toml::from_str(text);
+    }
+
+    /// Parses a configuration from a JSON string. Requires the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn from_json_str(text: &str) -> Result<Self, serde_json::Error> {
+        return serde_json::from_str(text);
+    }
+
+    /// Validates the configuration, creates its providers, and registers their event
+    /// sets.
+    ///
+    /// Loading continues after an error: a provider with an invalid or duplicate
+    /// name is skipped (along with its event sets), and an invalid or duplicate event
+    /// set is skipped. Event sets whose registration fails are kept but reported
+    /// with [`ConfigErrorKind::RegisterFailed`]. All errors are returned in
+    /// [`ConfigLoad::errors`].
+    pub fn load(&self) -> ConfigLoad {
+        let mut result = ConfigLoad::default();
+        for (index, config) in self.providers.iter().enumerate() {
+            let mut error = |set: Option<EventSetConfig>, kind: ConfigErrorKind| {
+                result.errors.push(ConfigError {
+                    provider_index: index,
+                    provider_name: config.name.clone(),
+                    set,
+                    kind,
+                });
+            };
+
+            if let Err(kind) = validate_names(&config.name, &config.group_name) {
+                error(None, kind);
+                continue;
+            }
+
+            if self.providers[..index]
+                .iter()
+                .any(|prev| prev.name == config.name && prev.group_name == config.group_name)
+            {
+                error(None, ConfigErrorKind::DuplicateProvider);
+                continue;
+            }
+
+            let mut options = ProviderOptions::new();
+            options.group_name(&config.group_name);
+            let provider = Provider::new(&config.name, &options);
+
+            let mut sets = Vec::with_capacity(config.sets.len());
+            for (set_index, &set_config) in config.sets.iter().enumerate() {
+                if set_config.level.as_int() == 0 {
+                    error(Some(set_config), ConfigErrorKind::InvalidLevel);
+                } else if config.sets[..set_index].contains(&set_config) {
+                    error(Some(set_config), ConfigErrorKind::DuplicateSet);
+                } else {
+                    let set = provider.register_set(set_config.level, set_config.keyword);
+                    if set.errno() != 0 {
+                        error(
+                            Some(set_config),
+                            ConfigErrorKind::RegisterFailed(set.errno()),
+                        );
+                    }
+                    sets.push(set);
+                }
+            }
+
+            result.providers.push(LoadedProvider {
+                index,
+                provider,
+                sets,
+            });
+        }
+
+        return result;
+    }
+}
+
+fn validate_names(name: &str, group_name: &str) -> Result<(), ConfigErrorKind> {
+    return TracepointName::validate_provider(name, group_name).map_err(|error| match error {
+        TracepointNameError::InvalidGroupName => ConfigErrorKind::InvalidGroupName,
+        TracepointNameError::NameTooLong => ConfigErrorKind::NameTooLong,
+        _ => ConfigErrorKind::InvalidName,
+    });
+}
+
+fn group_of(provider: &Provider) -> &str {
+    return TracepointName::new_unchecked(provider.name(), Level::Invalid, 0, provider.options())
+        .group_name();
+}
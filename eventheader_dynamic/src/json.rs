@@ -0,0 +1,422 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use eventheader::FieldFormat;
+use serde_json::Map;
+use serde_json::Number;
+use serde_json::Value;
+
+use crate::validation::STRUCT_NEST_LIMIT;
+use crate::EventBuilder;
+
+/// Format hints for [`EventBuilder::add_json`] and related methods.
+///
+/// By default, the format of each field is determined by its JSON value. A hint
+/// overrides the format for the field with the specified path. The path of a
+/// top-level field is its name. The path of a field nested in a struct is the
+/// struct's path and the field's name separated by `'.'`, e.g. `"request.id"`.
+/// Elements of a sequence use the path of the sequence.
+///
+/// Supported hints:
+///
+/// - Numbers: [`FieldFormat::UnsignedInt`] and [`FieldFormat::HexInt`] (`u64`),
+///   [`FieldFormat::SignedInt`] and [`FieldFormat::Time`] (`i64`),
+///   [`FieldFormat::Float`] (`f64`), [`FieldFormat::Errno`] (`i32`),
+///   [`FieldFormat::Pid`] and [`FieldFormat::Boolean`] (`u32`),
+///   [`FieldFormat::IPv4`] (`u32`, big-endian), [`FieldFormat::Port`] (`u16`,
+///   big-endian).
+/// - Strings: [`FieldFormat::Uuid`] (the string is parsed as a UUID, e.g.
+///   `"7ae27afb-11cf-4edd-8b15-9997ec20e0fc"`) or any string format such as
+///   [`FieldFormat::HexBytes`] or [`FieldFormat::StringJson`].
+/// - Objects and arrays: [`FieldFormat::StringJson`] (the value is written as a
+///   JSON string instead of a struct or sequence).
+///
+/// Hints that do not apply to the value (e.g. a `Uuid` hint for a string that is
+/// not a UUID) are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct JsonHints {
+    hints: BTreeMap<String, FieldFormat>,
+}
+
+impl JsonHints {
+    /// Returns an empty set of hints.
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Sets the format to use for the field with the specified path.
+    pub fn hint(&mut self, path: &str, format: FieldFormat) -> &mut Self {
+        self.hints.insert(String::from(path), format);
+        return self;
+    }
+
+    /// Returns the format hint for the field with the specified path, if any.
+    pub fn get(&self, path: &str) -> Option<FieldFormat> {
+        return self.hints.get(path).copied();
+    }
+}
+
+impl EventBuilder {
+    /// Adds a field for each member of a JSON object. Members with a `null` value
+    /// are skipped. See [`EventBuilder::add_json`] for details.
+    ///
+    /// Fields are added in the object's iteration order, which is sorted by key
+    /// unless `serde_json`'s `preserve_order` feature is enabled.
+    pub fn add_json_fields(&mut self, object: &Map<String, Value>, hints: &JsonHints) -> &mut Self {
+        let mut path = String::new();
+        for (name, value) in object {
+            add_json_value(self, name, &mut path, value, hints, 0);
+        }
+        return self;
+    }
+
+    /// Parses `json_text` as a JSON object and adds a field for each of its members.
+    /// See [`EventBuilder::add_json_fields`].
+    ///
+    /// Returns an error (and adds no fields) if `json_text` is not a valid JSON object.
+    pub fn add_json_fields_str(
+        &mut self,
+        json_text: &str,
+        hints: &JsonHints,
+    ) -> Result<&mut Self, serde_json::Error> {
+        let object: Map<String, Value> = serde_json::from_str(json_text)?;
+        return Ok(self.add_json_fields(&object, hints));
+    }
+
+    /// Adds a field containing a JSON value:
+    ///
+    /// - Numbers become `u64` (non-negative integers), `i64` with
+    ///   [`FieldFormat::SignedInt`] (negative integers), or `f64` with
+    ///   [`FieldFormat::Float`].
+    /// - Strings become counted UTF-8 strings (`str8`).
+    /// - Booleans become `u8` with [`FieldFormat::Boolean`] (`bool8`).
+    /// - Arrays of numbers, arrays of strings, and arrays of booleans become
+    ///   sequences. A number array uses `u64` if all elements are non-negative
+    ///   integers, `i64` if all elements are integers, and `f64` otherwise.
+    /// - Objects become structs, with a field for each member. Other arrays become
+    ///   structs with a field for each element, named by the element's index.
+    /// - `null` values are skipped.
+    /// - Values that cannot be represented as a field (empty objects or arrays,
+    ///   objects with only `null` members, structs with more than 127 fields, and
+    ///   structs that would be nested more than 8 levels deep) are written as a JSON
+    ///   string with [`FieldFormat::StringJson`]. Decoders reject events with more
+    ///   than 8 levels of nested structs, so an object or array at the 9th level
+    ///   (counting from `value`) is written as a JSON string instead.
+    ///
+    /// `hints` can override the format of specific fields. See [`JsonHints`].
+    pub fn add_json(&mut self, field_name: &str, value: &Value, hints: &JsonHints) -> &mut Self {
+        let mut path = String::new();
+        add_json_value(self, field_name, &mut path, value, hints, 0);
+        return self;
+    }
+}
+
+fn add_json_value(
+    builder: &mut EventBuilder,
+    name: &str,
+    path: &mut String,
+    value: &Value,
+    hints: &JsonHints,
+    depth: usize, // Number of struct fields that contain this field.
+) {
+    let path_len = path.len();
+    if path_len != 0 {
+        path.push('.');
+    }
+    path.push_str(name);
+    let hint = hints.get(path);
+
+    match value {
+        Value::Null => {}
+        Value::Bool(value) => {
+            builder.add_value(name, *value as u8, FieldFormat::Boolean, 0);
+        }
+        Value::Number(number) => add_json_number(builder, name, number, hint),
+        Value::String(value) => add_json_string(builder, name, value, hint),
+        Value::Array(elements) => {
+            if hint == Some(FieldFormat::StringJson)
+                || !add_json_sequence(builder, name, elements, hint)
+            {
+                let field_count = elements.iter().filter(|v| !v.is_null()).count();
+                if begin_struct(builder, name, value, hint, field_count, depth) {
+                    let mut index_name = String::new();
+                    for (index, element) in elements.iter().enumerate() {
+                        index_name.clear();
+                        write!(index_name, "{}", index).unwrap();
+                        add_json_value(builder, &index_name, path, element, hints, depth + 1);
+                    }
+                }
+            }
+        }
+        Value::Object(members) => {
+            let field_count = members.values().filter(|v| !v.is_null()).count();
+            if begin_struct(builder, name, value, hint, field_count, depth) {
+                for (member_name, member) in members {
+                    add_json_value(builder, member_name, path, member, hints, depth + 1);
+                }
+            }
+        }
+    }
+
+    path.truncate(path_len);
+}
+
+/// Adds a struct field with the specified field count and returns true, or (if the
+/// value cannot be represented as a struct) adds the value as a JSON string and
+/// returns false.
+fn begin_struct(
+    builder: &mut EventBuilder,
+    name: &str,
+    value: &Value,
+    hint: Option<FieldFormat>,
+    field_count: usize,
+    depth: usize,
+) -> bool {
+    if hint == Some(FieldFormat::StringJson)
+        || field_count == 0
+        || field_count > 127
+        || depth >= STRUCT_NEST_LIMIT
+    {
+        let json_text = serde_json::to_string(value).unwrap_or_default();
+        builder.add_str(name, json_text, FieldFormat::StringJson, 0);
+        return false;
+    }
+
+    builder.add_struct(name, field_count as u8, 0);
+    return true;
+}
+
+fn add_json_number(
+    builder: &mut EventBuilder,
+    name: &str,
+    number: &Number,
+    hint: Option<FieldFormat>,
+) {
+    match hint {
+        Some(FieldFormat::UnsignedInt) | Some(FieldFormat::HexInt) => {
+            builder.add_value(name, number_as_u64(number), hint.unwrap(), 0);
+        }
+        Some(FieldFormat::SignedInt) | Some(FieldFormat::Time) => {
+            builder.add_value(name, number_as_i64(number), hint.unwrap(), 0);
+        }
+        Some(FieldFormat::Float) => {
+            builder.add_value(name, number_as_f64(number), FieldFormat::Float, 0);
+        }
+        Some(FieldFormat::Errno) => {
+            builder.add_value(name, number_as_i64(number) as i32, FieldFormat::Errno, 0);
+        }
+        Some(FieldFormat::Pid) | Some(FieldFormat::Boolean) => {
+            builder.add_value(name, number_as_u64(number) as u32, hint.unwrap(), 0);
+        }
+        Some(FieldFormat::IPv4) => {
+            let value = (number_as_u64(number) as u32).to_be();
+            builder.add_value(name, value, FieldFormat::IPv4, 0);
+        }
+        Some(FieldFormat::Port) => {
+            let value = (number_as_u64(number) as u16).to_be();
+            builder.add_value(name, value, FieldFormat::Port, 0);
+        }
+        _ => {
+            if let Some(value) = number.as_u64() {
+                builder.add_value(name, value, FieldFormat::Default, 0);
+            } else if let Some(value) = number.as_i64() {
+                builder.add_value(name, value, FieldFormat::SignedInt, 0);
+            } else {
+                builder.add_value(name, number_as_f64(number), FieldFormat::Float, 0);
+            }
+        }
+    }
+}
+
+fn add_json_string(builder: &mut EventBuilder, name: &str, value: &str, hint: Option<FieldFormat>) {
+    match hint {
+        Some(FieldFormat::Uuid) => {
+            if let Some(uuid) = parse_uuid(value) {
+                builder.add_value(name, uuid, FieldFormat::Uuid, 0);
+                return;
+            }
+        }
+        Some(format) if is_string_format(format) => {
+            builder.add_str(name, value, format, 0);
+            return;
+        }
+        _ => {}
+    }
+
+    builder.add_str(name, value, FieldFormat::Default, 0);
+}
+
+/// If all elements are numbers, all are strings, or all are booleans, adds them
+/// as a sequence and returns true. Otherwise, adds nothing and returns false.
+fn add_json_sequence(
+    builder: &mut EventBuilder,
+    name: &str,
+    elements: &[Value],
+    hint: Option<FieldFormat>,
+) -> bool {
+    let first = match elements.first() {
+        None => return false,
+        Some(first) => first,
+    };
+
+    match first {
+        Value::Bool(_) => {
+            let mut values = Vec::with_capacity(elements.len());
+            for element in elements {
+                match element {
+                    Value::Bool(value) => values.push(*value as u8),
+                    _ => return false,
+                }
+            }
+            builder.add_value_sequence(name, &values, FieldFormat::Boolean, 0);
+        }
+        Value::String(_) => {
+            let mut values = Vec::with_capacity(elements.len());
+            for element in elements {
+                match element {
+                    Value::String(value) => values.push(value.as_str()),
+                    _ => return false,
+                }
+            }
+
+            if hint == Some(FieldFormat::Uuid) {
+                let uuids: Option<Vec<[u8; 16]>> = values.iter().map(|v| parse_uuid(v)).collect();
+                if let Some(uuids) = uuids {
+                    builder.add_value_sequence(name, &uuids, FieldFormat::Uuid, 0);
+                    return true;
+                }
+            }
+
+            let format = match hint {
+                Some(format) if is_string_format(format) => format,
+                _ => FieldFormat::Default,
+            };
+            builder.add_str_sequence(name, values, format, 0);
+        }
+        Value::Number(_) => {
+            let mut numbers = Vec::with_capacity(elements.len());
+            let mut all_u64 = true;
+            let mut all_i64 = true;
+            for element in elements {
+                match element {
+                    Value::Number(number) => {
+                        all_u64 &= number.is_u64();
+                        all_i64 &= number.is_i64();
+                        numbers.push(number);
+                    }
+                    _ => return false,
+                }
+            }
+
+            let kind = if all_u64 {
+                NumberKind::Unsigned
+            } else if all_i64 {
+                NumberKind::Signed
+            } else {
+                NumberKind::Float
+            };
+
+            // A hint selects the element type, the same as for a single number.
+            let kind = match hint {
+                Some(FieldFormat::UnsignedInt) | Some(FieldFormat::HexInt) => NumberKind::Unsigned,
+                Some(FieldFormat::SignedInt) | Some(FieldFormat::Time) => NumberKind::Signed,
+                Some(FieldFormat::Float) => NumberKind::Float,
+                _ => kind,
+            };
+            match kind {
+                NumberKind::Unsigned => {
+                    let values: Vec<u64> = numbers.iter().map(|n| number_as_u64(n)).collect();
+                    let format = match hint {
+                        Some(FieldFormat::HexInt) => FieldFormat::HexInt,
+                        _ => FieldFormat::Default,
+                    };
+                    builder.add_value_sequence(name, &values, format, 0);
+                }
+                NumberKind::Signed => {
+                    let values: Vec<i64> = numbers.iter().map(|n| number_as_i64(n)).collect();
+                    let format = match hint {
+                        Some(FieldFormat::Time) => FieldFormat::Time,
+                        _ => FieldFormat::SignedInt,
+                    };
+                    builder.add_value_sequence(name, &values, format, 0);
+                }
+                NumberKind::Float => {
+                    let values: Vec<f64> = numbers.iter().map(|n| number_as_f64(n)).collect();
+                    builder.add_value_sequence(name, &values, FieldFormat::Float, 0);
+                }
+            }
+        }
+        _ => return false,
+    }
+
+    return true;
+}
+
+#[derive(Clone, Copy)]
+enum NumberKind {
+    Unsigned,
+    Signed,
+    Float,
+}
+
+fn number_as_u64(number: &Number) -> u64 {
+    return match number.as_u64() {
+        Some(value) => value,
+        None => number_as_i64(number) as u64,
+    };
+}
+
+fn number_as_i64(number: &Number) -> i64 {
+    return match number.as_i64() {
+        Some(value) => value,
+        None => match number.as_u64() {
+            Some(value) => value as i64,
+            None => number_as_f64(number) as i64,
+        },
+    };
+}
+
+fn number_as_f64(number: &Number) -> f64 {
+    return number.as_f64().unwrap_or(0.0);
+}
+
+fn is_string_format(format: FieldFormat) -> bool {
+    return format == FieldFormat::Default
+        || format == FieldFormat::HexBytes
+        || format == FieldFormat::String8
+        || format == FieldFormat::StringUtf
+        || format == FieldFormat::StringUtfBom
+        || format == FieldFormat::StringXml
+        || format == FieldFormat::StringJson;
+}
+
+/// Parses a UUID string such as `"7ae27afb-11cf-4edd-8b15-9997ec20e0fc"`, with or
+/// without hyphens and optionally enclosed in braces.
+fn parse_uuid(value: &str) -> Option<[u8; 16]> {
+    let value = value.strip_prefix('{').unwrap_or(value);
+    let value = value.strip_suffix('}').unwrap_or(value);
+    let hyphenated = value.len() == 36;
+    if !hyphenated && value.len() != 32 {
+        return None;
+    }
+
+    let mut uuid = [0u8; 16];
+    let mut digits = value.bytes().enumerate().filter_map(|(i, ch)| {
+        if hyphenated && (i == 8 || i == 13 || i == 18 || i == 23) {
+            return if ch == b'-' { None } else { Some(None) };
+        }
+        return Some((ch as char).to_digit(16));
+    });
+    for byte in uuid.iter_mut() {
+        let hi = digits.next()??;
+        let lo = digits.next()??;
+        *byte = (hi * 16 + lo) as u8;
+    }
+
+    return Some(uuid);
+}
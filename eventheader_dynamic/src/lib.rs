@@ -42,7 +42,7 @@
 //! use eventheader_dynamic as ehd;
 //!
 //! // Create a provider to use for all "MyCompany_MyComponent" events.
-//! let mut provider = ehd::Provider::new("MyCompany_MyComponent", &ehd::Provider::new_options());
+//! let provider = ehd::Provider::new("MyCompany_MyComponent", &ehd::Provider::new_options());
 //!
 //! // Create an event_set to use for all "MyCompany_MyComponent" events with severity
 //! // level Verbose and event category bits 0x1f.
@@ -71,14 +71,49 @@
 //!
 //! The [EventBuilder] object is reusable. You may get a small performance benefit by
 //! reusing an EventBuilder object for multiple events rather than using a new
-//! EventBuilder for each event.
+//! EventBuilder for each event. With the `pool` feature enabled,
+//! `EventBuilder::pooled` returns a builder from a thread-local pool, avoiding
+//! both allocation and cross-thread sharing. A [FixedEventBuilder] stores the event in
+//! fixed-size inline buffers and never allocates, e.g. for use in a signal handler.
 //!
 //! Events are limited in size (event size = headers + metadata + data). The kernel will
-//! ignore any event that is larger than 64KB.
+//! ignore any event that is larger than 64KB. By default, [EventBuilder] will not write
+//! such an event. Use an [OverflowPolicy] to have it shorten string fields or drop
+//! trailing fields instead.
 //!
 //! All event sets registered with a provider will become unregistered when the provider
 //! is dropped or when you call `provider.unregister()`.
 //!
+//! [Provider] can be shared between threads, e.g. stored in an `Arc` or a `static`.
+//! Finding an already-registered event set does not take a lock. Registering a new
+//! event set takes a short internal lock, so register the sets you need up front
+//! when possible.
+//!
+//! For diagnostics, [`EventProvider::for_each_tracepoint`] reports the name, level,
+//! keyword, registration errno, and enabled state of each of a provider's event sets,
+//! and [for_each_provider] visits every provider in the process (both [Provider] and
+//! static `eventheader::Provider`).
+//!
+//! For events that are written many times with the same schema, an [EventTemplate]
+//! builds the metadata once, and a [TemplateWriter] supplies only the field values for
+//! each event.
+//!
+//! With the `serde` feature enabled, [EventBuilder] can add fields from any value that
+//! implements `serde::Serialize` via `add_serialize` (one field) or
+//! `add_serialize_fields` (the fields of a struct or map become event fields).
+//!
+//! With the `json` feature enabled, [EventBuilder] can add fields from a
+//! `serde_json::Value` via `add_json`, `add_json_fields`, or `add_json_fields_str`.
+//! Numbers, strings, booleans, arrays, and objects become the corresponding
+//! eventheader fields, and a `JsonHints` can force the format of specific fields.
+//!
+//! With the `config` feature enabled, a `ProvidersConfig` (deserialized with serde,
+//! e.g. via `ProvidersConfig::from_toml_str` with the `toml` feature or
+//! `ProvidersConfig::from_json_str` with the `json` feature) describes providers and
+//! the event sets to register for them. `ProvidersConfig::load` validates the names,
+//! creates the providers, registers the event sets, and reports per-provider and
+//! per-set errors.
+//!
 //! Each event set maps to one tracepoint name, e.g. if the provider name is
 //! "MyCompany_MyComponent", level is Verbose, and category bits are 0x1f, the event set
 //! will correspond to a tracepoint named "MyCompany_MyComponent_L5K1f".
@@ -89,18 +124,69 @@
 //! Decode events using a tool such as `decode-perf`.
 
 // Re-exports from eventheader:
+pub use eventheader::set_suppression_policy;
+pub use eventheader::suppression_policy;
+pub use eventheader::EventHeader;
+pub use eventheader::EventProvider;
+pub use eventheader::EventThrottle;
+pub use eventheader::ExtensionKind;
 pub use eventheader::FieldEncoding;
 pub use eventheader::FieldFormat;
+pub use eventheader::HeaderFlags;
 pub use eventheader::Level;
 pub use eventheader::NativeImplementation;
 pub use eventheader::Opcode;
+pub use eventheader::SuppressionPolicy;
+pub use eventheader::SuppressionPolicyError;
+pub use eventheader::TracepointNameError;
+pub use eventheader::TracepointStatus;
 pub use eventheader::NATIVE_IMPLEMENTATION;
+pub use eventheader::SUPPRESSION_POLICY_ENV;
+
+#[cfg(feature = "testing")]
+pub use eventheader::testing;
 
 // Exports from eventheader_dynamic:
+pub use buffer::BuilderBuffer;
+pub use buffer::FixedBuffer;
 pub use builder::EventBuilder;
+pub use builder::FixedEventBuilder;
+#[cfg(feature = "config")]
+pub use config::ConfigError;
+#[cfg(feature = "config")]
+pub use config::ConfigErrorKind;
+#[cfg(feature = "config")]
+pub use config::ConfigLoad;
+#[cfg(feature = "config")]
+pub use config::EventSetConfig;
+#[cfg(feature = "config")]
+pub use config::LoadedProvider;
+#[cfg(feature = "config")]
+pub use config::ProviderConfig;
+#[cfg(feature = "config")]
+pub use config::ProvidersConfig;
+pub use overflow::OverflowPolicy;
+#[cfg(feature = "pool")]
+pub use pool::PooledEventBuilder;
+pub use provider::for_each_provider;
 pub use provider::EventSet;
 pub use provider::Provider;
 pub use provider::ProviderOptions;
+pub use template::EventTemplate;
+pub use template::TemplateWriter;
+pub use validation::ValidationError;
+#[cfg(feature = "json")]
+pub use json::JsonHints;
+#[cfg(feature = "serde")]
+pub use serializer::FieldSerializer;
+#[cfg(feature = "serde")]
+pub use serializer::MapSerializer;
+#[cfg(feature = "serde")]
+pub use serializer::SeqSerializer;
+#[cfg(feature = "serde")]
+pub use serializer::SerializeError;
+#[cfg(feature = "serde")]
+pub use serializer::StructSerializer;
 
 pub mod changelog;
 
@@ -131,5 +217,19 @@ macro_rules! time_from_systemtime {
 }
 
 extern crate alloc;
+#[cfg(feature = "pool")]
+extern crate std;
+mod buffer;
 mod builder;
+#[cfg(feature = "config")]
+mod config;
+#[cfg(feature = "json")]
+mod json;
+mod overflow;
+#[cfg(feature = "pool")]
+mod pool;
 mod provider;
+#[cfg(feature = "serde")]
+mod serializer;
+mod template;
+mod validation;
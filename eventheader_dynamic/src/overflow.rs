@@ -0,0 +1,312 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use alloc::vec::Vec;
+
+use crate::FieldEncoding;
+use crate::FieldFormat;
+
+/// Metadata for the `_truncated` field: name, Value8 | ChainFlag, Boolean.
+pub(crate) const TRUNCATED_META: &[u8] = b"_truncated\0\x82\x07";
+
+/// Data for the `_truncated` field: true.
+pub(crate) const TRUNCATED_DATA: &[u8] = &[1];
+
+/// Controls what [`EventBuilder`](crate::EventBuilder) does when an event is too
+/// large to be written, i.e. when its metadata + data would exceed the 64KB
+/// tracepoint size limit.
+///
+/// The policy is applied when the event is written (or encoded). It does not change
+/// the fields stored in the builder. If the policy shrinks the event, a `u8` field
+/// named `"_truncated"` with format [`FieldFormat::Boolean`] and value `true` is
+/// appended to the written event.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum OverflowPolicy {
+    /// Do not write the event. `write` returns `ERANGE` (34). This is the default.
+    #[default]
+    Fail,
+
+    /// Shorten counted string and binary fields (fields added by `add_str` or
+    /// `add_binary`, including members of structs) until the event fits, starting
+    /// with the last such field. UTF-8 strings are shortened on a character boundary.
+    /// If the event still does not fit, `write` returns `ERANGE` (34).
+    ///
+    /// Nul-terminated strings and strings within sequences are not shortened.
+    TruncateStrings,
+
+    /// Drop trailing top-level fields (a struct is dropped as a unit) until the
+    /// event fits. If the event still does not fit (i.e. the event name alone is too
+    /// large), `write` returns `ERANGE` (34).
+    DropFields,
+}
+
+/// Location of a counted string or binary field's data.
+struct CountedField {
+    /// Position of the field's 16-bit length in the data.
+    pos: usize,
+    /// Size of each element of the field, in bytes.
+    elem_size: usize,
+    /// True if the field should only be shortened on a UTF-8 character boundary.
+    utf8: bool,
+}
+
+/// Walks the fields of an event's metadata and data.
+struct Walker<'a> {
+    meta: &'a [u8],
+    data: &'a [u8],
+    meta_pos: usize,
+    data_pos: usize,
+}
+
+/// Information about a field, passed to the callback of [`Walker::field`].
+struct FieldInfo {
+    depth: u32,
+    meta_start: usize,
+    data_start: usize,
+    encoding: u8,
+    format: u8,
+    is_array: bool,
+}
+
+impl<'a> Walker<'a> {
+    fn meta_u8(&mut self) -> Option<u8> {
+        let value = *self.meta.get(self.meta_pos)?;
+        self.meta_pos += 1;
+        return Some(value);
+    }
+
+    fn meta_u16(&mut self) -> Option<u16> {
+        let bytes = self.meta.get(self.meta_pos..self.meta_pos + 2)?;
+        self.meta_pos += 2;
+        return Some(u16::from_ne_bytes([bytes[0], bytes[1]]));
+    }
+
+    fn data_u16(&mut self) -> Option<u16> {
+        let bytes = self.data.get(self.data_pos..self.data_pos + 2)?;
+        self.data_pos += 2;
+        return Some(u16::from_ne_bytes([bytes[0], bytes[1]]));
+    }
+
+    fn skip_data(&mut self, len: usize) -> Option<()> {
+        if self.data.len() - self.data_pos < len {
+            return None;
+        }
+
+        self.data_pos += len;
+        return Some(());
+    }
+
+    fn skip_zstring(&mut self, elem_size: usize) -> Option<()> {
+        loop {
+            let elem = self.data.get(self.data_pos..self.data_pos + elem_size)?;
+            self.data_pos += elem_size;
+            if elem.iter().all(|b| *b == 0) {
+                return Some(());
+            }
+        }
+    }
+
+    /// Skips the data for one element of a non-struct field.
+    fn skip_value(&mut self, kind: u8) -> Option<()> {
+        return match kind {
+            2..=6 => self.skip_data(1 << (kind - 2)), // Value8..Value128
+            7..=9 => self.skip_zstring(1 << (kind - 7)), // ZStringChar8..ZStringChar32
+            10..=12 => {
+                // StringLength16Char8..StringLength16Char32
+                let len = self.data_u16()? as usize;
+                self.skip_data(len << (kind - 10))
+            }
+            13 => {
+                // BinaryLength16Char8
+                let len = self.data_u16()? as usize;
+                self.skip_data(len)
+            }
+            _ => None,
+        };
+    }
+
+    /// Walks the next field. Calls `on_field` for the field and, for a struct that is
+    /// not an array, for each of its members. Members of an array of struct are
+    /// skipped without calling `on_field`.
+    fn field(
+        &mut self,
+        depth: u32,
+        has_data: bool,
+        on_field: &mut dyn FnMut(&FieldInfo),
+    ) -> Option<()> {
+        let meta_start = self.meta_pos;
+        let data_start = self.data_pos;
+
+        let name_len = self.meta[self.meta_pos..].iter().position(|b| *b == 0)?;
+        self.meta_pos += name_len + 1;
+
+        let encoding = self.meta_u8()?;
+        let mut format = 0;
+        if 0 != encoding & FieldEncoding::ChainFlag {
+            format = self.meta_u8()?;
+            if 0 != format & FieldFormat::ChainFlag {
+                self.meta_u16()?; // field tag
+            }
+        }
+
+        let count = match encoding & FieldEncoding::ArrayFlagMask {
+            0 => None,
+            FieldEncoding::CArrayFlag => Some(self.meta_u16()?),
+            FieldEncoding::VArrayFlag if has_data => Some(self.data_u16()?),
+            FieldEncoding::VArrayFlag => Some(0),
+            _ => return None,
+        };
+
+        if has_data {
+            on_field(&FieldInfo {
+                depth,
+                meta_start,
+                data_start,
+                encoding,
+                format,
+                is_array: count.is_some(),
+            });
+        }
+
+        let kind = encoding & FieldEncoding::ValueMask;
+        if kind == FieldEncoding::Struct.as_int() {
+            let member_count = format & FieldFormat::ValueMask;
+            if member_count == 0 {
+                return None;
+            }
+
+            let members_start = self.meta_pos;
+            let elements = if has_data { count.unwrap_or(1) } else { 0 };
+            if elements == 0 {
+                // Skip the member metadata.
+                for _ in 0..member_count {
+                    self.field(depth + 1, false, &mut |_| {})?;
+                }
+            } else {
+                for element in 0..elements {
+                    self.meta_pos = members_start;
+                    for _ in 0..member_count {
+                        if count.is_none() && element == 0 {
+                            self.field(depth + 1, true, on_field)?;
+                        } else {
+                            self.field(depth + 1, true, &mut |_| {})?;
+                        }
+                    }
+                }
+            }
+        } else if has_data {
+            for _ in 0..count.unwrap_or(1) {
+                self.skip_value(kind)?;
+            }
+        }
+
+        return Some(());
+    }
+
+    /// Walks all fields of the event. Returns None if the event is malformed.
+    fn walk(meta: &'a [u8], data: &'a [u8], on_field: &mut dyn FnMut(&FieldInfo)) -> Option<()> {
+        let name_len = meta.iter().position(|b| *b == 0)?;
+        let mut walker = Walker {
+            meta,
+            data,
+            meta_pos: name_len + 1,
+            data_pos: 0,
+        };
+
+        while walker.meta_pos != meta.len() {
+            walker.field(0, true, on_field)?;
+        }
+
+        return if walker.data_pos == data.len() {
+            Some(())
+        } else {
+            None
+        };
+    }
+}
+
+/// Returns the (meta_len, data_len) of the longest prefix of the event's top-level
+/// fields such that `meta_len + data_len <= budget`, or None if no prefix fits.
+pub(crate) fn drop_fields(meta: &[u8], data: &[u8], budget: usize) -> Option<(usize, usize)> {
+    let mut fit = None;
+    let name_len = meta.iter().position(|b| *b == 0)? + 1;
+    if name_len <= budget {
+        fit = Some((name_len, 0));
+    }
+
+    Walker::walk(meta, data, &mut |field| {
+        if field.depth == 0 && field.meta_start + field.data_start <= budget {
+            fit = Some((field.meta_start, field.data_start));
+        }
+    })?;
+
+    return fit;
+}
+
+/// Returns a copy of the event's data in which the last counted string and binary
+/// fields have been shortened by a total of at least `excess` bytes, or None if the
+/// fields cannot be shortened enough.
+pub(crate) fn truncate_strings(meta: &[u8], data: &[u8], mut excess: usize) -> Option<Vec<u8>> {
+    let mut fields = Vec::new();
+    Walker::walk(meta, data, &mut |field| {
+        let kind = field.encoding & FieldEncoding::ValueMask;
+        if !field.is_array
+            && (FieldEncoding::StringLength16Char8.as_int()
+                ..=FieldEncoding::BinaryLength16Char8.as_int())
+                .contains(&kind)
+        {
+            let format = field.format & FieldFormat::ValueMask;
+            fields.push(CountedField {
+                pos: field.data_start,
+                elem_size: if kind == FieldEncoding::BinaryLength16Char8.as_int() {
+                    1
+                } else {
+                    1 << (kind - FieldEncoding::StringLength16Char8.as_int())
+                },
+                utf8: kind == FieldEncoding::StringLength16Char8.as_int()
+                    && format != FieldFormat::HexBytes.as_int()
+                    && format != FieldFormat::String8.as_int(),
+            });
+        }
+    })?;
+
+    // (position, old length, new length), last field first.
+    let mut cuts = Vec::new();
+    for field in fields.iter().rev() {
+        if excess == 0 {
+            break;
+        }
+
+        let len = u16::from_ne_bytes([data[field.pos], data[field.pos + 1]]) as usize;
+        let remove = (excess + field.elem_size - 1) / field.elem_size;
+        let mut new_len = len.saturating_sub(remove);
+        if field.utf8 {
+            // Don't split a multi-byte character.
+            while new_len != 0 && data[field.pos + 2 + new_len] & 0xC0 == 0x80 {
+                new_len -= 1;
+            }
+        }
+
+        if new_len != len {
+            excess = excess.saturating_sub((len - new_len) * field.elem_size);
+            cuts.push((field, len, new_len));
+        }
+    }
+
+    if excess != 0 {
+        return None;
+    }
+
+    let mut result = Vec::with_capacity(data.len());
+    let mut copied = 0;
+    for (field, len, new_len) in cuts.iter().rev() {
+        let values = field.pos + 2;
+        result.extend_from_slice(&data[copied..field.pos]);
+        result.extend_from_slice(&(*new_len as u16).to_ne_bytes());
+        result.extend_from_slice(&data[values..values + new_len * field.elem_size]);
+        copied = values + len * field.elem_size;
+    }
+
+    result.extend_from_slice(&data[copied..]);
+    return Some(result);
+}
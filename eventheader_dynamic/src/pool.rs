@@ -0,0 +1,92 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::ops;
+
+use crate::EventBuilder;
+#[allow(unused_imports)] // For docs
+use crate::OverflowPolicy;
+
+/// Maximum number of idle builders kept by each thread's pool.
+const POOL_MAX: usize = 4;
+
+std::thread_local! {
+    static POOL: RefCell<Vec<EventBuilder>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An [`EventBuilder`] borrowed from the current thread's pool, returned by
+/// [`EventBuilder::pooled`].
+///
+/// Dereferences to `EventBuilder`. When dropped, the builder is reset (including its
+/// [`OverflowPolicy`] and encode provider) and returned to the pool of the thread
+/// that drops it, keeping its buffers for the next event.
+#[derive(Debug)]
+pub struct PooledEventBuilder {
+    builder: Option<EventBuilder>,
+}
+
+impl EventBuilder {
+    /// Returns a builder from the current thread's pool, or a new builder if the pool
+    /// is empty. Requires the `pool` feature.
+    ///
+    /// The builder returns to the pool when the returned guard is dropped. Each thread
+    /// keeps up to 4 idle builders, so once the pool is warm, building and writing
+    /// events with pooled builders does not allocate (unless an event needs more
+    /// buffer space than the builder has used before).
+    ///
+    /// Call `reset` before adding fields, as for any builder:
+    ///
+    /// ```
+    /// # use eventheader_dynamic as ehd;
+    /// # let provider = ehd::Provider::new("MyCompany_MyComponent", &ehd::Provider::new_options());
+    /// # let event_set = provider.register_set(ehd::Level::Verbose, 0x1);
+    /// if event_set.enabled() {
+    ///     ehd::EventBuilder::pooled()
+    ///         .reset("MyEventName", 0)
+    ///         .add_value("FieldName", 1u32, ehd::FieldFormat::Default, 0)
+    ///         .write(&event_set, None, None);
+    /// }
+    /// ```
+    pub fn pooled() -> PooledEventBuilder {
+        let builder = POOL
+            .try_with(|pool| pool.borrow_mut().pop())
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        return PooledEventBuilder {
+            builder: Some(builder),
+        };
+    }
+}
+
+impl ops::Deref for PooledEventBuilder {
+    type Target = EventBuilder;
+
+    fn deref(&self) -> &EventBuilder {
+        return self.builder.as_ref().unwrap();
+    }
+}
+
+impl ops::DerefMut for PooledEventBuilder {
+    fn deref_mut(&mut self) -> &mut EventBuilder {
+        return self.builder.as_mut().unwrap();
+    }
+}
+
+impl Drop for PooledEventBuilder {
+    fn drop(&mut self) {
+        if let Some(mut builder) = self.builder.take() {
+            builder.reset("", 0).reset_settings();
+
+            // If the thread's pool has been destroyed (thread exit), drop the builder.
+            _ = POOL.try_with(move |pool| {
+                let mut pool = pool.borrow_mut();
+                if pool.len() < POOL_MAX {
+                    pool.push(builder);
+                }
+            });
+        }
+    }
+}
@@ -2,32 +2,94 @@
 // Licensed under the MIT license.
 
 use alloc::boxed::Box;
-use alloc::collections::BTreeSet;
+use alloc::string::ToString;
+use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
 
-use core::borrow;
+use core::cell::UnsafeCell;
 use core::cmp;
 use core::fmt;
 use core::hash;
+use core::hint;
+use core::mem;
 use core::pin::Pin;
 use core::str;
+use core::sync::atomic;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicPtr;
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::AtomicUsize;
 
+use eventheader::EventProvider;
 use eventheader::Level;
+use eventheader::TracepointName;
+use eventheader::TracepointNameError;
+use eventheader::TracepointStatus;
 use eventheader::_internal::*;
 
+use crate::ValidationError;
+
 #[allow(unused_imports)] // For docs
 use crate::EventBuilder;
 
-const fn is_option_val_char(ch: u8) -> bool {
-    return (b'0' <= ch && ch <= b'9') || (b'a' <= ch && ch <= b'z');
-}
-
 /// Represents a connection for writing dynamic Linux tracepoints.
+///
+/// `Provider` is `Send` and `Sync`, so it can be shared between threads, e.g. by
+/// storing it in an `Arc` or in a `static` (e.g. via `std::sync::OnceLock`).
+/// Looking up an existing event set ([`Provider::find_set`], or
+/// [`Provider::register_set`] when the set already exists) does not take a lock.
+/// Creating a new event set or unregistering the provider briefly takes an internal
+/// lock to update the provider's list of event sets. The lock is not held while
+/// registering or unregistering tracepoints with the kernel, but it may be held
+/// while waiting for concurrent lookups to finish before the provider's previous
+/// list of event sets is released.
 pub struct Provider {
-    name: Box<[u8]>,
+    // Boxed so that the process-wide list of providers can refer to it.
+    inner: Box<ProviderInner>,
+}
+
+struct ProviderInner {
+    name: Arc<[u8]>, // Shared with the provider's event sets.
     options: Box<[u8]>,
-    sets: BTreeSet<Pin<Arc<EventSet>>>,
+    sets: SetList,
+}
+
+/// Process-wide list of live dynamic providers.
+struct ProviderList {
+    lock: AtomicBool,
+    providers: UnsafeCell<Vec<*const ProviderInner>>,
+}
+
+// Safety: providers is only accessed while holding the lock, and ProviderInner is
+// Send + Sync.
+unsafe impl Sync for ProviderList {}
+
+static PROVIDERS: ProviderList = ProviderList {
+    lock: AtomicBool::new(false),
+    providers: UnsafeCell::new(Vec::new()),
+};
+
+/// Calls `f` for each provider in the process: each live [`Provider`] (i.e. each
+/// `Provider` that has not been dropped) and each registered static
+/// `eventheader::Provider` (see `eventheader::for_each_provider`). Use
+/// [`EventProvider::for_each_tracepoint`] to get the status of each provider's
+/// tracepoints.
+///
+/// `f` is called while holding a process-wide lock, so it must not create or drop a
+/// [`Provider`] or register or unregister a static provider.
+pub fn for_each_provider(mut f: impl FnMut(&dyn EventProvider)) {
+    {
+        let _lock = SpinLock::acquire(&PROVIDERS.lock);
+
+        // Safety: we hold the lock, and providers remove themselves (under the
+        // lock) before they are dropped.
+        for &provider in unsafe { &*PROVIDERS.providers.get() } {
+            f(unsafe { &*provider });
+        }
+    }
+
+    eventheader::for_each_provider(|provider| f(provider));
 }
 
 impl Provider {
@@ -54,15 +116,29 @@ impl Provider {
     ///
     /// Use [EventBuilder] to create events, then write them to a registered event set.
     pub fn new(name: &str, options: &ProviderOptions) -> Self {
-        const NAMES_MAX: usize = EVENTHEADER_NAME_MAX - "_LffKffffffffffffffffG".len();
+        let valid = TracepointName::validate_provider(name, options.group_name);
         assert!(
-            name.len() + options.group_name.len() < NAMES_MAX,
+            valid != Err(TracepointNameError::NameTooLong),
             "provider name.len() + group_name.len() must be less than 234"
         );
-        debug_assert!(!name.contains('\0'), "provider name must not contain '\\0'");
-        debug_assert!(!name.contains(' '), "provider name must not contain ' '");
-        debug_assert!(!name.contains(':'), "provider name must not contain ':'");
+        debug_assert!(valid.is_ok(), "{}", valid.unwrap_err());
+        return Self::create(name, options);
+    }
+
+    /// Creates a new provider, returning an error instead of panicking if the
+    /// provider name or group name is invalid. Use this instead of [`Provider::new`]
+    /// when the names come from user input.
+    ///
+    /// Returns [`ValidationError::ProviderName`] if `name` is empty or contains `'\0'`,
+    /// `' '`, or `':'`, if `options.group_name` contains characters other than `0..9`
+    /// and `a..z`, or if the total length of the provider name + the group name is not
+    /// less than 234.
+    pub fn try_new(name: &str, options: &ProviderOptions) -> Result<Self, ValidationError> {
+        TracepointName::validate_provider(name, options.group_name)?;
+        return Ok(Self::create(name, options));
+    }
 
+    fn create(name: &str, options: &ProviderOptions) -> Self {
         let group_name_bytes = options.group_name.as_bytes();
         let options_box = if group_name_bytes.is_empty() {
             <Box<[u8]>>::default()
@@ -73,21 +149,36 @@ impl Provider {
             options_vec.into()
         };
 
-        return Self {
-            name: name.as_bytes().into(),
+        let inner = Box::new(ProviderInner {
+            name: Arc::from(name.as_bytes()),
             options: options_box,
-            sets: BTreeSet::new(),
-        };
+            sets: SetList::new(),
+        });
+
+        // Read the suppression policy before any of the event sets can be enabled.
+        read_env_suppression_policy();
+
+        let _lock = SpinLock::acquire(&PROVIDERS.lock);
+        // Safety: we hold the lock.
+        unsafe { &mut *PROVIDERS.providers.get() }.push(&*inner);
+        return Self { inner };
     }
 
     /// Returns this provider's name.
     pub fn name(&self) -> &str {
-        return str::from_utf8(&self.name).unwrap();
+        return self.inner.name();
     }
 
     /// Returns this provider's options, e.g. "" or "Gmygroup".
     pub fn options(&self) -> &str {
-        return str::from_utf8(&self.options).unwrap();
+        return self.inner.options();
+    }
+
+    /// Returns the name of the tracepoint used for event sets with the specified
+    /// level and keyword, e.g. `"MyCompany_MyComponent_L5K1fGmygroup"`.
+    pub fn tracepoint_name(&self, level: Level, keyword: u64) -> String {
+        return TracepointName::new_unchecked(self.name(), level, keyword, self.options())
+            .to_string();
     }
 
     /// If this provider is not registered, does nothing and returns 0.
@@ -97,33 +188,30 @@ impl Provider {
     /// Use `provider.unregister()` if you want to unregister the provider before it goes
     /// out of scope. The provider automatically unregisters when it is dropped so most
     /// users do  not need to call `unregister` directly.
-    pub fn unregister(&mut self) {
-        for set in &self.sets {
+    ///
+    /// A set created by a [`Provider::register_set`] call that runs concurrently with
+    /// `unregister` might be unregistered by that call: it is then returned to the
+    /// caller but is not added to the list and will never be enabled.
+    pub fn unregister(&self) {
+        let retired = {
+            let _lock = self.inner.sets.lock();
+
+            // Safety: we hold the lock.
+            unsafe { self.inner.sets.retire() }
+        };
+
+        // Unregister outside of the lock.
+        for set in retired.iter() {
             set.state.unregister();
         }
-        self.sets.clear();
     }
 
     /// If an event set with the specified level and keyword is in the list of
     /// already-created sets, returns it. Otherwise, returns `None`.
+    ///
+    /// This method does not take a lock.
     pub fn find_set(&self, level: Level, keyword: u64) -> Option<Arc<EventSet>> {
-        let opt_val = self.sets.get(&EventSetKey { keyword, level });
-        match opt_val {
-            None => return None,
-            Some(val) => {
-                let val_pin_arc = val.clone();
-
-                // Safety:
-                // - unsafe because we're turning the pinned Arc into an unpinned Arc, and
-                //   it is possible to move-from an unpinned Arc via Arc::get_mut().
-                // - Ok here because Arc::get_mut() only works when the Arc's refcount is
-                //   1, and we will be holding our pinned Arc for as long as the set is
-                //   registered, so the caller will always see a refcount of 2 or more and
-                //   therefore will not be able to use Arc::get_mut().
-                let val_arc = unsafe { Pin::into_inner_unchecked(val_pin_arc) };
-                return Some(val_arc);
-            }
-        }
+        return self.inner.sets.find(level, keyword);
     }
 
     /// If an event set with the specified level and keyword is in the list of
@@ -131,50 +219,70 @@ impl Provider {
     /// the list of already-created sets, attempts to register it, and returns the new
     /// event set. If registration fails, the new event set will have a non-zero errno
     /// and will never be enabled.
-    pub fn register_set(&mut self, level: Level, keyword: u64) -> Arc<EventSet> {
-        let set_pin_arc = match self.sets.get(&EventSetKey { keyword, level }) {
-            Some(set_pin_arc) => set_pin_arc.clone(),
-            None => {
-                let mut set_arc = Arc::new(EventSet::new(0, level, keyword));
-                let set_mut = Arc::get_mut(&mut set_arc).unwrap();
-
-                // Safety:
-                // - unsafe because we must guarantee that nobody ever moves-from
-                //   or deallocates set_mut.state while it is registered.
-                // - We will guarantee this by pinning set and storing it in an Arc.
-                //   set is not pinned yet but will be soon, and we don't move-from it
-                //   in the meantime.
-                // - set will then remain pinned and referenced until self.unregister(),
-                //   which will invoke state.unregister() for all sets, and only then will
-                //   we release the Arc, so it cannot be deallocated/moved before then.
-                let state_pin = unsafe { Pin::new_unchecked(&set_mut.state) };
-
-                // Command = "ProviderName_LxKxOptions CommandTypes\0"
-                let mut command_string = CommandString::new();
-                let name_args = command_string.format(&self.name, &self.options, level, keyword);
-
-                // Safety:
-                // - unsafe because we must guarantee that state gets unregistered
-                //   before it moves or is deallocated.
-                // - state will get unregistered at self.unregister().
-                // - state cannot be deallocated or moved before it gets unregistered.
-                set_mut.errno = unsafe { state_pin.register(name_args) };
-
-                let set_pin_arc = unsafe { Pin::new_unchecked(set_arc) };
-                self.sets.insert(set_pin_arc.clone());
-                set_pin_arc
-            }
-        };
+    ///
+    /// Returning an already-created set does not take a lock. Creating a new set
+    /// registers it without holding a lock, then takes the provider's internal lock
+    /// to add it to the list. If another thread creates the same set at the same time,
+    /// one of the new sets is unregistered and both threads return the other. If
+    /// [`Provider::unregister`] runs while the new set is being registered, the new
+    /// set is unregistered and returned without being added to the list.
+    pub fn register_set(&self, level: Level, keyword: u64) -> Arc<EventSet> {
+        if let Some(set) = self.inner.sets.find(level, keyword) {
+            return set;
+        }
+
+        let generation = self.inner.sets.generation();
+
+        let mut set_arc = Arc::new(EventSet::new(self.inner.name.clone(), 0, level, keyword));
+        let set_mut = Arc::get_mut(&mut set_arc).unwrap();
+
+        // Safety:
+        // - unsafe because we must guarantee that nobody ever moves-from
+        //   or deallocates set_mut.state while it is registered.
+        // - We will guarantee this by pinning set and storing it in an Arc.
+        //   set is not pinned yet but will be soon, and we don't move-from it
+        //   in the meantime.
+        // - set will then remain pinned and referenced until self.unregister(),
+        //   which will invoke state.unregister() for all sets, and only then will
+        //   we release the Arc, so it cannot be deallocated/moved before then.
+        let state_pin = unsafe { Pin::new_unchecked(&set_mut.state) };
+
+        // Command = "ProviderName_LxKxOptions CommandTypes\0"
+        let mut command_string = CommandString::new();
+        let name_args = command_string.format(&TracepointName::new_unchecked(
+            self.inner.name(),
+            level,
+            keyword,
+            self.inner.options(),
+        ));
 
         // Safety:
-        // - unsafe because we're turning the pinned Arc into an unpinned Arc, and
-        //   it is possible to move-from an unpinned Arc via Arc::get_mut().
-        // - Ok here because Arc::get_mut() only works when the Arc's refcount is
-        //   1, and we will be holding our pinned Arc for as long as the set is
-        //   registered, so the caller will always see a refcount of 2 or more and
-        //   therefore will not be able to use Arc::get_mut().
-        let set_arc = unsafe { Pin::into_inner_unchecked(set_pin_arc) };
-        return set_arc;
+        // - unsafe because we must guarantee that state gets unregistered
+        //   before it moves or is deallocated.
+        // - state will get unregistered at self.unregister().
+        // - state cannot be deallocated or moved before it gets unregistered.
+        set_mut.errno = unsafe { register_tracepoint(state_pin, name_args) };
+
+        let set_pin_arc = unsafe { Pin::new_unchecked(set_arc) };
+
+        let lock = self.inner.sets.lock();
+
+        // The provider may have been unregistered while we were registering ours.
+        if generation != self.inner.sets.generation() {
+            drop(lock);
+            set_pin_arc.state.unregister();
+            return unpin_set(&set_pin_arc);
+        }
+
+        // Another thread may have created the set while we were registering ours.
+        if let Some(existing) = self.inner.sets.find(level, keyword) {
+            drop(lock);
+            set_pin_arc.state.unregister();
+            return existing;
+        }
+
+        // Safety: we hold the lock, and the list has no set with this key.
+        return unsafe { self.inner.sets.insert(set_pin_arc) };
     }
 
     /// For testing purposes: Creates an inactive (unregistered) event set.
@@ -183,51 +291,327 @@ impl Provider {
     /// already-created sets, returns it. Otherwise, creates a new **unregistered**
     /// event set, adds it to the list of already-created sets, and returns the new
     /// event set.
-    pub fn create_unregistered(
-        &mut self,
-        enabled: bool,
-        level: Level,
+    pub fn create_unregistered(&self, enabled: bool, level: Level, keyword: u64) -> Arc<EventSet> {
+        if let Some(set) = self.inner.sets.find(level, keyword) {
+            return set;
+        }
+
+        let _lock = self.inner.sets.lock();
+        if let Some(set) = self.inner.sets.find(level, keyword) {
+            return set;
+        }
+
+        let set_pin_arc = Arc::pin(EventSet::new(
+            self.inner.name.clone(),
+            enabled as u32,
+            level,
+            keyword,
+        ));
+
+        // Safety: we hold the lock, and the list has no set with this key.
+        return unsafe { self.inner.sets.insert(set_pin_arc) };
+    }
+}
+
+impl EventProvider for Provider {
+    fn name(&self) -> &str {
+        return self.inner.name();
+    }
+
+    fn options(&self) -> &str {
+        return self.inner.options();
+    }
+
+    fn enabled(&self, level: Level, keyword: u64) -> bool {
+        return self.inner.enabled(level, keyword);
+    }
+
+    fn write_encoded(
+        &self,
+        header: &EventHeader,
         keyword: u64,
-    ) -> Arc<EventSet> {
-        let set_pin_arc = match self.sets.get(&EventSetKey { keyword, level }) {
-            Some(set_pin_arc) => set_pin_arc.clone(),
-            None => {
-                let set_pin_arc = Arc::pin(EventSet::new(enabled as u32, level, keyword));
-                self.sets.insert(set_pin_arc.clone());
-                set_pin_arc
-            }
+        activity_id: Option<&[u8; 16]>,
+        related_id: Option<&[u8; 16]>,
+        metadata: &[u8],
+        data: &[u8],
+    ) -> i32 {
+        return self
+            .inner
+            .write_encoded(header, keyword, activity_id, related_id, metadata, data);
+    }
+
+    fn for_each_tracepoint(&self, f: &mut dyn FnMut(&TracepointStatus)) {
+        self.inner.for_each_tracepoint(f);
+    }
+}
+
+impl ProviderInner {
+    fn name(&self) -> &str {
+        return str::from_utf8(&self.name).unwrap();
+    }
+
+    fn options(&self) -> &str {
+        return str::from_utf8(&self.options).unwrap();
+    }
+}
+
+impl EventProvider for ProviderInner {
+    fn name(&self) -> &str {
+        return ProviderInner::name(self);
+    }
+
+    fn options(&self) -> &str {
+        return ProviderInner::options(self);
+    }
+
+    /// Returns true if an event set with the specified level and keyword has been
+    /// registered and is enabled.
+    fn enabled(&self, level: Level, keyword: u64) -> bool {
+        return match self.sets.find(level, keyword) {
+            Some(set) => set.enabled() && !set.suppressed(&[]),
+            None => false,
         };
+    }
 
-        // Safety:
-        // - unsafe because we're turning the pinned Arc into an unpinned Arc, and
-        //   it is possible to move-from an unpinned Arc via Arc::get_mut().
-        // - Ok here because Arc::get_mut() only works when the Arc's refcount is
-        //   1, and we will be holding our pinned Arc for as long as the set is
-        //   registered, so the caller will always see a refcount of 2 or more and
-        //   therefore will not be able to use Arc::get_mut().
-        let set_arc = unsafe { Pin::into_inner_unchecked(set_pin_arc) };
-        return set_arc;
+    fn write_encoded(
+        &self,
+        header: &EventHeader,
+        keyword: u64,
+        activity_id: Option<&[u8; 16]>,
+        related_id: Option<&[u8; 16]>,
+        metadata: &[u8],
+        data: &[u8],
+    ) -> i32 {
+        debug_assert!(related_id.is_none() || activity_id.is_some());
+        return match self.sets.find(header.level, keyword) {
+            Some(set) if set.enabled() && set.suppressed(metadata) => 9, // libc::EBADF
+            Some(set) => write_encoded(&set.state, header, activity_id, related_id, metadata, data),
+            None => 9, // libc::EBADF
+        };
+    }
+
+    /// Reports the status of each event set that has been registered (or created by
+    /// `create_unregistered`) since the provider was created or last unregistered.
+    fn for_each_tracepoint(&self, f: &mut dyn FnMut(&TracepointStatus)) {
+        self.sets.for_each(|set| {
+            f(&TracepointStatus::new(
+                self.name(),
+                self.options(),
+                set.key.level,
+                set.key.keyword,
+                set.errno,
+                set.enabled(),
+            ));
+        });
     }
 }
 
 impl Drop for Provider {
     fn drop(&mut self) {
+        {
+            let _lock = SpinLock::acquire(&PROVIDERS.lock);
+            let inner_ptr: *const ProviderInner = &*self.inner;
+            // Safety: we hold the lock.
+            unsafe { &mut *PROVIDERS.providers.get() }.retain(|&p| p != inner_ptr);
+        }
+
         self.unregister();
     }
 }
 
 impl fmt::Debug for Provider {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sets = 0;
+        self.inner.sets.for_each(|_| sets += 1);
         return write!(
             f,
             "Provider {{ name: \"{}\", options: \"{}\", sets: {} }}",
             self.name(),
-            str::from_utf8(&self.options).unwrap(),
-            self.sets.len(),
+            self.options(),
+            sets,
         );
     }
 }
 
+fn unpin_set(set_pin_arc: &Pin<Arc<EventSet>>) -> Arc<EventSet> {
+    // Safety:
+    // - unsafe because we're turning the pinned Arc into an unpinned Arc, and
+    //   it is possible to move-from an unpinned Arc via Arc::get_mut().
+    // - Ok here because Arc::get_mut() only works when the Arc's refcount is
+    //   1, and we will be holding our pinned Arc for as long as the set is
+    //   registered, so the caller will always see a refcount of 2 or more and
+    //   therefore will not be able to use Arc::get_mut().
+    return unsafe { Pin::into_inner_unchecked(set_pin_arc.clone()) };
+}
+
+/// The provider's event sets, sorted by key.
+type SetTable = Vec<Pin<Arc<EventSet>>>;
+
+/// The provider's list of event sets.
+///
+/// The list is an immutable, sorted [`SetTable`] that writers replace as a whole.
+/// Readers do not take the lock: they add themselves to one of two reader counts,
+/// load the table, and look up or clone what they need. Writers (insert, retire)
+/// must hold the lock, and must not make system calls while holding it. After
+/// replacing the table, a writer sends new readers to the other count and waits
+/// for the previous count to drain before releasing its reference to the old
+/// table, so replaced tables are freed as soon as no reader can be using them.
+struct SetList {
+    table: AtomicPtr<SetTable>, // From Arc::into_raw.
+    lock: AtomicBool,
+    epoch: AtomicUsize, // Index of the reader count used by new readers.
+    readers: [AtomicUsize; 2],
+    generation: AtomicU32, // Incremented by retire. Modified only under the lock.
+}
+
+/// Guard for a simple spin lock.
+struct SpinLock<'a>(&'a AtomicBool);
+
+impl<'a> SpinLock<'a> {
+    fn acquire(lock: &'a AtomicBool) -> Self {
+        while lock
+            .compare_exchange_weak(
+                false,
+                true,
+                atomic::Ordering::Acquire,
+                atomic::Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+
+        return Self(lock);
+    }
+}
+
+impl Drop for SpinLock<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, atomic::Ordering::Release);
+    }
+}
+
+impl SetList {
+    fn new() -> Self {
+        return Self {
+            table: AtomicPtr::new(Arc::into_raw(Arc::new(SetTable::new())) as *mut SetTable),
+            lock: AtomicBool::new(false),
+            epoch: AtomicUsize::new(0),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            generation: AtomicU32::new(0),
+        };
+    }
+
+    fn lock(&self) -> SpinLock<'_> {
+        return SpinLock::acquire(&self.lock);
+    }
+
+    /// Returns the number of times the list has been retired.
+    fn generation(&self) -> u32 {
+        return self.generation.load(atomic::Ordering::Acquire);
+    }
+
+    /// Calls `f` with the current table. `f` must be short and must not block:
+    /// writers wait for it to return before releasing a replaced table.
+    fn read<R>(&self, f: impl FnOnce(&Arc<SetTable>) -> R) -> R {
+        let epoch = loop {
+            let epoch = self.epoch.load(atomic::Ordering::SeqCst);
+            self.readers[epoch].fetch_add(1, atomic::Ordering::SeqCst);
+            if epoch == self.epoch.load(atomic::Ordering::SeqCst) {
+                break epoch;
+            }
+
+            // A writer switched counts. It might not wait for this count.
+            self.readers[epoch].fetch_sub(1, atomic::Ordering::Release);
+        };
+
+        // Safety: a writer that replaces the table waits for this reader count to
+        // drain before releasing the old table, so the table stays alive until we
+        // leave the count. ManuallyDrop: the reference is owned by the list.
+        let table = mem::ManuallyDrop::new(unsafe {
+            Arc::from_raw(self.table.load(atomic::Ordering::Acquire))
+        });
+        let result = f(&table);
+
+        self.readers[epoch].fetch_sub(1, atomic::Ordering::Release);
+        return result;
+    }
+
+    /// Calls `f` for each set in a snapshot of the list. The snapshot is not locked,
+    /// so `f` may block or modify the list.
+    fn for_each(&self, f: impl FnMut(&Pin<Arc<EventSet>>)) {
+        let table = self.read(|table| table.clone());
+        table.iter().for_each(f);
+    }
+
+    fn find(&self, level: Level, keyword: u64) -> Option<Arc<EventSet>> {
+        let key = EventSetKey { keyword, level };
+        return self.read(|table| {
+            table
+                .binary_search_by(|set| set.key.cmp(&key))
+                .ok()
+                .map(|index| unpin_set(&table[index]))
+        });
+    }
+
+    /// Adds `set` to the list and returns it.
+    ///
+    /// Requires: caller holds the lock, and no set with the same key is in the list.
+    unsafe fn insert(&self, set: Pin<Arc<EventSet>>) -> Arc<EventSet> {
+        // Only writers release tables, and we hold the lock, so the current table
+        // stays alive.
+        let current = &*self.table.load(atomic::Ordering::Relaxed);
+        let index = current
+            .binary_search_by(|existing| existing.key.cmp(&set.key))
+            .unwrap_err();
+
+        let mut table = SetTable::with_capacity(current.len() + 1);
+        table.extend_from_slice(&current[..index]);
+        table.push(set);
+        table.extend_from_slice(&current[index..]);
+        let result = unpin_set(&table[index]);
+
+        drop(self.replace(table));
+        return result;
+    }
+
+    /// Removes all sets from the list and returns the removed sets.
+    ///
+    /// Requires: caller holds the lock.
+    unsafe fn retire(&self) -> Arc<SetTable> {
+        self.generation.fetch_add(1, atomic::Ordering::Release);
+        return self.replace(SetTable::new());
+    }
+
+    /// Publishes `table` and returns the previous table once no reader can still be
+    /// accessing it through the list.
+    ///
+    /// Requires: caller holds the lock.
+    unsafe fn replace(&self, table: SetTable) -> Arc<SetTable> {
+        let new_table = Arc::into_raw(Arc::new(table)) as *mut SetTable;
+        let old_table = self.table.swap(new_table, atomic::Ordering::SeqCst);
+
+        // Readers that might have loaded the old table are in the current count.
+        // Send new readers to the other count, then wait for the current count to
+        // drain. Writers are serialized by the lock, so the other count was drained
+        // by the previous writer.
+        let epoch = self.epoch.load(atomic::Ordering::Relaxed);
+        self.epoch.store(epoch ^ 1, atomic::Ordering::SeqCst);
+        while self.readers[epoch].load(atomic::Ordering::SeqCst) != 0 {
+            hint::spin_loop();
+        }
+
+        return Arc::from_raw(old_table);
+    }
+}
+
+impl Drop for SetList {
+    fn drop(&mut self) {
+        // Safety: we have exclusive access, so no reader can be using the table.
+        drop(unsafe { Arc::from_raw(*self.table.get_mut()) });
+    }
+}
+
 /// Builder for provider configuration. Used when registering a provider.
 ///
 /// In most cases, you'll just use the default options.
@@ -241,19 +625,7 @@ impl<'a> ProviderOptions<'a> {
     /// A valid option value contains only ASCII digits and lowercase
     /// ASCII letters.
     pub const fn is_valid_option_value(value: &'a str) -> bool {
-        let value_bytes = value.as_bytes();
-        let mut i = 0;
-        return loop {
-            if i == value_bytes.len() {
-                break true;
-            }
-
-            let ch = value_bytes[i];
-            i += 1;
-            if !is_option_val_char(ch) {
-                break false;
-            }
-        };
+        return TracepointName::is_valid_option_value(value);
     }
 
     /// Creates default provider options.
@@ -267,12 +639,11 @@ impl<'a> ProviderOptions<'a> {
     /// lowercase ASCII letters).
     ///
     /// Most providers do not set any provider group so this is usually not called.
+    ///
+    /// The name is checked when the provider is created: [`Provider::new`] asserts
+    /// (in debug builds) and [`Provider::try_new`] returns an error if the name is
+    /// not valid.
     pub fn group_name(&mut self, name: &'a str) -> &mut Self {
-        debug_assert!(
-            Self::is_valid_option_value(name),
-            "group_name must contain only 0..9 and a..z"
-        );
-
         self.group_name = name;
         return self;
     }
@@ -328,14 +699,16 @@ pub struct EventSet {
     state: TracepointState,
     key: EventSetKey,
     errno: i32,
+    provider_name: Option<Arc<[u8]>>, // For the suppression policy.
 }
 
 impl EventSet {
-    fn new(enable_status: u32, level: Level, keyword: u64) -> EventSet {
+    fn new(provider_name: Arc<[u8]>, enable_status: u32, level: Level, keyword: u64) -> EventSet {
         return EventSet {
             state: TracepointState::new(enable_status),
             key: EventSetKey { keyword, level },
             errno: 0,
+            provider_name: Some(provider_name),
         };
     }
 
@@ -353,6 +726,7 @@ impl EventSet {
                 level: Level::from_int(0),
             },
             errno: 22, // EINVAL
+            provider_name: None,
         };
     }
 
@@ -360,11 +734,29 @@ impl EventSet {
         return &self.state;
     }
 
+    /// Returns true if the process's [`eventheader::SuppressionPolicy`] suppresses an
+    /// event in this set with the specified metadata (empty if the event name is not
+    /// known). For use after `enabled()` returns true.
+    pub(crate) fn suppressed(&self, metadata: &[u8]) -> bool {
+        return event_suppressed(
+            self.provider_name.as_deref().unwrap_or_default(),
+            metadata,
+            self.key.level,
+            self.key.keyword,
+        );
+    }
+
+    /// Returns the level of the events in this event set.
     #[inline(always)]
-    pub(crate) fn level(&self) -> Level {
+    pub fn level(&self) -> Level {
         return self.key.level;
     }
 
+    /// Returns the keyword of the events in this event set.
+    pub fn keyword(&self) -> u64 {
+        return self.key.keyword;
+    }
+
     /// Returns true if any logging session is listening for events with the
     /// provider, level, and keyword associated with this event set.
     #[inline(always)]
@@ -406,12 +798,6 @@ impl PartialOrd for EventSet {
     }
 }
 
-impl borrow::Borrow<EventSetKey> for Pin<Arc<EventSet>> {
-    fn borrow(&self) -> &EventSetKey {
-        return &self.key;
-    }
-}
-
 impl fmt::Debug for EventSet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         return write!(
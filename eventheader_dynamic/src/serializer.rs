@@ -0,0 +1,919 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+
+use eventheader::FieldEncoding;
+use eventheader::FieldFormat;
+use serde::ser;
+use serde::Serialize;
+
+use crate::validation::STRUCT_NEST_LIMIT;
+use crate::EventBuilder;
+
+const STRUCT_FIELD_COUNT_MAX: usize = FieldFormat::ValueMask as usize;
+
+/// Error returned when a value cannot be serialized into an [`EventBuilder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializeError {
+    message: String,
+}
+
+impl SerializeError {
+    fn new(message: impl fmt::Display) -> Self {
+        return Self {
+            message: message.to_string(),
+        };
+    }
+
+    /// Returns the error message.
+    pub fn message(&self) -> &str {
+        return &self.message;
+    }
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(&self.message);
+    }
+}
+
+impl ser::StdError for SerializeError {}
+
+impl ser::Error for SerializeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        return Self::new(msg);
+    }
+}
+
+impl EventBuilder {
+    /// Adds a field containing a value that implements `serde::Serialize`.
+    /// Requires the `serde` feature.
+    ///
+    /// - `field_name` should be a short and distinct string that describes the field.
+    ///
+    /// - `field_value` is the value to be serialized. See [`FieldSerializer`] for
+    ///   details about how values are mapped to eventheader fields.
+    ///
+    /// - `field_tag` is a 16-bit integer that will be recorded in the field and can be
+    ///   used for any provider-defined purpose. Use 0 if you are not using field tags.
+    ///
+    /// If the value cannot be serialized, returns an error and leaves the builder
+    /// unchanged. If the value serializes to nothing (e.g. `None`), no field is added.
+    pub fn add_serialize<T: ?Sized + Serialize>(
+        &mut self,
+        field_name: &str,
+        field_value: &T,
+        field_tag: u16,
+    ) -> Result<&mut Self, SerializeError> {
+        let (meta_len, data_len) = self.raw_lengths();
+        return match field_value.serialize(FieldSerializer::new(self, field_name, field_tag)) {
+            Ok(_) => Ok(self),
+            Err(e) => {
+                self.raw_truncate(meta_len, data_len);
+                Err(e)
+            }
+        };
+    }
+
+    /// Adds the fields of a struct or map that implements `serde::Serialize` as
+    /// top-level fields of the event. Requires the `serde` feature.
+    ///
+    /// For example, if the value is a struct with fields `a` and `b`, this adds event
+    /// fields `a` and `b` (whereas `add_serialize` would add one struct field that
+    /// contains `a` and `b`).
+    ///
+    /// Returns an error (leaving the builder unchanged) if the value does not serialize
+    /// as a struct or map or if one of its fields cannot be serialized.
+    pub fn add_serialize_fields<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<&mut Self, SerializeError> {
+        let (meta_len, data_len) = self.raw_lengths();
+        let serializer = FieldSerializer {
+            builder: self,
+            field_name: "",
+            field_tag: 0,
+            flatten: true,
+            depth: 0,
+        };
+        return match value.serialize(serializer) {
+            Ok(_) => Ok(self),
+            Err(e) => {
+                self.raw_truncate(meta_len, data_len);
+                Err(e)
+            }
+        };
+    }
+}
+
+/// A `serde::Serializer` that adds a value to an [`EventBuilder`] as a field.
+/// Requires the `serde` feature.
+///
+/// Usually used via [`EventBuilder::add_serialize`] or
+/// [`EventBuilder::add_serialize_fields`].
+///
+/// Values are mapped to eventheader fields as follows:
+///
+/// - `bool`, integers, floating-point values, and `char` become value fields
+///   formatted as `Boolean`, `SignedInt`/`UnsignedInt`, `Float`, and `StringUtf`.
+///   128-bit integers are not supported.
+/// - Strings become `StringLength16Char8` fields. Byte arrays (`serialize_bytes`)
+///   become `BinaryLength16Char8` fields.
+/// - Structs and maps become struct fields. Map keys must be strings, chars,
+///   integers, or bools. Tuples and tuple structs become struct fields with members
+///   named "0", "1", etc.
+/// - Sequences become arrays. All elements of a sequence must have the same type, i.e.
+///   they must produce identical metadata. Sequences of structs become arrays of
+///   structs. Sequences of sequences are not supported.
+/// - `None`, `()`, unit structs, empty sequences, and structs or maps with no fields
+///   produce no field.
+/// - Unit variants become a string field containing the variant name. Newtype, tuple,
+///   and struct variants become a struct field with one member named after the variant
+///   (like the externally-tagged enum representation used by `serde_json`).
+/// - Struct fields may be nested at most 8 levels deep (the limit supported by
+///   decoders). More deeply-nested values return an error.
+/// - Newtype structs and `Some(value)` are serialized as the contained value.
+///
+/// The serializer's `Ok` value is `true` if a field was added or `false` if the value
+/// produced no field.
+pub struct FieldSerializer<'a> {
+    builder: &'a mut EventBuilder,
+    field_name: &'a str,
+    field_tag: u16,
+    flatten: bool,
+    depth: usize, // Number of struct fields that contain this field.
+}
+
+impl<'a> FieldSerializer<'a> {
+    /// Returns a serializer that adds a field with the specified name and tag to the
+    /// builder.
+    pub fn new(builder: &'a mut EventBuilder, field_name: &'a str, field_tag: u16) -> Self {
+        return Self {
+            builder,
+            field_name,
+            field_tag,
+            flatten: false,
+            depth: 0,
+        };
+    }
+
+    fn nested(builder: &'a mut EventBuilder, field_name: &'a str, depth: usize) -> Self {
+        return Self {
+            builder,
+            field_name,
+            field_tag: 0,
+            flatten: false,
+            depth,
+        };
+    }
+
+    /// Returns the depth of the members of a new field that is made of `structs`
+    /// nested struct fields.
+    fn struct_depth(&self, structs: usize) -> Result<usize, SerializeError> {
+        return if self.depth + structs > STRUCT_NEST_LIMIT {
+            Err(SerializeError::new(
+                "struct fields are nested more than 8 levels deep",
+            ))
+        } else {
+            Ok(self.depth + structs)
+        };
+    }
+
+    fn check_field(&self) -> Result<(), SerializeError> {
+        return if self.flatten {
+            Err(SerializeError::new(
+                "top-level value must be a struct or a map",
+            ))
+        } else {
+            Ok(())
+        };
+    }
+
+    fn add_value<V: crate::builder::ValueField>(
+        self,
+        value: V,
+        format: FieldFormat,
+    ) -> Result<bool, SerializeError> {
+        self.check_field()?;
+        self.builder
+            .add_value(self.field_name, value, format, self.field_tag);
+        return Ok(true);
+    }
+
+    fn begin_struct(self, len: usize) -> Result<StructSerializer<'a>, SerializeError> {
+        let depth = if self.flatten {
+            self.depth
+        } else {
+            self.struct_depth(1)?
+        };
+        return Ok(StructSerializer::new(
+            self.builder,
+            self.field_name,
+            self.field_tag,
+            None,
+            self.flatten,
+            len,
+            depth,
+        ));
+    }
+
+    fn begin_variant(
+        self,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer<'a>, SerializeError> {
+        self.check_field()?;
+        let depth = self.struct_depth(2)?;
+        return Ok(StructSerializer::new(
+            self.builder,
+            self.field_name,
+            self.field_tag,
+            Some(variant),
+            false,
+            len,
+            depth,
+        ));
+    }
+}
+
+impl<'a> ser::Serializer for FieldSerializer<'a> {
+    type Ok = bool;
+    type Error = SerializeError;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = StructSerializer<'a>;
+    type SerializeTupleStruct = StructSerializer<'a>;
+    type SerializeTupleVariant = StructSerializer<'a>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = StructSerializer<'a>;
+    type SerializeStructVariant = StructSerializer<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::Boolean);
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::SignedInt);
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::SignedInt);
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::SignedInt);
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::SignedInt);
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::Default);
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::Default);
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::Default);
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::Default);
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::Float);
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::Float);
+    }
+
+    fn serialize_char(self, v: char) -> Result<bool, SerializeError> {
+        return self.add_value(v, FieldFormat::StringUtf);
+    }
+
+    fn serialize_str(self, v: &str) -> Result<bool, SerializeError> {
+        self.check_field()?;
+        self.builder.add_str(
+            self.field_name,
+            v.as_bytes(),
+            FieldFormat::Default,
+            self.field_tag,
+        );
+        return Ok(true);
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<bool, SerializeError> {
+        self.check_field()?;
+        self.builder
+            .add_binary(self.field_name, v, FieldFormat::Default, self.field_tag);
+        return Ok(true);
+    }
+
+    fn serialize_none(self) -> Result<bool, SerializeError> {
+        return Ok(false);
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<bool, SerializeError> {
+        return value.serialize(self);
+    }
+
+    fn serialize_unit(self) -> Result<bool, SerializeError> {
+        return Ok(false);
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<bool, SerializeError> {
+        return Ok(false);
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<bool, SerializeError> {
+        return self.serialize_str(variant);
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<bool, SerializeError> {
+        return value.serialize(self);
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<bool, SerializeError> {
+        self.check_field()?;
+        let mut state = self.begin_struct(1)?;
+        state.add_field(variant, value)?;
+        return state.finish();
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer<'a>, SerializeError> {
+        self.check_field()?;
+        return Ok(SeqSerializer::new(
+            self.builder,
+            self.field_name,
+            self.field_tag,
+            self.depth,
+        ));
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<StructSerializer<'a>, SerializeError> {
+        self.check_field()?;
+        return self.begin_struct(len);
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer<'a>, SerializeError> {
+        self.check_field()?;
+        return self.begin_struct(len);
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer<'a>, SerializeError> {
+        return self.begin_variant(variant, len);
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer<'a>, SerializeError> {
+        return Ok(MapSerializer {
+            state: self.begin_struct(len.unwrap_or(1))?,
+            key: None,
+        });
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer<'a>, SerializeError> {
+        return self.begin_struct(len);
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer<'a>, SerializeError> {
+        return self.begin_variant(variant, len);
+    }
+}
+
+/// State for serializing a struct, map, tuple, or variant.
+/// Implements the `serde::ser::SerializeStruct` family of traits.
+pub struct StructSerializer<'a> {
+    builder: &'a mut EventBuilder,
+    flatten: bool,
+    depth: usize, // Depth of the struct's members.
+    meta_start: usize,
+    data_start: usize,
+    field_count_bookmark: usize,
+    field_count: usize,
+}
+
+impl<'a> StructSerializer<'a> {
+    fn new(
+        builder: &'a mut EventBuilder,
+        field_name: &str,
+        field_tag: u16,
+        variant: Option<&str>,
+        flatten: bool,
+        len: usize,
+        depth: usize,
+    ) -> Self {
+        let (meta_start, data_start) = builder.raw_lengths();
+        let initial_count = len.clamp(1, STRUCT_FIELD_COUNT_MAX) as u8;
+        let mut field_count_bookmark = 0;
+        if !flatten {
+            match variant {
+                None => {
+                    builder.add_struct_with_bookmark(
+                        field_name,
+                        initial_count,
+                        field_tag,
+                        &mut field_count_bookmark,
+                    );
+                }
+                Some(variant) => {
+                    // Externally-tagged: { field_name: { variant: { ... } } }.
+                    builder.add_struct(field_name, 1, field_tag);
+                    builder.add_struct_with_bookmark(
+                        variant,
+                        initial_count,
+                        0,
+                        &mut field_count_bookmark,
+                    );
+                }
+            }
+        }
+
+        return Self {
+            builder,
+            flatten,
+            depth,
+            meta_start,
+            data_start,
+            field_count_bookmark,
+            field_count: 0,
+        };
+    }
+
+    fn add_field<T: ?Sized + Serialize>(
+        &mut self,
+        field_name: &str,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        if field_name.contains('\0') {
+            return Err(SerializeError::new("field name must not contain '\\0'"));
+        }
+
+        let added = value.serialize(FieldSerializer::nested(
+            self.builder,
+            field_name,
+            self.depth,
+        ))?;
+        if added {
+            if !self.flatten && self.field_count == STRUCT_FIELD_COUNT_MAX {
+                return Err(SerializeError::new("struct has more than 127 fields"));
+            }
+            self.field_count += 1;
+        }
+        return Ok(());
+    }
+
+    fn add_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerializeError> {
+        let index = self.field_count.to_string();
+        let added = value.serialize(FieldSerializer::nested(self.builder, &index, self.depth))?;
+        if !added {
+            // Keep the element names consistent with their position.
+            return Err(SerializeError::new(
+                "tuple elements must not be empty (None, unit, or empty struct)",
+            ));
+        } else if self.field_count == STRUCT_FIELD_COUNT_MAX {
+            return Err(SerializeError::new("tuple has more than 127 elements"));
+        }
+        self.field_count += 1;
+        return Ok(());
+    }
+
+    fn finish(self) -> Result<bool, SerializeError> {
+        if self.flatten {
+            return Ok(self.field_count != 0);
+        } else if self.field_count == 0 {
+            // Empty structs are not permitted, so remove it.
+            self.builder.raw_truncate(self.meta_start, self.data_start);
+            return Ok(false);
+        }
+
+        self.builder
+            .set_struct_field_count(self.field_count_bookmark, self.field_count as u8);
+        return Ok(true);
+    }
+}
+
+impl<'a> ser::SerializeStruct for StructSerializer<'a> {
+    type Ok = bool;
+    type Error = SerializeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        return self.add_field(key, value);
+    }
+
+    fn end(self) -> Result<bool, SerializeError> {
+        return self.finish();
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for StructSerializer<'a> {
+    type Ok = bool;
+    type Error = SerializeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        return self.add_field(key, value);
+    }
+
+    fn end(self) -> Result<bool, SerializeError> {
+        return self.finish();
+    }
+}
+
+impl<'a> ser::SerializeTuple for StructSerializer<'a> {
+    type Ok = bool;
+    type Error = SerializeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        return self.add_element(value);
+    }
+
+    fn end(self) -> Result<bool, SerializeError> {
+        return self.finish();
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for StructSerializer<'a> {
+    type Ok = bool;
+    type Error = SerializeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerializeError> {
+        return self.add_element(value);
+    }
+
+    fn end(self) -> Result<bool, SerializeError> {
+        return self.finish();
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for StructSerializer<'a> {
+    type Ok = bool;
+    type Error = SerializeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerializeError> {
+        return self.add_element(value);
+    }
+
+    fn end(self) -> Result<bool, SerializeError> {
+        return self.finish();
+    }
+}
+
+/// State for serializing a map as a struct.
+/// Implements `serde::ser::SerializeMap`.
+pub struct MapSerializer<'a> {
+    state: StructSerializer<'a>,
+    key: Option<String>,
+}
+
+impl<'a> ser::SerializeMap for MapSerializer<'a> {
+    type Ok = bool;
+    type Error = SerializeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), SerializeError> {
+        self.key = Some(key.serialize(MapKeySerializer)?);
+        return Ok(());
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerializeError> {
+        let key = match self.key.take() {
+            Some(key) => key,
+            None => return Err(SerializeError::new("map value without key")),
+        };
+        return self.state.add_field(&key, value);
+    }
+
+    fn end(self) -> Result<bool, SerializeError> {
+        return self.state.finish();
+    }
+}
+
+/// State for serializing a sequence as an array.
+/// Implements `serde::ser::SerializeSeq`.
+///
+/// Each element is serialized into a scratch builder. The first element's metadata
+/// (with the array flag set) becomes the array's metadata, and the metadata of each
+/// subsequent element must match it.
+pub struct SeqSerializer<'a> {
+    builder: &'a mut EventBuilder,
+    field_name: &'a str,
+    field_tag: u16,
+    depth: usize,
+    count_pos: usize,
+    count: u16,
+    element_meta: Vec<u8>,
+    scratch: EventBuilder,
+}
+
+impl<'a> SeqSerializer<'a> {
+    fn new(
+        builder: &'a mut EventBuilder,
+        field_name: &'a str,
+        field_tag: u16,
+        depth: usize,
+    ) -> Self {
+        let (_, count_pos) = builder.raw_lengths();
+        builder.raw_add_data_value(&0u16);
+        return Self {
+            builder,
+            field_name,
+            field_tag,
+            depth,
+            count_pos,
+            count: 0,
+            element_meta: Vec::new(),
+            scratch: EventBuilder::new(),
+        };
+    }
+}
+
+impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
+    type Ok = bool;
+    type Error = SerializeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        if self.count == u16::MAX {
+            // Same as add_value_sequence: extra elements are ignored.
+            return Ok(());
+        }
+
+        self.scratch.raw_truncate(1, 0); // Keep the empty event name.
+        let added = value.serialize(FieldSerializer {
+            builder: &mut self.scratch,
+            field_name: self.field_name,
+            field_tag: self.field_tag,
+            flatten: false,
+            depth: self.depth,
+        })?;
+        if !added {
+            return Err(SerializeError::new(
+                "sequence elements must not be empty (None, unit, or empty struct)",
+            ));
+        }
+
+        let (meta, data) = self.scratch.raw_buffers_mut();
+        let meta = &mut meta[1..];
+        let encoding_pos = self.field_name.len() + 1;
+        if 0 != meta[encoding_pos] & (FieldEncoding::VArrayFlag | FieldEncoding::CArrayFlag) {
+            return Err(SerializeError::new(
+                "sequences of sequences are not supported",
+            ));
+        }
+        meta[encoding_pos] |= FieldEncoding::VArrayFlag;
+
+        if self.count == 0 {
+            self.element_meta.extend_from_slice(meta);
+            self.builder.raw_buffers_mut().0.extend_from_slice(meta);
+        } else if self.element_meta != meta {
+            return Err(SerializeError::new(
+                "sequence elements must all have the same type",
+            ));
+        }
+
+        self.builder.raw_buffers_mut().1.extend_from_slice(data);
+        self.count += 1;
+        return Ok(());
+    }
+
+    fn end(self) -> Result<bool, SerializeError> {
+        let data = self.builder.raw_buffers_mut().1;
+        if self.count == 0 {
+            data.truncate(self.count_pos);
+            return Ok(false);
+        }
+
+        data[self.count_pos..self.count_pos + 2].copy_from_slice(&self.count.to_ne_bytes());
+        return Ok(true);
+    }
+}
+
+/// Serializes a map key to a string.
+struct MapKeySerializer;
+
+impl MapKeySerializer {
+    fn key(value: impl ToString) -> Result<String, SerializeError> {
+        return Ok(value.to_string());
+    }
+
+    fn unsupported() -> Result<String, SerializeError> {
+        return Err(Self::error());
+    }
+
+    fn error() -> SerializeError {
+        return SerializeError::new("map key must be a string, char, integer, or bool");
+    }
+}
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = SerializeError;
+    type SerializeSeq = ser::Impossible<String, SerializeError>;
+    type SerializeTuple = ser::Impossible<String, SerializeError>;
+    type SerializeTupleStruct = ser::Impossible<String, SerializeError>;
+    type SerializeTupleVariant = ser::Impossible<String, SerializeError>;
+    type SerializeMap = ser::Impossible<String, SerializeError>;
+    type SerializeStruct = ser::Impossible<String, SerializeError>;
+    type SerializeStructVariant = ser::Impossible<String, SerializeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, SerializeError> {
+        return Self::unsupported();
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, SerializeError> {
+        return Self::unsupported();
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, SerializeError> {
+        return Self::key(v);
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, SerializeError> {
+        return Self::unsupported();
+    }
+
+    fn serialize_none(self) -> Result<String, SerializeError> {
+        return Self::unsupported();
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<String, SerializeError> {
+        return value.serialize(self);
+    }
+
+    fn serialize_unit(self) -> Result<String, SerializeError> {
+        return Self::unsupported();
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, SerializeError> {
+        return Self::unsupported();
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, SerializeError> {
+        return Self::key(variant);
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, SerializeError> {
+        return value.serialize(self);
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, SerializeError> {
+        return Self::unsupported();
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerializeError> {
+        return Err(Self::error());
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerializeError> {
+        return Err(Self::error());
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerializeError> {
+        return Err(Self::error());
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+        return Err(Self::error());
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerializeError> {
+        return Err(Self::error());
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SerializeError> {
+        return Err(Self::error());
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerializeError> {
+        return Err(Self::error());
+    }
+}
@@ -0,0 +1,498 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use eventheader::FieldEncoding;
+use eventheader::FieldFormat;
+use eventheader::Opcode;
+
+use crate::builder::BinaryField;
+use crate::builder::BinaryFieldEncoding;
+use crate::builder::StringField;
+use crate::builder::StringFieldEncoding;
+use crate::builder::ValueField;
+use crate::builder::ValueFieldEncoding;
+use crate::EventBuilder;
+use crate::EventSet;
+use crate::ValidationError;
+
+/// `EventTemplate` holds the metadata (event name, field names, encodings, formats,
+/// tags, and struct shapes) of an event that is written many times with the same
+/// schema.
+///
+/// Building the metadata with [`EventBuilder`] on every call has a cost. With a
+/// template, the metadata is built once and each event only supplies the field
+/// values through a [`TemplateWriter`].
+///
+/// # Overview
+///
+/// - Create the template with `EventTemplate::new("EventName", event_tag)`.
+/// - Describe each field in order with the `add` methods, e.g.
+///   `template.add_value::<u32>("FieldName", FieldFormat::Default, 0)`. The type
+///   parameter selects the field's encoding, the same as for the corresponding
+///   [`EventBuilder`] method.
+/// - Get a writer with `template.writer()`. The writer is reusable.
+/// - For each event, call `writer.reset()`, supply the value of each non-struct field
+///   in the same order as the template, then call `writer.write(event_set, ...)`.
+///
+/// The writer checks each value against the template. `write` does nothing and
+/// returns `EINVAL` (22) if:
+///
+/// - a value has a different encoding than the template's field (e.g. a `u16` for a
+///   `u32` field, or a single value for a sequence field),
+/// - a floating-point value (`f32` or `f64`) is supplied for a field whose format is
+///   not [`FieldFormat::Float`], or another value is supplied for a `Float` field,
+/// - the number of values does not match the template, or
+/// - a struct field of the template is not followed by `struct_field_count` fields
+///   (see [`EventTemplate::validate`]).
+///
+/// Other format differences are not checked, e.g. a `u32` can be used for an `i32`
+/// field.
+///
+/// An `EventTemplate` is not modified by writing events, so it can be shared, e.g.
+/// stored in an `Arc` and used by writers on several threads.
+///
+/// ```
+/// use eventheader_dynamic as ehd;
+///
+/// let provider = ehd::Provider::new("MyCompany_MyComponent", &ehd::Provider::new_options());
+/// let event_l5k1 = provider.register_set(ehd::Level::Verbose, 0x1);
+///
+/// let mut template = ehd::EventTemplate::new("MyEventName", 0);
+/// template
+///     .add_value::<u32>("Count", ehd::FieldFormat::Default, 0)
+///     .add_str::<u8>("Message", ehd::FieldFormat::Default, 0);
+///
+/// let mut writer = template.writer();
+/// for count in 0..3u32 {
+///     if event_l5k1.enabled() {
+///         writer
+///             .reset()
+///             .add_value(count)
+///             .add_str("Hello")
+///             .write(&event_l5k1, None, None);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct EventTemplate {
+    builder: EventBuilder,
+    values: Vec<TemplateValue>,
+    struct_members: usize, // Number of fields still needed to complete open structs.
+}
+
+/// The expected encoding and format of a value supplied by a [`TemplateWriter`].
+#[derive(Clone, Copy, Debug)]
+struct TemplateValue {
+    encoding: u8, // Includes VArrayFlag for sequences.
+    format: FieldFormat,
+}
+
+impl EventTemplate {
+    /// Creates a template for an event with the specified name and tag.
+    ///
+    /// - `name` is the event name. It should be short and unique. It must not contain any
+    ///   `'\0'` bytes.
+    ///
+    /// - `event_tag` is a 16-bit integer that will be recorded in the event and can be
+    ///   used for any provider-defined purpose. Use 0 if you are not using event tags.
+    pub fn new(name: &str, event_tag: u16) -> Self {
+        let mut builder = EventBuilder::new_with_capacity(256, 0);
+        builder.reset(name, event_tag);
+        return Self {
+            builder,
+            values: Vec::new(),
+            struct_members: 0,
+        };
+    }
+
+    /// Returns a new writer for supplying the values of events that use this template.
+    pub fn writer(&self) -> TemplateWriter<'_> {
+        return TemplateWriter {
+            template: self,
+            values: EventBuilder::new_with_capacity(0, 256),
+            next: 0,
+            valid: true,
+        };
+    }
+
+    /// Returns the number of values that each event must supply, i.e. the number of
+    /// non-struct fields in the template.
+    pub fn value_count(&self) -> usize {
+        return self.values.len();
+    }
+
+    /// Checks the template for problems that would prevent its events from decoding
+    /// correctly. Returns [`ValidationError::InvalidStructFieldCount`] if a struct
+    /// field is not followed by `struct_field_count` fields, or the errors described
+    /// for [`EventBuilder::validate`]. Writers do not write events for a template
+    /// with missing struct fields.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.struct_members != 0 {
+            return Err(ValidationError::InvalidStructFieldCount);
+        }
+
+        return self.builder.validate();
+    }
+
+    /// Sets the id and version of the event. Default is id = 0, version = 0.
+    /// See [`EventBuilder::id_version`].
+    pub fn id_version(&mut self, id: u16, version: u8) -> &mut Self {
+        self.builder.id_version(id, version);
+        return self;
+    }
+
+    /// Sets the opcode of the event. Default is [Opcode::Info].
+    /// See [`EventBuilder::opcode`].
+    pub fn opcode(&mut self, opcode: Opcode) -> &mut Self {
+        self.builder.opcode(opcode);
+        return self;
+    }
+
+    /// Adds a field that contains a single value of type `V`.
+    /// The value is supplied by [`TemplateWriter::add_value`].
+    pub fn add_value<V: ValueField>(
+        &mut self,
+        field_name: &str,
+        format: FieldFormat,
+        field_tag: u16,
+    ) -> &mut Self {
+        return self.add_scalar(field_name, V::VALUE_ENCODING, format, field_tag);
+    }
+
+    /// Adds a field that contains a sequence of values of type `V`.
+    /// The values are supplied by [`TemplateWriter::add_value_sequence`].
+    pub fn add_value_sequence<V: ValueField>(
+        &mut self,
+        field_name: &str,
+        format: FieldFormat,
+        field_tag: u16,
+    ) -> &mut Self {
+        return self.add_vcount(field_name, V::VALUE_ENCODING, format, field_tag);
+    }
+
+    /// Adds a field that contains a counted string with characters of type `V`.
+    /// The value is supplied by [`TemplateWriter::add_str`].
+    pub fn add_str<V: StringField>(
+        &mut self,
+        field_name: &str,
+        format: FieldFormat,
+        field_tag: u16,
+    ) -> &mut Self {
+        return self.add_scalar(field_name, V::STRING_ENCODING, format, field_tag);
+    }
+
+    /// Adds a field that contains a sequence of counted strings with characters of
+    /// type `V`. The values are supplied by [`TemplateWriter::add_str_sequence`].
+    pub fn add_str_sequence<V: StringField>(
+        &mut self,
+        field_name: &str,
+        format: FieldFormat,
+        field_tag: u16,
+    ) -> &mut Self {
+        return self.add_vcount(field_name, V::STRING_ENCODING, format, field_tag);
+    }
+
+    /// Adds a field that contains a nul-terminated string with characters of type `V`.
+    /// The value is supplied by [`TemplateWriter::add_cstr`].
+    pub fn add_cstr<V: StringField>(
+        &mut self,
+        field_name: &str,
+        format: FieldFormat,
+        field_tag: u16,
+    ) -> &mut Self {
+        return self.add_scalar(field_name, V::ZSTRING_ENCODING, format, field_tag);
+    }
+
+    /// Adds a field that contains a sequence of nul-terminated strings with characters
+    /// of type `V`. The values are supplied by [`TemplateWriter::add_cstr_sequence`].
+    pub fn add_cstr_sequence<V: StringField>(
+        &mut self,
+        field_name: &str,
+        format: FieldFormat,
+        field_tag: u16,
+    ) -> &mut Self {
+        return self.add_vcount(field_name, V::ZSTRING_ENCODING, format, field_tag);
+    }
+
+    /// Adds a field that contains a binary blob with elements of type `V`.
+    /// The value is supplied by [`TemplateWriter::add_binary`].
+    pub fn add_binary<V: BinaryField>(
+        &mut self,
+        field_name: &str,
+        format: FieldFormat,
+        field_tag: u16,
+    ) -> &mut Self {
+        return self.add_scalar(field_name, V::BINARY_ENCODING, format, field_tag);
+    }
+
+    /// Adds a field that contains a sequence of binary blobs with elements of type `V`.
+    /// The values are supplied by [`TemplateWriter::add_binary_sequence`].
+    pub fn add_binary_sequence<V: BinaryField>(
+        &mut self,
+        field_name: &str,
+        format: FieldFormat,
+        field_tag: u16,
+    ) -> &mut Self {
+        return self.add_vcount(field_name, V::BINARY_ENCODING, format, field_tag);
+    }
+
+    /// Adds a field that contains the next `struct_field_count` fields of the template.
+    /// Struct fields do not have a value, so the writer does not supply anything for
+    /// them. See [`EventBuilder::add_struct`].
+    pub fn add_struct(
+        &mut self,
+        field_name: &str,
+        struct_field_count: u8,
+        field_tag: u16,
+    ) -> &mut Self {
+        self.builder
+            .add_struct(field_name, struct_field_count, field_tag);
+        self.member_added();
+        self.struct_members += struct_field_count as usize;
+        return self;
+    }
+
+    /// Counts a field as a member of the innermost open struct, if any.
+    fn member_added(&mut self) {
+        self.struct_members = self.struct_members.saturating_sub(1);
+    }
+
+    fn add_scalar(
+        &mut self,
+        field_name: &str,
+        encoding: FieldEncoding,
+        format: FieldFormat,
+        field_tag: u16,
+    ) -> &mut Self {
+        self.builder
+            .raw_add_meta_scalar(field_name, encoding, format, field_tag);
+        self.values.push(TemplateValue {
+            encoding: encoding.as_int(),
+            format,
+        });
+        self.member_added();
+        return self;
+    }
+
+    fn add_vcount(
+        &mut self,
+        field_name: &str,
+        encoding: FieldEncoding,
+        format: FieldFormat,
+        field_tag: u16,
+    ) -> &mut Self {
+        self.builder
+            .raw_add_meta_vcount(field_name, encoding, format, field_tag);
+        self.values.push(TemplateValue {
+            encoding: encoding.as_int() | FieldEncoding::VArrayFlag,
+            format,
+        });
+        self.member_added();
+        return self;
+    }
+}
+
+/// Supplies the field values for an event that uses an [`EventTemplate`].
+///
+/// Values must be supplied in the same order as the template's fields, using the
+/// `add` method that corresponds to the template's `add` method for the field.
+/// The writer is reusable: call [`TemplateWriter::reset`] before each event.
+pub struct TemplateWriter<'a> {
+    template: &'a EventTemplate,
+    values: EventBuilder,
+    next: usize,
+    valid: bool,
+}
+
+impl<'a> TemplateWriter<'a> {
+    /// Returns the template used by this writer.
+    pub fn template(&self) -> &'a EventTemplate {
+        return self.template;
+    }
+
+    /// Clears the values of the previous event (if any) and starts supplying values
+    /// for a new event.
+    pub fn reset(&mut self) -> &mut Self {
+        self.values.raw_clear_data();
+        self.next = 0;
+        self.valid = true;
+        return self;
+    }
+
+    /// Returns true if all values supplied so far match the template.
+    pub fn is_valid(&self) -> bool {
+        return self.valid;
+    }
+
+    /// Returns true if all of the template's values have been supplied, they match
+    /// the template, and the template's structs are complete, i.e. `write` will send
+    /// the event.
+    pub fn is_complete(&self) -> bool {
+        return self.valid
+            && self.next == self.template.values.len()
+            && self.template.struct_members == 0;
+    }
+
+    /// Supplies the value for a field added by [`EventTemplate::add_value`].
+    pub fn add_value<V: ValueField>(&mut self, field_value: V) -> &mut Self {
+        if self.check(V::VALUE_ENCODING.as_int(), Some(V::FLOAT)) {
+            self.values.raw_add_data_value(&field_value);
+        }
+        return self;
+    }
+
+    /// Supplies the values for a field added by [`EventTemplate::add_value_sequence`].
+    pub fn add_value_sequence<'b, V: 'b + ValueField>(
+        &mut self,
+        field_values: impl IntoIterator<Item = &'b V>,
+    ) -> &mut Self {
+        if self.check(
+            V::VALUE_ENCODING.as_int() | FieldEncoding::VArrayFlag,
+            Some(V::FLOAT),
+        ) {
+            self.values.raw_add_data_range(field_values, |this, value| {
+                this.raw_add_data_value(value);
+            });
+        }
+        return self;
+    }
+
+    /// Supplies the value for a field added by [`EventTemplate::add_str`].
+    pub fn add_str<V: StringField>(&mut self, field_value: impl AsRef<[V]>) -> &mut Self {
+        if self.check(V::STRING_ENCODING.as_int(), None) {
+            self.values.raw_add_data_counted(field_value.as_ref());
+        }
+        return self;
+    }
+
+    /// Supplies the values for a field added by [`EventTemplate::add_str_sequence`].
+    pub fn add_str_sequence<I: IntoIterator, V: StringField>(
+        &mut self,
+        field_values: I,
+    ) -> &mut Self
+    where
+        I::Item: AsRef<[V]>,
+    {
+        if self.check(
+            V::STRING_ENCODING.as_int() | FieldEncoding::VArrayFlag,
+            None,
+        ) {
+            self.values.raw_add_data_range(field_values, |this, value| {
+                this.raw_add_data_counted(value.as_ref());
+            });
+        }
+        return self;
+    }
+
+    /// Supplies the value for a field added by [`EventTemplate::add_cstr`].
+    pub fn add_cstr<V: StringField>(&mut self, field_value: impl AsRef<[V]>) -> &mut Self {
+        if self.check(V::ZSTRING_ENCODING.as_int(), None) {
+            self.values.raw_add_data_cstr(field_value.as_ref());
+        }
+        return self;
+    }
+
+    /// Supplies the values for a field added by [`EventTemplate::add_cstr_sequence`].
+    pub fn add_cstr_sequence<I: IntoIterator, V: StringField>(
+        &mut self,
+        field_values: I,
+    ) -> &mut Self
+    where
+        I::Item: AsRef<[V]>,
+    {
+        if self.check(
+            V::ZSTRING_ENCODING.as_int() | FieldEncoding::VArrayFlag,
+            None,
+        ) {
+            self.values.raw_add_data_range(field_values, |this, value| {
+                this.raw_add_data_cstr(value.as_ref());
+            });
+        }
+        return self;
+    }
+
+    /// Supplies the value for a field added by [`EventTemplate::add_binary`].
+    pub fn add_binary<V: BinaryField>(&mut self, field_value: impl AsRef<[V]>) -> &mut Self {
+        if self.check(V::BINARY_ENCODING.as_int(), None) {
+            self.values.raw_add_data_counted(field_value.as_ref());
+        }
+        return self;
+    }
+
+    /// Supplies the values for a field added by [`EventTemplate::add_binary_sequence`].
+    pub fn add_binary_sequence<I: IntoIterator, V: BinaryField>(
+        &mut self,
+        field_values: I,
+    ) -> &mut Self
+    where
+        I::Item: AsRef<[V]>,
+    {
+        if self.check(
+            V::BINARY_ENCODING.as_int() | FieldEncoding::VArrayFlag,
+            None,
+        ) {
+            self.values.raw_add_data_range(field_values, |this, value| {
+                this.raw_add_data_counted(value.as_ref());
+            });
+        }
+        return self;
+    }
+
+    /// Sends the event (the template's metadata with the supplied values) to the
+    /// kernel. Parameters and return values are the same as for
+    /// [`EventBuilder::write`], except that this returns `EINVAL` (22) without
+    /// sending anything if the supplied values do not match the template.
+    pub fn write(
+        &self,
+        event_set: &EventSet,
+        activity_id: Option<&[u8; 16]>,
+        related_id: Option<&[u8; 16]>,
+    ) -> i32 {
+        if !self.is_complete() {
+            return 22; // libc::EINVAL
+        }
+
+        return self.template.builder.write_with_data(
+            self.values.raw_data(),
+            event_set,
+            activity_id,
+            related_id,
+        );
+    }
+
+    /// Checks the next value against the template. `float` is `Some` for value
+    /// fields: true if the value is a floating-point type.
+    fn check(&mut self, encoding: u8, float: Option<bool>) -> bool {
+        let matches = match self.template.values.get(self.next) {
+            None => false,
+            Some(expected) => {
+                expected.encoding == encoding
+                    && float.map_or(true, |float| {
+                        float == (expected.format.without_flags() == FieldFormat::Float)
+                    })
+            }
+        };
+
+        if self.valid && matches {
+            self.next += 1;
+        } else {
+            self.valid = false;
+        }
+        return self.valid;
+    }
+}
+
+impl<'a> fmt::Debug for TemplateWriter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(
+            f,
+            "TemplateWriter {{ values: {}/{}, valid: {} }}",
+            self.next,
+            self.template.values.len(),
+            self.valid,
+        );
+    }
+}
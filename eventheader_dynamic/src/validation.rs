@@ -0,0 +1,117 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use core::fmt;
+
+use eventheader::FieldEncoding;
+use eventheader::FieldFormat;
+use eventheader::TracepointNameError;
+
+/// Maximum levels of struct nesting supported by decoders, currently 8.
+pub(crate) const STRUCT_NEST_LIMIT: usize = 8;
+
+/// Error returned by the fallible validation methods, e.g. [`crate::Provider::try_new`],
+/// [`crate::EventBuilder::try_reset`], and [`crate::EventBuilder::validate`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ValidationError {
+    /// The provider name or group name is invalid. Contains the error from
+    /// [`eventheader::TracepointName::validate_provider`].
+    ProviderName(TracepointNameError),
+
+    /// The event name contains `'\0'`.
+    InvalidEventName,
+
+    /// A field name contains `'\0'`.
+    InvalidFieldName,
+
+    /// A struct's field count is 0 or greater than 127, or (for an
+    /// [`crate::EventTemplate`]) greater than the number of fields after the struct.
+    InvalidStructFieldCount,
+
+    /// Structs are nested more than 8 levels deep.
+    StructNestingTooDeep,
+}
+
+impl From<TracepointNameError> for ValidationError {
+    fn from(value: TracepointNameError) -> Self {
+        return Self::ProviderName(value);
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Self::ProviderName(err) => fmt::Display::fmt(err, f),
+            Self::InvalidEventName => f.write_str("event name must not contain '\\0'"),
+            Self::InvalidFieldName => f.write_str("field name must not contain '\\0'"),
+            Self::InvalidStructFieldCount => {
+                f.write_str("struct field count must be in the range 1 to 127")
+            }
+            Self::StructNestingTooDeep => {
+                f.write_str("structs must not be nested more than 8 levels deep")
+            }
+        };
+    }
+}
+
+/// Checks the struct field counts and struct nesting depth of an event's fields.
+/// `fields` is the event's metadata after the nul-terminated event name. Stops
+/// without error at truncated metadata (e.g. a fixed-capacity buffer that overflowed).
+pub(crate) fn check_fields(fields: &[u8]) -> Result<(), ValidationError> {
+    let mut remaining = [0u8; STRUCT_NEST_LIMIT];
+    let mut depth = 0;
+    let mut pos = 0;
+    while pos < fields.len() {
+        let name_len = match fields[pos..].iter().position(|b| *b == 0) {
+            Some(len) => len,
+            None => break,
+        };
+        pos += name_len + 1;
+
+        let encoding = match fields.get(pos) {
+            Some(&encoding) => encoding,
+            None => break,
+        };
+        pos += 1;
+
+        let mut format = 0;
+        if 0 != (encoding & FieldEncoding::ChainFlag) {
+            format = match fields.get(pos) {
+                Some(&format) => format,
+                None => break,
+            };
+            pos += 1;
+            if 0 != (format & FieldFormat::ChainFlag) {
+                pos += 2; // field tag
+            }
+        }
+
+        if 0 != (encoding & FieldEncoding::CArrayFlag) {
+            pos += 2; // array count
+        }
+
+        if (encoding & FieldEncoding::ValueMask) == FieldEncoding::Struct.as_int() {
+            let field_count = format & FieldFormat::ValueMask;
+            if field_count == 0 {
+                return Err(ValidationError::InvalidStructFieldCount);
+            } else if depth == STRUCT_NEST_LIMIT {
+                return Err(ValidationError::StructNestingTooDeep);
+            }
+
+            remaining[depth] = field_count;
+            depth += 1;
+        } else {
+            // Field is complete. Close any structs that are now complete.
+            while depth != 0 {
+                remaining[depth - 1] -= 1;
+                if remaining[depth - 1] != 0 {
+                    break;
+                }
+
+                depth -= 1;
+            }
+        }
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,365 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+// Capture mode is process-wide, so these tests are in their own test binary.
+#![cfg(feature = "testing")]
+
+use eventheader_dynamic::testing::Capture;
+use eventheader_dynamic::*;
+
+#[test]
+fn capture() {
+    let capture = Capture::start();
+    let provider = Provider::new("TestCapture_Dynamic", &Provider::new_options());
+    let es_l4k3 = provider.register_set(Level::Informational, 0x3);
+    assert!(es_l4k3.enabled());
+
+    let result = EventBuilder::new()
+        .reset("Event1", 0)
+        .add_value("Field1", 42u32, FieldFormat::Default, 0)
+        .add_str("Field2", "hello", FieldFormat::Default, 0)
+        .write(&es_l4k3, None, None);
+    assert_eq!(result, 0);
+
+    capture
+        .assert_written("TestCapture_Dynamic", "Event1")
+        .assert_level(Level::Informational)
+        .assert_keyword(0x3)
+        .assert_field("Field1", 42)
+        .assert_field("Field2", "hello");
+    assert_eq!(
+        capture.events()[0].tracepoint_name(),
+        "TestCapture_Dynamic_L4K3"
+    );
+
+    provider.unregister();
+    assert!(!es_l4k3.enabled());
+}
+
+#[test]
+fn capture_suppressed() {
+    let capture = Capture::start();
+    let provider = Provider::new("TestCapture_DynamicSuppressed", &Provider::new_options());
+    let es_l4k1 = provider.register_set(Level::Informational, 0x1);
+
+    // The policy is process-wide, so only suppress this test's provider.
+    let policy = SuppressionPolicy::parse("provider=TestCapture_DynamicSuppressed, event=Quiet");
+    set_suppression_policy(Some(Box::leak(Box::new(policy.unwrap()))));
+
+    let mut builder = EventBuilder::new();
+    assert_eq!(0, builder.reset("Loud", 0).write(&es_l4k1, None, None));
+    assert_eq!(9, builder.reset("Quiet", 0).write(&es_l4k1, None, None));
+    let throttle = EventThrottle::new(0, 0);
+    assert_eq!(9, builder.write_throttled(&es_l4k1, &throttle, None, None));
+    assert!(EventProvider::enabled(&provider, Level::Informational, 0x1));
+
+    capture.assert_written("TestCapture_DynamicSuppressed", "Loud");
+    capture.assert_not_written("TestCapture_DynamicSuppressed", "Quiet");
+
+    set_suppression_policy(None);
+    assert_eq!(0, builder.write(&es_l4k1, None, None));
+    capture.assert_written("TestCapture_DynamicSuppressed", "Quiet");
+
+    provider.unregister();
+}
+
+#[test]
+fn capture_template() {
+    let capture = Capture::start();
+    let provider = Provider::new("TestCapture_Template", &Provider::new_options());
+    let es_l5k1 = provider.register_set(Level::Verbose, 0x1);
+
+    let mut template = EventTemplate::new("Templated", 5);
+    template
+        .id_version(1, 2)
+        .add_value::<u32>("Count", FieldFormat::Default, 0)
+        .add_struct("Info", 2, 0)
+        .add_str::<u8>("Name", FieldFormat::Default, 0)
+        .add_value_sequence::<u16>("Values", FieldFormat::HexInt, 7)
+        .add_cstr_sequence::<u8>("Labels", FieldFormat::Default, 0)
+        .add_binary::<u8>("Blob", FieldFormat::Default, 0);
+
+    let mut writer = template.writer();
+    for count in 0..2u32 {
+        let result = writer
+            .reset()
+            .add_value(count)
+            .add_str("abc")
+            .add_value_sequence(&[1u16, 2, 3])
+            .add_cstr_sequence(["x", "yz"])
+            .add_binary([0xABu8])
+            .write(&es_l5k1, None, None);
+        assert_eq!(result, 0);
+    }
+
+    // Same event built with EventBuilder.
+    EventBuilder::new()
+        .reset("Templated", 5)
+        .id_version(1, 2)
+        .add_value("Count", 1u32, FieldFormat::Default, 0)
+        .add_struct("Info", 2, 0)
+        .add_str("Name", "abc", FieldFormat::Default, 0)
+        .add_value_sequence("Values", &[1u16, 2, 3], FieldFormat::HexInt, 7)
+        .add_cstr_sequence("Labels", ["x", "yz"], FieldFormat::Default, 0)
+        .add_binary("Blob", [0xABu8], FieldFormat::Default, 0)
+        .write(&es_l5k1, None, None);
+
+    let events = capture.events();
+    assert_eq!(events.len(), 3);
+    events[0]
+        .assert_field("Count", 0)
+        .assert_field("Info.Name", "abc")
+        .assert_field("Info.Values", "0x1, 0x2, 0x3")
+        .assert_field("Labels", "x, yz");
+    assert_eq!(events[1].data(), events[2].data());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn capture_serialize() {
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Serialize)]
+    enum Shape {
+        Empty,
+        Circle(u32),
+        Rect { w: u32, h: u32 },
+    }
+
+    #[derive(Serialize)]
+    struct Payload {
+        name: &'static str,
+        flag: bool,
+        ratio: f64,
+        missing: Option<u32>,
+        origin: Point,
+        values: Vec<u16>,
+        labels: Vec<&'static str>,
+        points: Vec<Point>,
+        empty: Vec<u8>,
+        pair: (u8, &'static str),
+        shapes: (Shape, Shape, Shape),
+        map: BTreeMap<&'static str, u64>,
+    }
+
+    let payload = Payload {
+        name: "abc",
+        flag: true,
+        ratio: 0.5,
+        missing: None,
+        origin: Point { x: -1, y: 2 },
+        values: vec![1, 2, 3],
+        labels: vec!["a", "b"],
+        points: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+        empty: Vec::new(),
+        pair: (7, "seven"),
+        shapes: (Shape::Empty, Shape::Circle(5), Shape::Rect { w: 2, h: 3 }),
+        map: BTreeMap::from([("k1", 10), ("k2", 20)]),
+    };
+
+    let capture = Capture::start();
+    let provider = Provider::new("TestCapture_Serde", &Provider::new_options());
+    let es = provider.register_set(Level::Verbose, 0x1);
+
+    let mut builder = EventBuilder::new();
+    builder.reset("Flattened", 0);
+    builder.add_serialize_fields(&payload).unwrap();
+    assert_eq!(0, builder.write(&es, None, None));
+
+    builder.reset("Nested", 0);
+    builder.add_serialize("payload", &payload, 0).unwrap();
+    builder.add_serialize("scalar", &5u8, 0).unwrap();
+    assert_eq!(0, builder.write(&es, None, None));
+
+    let flattened = capture.assert_written("TestCapture_Serde", "Flattened");
+    flattened
+        .assert_field("name", "abc")
+        .assert_field("flag", true)
+        .assert_field("ratio", 0.5)
+        .assert_field("origin.x", -1)
+        .assert_field("origin.y", 2)
+        .assert_field("values", "1, 2, 3")
+        .assert_field("labels", "a, b")
+        .assert_field("points", r#"[ { "x": 1, "y": 2 }, { "x": 3, "y": 4 } ]"#)
+        .assert_field("pair.0", 7)
+        .assert_field("pair.1", "seven")
+        .assert_field("shapes.0", "Empty")
+        .assert_field("shapes.1.Circle", 5)
+        .assert_field("shapes.2.Rect.w", 2)
+        .assert_field("shapes.2.Rect.h", 3)
+        .assert_field("map.k1", 10)
+        .assert_field("map.k2", 20);
+    assert_eq!(flattened.field("missing"), None);
+    assert_eq!(flattened.field("empty"), None);
+    assert_eq!(
+        flattened.decode_error(),
+        tracepoint_decode::EventHeaderEnumeratorError::Success
+    );
+
+    capture
+        .assert_written("TestCapture_Serde", "Nested")
+        .assert_field("payload.name", "abc")
+        .assert_field(
+            "payload.points",
+            r#"[ { "x": 1, "y": 2 }, { "x": 3, "y": 4 } ]"#,
+        )
+        .assert_field("payload.map.k2", 20)
+        .assert_field("scalar", 5);
+
+    provider.unregister();
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn capture_json() {
+    let value = serde_json::json!({
+        "name": "abc",
+        "count": 5,
+        "delta": -3,
+        "ratio": 0.5,
+        "flag": true,
+        "missing": null,
+        "values": [1, 2, 3],
+        "signed": [1, -2],
+        "mixed": [1, 2.5],
+        "labels": ["a", "b"],
+        "bits": [true, false],
+        "tuple": [7, "seven", null],
+        "nested": { "id": "7ae27afb-11cf-4edd-8b15-9997ec20e0fc", "flags": 255 },
+        "when": 1700000000,
+        "raw": { "a": [1] },
+        "empty": [],
+        "nothing": {},
+        "bad_id": "not-a-uuid",
+    });
+
+    let mut hints = JsonHints::new();
+    hints
+        .hint("nested.id", FieldFormat::Uuid)
+        .hint("bad_id", FieldFormat::Uuid)
+        .hint("nested.flags", FieldFormat::HexInt)
+        .hint("when", FieldFormat::Time)
+        .hint("raw", FieldFormat::StringJson);
+
+    let capture = Capture::start();
+    let provider = Provider::new("TestCapture_Json", &Provider::new_options());
+    let es = provider.register_set(Level::Verbose, 0x1);
+
+    let mut builder = EventBuilder::new();
+    builder.reset("Object", 0);
+    builder.add_json_fields(value.as_object().unwrap(), &hints);
+    assert_eq!(0, builder.write(&es, None, None));
+
+    builder.reset("Text", 0);
+    builder
+        .add_json_fields_str(r#"{ "b": 1, "a": "x" }"#, &JsonHints::new())
+        .unwrap();
+    assert_eq!(0, builder.write(&es, None, None));
+    assert!(builder.add_json_fields_str("[1]", &hints).is_err());
+
+    builder.reset("Value", 0);
+    builder.add_json("value", &serde_json::json!([1, [2]]), &JsonHints::new());
+    assert_eq!(0, builder.write(&es, None, None));
+
+    // Objects nested more than 8 levels deep are written as JSON strings.
+    let mut deep = serde_json::json!({ "x": 1 });
+    for _ in 0..8 {
+        deep = serde_json::json!({ "a": deep });
+    }
+    builder.reset("Deep", 0);
+    builder.add_json("deep", &deep, &JsonHints::new());
+    assert_eq!(0, builder.write(&es, None, None));
+
+    let event = capture.assert_written("TestCapture_Json", "Object");
+    event
+        .assert_field("name", "abc")
+        .assert_field("count", 5)
+        .assert_field("delta", -3)
+        .assert_field("ratio", 0.5)
+        .assert_field("flag", true)
+        .assert_field("values", "1, 2, 3")
+        .assert_field("signed", "1, -2")
+        .assert_field("mixed", "1, 2.5")
+        .assert_field("labels", "a, b")
+        .assert_field("bits", "true, false")
+        .assert_field("tuple.0", 7)
+        .assert_field("tuple.1", "seven")
+        .assert_field("nested.id", "7ae27afb-11cf-4edd-8b15-9997ec20e0fc")
+        .assert_field("nested.flags", "0xFF")
+        .assert_field("raw", r#"{"a":[1]}"#)
+        .assert_field("empty", "[]")
+        .assert_field("nothing", "{}")
+        .assert_field("bad_id", "not-a-uuid");
+    assert_eq!(event.field("missing"), None);
+    assert_eq!(event.field("tuple.2"), None);
+    assert!(event.field("when").unwrap().starts_with("2023-11-14"));
+    assert_eq!(
+        event.decode_error(),
+        tracepoint_decode::EventHeaderEnumeratorError::Success
+    );
+
+    let event = capture.assert_written("TestCapture_Json", "Text");
+    assert_eq!(event.fields()[0].0, "a");
+    event.assert_field("a", "x").assert_field("b", 1);
+
+    capture
+        .assert_written("TestCapture_Json", "Value")
+        .assert_field("value.0", 1)
+        .assert_field("value.1", "2");
+
+    let event = capture.assert_written("TestCapture_Json", "Deep");
+    event.assert_field("deep.a.a.a.a.a.a.a.a", r#"{"x":1}"#);
+    assert_eq!(
+        event.decode_error(),
+        tracepoint_decode::EventHeaderEnumeratorError::Success
+    );
+
+    provider.unregister();
+}
+
+#[test]
+fn capture_overflow() {
+    let capture = Capture::start();
+    let provider = Provider::new("TestCapture_Overflow", &Provider::new_options());
+    let es_l5k1 = provider.register_set(Level::Verbose, 0x1);
+    let throttle = EventThrottle::new(0, 0);
+    let big = "x".repeat(40000);
+
+    let mut b = EventBuilder::new();
+    b.reset("Big", 0)
+        .add_str("s1", &big, FieldFormat::Default, 0)
+        .add_str("s2", &big, FieldFormat::Default, 0)
+        .add_value("last", 7u8, FieldFormat::Default, 0);
+    assert_eq!(b.write(&es_l5k1, None, None), 34);
+    assert!(capture.events().is_empty());
+
+    b.overflow_policy(OverflowPolicy::TruncateStrings);
+    assert_eq!(b.write(&es_l5k1, None, None), 0);
+    assert_eq!(b.write_throttled(&es_l5k1, &throttle, None, None), 0);
+
+    b.overflow_policy(OverflowPolicy::DropFields);
+    assert_eq!(b.write(&es_l5k1, None, None), 0);
+
+    let events = capture.events();
+    assert_eq!(events.len(), 3);
+    events[0]
+        .assert_field("last", 7)
+        .assert_field("_truncated", true);
+    assert!(events[0].field("s2").unwrap().len() < 40000);
+    events[1]
+        .assert_field("last", 7)
+        .assert_field("_truncated", true)
+        .assert_field("_suppressed", 0);
+    assert!(events[1].data().len() <= 65535);
+    events[2]
+        .assert_field("s1", &big)
+        .assert_field("_truncated", true);
+    assert!(events[2].field("s2").is_none());
+}
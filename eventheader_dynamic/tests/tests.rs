@@ -14,13 +14,13 @@ fn provider() {
     assert_eq!(provider.name(), "MyCompany_MyComponent");
     assert_eq!(provider.options(), "Gmygroup");
 
-    let mut provider = Provider::new("Hello", &Provider::new_options());
+    let provider = Provider::new("Hello", &Provider::new_options());
     assert_eq!(provider.name(), "Hello");
     assert_eq!(provider.options(), "");
 
     provider.unregister();
 
-    let mut provider = Provider::new("MyCompany_MyComponent", &Provider::new_options());
+    let provider = Provider::new("MyCompany_MyComponent", &Provider::new_options());
     let es_l5k123 = provider.register_set(Level::Verbose, 0x123);
     _ = es_l5k123.enabled();
 
@@ -29,7 +29,7 @@ fn provider() {
 
     let mut b = EventBuilder::new();
 
-    let mut provider = Provider::new(
+    let provider = Provider::new(
         "EventHeaderDynamicTest",
         Provider::new_options().group_name("testgroup"),
     );
@@ -44,11 +44,741 @@ fn provider() {
     b.reset("GroupEvent-Stop", 0)
         .opcode(Opcode::ActivityStop)
         .write(&es_l5k1, Some(aid.as_bytes()), None);
+
+    static THROTTLE: EventThrottle = EventThrottle::new(2, 0);
+    for i in 0..10u32 {
+        b.reset("Throttled", 0)
+            .add_value("i", i, FieldFormat::Default, 0)
+            .write_throttled(&es_l5k1, &THROTTLE, None, None);
+    }
+}
+
+#[test]
+fn event_provider() {
+    fn write_encoded<P: EventProvider>(provider: &P, level: Level) -> i32 {
+        const META: &[u8] = b"Encoded\0value\0\x04"; // name, Value32
+        let header = EventHeader::from_parts(
+            HeaderFlags::DefaultWithExtension,
+            0,
+            0,
+            0,
+            Opcode::Info,
+            level,
+        );
+        provider.write_encoded(&header, 0x5, None, None, META, &123u32.to_ne_bytes())
+    }
+
+    let provider = Provider::new(
+        "EventHeaderDynamicTest",
+        Provider::new_options().group_name("testgroup"),
+    );
+    assert_eq!(EventProvider::name(&provider), "EventHeaderDynamicTest");
+    assert_eq!(EventProvider::options(&provider), "Gtestgroup");
+
+    // No event set registered for Verbose + 0x5.
+    assert!(!EventProvider::enabled(&provider, Level::Verbose, 0x5));
+    assert_eq!(write_encoded(&provider, Level::Verbose), 9);
+
+    let es = provider.register_set(Level::Verbose, 0x5);
+    assert_eq!(
+        EventProvider::enabled(&provider, Level::Verbose, 0x5),
+        es.enabled()
+    );
+    _ = write_encoded(&provider, Level::Verbose);
+}
+
+#[test]
+fn shared_provider() {
+    let provider = std::sync::Arc::new(Provider::new(
+        "EventHeaderDynamicTest",
+        &Provider::new_options(),
+    ));
+
+    let threads: Vec<_> = (0..4u64)
+        .map(|thread| {
+            let provider = provider.clone();
+            std::thread::spawn(move || {
+                for keyword in 0..16u64 {
+                    let es = provider.create_unregistered(true, Level::Verbose, keyword);
+                    assert!(es.enabled());
+                    let found = provider.find_set(Level::Verbose, keyword).unwrap();
+                    assert!(std::sync::Arc::ptr_eq(&es, &found));
+                    EventBuilder::new()
+                        .reset("Shared", 0)
+                        .add_value("thread", thread, FieldFormat::Default, 0)
+                        .write(&es, None, None);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    // Each level/keyword combination was created exactly once.
+    assert_eq!(
+        format!("{:?}", provider),
+        "Provider { name: \"EventHeaderDynamicTest\", options: \"\", sets: 16 }"
+    );
+
+    provider.unregister();
+    assert!(provider.find_set(Level::Verbose, 0).is_none());
+    let es = provider.register_set(Level::Verbose, 0);
+    assert!(std::sync::Arc::ptr_eq(
+        &es,
+        &provider.find_set(Level::Verbose, 0).unwrap()
+    ));
+}
+
+#[test]
+fn shared_provider_unregister() {
+    let provider = std::sync::Arc::new(Provider::new(
+        "EventHeaderDynamicTest",
+        &Provider::new_options(),
+    ));
+
+    let threads: Vec<_> = (0..4u64)
+        .map(|thread| {
+            let provider = provider.clone();
+            std::thread::spawn(move || {
+                for i in 0..256u64 {
+                    if thread == 0 && i % 16 == 0 {
+                        provider.unregister();
+                    }
+
+                    let es = provider.create_unregistered(true, Level::Verbose, i % 16);
+                    assert_eq!(es.level(), Level::Verbose);
+                    assert_eq!(es.keyword(), i % 16);
+                    if let Some(found) = provider.find_set(Level::Verbose, i % 16) {
+                        assert_eq!(found.keyword(), i % 16);
+                    }
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    provider.unregister();
+    assert_eq!(
+        format!("{:?}", provider),
+        "Provider { name: \"EventHeaderDynamicTest\", options: \"\", sets: 0 }"
+    );
+}
+
+#[test]
+fn provider_tracepoints() {
+    fn statuses(provider: &dyn EventProvider) -> Vec<(String, Level, u64, i32, bool)> {
+        let mut statuses = Vec::new();
+        provider.for_each_tracepoint(&mut |status| {
+            statuses.push((
+                status.to_string(),
+                status.level(),
+                status.keyword(),
+                status.errno(),
+                status.enabled(),
+            ));
+        });
+        statuses.sort_by(|a, b| a.0.cmp(&b.0));
+        statuses
+    }
+
+    fn listed(name: &str) -> usize {
+        let mut count = 0;
+        for_each_provider(|provider| {
+            if provider.name() == name {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    let provider = Provider::new(
+        "TracepointStatusDynamic",
+        Provider::new_options().group_name("mygroup"),
+    );
+    assert_eq!(listed("TracepointStatusDynamic"), 1);
+    assert!(statuses(&provider).is_empty());
+
+    provider.create_unregistered(true, Level::Verbose, 0x5);
+    provider.create_unregistered(false, Level::Warning, 0x1);
+    assert_eq!(
+        statuses(&provider),
+        [
+            (
+                "TracepointStatusDynamic_L3K1Gmygroup".to_string(),
+                Level::Warning,
+                0x1,
+                0,
+                false
+            ),
+            (
+                "TracepointStatusDynamic_L5K5Gmygroup".to_string(),
+                Level::Verbose,
+                0x5,
+                0,
+                true
+            ),
+        ]
+    );
+
+    let set = provider.register_set(Level::Error, 0x2);
+    assert_eq!((set.level(), set.keyword()), (Level::Error, 0x2));
+    let registered = statuses(&provider);
+    assert_eq!(registered.len(), 3);
+    assert_eq!(registered[0].0, "TracepointStatusDynamic_L2K2Gmygroup");
+    assert_eq!(registered[0].3, set.errno());
+
+    // Moving the provider does not affect the process-wide list.
+    let moved = Box::new(provider);
+    assert_eq!(listed("TracepointStatusDynamic"), 1);
+    for_each_provider(|provider| {
+        if provider.name() == "TracepointStatusDynamic" {
+            assert_eq!(statuses(provider).len(), 3);
+        }
+    });
+
+    moved.unregister();
+    assert!(statuses(&*moved).is_empty());
+    drop(moved);
+    assert_eq!(listed("TracepointStatusDynamic"), 0);
+}
+
+#[test]
+fn encode_to() {
+    let provider = Provider::new(
+        "EventHeaderDynamicTest",
+        Provider::new_options().group_name("testgroup"),
+    );
+    let aid = [1u8; 16];
+    let rid = [2u8; 16];
+
+    // The tracepoint name requires a provider.
+    let mut b = EventBuilder::new();
+    assert_eq!(
+        b.encode_to(&mut Vec::new(), Level::Warning, None, None),
+        Err(22)
+    );
+
+    b.encode_provider(&provider, 0x1f)
+        .reset("Encoded", 0x12)
+        .id_version(3, 4)
+        .opcode(Opcode::ActivityStart)
+        .add_value("u32", 42u32, FieldFormat::Default, 0)
+        .add_str("str", "hello", FieldFormat::Default, 0);
+
+    let mut buffer = vec![0xFF];
+    let name = b
+        .encode_to(&mut buffer, Level::Warning, Some(&aid), Some(&rid))
+        .unwrap();
+    assert_eq!(name, "EventHeaderDynamicTest_L3K1fGtestgroup");
+    assert_eq!(buffer[0], 0xFF); // Appended, not overwritten.
+
+    let mut ctx = tracepoint_decode::EventHeaderEnumeratorContext::new();
+    let mut e = ctx
+        .enumerate_with_name_and_data(
+            &name,
+            &buffer[1..],
+            tracepoint_decode::EventHeaderEnumeratorContext::MOVE_NEXT_LIMIT_DEFAULT,
+        )
+        .unwrap();
+    let info = e.event_info();
+    assert_eq!(info.provider_name(), "EventHeaderDynamicTest");
+    assert_eq!(info.name_bytes(), b"Encoded");
+    assert_eq!(info.keyword(), 0x1f);
+    assert_eq!(info.activity_id(), Some(&aid));
+    assert_eq!(info.related_activity_id(), Some(&rid));
+    let header = info.header();
+    assert_eq!(header.level, Level::Warning);
+    assert_eq!(header.opcode, Opcode::ActivityStart);
+    assert_eq!((header.id, header.version, header.tag), (3, 4, 0x12));
+
+    assert!(e.move_next());
+    assert_eq!(e.item_info().name_bytes(), b"u32");
+    assert_eq!(e.item_info().value().to_u32(0), 42);
+    assert!(e.move_next());
+    assert_eq!(e.item_info().name_bytes(), b"str");
+    assert!(!e.move_next());
+    assert_eq!(
+        e.state(),
+        tracepoint_decode::EventHeaderEnumeratorState::AfterLastItem
+    );
+
+    // Too-large events are rejected without modifying the buffer.
+    b.reset("TooLarge", 0)
+        .add_binary("big", &[0u8; 65535][..], FieldFormat::Default, 0);
+    let len = buffer.len();
+    assert_eq!(
+        b.encode_to(&mut buffer, Level::Verbose, None, None),
+        Err(34)
+    );
+    assert_eq!(buffer.len(), len);
 }
 
 #[test]
+fn extensions() {
+    use tracepoint_decode::PerfMetaOptions;
+
+    const TRACE_FLAGS: ExtensionKind = ExtensionKind::from_int(0x100);
+    const TENANT: ExtensionKind = ExtensionKind::from_int(0x7FFF);
+
+    let provider = Provider::new("EventHeaderDynamicTest", &Provider::new_options());
+    let aid = [1u8; 16];
+
+    let mut b = EventBuilder::new();
+    b.encode_provider(&provider, 1)
+        .reset("Extended", 0)
+        .add_extension(TRACE_FLAGS, &[0x01])
+        .add_extension(TENANT, b"contoso")
+        .add_value("u32", 42u32, FieldFormat::Default, 0);
+    assert_eq!(b.encoded_size(), (9 + 4 + 1) + (4 + 1 + 4 + 7) + 4);
+
+    let mut buffer = Vec::new();
+    let name = b
+        .encode_to(&mut buffer, Level::Verbose, Some(&aid), None)
+        .unwrap();
+
+    let mut ctx = tracepoint_decode::EventHeaderEnumeratorContext::new();
+    let mut e = ctx
+        .enumerate_with_name_and_data(
+            &name,
+            &buffer,
+            tracepoint_decode::EventHeaderEnumeratorContext::MOVE_NEXT_LIMIT_DEFAULT,
+        )
+        .unwrap();
+    let info = e.event_info();
+    assert_eq!(info.activity_id(), Some(&aid));
+    assert_eq!(info.name_bytes(), b"Extended");
+
+    let exts: Vec<_> = info
+        .extensions()
+        .map(|ext| (ext.kind(), ext.chain(), ext.data()))
+        .collect();
+    assert_eq!(exts.len(), 4);
+    assert_eq!(exts[0].0, ExtensionKind::ActivityId);
+    assert_eq!(exts[1].0, ExtensionKind::Metadata);
+    assert_eq!(exts[1].2, info.metadata());
+    assert_eq!(exts[2], (TRACE_FLAGS, true, &[0x01u8][..]));
+    assert_eq!(exts[3], (TENANT, false, &b"contoso"[..]));
+    assert!(exts[0].1 && exts[1].1);
+
+    let meta = info
+        .json_meta_display(None)
+        .meta_options(PerfMetaOptions::Extensions)
+        .to_string();
+    assert_eq!(
+        meta,
+        r#""extensions": [ { "kind": "0x100", "data": "01" }, { "kind": "0x7FFF", "data": "63 6F 6E 74 6F 73 6F" } ]"#
+    );
+
+    assert!(e.move_next());
+    assert_eq!(e.item_info().value().to_u32(0), 42);
+    assert!(!e.move_next());
+
+    // Extensions are cleared by reset.
+    b.reset("Plain", 0);
+    buffer.clear();
+    let name = b
+        .encode_to(&mut buffer, Level::Verbose, None, None)
+        .unwrap();
+    let e = ctx
+        .enumerate_with_name_and_data(
+            &name,
+            &buffer,
+            tracepoint_decode::EventHeaderEnumeratorContext::MOVE_NEXT_LIMIT_DEFAULT,
+        )
+        .unwrap();
+    assert_eq!(e.event_info().extensions().count(), 1);
+
+    // Extension blocks count toward the event size limit.
+    b.reset("TooLarge", 0)
+        .add_extension(TRACE_FLAGS, &[0u8; 65535][..]);
+    assert_eq!(
+        b.encode_to(&mut buffer, Level::Verbose, None, None),
+        Err(34)
+    );
+}
+
+#[test]
+fn validation() {
+    let opts = Provider::new_options();
+    assert!(Provider::try_new("EventHeaderDynamicTest", &opts).is_ok());
+    assert_eq!(
+        Provider::try_new("", &opts).err(),
+        Some(ValidationError::ProviderName(
+            TracepointNameError::InvalidProviderName
+        ))
+    );
+    assert_eq!(
+        Provider::try_new("Bad:Name", &opts).err(),
+        Some(ValidationError::ProviderName(
+            TracepointNameError::InvalidProviderName
+        ))
+    );
+    assert_eq!(
+        Provider::try_new("Name", Provider::new_options().group_name("Group")).err(),
+        Some(ValidationError::ProviderName(
+            TracepointNameError::InvalidGroupName
+        ))
+    );
+    assert_eq!(
+        Provider::try_new(&"n".repeat(234), &opts).err(),
+        Some(ValidationError::ProviderName(
+            TracepointNameError::NameTooLong
+        ))
+    );
+
+    let mut b = EventBuilder::new();
+    assert_eq!(
+        b.try_reset("Bad\0Name", 0).err(),
+        Some(ValidationError::InvalidEventName)
+    );
+    b.try_reset("Event", 0)
+        .unwrap()
+        .add_value("u8", 1u8, FieldFormat::Default, 0);
+    assert_eq!(b.validate(), Ok(()));
+    assert_eq!(
+        b.try_add_struct("s", 0, 0).err(),
+        Some(ValidationError::InvalidStructFieldCount)
+    );
+    assert_eq!(
+        b.try_add_struct("s", 128, 0).err(),
+        Some(ValidationError::InvalidStructFieldCount)
+    );
+    assert_eq!(b.validate(), Ok(()));
+    assert_eq!(
+        b.try_add_struct("bad\0struct", 1, 0).err(),
+        Some(ValidationError::InvalidFieldName)
+    );
+    assert_eq!(b.validate(), Err(ValidationError::InvalidFieldName));
+
+    // reset clears the recorded error.
+    b.reset("Event", 0);
+    assert_eq!(b.validate(), Ok(()));
+
+    // 8 levels of nesting are supported by the decoder.
+    for _ in 0..8 {
+        b.try_add_struct("s", 2, 0).unwrap();
+    }
+    b.add_value("u8", 1u8, FieldFormat::Default, 0);
+    for _ in 0..8 {
+        b.add_value("u8", 1u8, FieldFormat::Default, 0);
+    }
+    assert_eq!(b.validate(), Ok(()));
+
+    // 9 levels of nesting are not.
+    b.reset("Event", 0);
+    for _ in 0..9 {
+        b.try_add_struct("s", 1, 0).unwrap();
+    }
+    b.add_value("u8", 1u8, FieldFormat::Default, 0);
+    assert_eq!(b.validate(), Err(ValidationError::StructNestingTooDeep));
+
+    // Sibling structs do not add to the depth.
+    b.reset("Event", 0);
+    for _ in 0..16 {
+        b.try_add_struct("s", 1, 0).unwrap();
+        b.add_value("u8", 1u8, FieldFormat::Default, 0);
+    }
+    assert_eq!(b.validate(), Ok(()));
+
+    // Empty structs added via raw metadata are detected.
+    b.reset("Event", 0)
+        .raw_add_meta_scalar("s", FieldEncoding::Struct, FieldFormat::Default, 0);
+    assert_eq!(b.validate(), Err(ValidationError::InvalidStructFieldCount));
+}
+
+#[test]
+fn overflow_policy() {
+    use tracepoint_decode::EventHeaderEnumeratorContext as Context;
+    use tracepoint_decode::EventHeaderEnumeratorState as State;
+
+    // Returns the name and value bytes of each field, or "{" for a struct.
+    fn decode(name: &str, buffer: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut ctx = Context::new();
+        let mut e = ctx
+            .enumerate_with_name_and_data(name, buffer, Context::MOVE_NEXT_LIMIT_DEFAULT)
+            .unwrap();
+        let mut fields = Vec::new();
+        while e.move_next() {
+            let item = e.item_info();
+            let name = String::from_utf8(item.name_bytes().to_vec()).unwrap();
+            match e.state() {
+                State::Value => fields.push((name, item.value().bytes().to_vec())),
+                State::StructBegin => fields.push((name, b"{".to_vec())),
+                _ => {}
+            }
+        }
+        assert_eq!(e.state(), State::AfterLastItem);
+        fields
+    }
+
+    let provider = Provider::new("EventHeaderDynamicTest", &Provider::new_options());
+    let big = "\u{e9}".repeat(20000); // 40000 bytes, 2 bytes per char.
+    let mut b = EventBuilder::new();
+    b.encode_provider(&provider, 1).reset("Overflow", 0);
+    let empty_size = b.encoded_size();
+    assert_eq!(empty_size, 9);
+    assert_eq!(b.remaining_size(), 65535 - 68 - 9);
+
+    b.add_value("first", 1u8, FieldFormat::Default, 0)
+        .add_str("s1", &big, FieldFormat::Default, 0)
+        .add_struct("st", 2, 0)
+        .add_value("a", 2u32, FieldFormat::Default, 0)
+        .add_str("s2", &big, FieldFormat::Default, 0)
+        .add_value("last", 3u16, FieldFormat::Default, 0);
+    assert!(b.encoded_size() > 80000);
+    assert_eq!(b.remaining_size(), 0);
+
+    // Default policy: the event is rejected.
+    let mut buffer = Vec::new();
+    assert_eq!(
+        b.encode_to(&mut buffer, Level::Verbose, None, None),
+        Err(34)
+    );
+    assert!(buffer.is_empty());
+
+    // TruncateStrings: s2 is shortened first, then s1.
+    b.overflow_policy(OverflowPolicy::TruncateStrings);
+    let name = b
+        .encode_to(&mut buffer, Level::Verbose, None, None)
+        .unwrap();
+    assert!(buffer.len() <= 65535);
+    let fields = decode(&name, &buffer);
+    let names: Vec<&str> = fields.iter().map(|f| f.0.as_str()).collect();
+    assert_eq!(
+        names,
+        ["first", "s1", "st", "a", "s2", "last", "_truncated"]
+    );
+    assert_eq!(fields[1].1.len(), 40000);
+    assert!(fields[4].1.len() < 40000 && fields[4].1.len() % 2 == 0);
+    assert!(std::str::from_utf8(&fields[4].1).is_ok());
+    assert_eq!(fields[5].1, 3u16.to_ne_bytes());
+    assert_eq!(fields[6].1, [1]);
+
+    // Policy is not changed by reset. Strings that cannot be shortened enough fail.
+    b.reset("Overflow", 0)
+        .add_value_sequence("values", &[0u64; 9000], FieldFormat::Default, 0);
+    buffer.clear();
+    assert_eq!(
+        b.encode_to(&mut buffer, Level::Verbose, None, None),
+        Err(34)
+    );
+
+    // DropFields: trailing top-level fields are dropped, structs as a unit.
+    b.reset("Overflow", 0)
+        .overflow_policy(OverflowPolicy::DropFields)
+        .add_value("first", 1u8, FieldFormat::Default, 0)
+        .add_str("s1", &big, FieldFormat::Default, 0)
+        .add_struct("st", 2, 0)
+        .add_value("a", 2u32, FieldFormat::Default, 0)
+        .add_str("s2", &big, FieldFormat::Default, 0)
+        .add_value("last", 3u16, FieldFormat::Default, 0);
+    let name = b
+        .encode_to(&mut buffer, Level::Verbose, None, None)
+        .unwrap();
+    let fields = decode(&name, &buffer);
+    let names: Vec<&str> = fields.iter().map(|f| f.0.as_str()).collect();
+    assert_eq!(names, ["first", "s1", "_truncated"]);
+    assert_eq!(fields[1].1.len(), 40000);
+
+    // Events that fit are not changed.
+    b.reset("Small", 0)
+        .add_value("first", 1u8, FieldFormat::Default, 0);
+    buffer.clear();
+    let name = b
+        .encode_to(&mut buffer, Level::Verbose, None, None)
+        .unwrap();
+    assert_eq!(decode(&name, &buffer), [("first".to_string(), vec![1u8])]);
+}
+
+#[test]
+fn fixed_builder() {
+    fn build<B: BuilderBuffer>(b: &mut EventBuilder<B>) -> &mut EventBuilder<B> {
+        let mut bookmark = 0;
+        b.reset("Fixed", 0x12)
+            .add_value("u32", 42u32, FieldFormat::Default, 0)
+            .add_struct_with_bookmark("s", 1, 0, &mut bookmark)
+            .add_str("str", "hello", FieldFormat::Default, 5)
+            .add_str("str2", "world", FieldFormat::Default, 0)
+            .set_struct_field_count(bookmark, 2)
+            .add_cstr_sequence("seq", ["a", "bc"], FieldFormat::Default, 0)
+    }
+
+    let provider = Provider::new("EventHeaderDynamicTest", &Provider::new_options());
+    let mut expected = Vec::new();
+    build(EventBuilder::new().encode_provider(&provider, 1))
+        .encode_to(&mut expected, Level::Verbose, None, None)
+        .unwrap();
+
+    // Same encoding as the Vec-based builder.
+    let mut fixed = FixedEventBuilder::<64>::new_fixed();
+    assert!(!build(fixed.encode_provider(&provider, 1)).capacity_exceeded());
+    let mut actual = Vec::new();
+    fixed
+        .encode_to(&mut actual, Level::Verbose, None, None)
+        .unwrap();
+    assert_eq!(actual, expected);
+
+    // Fields that do not fit make the event unwritable until reset.
+    let mut small = FixedEventBuilder::<16>::new_fixed();
+    build(small.encode_provider(&provider, 1));
+    assert!(small.capacity_exceeded());
+    assert_eq!(
+        small.encode_to(&mut actual, Level::Verbose, None, None),
+        Err(34)
+    );
+    let set = provider.create_unregistered(true, Level::Verbose, 1);
+    assert_eq!(small.write(&set, None, None), 34);
+
+    small
+        .reset("Small", 0)
+        .add_value("v", 1u8, FieldFormat::Default, 0);
+    assert!(!small.capacity_exceeded());
+    assert_eq!(small.encoded_size(), 6 + 3 + 1);
+}
+
+#[cfg(feature = "pool")]
+#[test]
+fn pooled_builder() {
+    let provider = Provider::new("EventHeaderDynamicTest", &Provider::new_options());
+    let mut expected = Vec::new();
+    EventBuilder::new()
+        .encode_provider(&provider, 1)
+        .reset("Pooled", 0)
+        .add_value("v", 7u32, FieldFormat::Default, 0)
+        .encode_to(&mut expected, Level::Verbose, None, None)
+        .unwrap();
+
+    for _ in 0..3 {
+        let mut b = EventBuilder::pooled();
+        assert_eq!(b.encoded_size(), 1); // Returned builders are reset.
+        b.overflow_policy(OverflowPolicy::DropFields)
+            .encode_provider(&provider, 1)
+            .reset("Pooled", 0)
+            .add_value("v", 7u32, FieldFormat::Default, 0);
+        let mut actual = Vec::new();
+        b.encode_to(&mut actual, Level::Verbose, None, None)
+            .unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    // Nested guards use distinct builders.
+    let mut outer = EventBuilder::pooled();
+    outer.reset("Outer", 0);
+    {
+        let mut inner = EventBuilder::pooled();
+        inner.reset("Inner", 0);
+        assert_eq!(inner.encoded_size(), 6);
+    }
+    assert_eq!(outer.encoded_size(), 6);
+
+    // The settings are reset when a builder returns to the pool.
+    drop(outer);
+    let mut b = EventBuilder::pooled();
+    b.reset("TooLarge", 0)
+        .add_binary("big", &[0u8; 65535][..], FieldFormat::Default, 0);
+    assert_eq!(
+        b.encode_to(&mut Vec::new(), Level::Verbose, None, None),
+        Err(22)
+    );
+    b.encode_provider(&provider, 1);
+    assert_eq!(
+        b.encode_to(&mut Vec::new(), Level::Verbose, None, None),
+        Err(34)
+    );
+}
+
+#[test]
+fn template() {
+    let provider = Provider::new("EventHeaderDynamicTest", &Provider::new_options());
+    let es = provider.create_unregistered(true, Level::Verbose, 0x1);
+
+    let mut template = EventTemplate::new("Templated", 0);
+    template
+        .add_value::<u32>("u32", FieldFormat::Default, 0)
+        .add_struct("s", 1, 0)
+        .add_str_sequence::<u8>("strs", FieldFormat::Default, 0);
+    assert_eq!(template.value_count(), 2);
+
+    let mut writer = template.writer();
+    writer.add_value(1u32).add_str_sequence(["a", "b"]);
+    assert!(writer.is_complete());
+
+    // Wrong type.
+    writer.reset().add_value(1u16);
+    assert!(!writer.is_valid());
+    assert_eq!(writer.write(&es, None, None), 22);
+
+    // Scalar for a sequence field.
+    writer.reset().add_value(1u32).add_str("a");
+    assert!(!writer.is_valid());
+
+    // Too few values.
+    writer.reset().add_value(1u32);
+    assert!(writer.is_valid());
+    assert!(!writer.is_complete());
+    assert_eq!(writer.write(&es, None, None), 22);
+
+    // Too many values.
+    writer
+        .reset()
+        .add_value(1u32)
+        .add_str_sequence(["a"])
+        .add_value(2u32);
+    assert!(!writer.is_valid());
+    assert_eq!(writer.write(&es, None, None), 22);
+
+    // Signedness is not checked.
+    writer.reset().add_value(-1i32).add_str_sequence(["a"]);
+    assert!(writer.is_complete());
+    println!("{:?} {:?}", template, writer);
+
+    // Floating-point values must be used for exactly the Float fields.
+    let mut floats = EventTemplate::new("Floats", 0);
+    floats
+        .add_value::<f32>("f32", FieldFormat::Float, 0)
+        .add_value_sequence::<u64>("u64s", FieldFormat::Default, 0);
+    let mut writer = floats.writer();
+    writer.add_value(1.5f32).add_value_sequence(&[1u64, 2]);
+    assert!(writer.is_complete());
+    writer.reset().add_value(1u32);
+    assert!(!writer.is_valid());
+    writer
+        .reset()
+        .add_value(1.5f32)
+        .add_value_sequence(&[1.5f64]);
+    assert!(!writer.is_valid());
+
+    // Structs must be followed by their fields.
+    let mut partial = EventTemplate::new("Partial", 0);
+    partial
+        .add_struct("s", 2, 0)
+        .add_value::<u32>("a", FieldFormat::Default, 0);
+    assert_eq!(
+        partial.validate(),
+        Err(ValidationError::InvalidStructFieldCount)
+    );
+    let mut writer = partial.writer();
+    writer.add_value(1u32);
+    assert!(writer.is_valid());
+    assert!(!writer.is_complete());
+    assert_eq!(writer.write(&es, None, None), 22);
+
+    partial
+        .add_struct("t", 1, 0)
+        .add_value::<u32>("b", FieldFormat::Default, 0);
+    assert_eq!(partial.validate(), Ok(()));
+    let mut writer = partial.writer();
+    writer.add_value(1u32).add_value(2u32);
+    assert!(writer.is_complete());
+}
+
+#[test]
+#[allow(clippy::const_is_empty)]
 fn builder() {
-    let mut p = Provider::new("EventHeaderDynamicTest", &Provider::new_options());
+    let p = Provider::new("EventHeaderDynamicTest", &Provider::new_options());
     let mut b = EventBuilder::new();
     println!("{:?}", b);
 
@@ -404,3 +1134,249 @@ fn validate<T: Copy>(
     b.add_value("A", b'A', FieldFormat::String8, 0);
     b.write(&es, None, None);
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialize_errors() {
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Mixed {
+        Int(u32),
+        Str(&'static str),
+    }
+
+    let mut b = EventBuilder::new();
+    b.reset("Errors", 0);
+
+    let err = b
+        .add_serialize("mixed", &vec![Mixed::Int(1), Mixed::Str("a")], 0)
+        .unwrap_err();
+    assert_eq!(
+        err.message(),
+        "sequence elements must all have the same type"
+    );
+
+    let err = b
+        .add_serialize("nested", &vec![vec![1u8], vec![2u8]], 0)
+        .unwrap_err();
+    assert_eq!(err.message(), "sequences of sequences are not supported");
+
+    let err = b
+        .add_serialize("options", &vec![Some(1u8), None], 0)
+        .unwrap_err();
+    assert_eq!(
+        err.message(),
+        "sequence elements must not be empty (None, unit, or empty struct)"
+    );
+
+    let err = b.add_serialize("big", &1u128, 0).unwrap_err();
+    assert!(err.message().contains("128"), "{}", err);
+
+    let err = b
+        .add_serialize("map", &BTreeMap::from([((1u8, 2u8), 3u8)]), 0)
+        .unwrap_err();
+    assert_eq!(
+        err.message(),
+        "map key must be a string, char, integer, or bool"
+    );
+
+    let err = b.add_serialize_fields(&5u32).unwrap_err();
+    assert_eq!(err.message(), "top-level value must be a struct or a map");
+
+    let big: BTreeMap<u32, u32> = (0..128).map(|i| (i, i)).collect();
+    let err = b.add_serialize("big", &big, 0).unwrap_err();
+    assert_eq!(err.message(), "struct has more than 127 fields");
+    b.add_serialize_fields(&big).unwrap();
+
+    b.add_serialize("ok", &(1u8, "two"), 0x123).unwrap();
+    b.add_serialize("none", &None::<u32>, 0).unwrap();
+
+    // Decoders support at most 8 levels of struct nesting.
+    let nested8 = ((((((((1u8,),),),),),),),);
+    b.add_serialize("nested8", &nested8, 0).unwrap();
+    b.add_serialize("array8", &vec![nested8], 0).unwrap();
+    let err = b.add_serialize("nested9", &(nested8,), 0).unwrap_err();
+    assert_eq!(
+        err.message(),
+        "struct fields are nested more than 8 levels deep"
+    );
+    let err = b.add_serialize("array9", &vec![(nested8,)], 0).unwrap_err();
+    assert_eq!(
+        err.message(),
+        "struct fields are nested more than 8 levels deep"
+    );
+    b.add_serialize_fields(&BTreeMap::from([("nested8", nested8)]))
+        .unwrap();
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn providers_config() {
+    let config = ProvidersConfig {
+        providers: vec![
+            ProviderConfig {
+                name: "ConfigProvider1".into(),
+                group_name: String::new(),
+                sets: vec![
+                    EventSetConfig {
+                        level: Level::Informational,
+                        keyword: 0x1,
+                    },
+                    EventSetConfig {
+                        level: Level::from_int(0),
+                        keyword: 0x2,
+                    },
+                    EventSetConfig {
+                        level: Level::Informational,
+                        keyword: 0x1,
+                    },
+                    EventSetConfig {
+                        level: Level::Verbose,
+                        keyword: 0x1f,
+                    },
+                ],
+            },
+            ProviderConfig {
+                name: "Config Provider".into(),
+                group_name: String::new(),
+                sets: vec![EventSetConfig::default()],
+            },
+            ProviderConfig {
+                name: "ConfigProvider2".into(),
+                group_name: "BadGroup".into(),
+                sets: Vec::new(),
+            },
+            ProviderConfig {
+                name: "x".repeat(230),
+                group_name: "abcd".into(),
+                sets: Vec::new(),
+            },
+            ProviderConfig {
+                name: "ConfigProvider1".into(),
+                group_name: String::new(),
+                sets: Vec::new(),
+            },
+            ProviderConfig {
+                name: "ConfigProvider1".into(),
+                group_name: "mygroup".into(),
+                sets: vec![EventSetConfig {
+                    level: Level::Error,
+                    keyword: 0,
+                }],
+            },
+        ],
+    };
+
+    let loaded = config.load();
+    let kinds: Vec<_> = loaded
+        .errors
+        .iter()
+        .filter(|e| !matches!(e.kind, ConfigErrorKind::RegisterFailed(_)))
+        .map(|e| (e.provider_index, e.set, e.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (
+                0,
+                Some(EventSetConfig {
+                    level: Level::from_int(0),
+                    keyword: 0x2
+                }),
+                ConfigErrorKind::InvalidLevel
+            ),
+            (
+                0,
+                Some(EventSetConfig {
+                    level: Level::Informational,
+                    keyword: 0x1
+                }),
+                ConfigErrorKind::DuplicateSet
+            ),
+            (1, None, ConfigErrorKind::InvalidName),
+            (2, None, ConfigErrorKind::InvalidGroupName),
+            (3, None, ConfigErrorKind::NameTooLong),
+            (4, None, ConfigErrorKind::DuplicateProvider),
+        ]
+    );
+    assert!(loaded
+        .errors
+        .iter()
+        .any(|e| e.to_string() == "provider \"ConfigProvider1\" set L0K2: level must not be 0"));
+
+    // Registration failures (e.g. no user_events support) are reported per set.
+    for error in &loaded.errors {
+        if let ConfigErrorKind::RegisterFailed(errno) = error.kind {
+            let provider = loaded
+                .providers
+                .iter()
+                .find(|p| p.index == error.provider_index)
+                .unwrap();
+            let set = error.set.unwrap();
+            let set = provider.provider.find_set(set.level, set.keyword).unwrap();
+            assert_eq!(set.errno(), errno);
+        }
+    }
+
+    assert_eq!(loaded.providers.len(), 2);
+    let first = loaded.provider("ConfigProvider1", "").unwrap();
+    assert_eq!(first.index, 0);
+    assert_eq!(
+        first
+            .sets
+            .iter()
+            .map(|s| (s.level(), s.keyword()))
+            .collect::<Vec<_>>(),
+        [(Level::Informational, 0x1), (Level::Verbose, 0x1f)]
+    );
+    let grouped = loaded.provider("ConfigProvider1", "mygroup").unwrap();
+    assert_eq!(grouped.index, 5);
+    assert_eq!(
+        grouped.provider.tracepoint_name(Level::Error, 0),
+        "ConfigProvider1_L2K0Gmygroup"
+    );
+    assert_eq!(grouped.sets.len(), 1);
+    assert!(loaded.provider("ConfigProvider2", "badgroup").is_none());
+
+    #[cfg(feature = "toml")]
+    {
+        let parsed = ProvidersConfig::from_toml_str(
+            r#"
+            [[providers]]
+            name = "ConfigProvider1"
+            sets = [{ level = "informational", keyword = 0x1 }, { level = 5, keyword = 0x1f }]
+
+            [[providers]]
+            name = "ConfigProvider1"
+            group_name = "mygroup"
+            sets = [{ level = "Error" }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(parsed.providers.len(), 2);
+        assert_eq!(parsed.providers[0].sets[0].level, Level::Informational);
+        assert_eq!(parsed.providers[0].sets[1].level, Level::Verbose);
+        assert_eq!(parsed.providers[0].sets[1].keyword, 0x1f);
+        assert_eq!(parsed.providers[1].sets[0].level, Level::Error);
+        assert_eq!(parsed.providers[1].group_name, "mygroup");
+        assert_eq!(parsed.providers[1].sets[0].keyword, 0);
+        assert!(ProvidersConfig::from_toml_str("[[providers]]\nname = \"a\"\nlevel = 1").is_err());
+        assert!(ProvidersConfig::from_toml_str(
+            "[[providers]]\nname = \"a\"\nsets = [{ level = \"Warn\" }]"
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "json")]
+    {
+        let parsed = ProvidersConfig::from_json_str(
+            r#"{"providers":[{"name":"ConfigProvider1","sets":[{"level":4,"keyword":1}]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.providers[0].sets[0].level, Level::Informational);
+        assert!(ProvidersConfig::from_json_str(r#"{"providers":[{"sets":[]}]}"#).is_err());
+    }
+}
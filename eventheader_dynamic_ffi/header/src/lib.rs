@@ -0,0 +1,45 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+#![allow(clippy::needless_return)]
+
+//! Generates `include/eventheader_dynamic.h` for the `eventheader_dynamic_ffi`
+//! crate using `cbindgen`.
+//!
+//! This is a separate package so that `eventheader_dynamic_ffi` does not depend on
+//! `cbindgen`, which requires a newer Rust than the crate's `rust-version`.
+//!
+//! - `cargo run -p eventheader_dynamic_ffi_header` rewrites the header.
+//! - `cargo test -p eventheader_dynamic_ffi_header` verifies that the header is up
+//!   to date.
+
+use std::path::PathBuf;
+
+/// Returns the path to the `eventheader_dynamic_ffi` crate.
+pub fn ffi_crate_dir() -> PathBuf {
+    return PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf();
+}
+
+/// Returns the path to the checked-in header.
+pub fn header_path() -> PathBuf {
+    return ffi_crate_dir().join("include/eventheader_dynamic.h");
+}
+
+/// Generates the header from the `eventheader_dynamic_ffi` sources and
+/// `cbindgen.toml`.
+pub fn generate_header() -> Vec<u8> {
+    let crate_dir = ffi_crate_dir();
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir.join("src/lib.rs"))
+        .generate()
+        .unwrap()
+        .write(&mut generated);
+    return generated;
+}
@@ -0,0 +1,12 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//! Rewrites `eventheader_dynamic_ffi/include/eventheader_dynamic.h`.
+
+use eventheader_dynamic_ffi_header::*;
+
+fn main() {
+    let header_path = header_path();
+    std::fs::write(&header_path, generate_header()).unwrap();
+    println!("Wrote {}", header_path.display());
+}
@@ -0,0 +1,14 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use eventheader_dynamic_ffi_header::*;
+
+/// Regenerates the C header and compares it with `include/eventheader_dynamic.h`.
+#[test]
+fn header() {
+    let expected = std::fs::read(header_path()).unwrap();
+    assert!(
+        expected == generate_header(),
+        "include/eventheader_dynamic.h is out of date; run `cargo run -p eventheader_dynamic_ffi_header`"
+    );
+}
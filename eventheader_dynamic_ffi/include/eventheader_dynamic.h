@@ -0,0 +1,429 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+// Generated from the eventheader_dynamic_ffi crate by cbindgen. Do not edit.
+
+#ifndef EVENTHEADER_DYNAMIC_H
+#define EVENTHEADER_DYNAMIC_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Opaque handle to an `eventheader_dynamic::EventBuilder`.
+typedef struct ehd_builder ehd_builder;
+
+// Opaque reference to an `eventheader_dynamic::EventSet`.
+//
+// Pointers to `ehd_event_set` are `Arc<EventSet>` pointers.
+typedef struct ehd_event_set ehd_event_set;
+
+// Opaque handle to an `eventheader_dynamic::Provider`.
+typedef struct ehd_provider ehd_provider;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates an event builder.
+struct ehd_builder *ehd_builder_new(void);
+
+// Frees an event builder. Does nothing if `builder` is NULL.
+//
+// # Safety
+//
+// `builder` must be NULL or a handle returned by `ehd_builder_new` that has not
+// been freed.
+void ehd_builder_free(struct ehd_builder *builder);
+
+// Clears the previous event (if any) from the builder and starts building a new
+// event with the specified name and tag. Returns `EINVAL` if `builder` is NULL or
+// `event_name` is not a valid nul-terminated UTF-8 string.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `event_name` must be NULL or a
+// valid nul-terminated string.
+int ehd_builder_reset(struct ehd_builder *builder, const char *event_name, uint16_t event_tag);
+
+// Sets the id and version of the event. Default is id = 0, version = 0.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle.
+int ehd_builder_id_version(struct ehd_builder *builder, uint16_t id, uint8_t version);
+
+// Sets the opcode of the event. Default is 0 (Info).
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle.
+int ehd_builder_opcode(struct ehd_builder *builder, uint8_t opcode);
+
+// Adds a struct field that contains the next `struct_field_count` fields.
+// Returns `EINVAL` if `struct_field_count` is 0 or greater than 127.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string.
+int ehd_builder_add_struct(struct ehd_builder *builder,
+                           const char *field_name,
+                           uint8_t struct_field_count,
+                           uint16_t field_tag);
+
+// Adds a field containing a 1-byte value (`Value8` encoding).
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string.
+int ehd_builder_add_value8(struct ehd_builder *builder,
+                           const char *field_name,
+                           uint8_t value,
+                           uint8_t format,
+                           uint16_t field_tag);
+
+// Adds a field containing a 2-byte value (`Value16` encoding).
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string.
+int ehd_builder_add_value16(struct ehd_builder *builder,
+                            const char *field_name,
+                            uint16_t value,
+                            uint8_t format,
+                            uint16_t field_tag);
+
+// Adds a field containing a 4-byte value (`Value32` encoding).
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string.
+int ehd_builder_add_value32(struct ehd_builder *builder,
+                            const char *field_name,
+                            uint32_t value,
+                            uint8_t format,
+                            uint16_t field_tag);
+
+// Adds a field containing an 8-byte value (`Value64` encoding).
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string.
+int ehd_builder_add_value64(struct ehd_builder *builder,
+                            const char *field_name,
+                            uint64_t value,
+                            uint8_t format,
+                            uint16_t field_tag);
+
+// Adds a field containing a 16-byte value (`Value128` encoding), e.g. a UUID.
+// Returns `EINVAL` if `value` is NULL.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `value` must be NULL or point to 16 bytes.
+int ehd_builder_add_value128(struct ehd_builder *builder,
+                             const char *field_name,
+                             const uint8_t (*value)[16],
+                             uint8_t format,
+                             uint16_t field_tag);
+
+// Adds a field containing a sequence of 1-byte values.
+// `values` may be NULL if `count` is 0.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `values` must point to `count` values.
+int ehd_builder_add_value8_sequence(struct ehd_builder *builder,
+                                    const char *field_name,
+                                    const uint8_t *values,
+                                    size_t count,
+                                    uint8_t format,
+                                    uint16_t field_tag);
+
+// Adds a field containing a sequence of 2-byte values.
+// `values` may be NULL if `count` is 0.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `values` must point to `count` values.
+int ehd_builder_add_value16_sequence(struct ehd_builder *builder,
+                                     const char *field_name,
+                                     const uint16_t *values,
+                                     size_t count,
+                                     uint8_t format,
+                                     uint16_t field_tag);
+
+// Adds a field containing a sequence of 4-byte values.
+// `values` may be NULL if `count` is 0.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `values` must point to `count` values.
+int ehd_builder_add_value32_sequence(struct ehd_builder *builder,
+                                     const char *field_name,
+                                     const uint32_t *values,
+                                     size_t count,
+                                     uint8_t format,
+                                     uint16_t field_tag);
+
+// Adds a field containing a sequence of 8-byte values.
+// `values` may be NULL if `count` is 0.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `values` must point to `count` values.
+int ehd_builder_add_value64_sequence(struct ehd_builder *builder,
+                                     const char *field_name,
+                                     const uint64_t *values,
+                                     size_t count,
+                                     uint8_t format,
+                                     uint16_t field_tag);
+
+// Adds a field containing a sequence of 16-byte values, e.g. UUIDs.
+// `values` may be NULL if `count` is 0.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `values` must point to `count` values.
+int ehd_builder_add_value128_sequence(struct ehd_builder *builder,
+                                      const char *field_name,
+                                      const uint8_t (*values)[16],
+                                      size_t count,
+                                      uint8_t format,
+                                      uint16_t field_tag);
+
+// Adds a field containing a counted string of 8-bit chars (`StringLength16Char8`
+// encoding). `value` may be NULL if `len` is 0.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `value` must point to `len` chars.
+int ehd_builder_add_str8(struct ehd_builder *builder,
+                         const char *field_name,
+                         const char *value,
+                         size_t len,
+                         uint8_t format,
+                         uint16_t field_tag);
+
+// Adds a field containing a counted string of 16-bit chars (`StringLength16Char16`
+// encoding). `value` may be NULL if `len` is 0.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `value` must point to `len` chars.
+int ehd_builder_add_str16(struct ehd_builder *builder,
+                          const char *field_name,
+                          const uint16_t *value,
+                          size_t len,
+                          uint8_t format,
+                          uint16_t field_tag);
+
+// Adds a field containing a counted string of 32-bit chars (`StringLength16Char32`
+// encoding). `value` may be NULL if `len` is 0.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `value` must point to `len` chars.
+int ehd_builder_add_str32(struct ehd_builder *builder,
+                          const char *field_name,
+                          const uint32_t *value,
+                          size_t len,
+                          uint8_t format,
+                          uint16_t field_tag);
+
+// Adds a field containing a nul-terminated string of 8-bit chars (`ZStringChar8`
+// encoding). Returns `EINVAL` if `value` is NULL.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `value` must be NULL or a valid nul-terminated string.
+int ehd_builder_add_cstr8(struct ehd_builder *builder,
+                          const char *field_name,
+                          const char *value,
+                          uint8_t format,
+                          uint16_t field_tag);
+
+// Adds a field containing a nul-terminated string of 16-bit chars (`ZStringChar16`
+// encoding). Returns `EINVAL` if `value` is NULL.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `value` must be NULL or a valid nul-terminated string.
+int ehd_builder_add_cstr16(struct ehd_builder *builder,
+                           const char *field_name,
+                           const uint16_t *value,
+                           uint8_t format,
+                           uint16_t field_tag);
+
+// Adds a field containing a nul-terminated string of 32-bit chars (`ZStringChar32`
+// encoding). Returns `EINVAL` if `value` is NULL.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `value` must be NULL or a valid nul-terminated string.
+int ehd_builder_add_cstr32(struct ehd_builder *builder,
+                           const char *field_name,
+                           const uint32_t *value,
+                           uint8_t format,
+                           uint16_t field_tag);
+
+// Adds a field containing a sequence of nul-terminated strings of 8-bit chars.
+// `values` may be NULL if `count` is 0. Returns `EINVAL` if any of the strings is
+// NULL.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `values` must point to `count` pointers, each NULL
+// or a valid nul-terminated string.
+int ehd_builder_add_cstr8_sequence(struct ehd_builder *builder,
+                                   const char *field_name,
+                                   const char *const *values,
+                                   size_t count,
+                                   uint8_t format,
+                                   uint16_t field_tag);
+
+// Adds a field containing binary data (`BinaryLength16Char8` encoding).
+// `value` may be NULL if `len` is 0.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+// valid nul-terminated string. `value` must point to `len` bytes.
+int ehd_builder_add_binary(struct ehd_builder *builder,
+                           const char *field_name,
+                           const uint8_t *value,
+                           size_t len,
+                           uint8_t format,
+                           uint16_t field_tag);
+
+// Sends the finished event to the kernel with the provider, level, and keyword of
+// the specified event set.
+//
+// - `activity_id` may be NULL if the event is not part of an activity.
+// - `related_id` may be NULL. It must be NULL if `activity_id` is NULL.
+//
+// Returns 0 for success, `EBADF` (9) if no consumer is listening to the event set,
+// `ERANGE` (34) if the event is too large, or `EINVAL` (22) if `builder` or
+// `event_set` is NULL or `related_id` is set without `activity_id`.
+//
+// # Safety
+//
+// `builder` must be NULL or a valid builder handle. `event_set` must be NULL or a
+// valid event set reference. `activity_id` and `related_id` must each be NULL or
+// point to 16 bytes.
+int ehd_builder_write(const struct ehd_builder *builder,
+                      const struct ehd_event_set *event_set,
+                      const uint8_t (*activity_id)[16],
+                      const uint8_t (*related_id)[16]);
+
+// Creates a provider with the specified name and optional group name.
+//
+// - `name` must be a non-empty nul-terminated UTF-8 string. It must not contain
+//   `' '` or `':'`, and the name + group name must be less than 234 chars.
+// - `group_name` may be NULL. If not NULL, it must contain only ASCII digits and
+//   lowercase ASCII letters.
+//
+// Returns NULL if the parameters are not valid.
+//
+// # Safety
+//
+// `name` must be NULL or a valid nul-terminated string. `group_name` must be NULL
+// or a valid nul-terminated string.
+struct ehd_provider *ehd_provider_new(const char *name, const char *group_name);
+
+// Unregisters all of the provider's event sets and frees the provider.
+// Does nothing if `provider` is NULL.
+//
+// Event set references returned by the provider remain valid (but disabled) until
+// they are released.
+//
+// # Safety
+//
+// `provider` must be NULL or a handle returned by `ehd_provider_new` that has not
+// been freed. No other thread may be using the provider.
+void ehd_provider_free(struct ehd_provider *provider);
+
+// Unregisters all of the provider's event sets. Subsequent calls to
+// `ehd_provider_register_set` will register new event sets.
+//
+// # Safety
+//
+// `provider` must be NULL or a valid provider handle.
+void ehd_provider_unregister(const struct ehd_provider *provider);
+
+// Returns the event set for the specified level and keyword, registering it if
+// it does not already exist. Returns NULL if `provider` is NULL.
+//
+// The returned reference must be released with `ehd_event_set_release`. If
+// registration failed, the event set is never enabled and `ehd_event_set_errno`
+// returns the error.
+//
+// # Safety
+//
+// `provider` must be NULL or a valid provider handle.
+const struct ehd_event_set *ehd_provider_register_set(const struct ehd_provider *provider,
+                                                      uint8_t level,
+                                                      uint64_t keyword);
+
+// Returns the already-registered event set for the specified level and keyword,
+// or NULL if there is no such event set (or if `provider` is NULL).
+//
+// A non-NULL result must be released with `ehd_event_set_release`.
+//
+// # Safety
+//
+// `provider` must be NULL or a valid provider handle.
+const struct ehd_event_set *ehd_provider_find_set(const struct ehd_provider *provider,
+                                                  uint8_t level,
+                                                  uint64_t keyword);
+
+// Releases an event set reference. Does nothing if `event_set` is NULL.
+//
+// # Safety
+//
+// `event_set` must be NULL or a reference returned by `ehd_provider_register_set`
+// or `ehd_provider_find_set` that has not been released.
+void ehd_event_set_release(const struct ehd_event_set *event_set);
+
+// Returns true if any logging session is listening for events with the
+// provider, level, and keyword of the event set. Returns false if `event_set`
+// is NULL.
+//
+// # Safety
+//
+// `event_set` must be NULL or a valid event set reference.
+bool ehd_event_set_enabled(const struct ehd_event_set *event_set);
+
+// Returns 0 if the event set was successfully registered, or the error code
+// from registration. Returns `EINVAL` if `event_set` is NULL.
+//
+// # Safety
+//
+// `event_set` must be NULL or a valid event set reference.
+int ehd_event_set_errno(const struct ehd_event_set *event_set);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* EVENTHEADER_DYNAMIC_H */
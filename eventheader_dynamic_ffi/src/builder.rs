@@ -0,0 +1,671 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use core::ffi::c_char;
+use core::ffi::c_int;
+
+use eventheader_dynamic::EventBuilder;
+use eventheader_dynamic::FieldFormat;
+use eventheader_dynamic::Opcode;
+
+use crate::ehd_event_set;
+use crate::name_str;
+use crate::provider::event_set_from;
+use crate::slice_from;
+use crate::zstr_from;
+use crate::EINVAL;
+
+/// Opaque handle to an `eventheader_dynamic::EventBuilder`.
+#[allow(non_camel_case_types)]
+pub struct ehd_builder(EventBuilder);
+
+/// Runs `add` with the builder and field name, or returns `EINVAL` if the builder
+/// is NULL or the name is not valid.
+unsafe fn with_field(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    add: impl FnOnce(&mut EventBuilder, &str) -> bool,
+) -> c_int {
+    let builder = match unsafe { builder.as_mut() } {
+        None => return EINVAL,
+        Some(builder) => &mut builder.0,
+    };
+    let field_name = match unsafe { name_str(field_name) } {
+        None => return EINVAL,
+        Some(field_name) => field_name,
+    };
+
+    return if add(builder, field_name) { 0 } else { EINVAL };
+}
+
+/// Creates an event builder.
+#[no_mangle]
+pub extern "C" fn ehd_builder_new() -> *mut ehd_builder {
+    return Box::into_raw(Box::new(ehd_builder(EventBuilder::new())));
+}
+
+/// Frees an event builder. Does nothing if `builder` is NULL.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a handle returned by `ehd_builder_new` that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_free(builder: *mut ehd_builder) {
+    if !builder.is_null() {
+        drop(unsafe { Box::from_raw(builder) });
+    }
+}
+
+/// Clears the previous event (if any) from the builder and starts building a new
+/// event with the specified name and tag. Returns `EINVAL` if `builder` is NULL or
+/// `event_name` is not a valid nul-terminated UTF-8 string.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `event_name` must be NULL or a
+/// valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_reset(
+    builder: *mut ehd_builder,
+    event_name: *const c_char,
+    event_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, event_name, |b, name| {
+            b.reset(name, event_tag);
+            true
+        })
+    };
+}
+
+/// Sets the id and version of the event. Default is id = 0, version = 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_id_version(
+    builder: *mut ehd_builder,
+    id: u16,
+    version: u8,
+) -> c_int {
+    return match unsafe { builder.as_mut() } {
+        None => EINVAL,
+        Some(builder) => {
+            builder.0.id_version(id, version);
+            0
+        }
+    };
+}
+
+/// Sets the opcode of the event. Default is 0 (Info).
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_opcode(builder: *mut ehd_builder, opcode: u8) -> c_int {
+    return match unsafe { builder.as_mut() } {
+        None => EINVAL,
+        Some(builder) => {
+            builder.0.opcode(Opcode::from_int(opcode));
+            0
+        }
+    };
+}
+
+/// Adds a struct field that contains the next `struct_field_count` fields.
+/// Returns `EINVAL` if `struct_field_count` is 0 or greater than 127.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_struct(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    struct_field_count: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            b.try_add_struct(name, struct_field_count, field_tag)
+                .is_ok()
+        })
+    };
+}
+
+/// Adds a field containing a 1-byte value (`Value8` encoding).
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_value8(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: u8,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            b.add_value(name, value, FieldFormat::from_int(format), field_tag);
+            true
+        })
+    };
+}
+
+/// Adds a field containing a 2-byte value (`Value16` encoding).
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_value16(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: u16,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            b.add_value(name, value, FieldFormat::from_int(format), field_tag);
+            true
+        })
+    };
+}
+
+/// Adds a field containing a 4-byte value (`Value32` encoding).
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_value32(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: u32,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            b.add_value(name, value, FieldFormat::from_int(format), field_tag);
+            true
+        })
+    };
+}
+
+/// Adds a field containing an 8-byte value (`Value64` encoding).
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_value64(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: u64,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            b.add_value(name, value, FieldFormat::from_int(format), field_tag);
+            true
+        })
+    };
+}
+
+/// Adds a field containing a 16-byte value (`Value128` encoding), e.g. a UUID.
+/// Returns `EINVAL` if `value` is NULL.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `value` must be NULL or point to 16 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_value128(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: *const [u8; 16],
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    let value = match unsafe { value.as_ref() } {
+        None => return EINVAL,
+        Some(value) => *value,
+    };
+
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            b.add_value(name, value, FieldFormat::from_int(format), field_tag);
+            true
+        })
+    };
+}
+
+/// Adds a field containing a sequence of 1-byte values.
+/// `values` may be NULL if `count` is 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `values` must point to `count` values.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_value8_sequence(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    values: *const u8,
+    count: usize,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            match slice_from(values, count) {
+                None => false,
+                Some(values) => {
+                    b.add_value_sequence(name, values, FieldFormat::from_int(format), field_tag);
+                    true
+                }
+            }
+        })
+    };
+}
+
+/// Adds a field containing a sequence of 2-byte values.
+/// `values` may be NULL if `count` is 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `values` must point to `count` values.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_value16_sequence(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    values: *const u16,
+    count: usize,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            match slice_from(values, count) {
+                None => false,
+                Some(values) => {
+                    b.add_value_sequence(name, values, FieldFormat::from_int(format), field_tag);
+                    true
+                }
+            }
+        })
+    };
+}
+
+/// Adds a field containing a sequence of 4-byte values.
+/// `values` may be NULL if `count` is 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `values` must point to `count` values.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_value32_sequence(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    values: *const u32,
+    count: usize,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            match slice_from(values, count) {
+                None => false,
+                Some(values) => {
+                    b.add_value_sequence(name, values, FieldFormat::from_int(format), field_tag);
+                    true
+                }
+            }
+        })
+    };
+}
+
+/// Adds a field containing a sequence of 8-byte values.
+/// `values` may be NULL if `count` is 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `values` must point to `count` values.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_value64_sequence(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    values: *const u64,
+    count: usize,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            match slice_from(values, count) {
+                None => false,
+                Some(values) => {
+                    b.add_value_sequence(name, values, FieldFormat::from_int(format), field_tag);
+                    true
+                }
+            }
+        })
+    };
+}
+
+/// Adds a field containing a sequence of 16-byte values, e.g. UUIDs.
+/// `values` may be NULL if `count` is 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `values` must point to `count` values.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_value128_sequence(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    values: *const [u8; 16],
+    count: usize,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            match slice_from(values, count) {
+                None => false,
+                Some(values) => {
+                    b.add_value_sequence(name, values, FieldFormat::from_int(format), field_tag);
+                    true
+                }
+            }
+        })
+    };
+}
+
+/// Adds a field containing a counted string of 8-bit chars (`StringLength16Char8`
+/// encoding). `value` may be NULL if `len` is 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `value` must point to `len` chars.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_str8(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: *const c_char,
+    len: usize,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            match slice_from(value as *const u8, len) {
+                None => false,
+                Some(value) => {
+                    b.add_str(name, value, FieldFormat::from_int(format), field_tag);
+                    true
+                }
+            }
+        })
+    };
+}
+
+/// Adds a field containing a counted string of 16-bit chars (`StringLength16Char16`
+/// encoding). `value` may be NULL if `len` is 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `value` must point to `len` chars.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_str16(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: *const u16,
+    len: usize,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            match slice_from(value, len) {
+                None => false,
+                Some(value) => {
+                    b.add_str(name, value, FieldFormat::from_int(format), field_tag);
+                    true
+                }
+            }
+        })
+    };
+}
+
+/// Adds a field containing a counted string of 32-bit chars (`StringLength16Char32`
+/// encoding). `value` may be NULL if `len` is 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `value` must point to `len` chars.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_str32(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: *const u32,
+    len: usize,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            match slice_from(value, len) {
+                None => false,
+                Some(value) => {
+                    b.add_str(name, value, FieldFormat::from_int(format), field_tag);
+                    true
+                }
+            }
+        })
+    };
+}
+
+/// Adds a field containing a nul-terminated string of 8-bit chars (`ZStringChar8`
+/// encoding). Returns `EINVAL` if `value` is NULL.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `value` must be NULL or a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_cstr8(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: *const c_char,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            match zstr_from(value as *const u8) {
+                None => false,
+                Some(value) => {
+                    b.add_cstr(name, value, FieldFormat::from_int(format), field_tag);
+                    true
+                }
+            }
+        })
+    };
+}
+
+/// Adds a field containing a nul-terminated string of 16-bit chars (`ZStringChar16`
+/// encoding). Returns `EINVAL` if `value` is NULL.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `value` must be NULL or a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_cstr16(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: *const u16,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| match zstr_from(value) {
+            None => false,
+            Some(value) => {
+                b.add_cstr(name, value, FieldFormat::from_int(format), field_tag);
+                true
+            }
+        })
+    };
+}
+
+/// Adds a field containing a nul-terminated string of 32-bit chars (`ZStringChar32`
+/// encoding). Returns `EINVAL` if `value` is NULL.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `value` must be NULL or a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_cstr32(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: *const u32,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| match zstr_from(value) {
+            None => false,
+            Some(value) => {
+                b.add_cstr(name, value, FieldFormat::from_int(format), field_tag);
+                true
+            }
+        })
+    };
+}
+
+/// Adds a field containing a sequence of nul-terminated strings of 8-bit chars.
+/// `values` may be NULL if `count` is 0. Returns `EINVAL` if any of the strings is
+/// NULL.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `values` must point to `count` pointers, each NULL
+/// or a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_cstr8_sequence(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    values: *const *const c_char,
+    count: usize,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    let values = match unsafe { slice_from(values, count) } {
+        None => return EINVAL,
+        Some(values) => values,
+    };
+    if values.iter().any(|value| value.is_null()) {
+        return EINVAL;
+    }
+
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            b.add_cstr_sequence(
+                name,
+                values
+                    .iter()
+                    .map(|value| zstr_from(*value as *const u8).unwrap()),
+                FieldFormat::from_int(format),
+                field_tag,
+            );
+            true
+        })
+    };
+}
+
+/// Adds a field containing binary data (`BinaryLength16Char8` encoding).
+/// `value` may be NULL if `len` is 0.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `field_name` must be NULL or a
+/// valid nul-terminated string. `value` must point to `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_add_binary(
+    builder: *mut ehd_builder,
+    field_name: *const c_char,
+    value: *const u8,
+    len: usize,
+    format: u8,
+    field_tag: u16,
+) -> c_int {
+    return unsafe {
+        with_field(builder, field_name, |b, name| {
+            match slice_from(value, len) {
+                None => false,
+                Some(value) => {
+                    b.add_binary(name, value, FieldFormat::from_int(format), field_tag);
+                    true
+                }
+            }
+        })
+    };
+}
+
+/// Sends the finished event to the kernel with the provider, level, and keyword of
+/// the specified event set.
+///
+/// - `activity_id` may be NULL if the event is not part of an activity.
+/// - `related_id` may be NULL. It must be NULL if `activity_id` is NULL.
+///
+/// Returns 0 for success, `EBADF` (9) if no consumer is listening to the event set,
+/// `ERANGE` (34) if the event is too large, or `EINVAL` (22) if `builder` or
+/// `event_set` is NULL or `related_id` is set without `activity_id`.
+///
+/// # Safety
+///
+/// `builder` must be NULL or a valid builder handle. `event_set` must be NULL or a
+/// valid event set reference. `activity_id` and `related_id` must each be NULL or
+/// point to 16 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_builder_write(
+    builder: *const ehd_builder,
+    event_set: *const ehd_event_set,
+    activity_id: *const [u8; 16],
+    related_id: *const [u8; 16],
+) -> c_int {
+    let (builder, event_set) = match unsafe { (builder.as_ref(), event_set_from(event_set)) } {
+        (Some(builder), Some(event_set)) => (builder, event_set),
+        _ => return EINVAL,
+    };
+    let activity_id = unsafe { activity_id.as_ref() };
+    let related_id = unsafe { related_id.as_ref() };
+    if activity_id.is_none() && related_id.is_some() {
+        return EINVAL;
+    }
+
+    return builder.0.write(event_set, activity_id, related_id);
+}
@@ -0,0 +1,13 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//! Release history
+
+#[allow(unused_imports)]
+use crate::*; // For docs
+
+/// # v0.6.0 (TBD)
+/// - Initial release: C API for `eventheader_dynamic` providers, event sets, and
+///   event builders, with a header generated by `cbindgen` (see the
+///   `eventheader_dynamic_ffi_header` tool in the `header` directory).
+pub mod v0_6_0 {}
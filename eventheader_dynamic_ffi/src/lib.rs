@@ -0,0 +1,83 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+#![warn(missing_docs)]
+#![allow(clippy::needless_return)]
+
+//! # C API for dynamic EventHeader-encoded Linux Tracepoints
+//!
+//! `eventheader_dynamic_ffi` exposes [`eventheader_dynamic`] through an
+//! `extern "C"` API so that C and C++ components in the same process can use the
+//! same provider implementation (and the same `user_events_data` file handle) as
+//! Rust components.
+//!
+//! The C declarations are in `include/eventheader_dynamic.h`, which is generated
+//! from this crate by `cbindgen`. Link with the crate's `staticlib` or `cdylib`
+//! output.
+//!
+//! # Overview
+//!
+//! - `ehd_provider_new` creates an [`ehd_provider`] handle. Free it with
+//!   `ehd_provider_free`, which unregisters all of the provider's event sets.
+//! - `ehd_provider_register_set` returns an [`ehd_event_set`] reference for a
+//!   level/keyword combination. Release it with `ehd_event_set_release`.
+//! - `ehd_event_set_enabled` returns true if a session is listening for the event set.
+//! - `ehd_builder_new` creates an [`ehd_builder`] handle. Use `ehd_builder_reset`,
+//!   the `ehd_builder_add_*` functions, and `ehd_builder_write` to build and write
+//!   events. Free it with `ehd_builder_free`.
+//!
+//! Provider and event set handles may be used from any thread. A builder handle
+//! must not be used by more than one thread at a time.
+//!
+//! Functions that return `int` return 0 for success or an errno value, e.g.
+//! `EINVAL` (22) for a NULL handle, a NULL or non-UTF-8 name, or another invalid
+//! argument.
+
+pub use builder::*;
+pub use provider::*;
+
+pub mod changelog;
+
+mod builder;
+mod provider;
+
+use core::ffi::c_char;
+use core::ffi::CStr;
+use core::slice;
+
+const EINVAL: i32 = 22;
+
+/// Returns the string for a non-NULL, nul-terminated UTF-8 `name`.
+unsafe fn name_str<'a>(name: *const c_char) -> Option<&'a str> {
+    if name.is_null() {
+        return None;
+    }
+
+    return unsafe { CStr::from_ptr(name) }.to_str().ok();
+}
+
+/// Returns a slice for `len` elements at `ptr`. `ptr` may be NULL if `len` is 0.
+unsafe fn slice_from<'a, T>(ptr: *const T, len: usize) -> Option<&'a [T]> {
+    return if len == 0 {
+        Some(&[])
+    } else if ptr.is_null() {
+        None
+    } else {
+        Some(unsafe { slice::from_raw_parts(ptr, len) })
+    };
+}
+
+/// Returns a slice for the nul-terminated string at `ptr` (not including the nul).
+unsafe fn zstr_from<'a, T: Copy + Default + Eq>(ptr: *const T) -> Option<&'a [T]> {
+    if ptr.is_null() {
+        return None;
+    }
+
+    let zero = T::default();
+    let mut len = 0;
+    while unsafe { *ptr.add(len) } != zero {
+        len += 1;
+    }
+
+    return Some(unsafe { slice::from_raw_parts(ptr, len) });
+}
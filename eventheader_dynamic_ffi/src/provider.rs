@@ -0,0 +1,198 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use core::ffi::c_char;
+use core::ffi::c_int;
+use core::ptr;
+use std::sync::Arc;
+
+use eventheader_dynamic::EventSet;
+use eventheader_dynamic::Level;
+use eventheader_dynamic::Provider;
+
+use crate::name_str;
+
+/// Opaque handle to an `eventheader_dynamic::Provider`.
+#[allow(non_camel_case_types)]
+pub struct ehd_provider(Provider);
+
+/// Opaque reference to an `eventheader_dynamic::EventSet`.
+///
+/// Pointers to `ehd_event_set` are `Arc<EventSet>` pointers.
+#[allow(non_camel_case_types)]
+pub struct ehd_event_set {
+    _private: [u8; 0],
+}
+
+fn event_set_ref(set: Arc<EventSet>) -> *const ehd_event_set {
+    return Arc::into_raw(set) as *const ehd_event_set;
+}
+
+/// Creates a provider with the specified name and optional group name.
+///
+/// - `name` must be a non-empty nul-terminated UTF-8 string. It must not contain
+///   `' '` or `':'`, and the name + group name must be less than 234 chars.
+/// - `group_name` may be NULL. If not NULL, it must contain only ASCII digits and
+///   lowercase ASCII letters.
+///
+/// Returns NULL if the parameters are not valid.
+///
+/// # Safety
+///
+/// `name` must be NULL or a valid nul-terminated string. `group_name` must be NULL
+/// or a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_provider_new(
+    name: *const c_char,
+    group_name: *const c_char,
+) -> *mut ehd_provider {
+    let name = match unsafe { name_str(name) } {
+        Some(name) => name,
+        None => return ptr::null_mut(),
+    };
+
+    let group_name = if group_name.is_null() {
+        ""
+    } else {
+        match unsafe { name_str(group_name) } {
+            Some(group_name) => group_name,
+            None => return ptr::null_mut(),
+        }
+    };
+
+    let mut options = Provider::new_options();
+    options.group_name(group_name);
+    return match Provider::try_new(name, &options) {
+        Ok(provider) => Box::into_raw(Box::new(ehd_provider(provider))),
+        Err(_) => ptr::null_mut(),
+    };
+}
+
+/// Unregisters all of the provider's event sets and frees the provider.
+/// Does nothing if `provider` is NULL.
+///
+/// Event set references returned by the provider remain valid (but disabled) until
+/// they are released.
+///
+/// # Safety
+///
+/// `provider` must be NULL or a handle returned by `ehd_provider_new` that has not
+/// been freed. No other thread may be using the provider.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_provider_free(provider: *mut ehd_provider) {
+    if !provider.is_null() {
+        drop(unsafe { Box::from_raw(provider) });
+    }
+}
+
+/// Unregisters all of the provider's event sets. Subsequent calls to
+/// `ehd_provider_register_set` will register new event sets.
+///
+/// # Safety
+///
+/// `provider` must be NULL or a valid provider handle.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_provider_unregister(provider: *const ehd_provider) {
+    if let Some(provider) = unsafe { provider.as_ref() } {
+        provider.0.unregister();
+    }
+}
+
+/// Returns the event set for the specified level and keyword, registering it if
+/// it does not already exist. Returns NULL if `provider` is NULL.
+///
+/// The returned reference must be released with `ehd_event_set_release`. If
+/// registration failed, the event set is never enabled and `ehd_event_set_errno`
+/// returns the error.
+///
+/// # Safety
+///
+/// `provider` must be NULL or a valid provider handle.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_provider_register_set(
+    provider: *const ehd_provider,
+    level: u8,
+    keyword: u64,
+) -> *const ehd_event_set {
+    return match unsafe { provider.as_ref() } {
+        None => ptr::null(),
+        Some(provider) => event_set_ref(provider.0.register_set(Level::from_int(level), keyword)),
+    };
+}
+
+/// Returns the already-registered event set for the specified level and keyword,
+/// or NULL if there is no such event set (or if `provider` is NULL).
+///
+/// A non-NULL result must be released with `ehd_event_set_release`.
+///
+/// # Safety
+///
+/// `provider` must be NULL or a valid provider handle.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_provider_find_set(
+    provider: *const ehd_provider,
+    level: u8,
+    keyword: u64,
+) -> *const ehd_event_set {
+    return match unsafe { provider.as_ref() } {
+        None => ptr::null(),
+        Some(provider) => match provider.0.find_set(Level::from_int(level), keyword) {
+            None => ptr::null(),
+            Some(set) => event_set_ref(set),
+        },
+    };
+}
+
+/// Releases an event set reference. Does nothing if `event_set` is NULL.
+///
+/// # Safety
+///
+/// `event_set` must be NULL or a reference returned by `ehd_provider_register_set`
+/// or `ehd_provider_find_set` that has not been released.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_event_set_release(event_set: *const ehd_event_set) {
+    if !event_set.is_null() {
+        drop(unsafe { Arc::from_raw(event_set as *const EventSet) });
+    }
+}
+
+/// Returns true if any logging session is listening for events with the
+/// provider, level, and keyword of the event set. Returns false if `event_set`
+/// is NULL.
+///
+/// # Safety
+///
+/// `event_set` must be NULL or a valid event set reference.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_event_set_enabled(event_set: *const ehd_event_set) -> bool {
+    return match unsafe { event_set_from(event_set) } {
+        None => false,
+        Some(event_set) => event_set.enabled(),
+    };
+}
+
+/// Returns 0 if the event set was successfully registered, or the error code
+/// from registration. Returns `EINVAL` if `event_set` is NULL.
+///
+/// # Safety
+///
+/// `event_set` must be NULL or a valid event set reference.
+#[no_mangle]
+pub unsafe extern "C" fn ehd_event_set_errno(event_set: *const ehd_event_set) -> c_int {
+    return match unsafe { event_set_from(event_set) } {
+        None => crate::EINVAL,
+        Some(event_set) => event_set.errno(),
+    };
+}
+
+/// Returns the `EventSet` for an event set reference, or None if `event_set` is NULL.
+///
+/// The pointer is cast directly (never through a `&ehd_event_set`, which would only
+/// cover zero bytes).
+///
+/// # Safety
+///
+/// `event_set` must be NULL or a valid event set reference.
+pub(crate) unsafe fn event_set_from<'a>(event_set: *const ehd_event_set) -> Option<&'a EventSet> {
+    return unsafe { (event_set as *const EventSet).as_ref() };
+}
@@ -0,0 +1,139 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use std::ffi::CString;
+use std::ptr;
+
+use eventheader_dynamic_ffi::*;
+
+#[test]
+fn provider() {
+    let name = CString::new("EhdFfiTest").unwrap();
+    let group = CString::new("mygroup").unwrap();
+    let bad_name = CString::new("Ehd Ffi Test").unwrap();
+    let empty_name = CString::new("").unwrap();
+    let long_name = CString::new("x".repeat(234)).unwrap();
+    let bad_group = CString::new("MyGroup").unwrap();
+
+    unsafe {
+        assert!(ehd_provider_new(ptr::null(), ptr::null()).is_null());
+        assert!(ehd_provider_new(bad_name.as_ptr(), ptr::null()).is_null());
+        assert!(ehd_provider_new(empty_name.as_ptr(), ptr::null()).is_null());
+        assert!(ehd_provider_new(long_name.as_ptr(), ptr::null()).is_null());
+        assert!(ehd_provider_new(name.as_ptr(), bad_group.as_ptr()).is_null());
+
+        let provider = ehd_provider_new(name.as_ptr(), group.as_ptr());
+        assert!(!provider.is_null());
+
+        assert!(ehd_provider_find_set(provider, 5, 0x123).is_null());
+        let set = ehd_provider_register_set(provider, 5, 0x123);
+        assert!(!set.is_null());
+        assert!(!ehd_event_set_enabled(set) || ehd_event_set_errno(set) == 0);
+
+        let found = ehd_provider_find_set(provider, 5, 0x123);
+        assert_eq!(set, found);
+        ehd_event_set_release(found);
+
+        ehd_provider_unregister(provider);
+        assert!(!ehd_event_set_enabled(set));
+        ehd_provider_free(provider);
+
+        // Event set references outlive the provider.
+        assert!(!ehd_event_set_enabled(set));
+        ehd_event_set_release(set);
+
+        assert!(!ehd_event_set_enabled(ptr::null()));
+        assert_eq!(22, ehd_event_set_errno(ptr::null()));
+        assert!(ehd_provider_register_set(ptr::null(), 5, 1).is_null());
+        ehd_provider_free(ptr::null_mut());
+        ehd_event_set_release(ptr::null());
+    }
+}
+
+#[test]
+fn builder() {
+    let provider_name = CString::new("EhdFfiTest").unwrap();
+    let event_name = CString::new("Event").unwrap();
+    let field_name = CString::new("Field").unwrap();
+    let value = CString::new("Value").unwrap();
+    let values = [value.as_ptr(), event_name.as_ptr()];
+    let utf16 = [0x48u16, 0x69, 0];
+    let utf32 = [0x48u32, 0x69];
+    let guid = [1u8; 16];
+
+    unsafe {
+        let provider = ehd_provider_new(provider_name.as_ptr(), ptr::null());
+        let set = ehd_provider_register_set(provider, 4, 1);
+        let b = ehd_builder_new();
+
+        assert_eq!(
+            22,
+            ehd_builder_reset(ptr::null_mut(), event_name.as_ptr(), 0)
+        );
+        assert_eq!(22, ehd_builder_reset(b, ptr::null(), 0));
+        assert_eq!(0, ehd_builder_reset(b, event_name.as_ptr(), 0x12));
+        assert_eq!(0, ehd_builder_id_version(b, 1, 2));
+        assert_eq!(0, ehd_builder_opcode(b, 1));
+
+        let f = field_name.as_ptr();
+        assert_eq!(22, ehd_builder_add_value8(b, ptr::null(), 1, 0, 0));
+        assert_eq!(0, ehd_builder_add_value8(b, f, 1, 0, 0));
+        assert_eq!(0, ehd_builder_add_value16(b, f, 2, 0, 0));
+        assert_eq!(0, ehd_builder_add_value32(b, f, 3, 0, 0));
+        assert_eq!(0, ehd_builder_add_value64(b, f, 4, 0, 0));
+        assert_eq!(0, ehd_builder_add_value128(b, f, &guid, 0, 0));
+        assert_eq!(22, ehd_builder_add_value128(b, f, ptr::null(), 0, 0));
+        assert_eq!(
+            0,
+            ehd_builder_add_value8_sequence(b, f, ptr::null(), 0, 0, 0)
+        );
+        assert_eq!(
+            22,
+            ehd_builder_add_value8_sequence(b, f, ptr::null(), 1, 0, 0)
+        );
+        assert_eq!(
+            0,
+            ehd_builder_add_value16_sequence(b, f, utf16.as_ptr(), 3, 0, 0)
+        );
+        assert_eq!(
+            0,
+            ehd_builder_add_value32_sequence(b, f, utf32.as_ptr(), 2, 0, 0)
+        );
+        assert_eq!(
+            0,
+            ehd_builder_add_value64_sequence(b, f, [5u64].as_ptr(), 1, 0, 0)
+        );
+        assert_eq!(0, ehd_builder_add_value128_sequence(b, f, &guid, 1, 0, 0));
+        assert_eq!(0, ehd_builder_add_str8(b, f, value.as_ptr(), 5, 0, 0));
+        assert_eq!(0, ehd_builder_add_str16(b, f, utf16.as_ptr(), 2, 0, 0));
+        assert_eq!(0, ehd_builder_add_str32(b, f, utf32.as_ptr(), 2, 0, 0));
+        assert_eq!(0, ehd_builder_add_cstr8(b, f, value.as_ptr(), 0, 0));
+        assert_eq!(22, ehd_builder_add_cstr8(b, f, ptr::null(), 0, 0));
+        assert_eq!(0, ehd_builder_add_cstr16(b, f, utf16.as_ptr(), 0, 0));
+        assert_eq!(
+            0,
+            ehd_builder_add_cstr8_sequence(b, f, values.as_ptr(), 2, 0, 0)
+        );
+        assert_eq!(0, ehd_builder_add_binary(b, f, guid.as_ptr(), 16, 0, 0));
+        assert_eq!(22, ehd_builder_add_struct(b, f, 0, 0));
+        assert_eq!(22, ehd_builder_add_struct(b, f, 128, 0));
+        assert_eq!(0, ehd_builder_add_struct(b, f, 1, 0));
+        assert_eq!(0, ehd_builder_add_value8(b, f, 1, 0, 0));
+
+        assert_eq!(
+            22,
+            ehd_builder_write(b, ptr::null(), ptr::null(), ptr::null())
+        );
+        assert_eq!(
+            22,
+            ehd_builder_write(ptr::null(), set, ptr::null(), ptr::null())
+        );
+        assert_eq!(22, ehd_builder_write(b, set, ptr::null(), &guid));
+        let result = ehd_builder_write(b, set, &guid, ptr::null());
+        assert!(result == 0 || result == 9 || result == ehd_event_set_errno(set));
+
+        ehd_builder_free(b);
+        ehd_event_set_release(set);
+        ehd_provider_free(provider);
+    }
+}
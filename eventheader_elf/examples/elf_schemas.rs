@@ -0,0 +1,205 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//! Demonstrates how to use [`ee::ElfProvider`] to list the eventheader providers,
+//! events, and fields defined in an ELF binary, as text or as JSON.
+
+#![allow(clippy::needless_return)]
+
+use std::env;
+use std::process;
+use std::vec;
+
+use eventheader_elf as ee;
+use eventheader_types::FieldEncoding;
+use tracepoint_decode as td;
+
+fn main() -> process::ExitCode {
+    let mut result = process::ExitCode::SUCCESS;
+
+    let mut json = false;
+    let mut filenames = vec::Vec::new();
+
+    for arg in env::args().skip(1) {
+        if arg == "--json" {
+            json = true;
+        } else if arg.starts_with('-') {
+            eprintln!("Unknown option: {}", arg);
+            return usage();
+        } else {
+            filenames.push(arg);
+        }
+    }
+
+    if filenames.is_empty() {
+        eprintln!("No input files specified.");
+        return usage();
+    }
+
+    if json {
+        println!("[");
+    }
+
+    for (file_index, filename) in filenames.iter().enumerate() {
+        let providers = match ee::ElfProvider::read_all_from_file(filename) {
+            Ok(providers) => providers,
+            Err(e) => {
+                eprintln!("Error {} reading {}", e, filename);
+                result = process::ExitCode::FAILURE;
+                continue;
+            }
+        };
+
+        if json {
+            write_json(file_index != 0, filename, &providers);
+        } else {
+            write_text(filename, &providers);
+        }
+    }
+
+    if json {
+        println!("\n]");
+    }
+
+    return result;
+}
+
+fn write_text(filename: &str, providers: &[ee::ElfProvider]) {
+    println!("******* File: {}", filename);
+    for provider in providers {
+        println!(
+            "Provider: {} = \"{}\" options=\"{}\"",
+            provider.symbol(),
+            provider.name(),
+            provider.options()
+        );
+
+        for event in provider.events() {
+            let header = event.header();
+            println!(
+                "  Event: {} tracepoint={} id={} version={} level={} keyword=0x{:x} opcode={} tag=0x{:x}",
+                event.name(),
+                event.tracepoint_name(),
+                header.id,
+                header.version,
+                header.level,
+                event.keyword(),
+                header.opcode,
+                header.tag,
+            );
+
+            match event.fields() {
+                Err(e) => println!("    error decoding metadata: {}", e),
+                Ok(fields) => {
+                    for field in fields {
+                        let meta = field.metadata();
+                        let array = if !field.is_array() {
+                            String::new()
+                        } else if field.element_count() == 0 {
+                            String::from("[]")
+                        } else {
+                            format!("[{}]", field.element_count())
+                        };
+
+                        if meta.encoding() == FieldEncoding::Struct {
+                            println!(
+                                "    {}{}: encoding={} fields={} tag=0x{:x}",
+                                field.name(),
+                                array,
+                                meta.encoding(),
+                                meta.struct_field_count(),
+                                meta.field_tag(),
+                            );
+                        } else {
+                            println!(
+                                "    {}{}: encoding={} format={} tag=0x{:x}",
+                                field.name(),
+                                array,
+                                meta.encoding(),
+                                meta.format(),
+                                meta.field_tag(),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn write_json(add_comma: bool, filename: &str, providers: &[ee::ElfProvider]) {
+    use td::display::JsonEscapeDisplay as Json;
+
+    if add_comma {
+        println!(",");
+    }
+
+    println!(
+        " {{\n  \"file\": \"{}\",\n  \"providers\": [",
+        Json::new(filename)
+    );
+    for (provider_index, provider) in providers.iter().enumerate() {
+        print!(
+            "{}   {{ \"symbol\": \"{}\", \"name\": \"{}\", \"options\": \"{}\", \"events\": [",
+            if provider_index == 0 { "" } else { ",\n" },
+            Json::new(provider.symbol()),
+            Json::new(provider.name()),
+            Json::new(provider.options())
+        );
+
+        for (event_index, event) in provider.events().iter().enumerate() {
+            let header = event.header();
+            print!(
+                "{}\n    {{ \"name\": \"{}\", \"tracepoint\": \"{}\", \"id\": {}, \"version\": {}, \"level\": {}, \"keyword\": \"0x{:x}\", \"opcode\": {}, \"tag\": \"0x{:x}\", \"fields\": [",
+                if event_index == 0 { "" } else { "," },
+                Json::new(event.name()),
+                Json::new(event.tracepoint_name()),
+                header.id,
+                header.version,
+                header.level,
+                event.keyword(),
+                header.opcode,
+                header.tag,
+            );
+
+            if let Ok(fields) = event.fields() {
+                for (field_index, field) in fields.iter().enumerate() {
+                    let meta = field.metadata();
+                    print!(
+                        "{} {{ \"name\": \"{}\", \"encoding\": {}, ",
+                        if field_index == 0 { "" } else { "," },
+                        Json::new(field.name()),
+                        meta.encoding().as_int(),
+                    );
+                    if meta.encoding() == FieldEncoding::Struct {
+                        print!("\"fields\": {}, ", meta.struct_field_count());
+                    } else {
+                        print!("\"format\": {}, ", meta.format().as_int());
+                    }
+                    if field.is_array() {
+                        print!("\"count\": {}, ", field.element_count());
+                    }
+                    print!("\"tag\": {} }}", meta.field_tag());
+                }
+            }
+
+            print!(" ] }}");
+        }
+
+        print!(" ] }}");
+    }
+
+    print!("\n  ]\n }}");
+}
+
+fn usage() -> process::ExitCode {
+    eprintln!(
+        r#"
+Usage: elf_schemas [--json] <elf file1> [<elf file2> ...]
+
+Lists the eventheader providers, events, and fields defined in the specified
+executables or shared objects.
+"#
+    );
+    return process::ExitCode::FAILURE;
+}
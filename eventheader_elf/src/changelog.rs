@@ -0,0 +1,11 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//! Release history
+
+#[allow(unused_imports)]
+use crate::*; // For docs
+
+/// # v0.6.0 (TBD)
+/// - Initial release.
+pub mod v0_6_0 {}
@@ -0,0 +1,282 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::str;
+
+use tracepoint_decode::PerfByteReader;
+
+const SHT_RELA: u32 = 4;
+const SHT_NOBITS: u32 = 8;
+const SHF_ALLOC: u64 = 2;
+const SHN_XINDEX: u16 = 0xFFFF;
+
+fn invalid_data(message: &'static str) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, message);
+}
+
+pub struct ElfSection<'a> {
+    pub name: &'a str,
+    pub sh_type: u32,
+    pub flags: u64,
+    pub addr: u64,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl<'a> ElfSection<'a> {
+    /// Returns true if the section occupies memory and has contents in the file.
+    pub fn is_loaded(&self) -> bool {
+        return self.flags & SHF_ALLOC != 0 && self.sh_type != SHT_NOBITS;
+    }
+}
+
+/// Minimal read-only view of a linked ELF file (executable or shared object).
+/// Supports ELF32 and ELF64, little-endian and big-endian.
+pub struct ElfImage<'a> {
+    bytes: &'a [u8],
+    byte_reader: PerfByteReader,
+    is_64: bool,
+    sections: Vec<ElfSection<'a>>,
+
+    /// Relocated pointer values from `R_*_RELATIVE`-style relocations, i.e. RELA
+    /// relocations with no symbol. Key is the address, value is the addend.
+    relative_relocs: BTreeMap<u64, u64>,
+}
+
+impl<'a> ElfImage<'a> {
+    pub fn new(bytes: &'a [u8]) -> io::Result<Self> {
+        if bytes.len() < 16 || &bytes[0..4] != b"\x7FELF" {
+            return Err(invalid_data("not an ELF file"));
+        }
+
+        let is_64 = match bytes[4] {
+            1 => false,
+            2 => true,
+            _ => return Err(invalid_data("unsupported ELF class")),
+        };
+
+        let byte_reader = match bytes[5] {
+            1 => PerfByteReader::new(false),
+            2 => PerfByteReader::new(true),
+            _ => return Err(invalid_data("unsupported ELF data encoding")),
+        };
+
+        let mut image = Self {
+            bytes,
+            byte_reader,
+            is_64,
+            sections: Vec::new(),
+            relative_relocs: BTreeMap::new(),
+        };
+
+        // e_shoff, e_shentsize, e_shnum, e_shstrndx
+        let (shoff, shentsize, shnum, shstrndx) = if is_64 {
+            (
+                image.file_u64(0x28)?,
+                image.file_u16(0x3A)? as u64,
+                image.file_u16(0x3C)? as u64,
+                image.file_u16(0x3E)?,
+            )
+        } else {
+            (
+                image.file_u32(0x20)? as u64,
+                image.file_u16(0x2E)? as u64,
+                image.file_u16(0x30)? as u64,
+                image.file_u16(0x32)?,
+            )
+        };
+
+        if shoff == 0 {
+            return Err(invalid_data("ELF file has no section headers"));
+        }
+
+        if shentsize < if is_64 { 64 } else { 40 } {
+            return Err(invalid_data("invalid ELF section header size"));
+        }
+
+        // Extended numbering: real values are stored in section header 0.
+        let raw0 = image.raw_section_header(shoff)?;
+        let shnum = if shnum == 0 { raw0.size } else { shnum };
+        let shstrndx = if shstrndx == SHN_XINDEX {
+            raw0.link as u64
+        } else {
+            shstrndx as u64
+        };
+
+        if shnum > (bytes.len() as u64) / shentsize {
+            return Err(invalid_data("invalid ELF section header count"));
+        }
+
+        let mut raw_headers = Vec::with_capacity(shnum as usize);
+        for i in 0..shnum {
+            raw_headers.push(image.raw_section_header(shoff + i * shentsize)?);
+        }
+
+        let names = match raw_headers.get(shstrndx as usize) {
+            Some(strtab) => image.file_bytes(strtab.offset, strtab.size)?,
+            None => return Err(invalid_data("invalid ELF section name table index")),
+        };
+
+        for raw in &raw_headers {
+            let name_start = raw.name as usize;
+            let name = match names.get(name_start..) {
+                Some(tail) => {
+                    let name_len = tail.iter().position(|&b| b == 0).unwrap_or(tail.len());
+                    str::from_utf8(&tail[..name_len]).unwrap_or("")
+                }
+                None => "",
+            };
+
+            image.sections.push(ElfSection {
+                name,
+                sh_type: raw.sh_type,
+                flags: raw.flags,
+                addr: raw.addr,
+                offset: raw.offset,
+                size: raw.size,
+            });
+        }
+
+        image.load_relative_relocs()?;
+        return Ok(image);
+    }
+
+    /// Size of a pointer in the target, 4 or 8.
+    pub fn pointer_size(&self) -> u64 {
+        return if self.is_64 { 8 } else { 4 };
+    }
+
+    pub fn byte_reader(&self) -> PerfByteReader {
+        return self.byte_reader;
+    }
+
+    pub fn sections(&self) -> &[ElfSection<'a>] {
+        return &self.sections;
+    }
+
+    /// Returns the file contents for the specified virtual address range, or
+    /// None if the range is not contained within a single loaded section.
+    pub fn read_bytes(&self, addr: u64, size: u64) -> Option<&'a [u8]> {
+        let end = addr.checked_add(size)?;
+        for section in &self.sections {
+            if section.is_loaded() && section.addr <= addr && end <= section.addr + section.size {
+                return self
+                    .file_bytes(section.offset + (addr - section.addr), size)
+                    .ok();
+            }
+        }
+
+        return None;
+    }
+
+    /// Reads a u64 value from the specified virtual address.
+    pub fn read_u64(&self, addr: u64) -> Option<u64> {
+        return self
+            .read_bytes(addr, 8)
+            .map(|bytes| self.byte_reader.read_u64(bytes));
+    }
+
+    /// Reads a pointer-sized value from the specified virtual address,
+    /// applying relative relocations if present.
+    pub fn read_pointer(&self, addr: u64) -> Option<u64> {
+        if let Some(value) = self.relative_relocs.get(&addr) {
+            return Some(*value);
+        }
+
+        let bytes = self.read_bytes(addr, self.pointer_size())?;
+        return Some(if self.is_64 {
+            self.byte_reader.read_u64(bytes)
+        } else {
+            self.byte_reader.read_u32(bytes) as u64
+        });
+    }
+
+    fn load_relative_relocs(&mut self) -> io::Result<()> {
+        let entry_size = if self.is_64 { 24 } else { 12 };
+        for section in &self.sections {
+            if section.sh_type != SHT_RELA || section.flags & SHF_ALLOC == 0 {
+                continue;
+            }
+
+            let relocs = self.file_bytes(section.offset, section.size)?;
+            for entry in relocs.chunks_exact(entry_size) {
+                let (offset, sym, addend) = if self.is_64 {
+                    (
+                        self.byte_reader.read_u64(&entry[0..]),
+                        self.byte_reader.read_u64(&entry[8..]) >> 32,
+                        self.byte_reader.read_u64(&entry[16..]),
+                    )
+                } else {
+                    (
+                        self.byte_reader.read_u32(&entry[0..]) as u64,
+                        (self.byte_reader.read_u32(&entry[4..]) >> 8) as u64,
+                        self.byte_reader.read_u32(&entry[8..]) as u64,
+                    )
+                };
+
+                if sym == 0 {
+                    self.relative_relocs.insert(offset, addend);
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn raw_section_header(&self, pos: u64) -> io::Result<RawSectionHeader> {
+        return Ok(if self.is_64 {
+            RawSectionHeader {
+                name: self.file_u32(pos)?,
+                sh_type: self.file_u32(pos + 4)?,
+                flags: self.file_u64(pos + 8)?,
+                addr: self.file_u64(pos + 16)?,
+                offset: self.file_u64(pos + 24)?,
+                size: self.file_u64(pos + 32)?,
+                link: self.file_u32(pos + 40)?,
+            }
+        } else {
+            RawSectionHeader {
+                name: self.file_u32(pos)?,
+                sh_type: self.file_u32(pos + 4)?,
+                flags: self.file_u32(pos + 8)? as u64,
+                addr: self.file_u32(pos + 12)? as u64,
+                offset: self.file_u32(pos + 16)? as u64,
+                size: self.file_u32(pos + 20)? as u64,
+                link: self.file_u32(pos + 24)?,
+            }
+        });
+    }
+
+    fn file_bytes(&self, pos: u64, size: u64) -> io::Result<&'a [u8]> {
+        let bytes = self.bytes;
+        return pos
+            .checked_add(size)
+            .filter(|end| *end <= bytes.len() as u64)
+            .map(|end| &bytes[pos as usize..end as usize])
+            .ok_or_else(|| invalid_data("ELF file is truncated"));
+    }
+
+    fn file_u16(&self, pos: u64) -> io::Result<u16> {
+        return Ok(self.byte_reader.read_u16(self.file_bytes(pos, 2)?));
+    }
+
+    fn file_u32(&self, pos: u64) -> io::Result<u32> {
+        return Ok(self.byte_reader.read_u32(self.file_bytes(pos, 4)?));
+    }
+
+    fn file_u64(&self, pos: u64) -> io::Result<u64> {
+        return Ok(self.byte_reader.read_u64(self.file_bytes(pos, 8)?));
+    }
+}
+
+struct RawSectionHeader {
+    name: u32,
+    sh_type: u32,
+    flags: u64,
+    addr: u64,
+    offset: u64,
+    size: u64,
+    link: u32,
+}
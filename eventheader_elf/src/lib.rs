@@ -0,0 +1,16 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+#![warn(missing_docs)]
+#![allow(clippy::needless_return)]
+
+//! Extraction of eventheader event schemas from ELF binaries
+
+pub use providers::ElfEvent;
+pub use providers::ElfField;
+pub use providers::ElfProvider;
+
+pub mod changelog;
+
+mod elf_image;
+mod providers;
@@ -0,0 +1,366 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use std::fs;
+use std::io;
+use std::path;
+use std::str;
+
+use eventheader_types::*;
+use tracepoint_decode::EventHeaderEnumeratorContext;
+use tracepoint_decode::EventHeaderEnumeratorError;
+use tracepoint_decode::EventHeaderEnumeratorState;
+use tracepoint_decode::PerfItemMetadata;
+
+use crate::elf_image::ElfImage;
+
+/// Name of the linker section that contains the `ProviderDescriptor` objects
+/// created by `define_provider!`.
+const PROVIDERS_SECTION: &str = "_eh_providers";
+
+/// Maximum length of a Tracepoint name "ProviderName_Attributes\0" (includes nul).
+const EVENTHEADER_NAME_MAX: u64 = 256;
+
+// Offsets of the fields of eventheader's `#[repr(C)]` `ProviderDescriptor`, in
+// pointers. Each field is pointer-sized.
+
+/// Offset of `ProviderDescriptor::symbol` (pointer, then length).
+const DESCRIPTOR_SYMBOL_OFFSET: u64 = 0;
+/// Offset of `ProviderDescriptor::name` (pointer, then length).
+const DESCRIPTOR_NAME_OFFSET: u64 = 2;
+/// Offset of `ProviderDescriptor::options` (pointer, then length).
+const DESCRIPTOR_OPTIONS_OFFSET: u64 = 4;
+/// Offset of `ProviderDescriptor::events_start` (`__start__eh_tracepoints_SYMBOL`).
+const DESCRIPTOR_EVENTS_START_OFFSET: u64 = 6;
+/// Offset of `ProviderDescriptor::events_stop` (`__stop__eh_tracepoints_SYMBOL`).
+const DESCRIPTOR_EVENTS_STOP_OFFSET: u64 = 7;
+/// Size of `ProviderDescriptor`.
+const DESCRIPTOR_SIZE: u64 = 8;
+
+// Offsets of the fields of eventheader's `#[repr(C)]` `EventHeaderTracepoint`, in
+// bytes: `state` (8 bytes), `header` (8 bytes), `keyword` (u64), metadata pointer,
+// metadata length.
+
+/// Offset of `EventHeaderTracepoint::header` (`EventHeader`).
+const TRACEPOINT_HEADER_OFFSET: u64 = 8;
+/// Offset of `EventHeaderTracepoint::keyword` (`u64`).
+const TRACEPOINT_KEYWORD_OFFSET: u64 = 16;
+/// Offset of `EventHeaderTracepoint::metadata_ptr`, followed by `metadata_len`.
+const TRACEPOINT_METADATA_OFFSET: u64 = 24;
+
+/// A provider defined by `define_provider!`, as found in an ELF binary.
+#[derive(Clone, Debug)]
+pub struct ElfProvider {
+    symbol: String,
+    name: String,
+    options: String,
+    events: Vec<ElfEvent>,
+}
+
+impl ElfProvider {
+    /// Reads the file at `path` and returns the providers defined in it.
+    /// Equivalent to `ElfProvider::read_all(&fs::read(path)?)`.
+    pub fn read_all_from_file<P: AsRef<path::Path>>(path: P) -> io::Result<Vec<ElfProvider>> {
+        return Self::read_all(&fs::read(path)?);
+    }
+
+    /// Returns the providers defined in the specified ELF binary (executable or
+    /// shared object), sorted by symbol. Returns an `InvalidData` error if the
+    /// binary is not a valid ELF file or if a provider's data is invalid.
+    ///
+    /// Providers are located via the `ProviderDescriptor` objects that
+    /// `define_provider!` places in the `_eh_providers` linker section. Each
+    /// descriptor has the provider's symbol, name, and options, and the bounds of
+    /// the provider's `_eh_tracepoints_SYMBOL` section, which contains pointers to
+    /// the provider's events.
+    pub fn read_all(elf_bytes: &[u8]) -> io::Result<Vec<ElfProvider>> {
+        let image = ElfImage::new(elf_bytes)?;
+        let pointer_size = image.pointer_size();
+        let descriptor_size = pointer_size * DESCRIPTOR_SIZE;
+
+        let mut providers = Vec::new();
+        for section in image.sections() {
+            if section.name != PROVIDERS_SECTION || !section.is_loaded() {
+                continue;
+            }
+
+            let mut pos = section.addr;
+            while pos + descriptor_size <= section.addr + section.size {
+                providers.push(Self::read(&image, pos)?);
+                pos += descriptor_size;
+            }
+        }
+
+        providers.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        return Ok(providers);
+    }
+
+    /// Reads the `ProviderDescriptor` at `addr` and the provider's events.
+    fn read(image: &ElfImage, addr: u64) -> io::Result<Self> {
+        let pointer_size = image.pointer_size();
+        let field = |offset: u64| addr + pointer_size * offset;
+        let (symbol, name, options, events_start, events_stop) = match (
+            read_str(image, field(DESCRIPTOR_SYMBOL_OFFSET)),
+            read_str(image, field(DESCRIPTOR_NAME_OFFSET)),
+            read_str(image, field(DESCRIPTOR_OPTIONS_OFFSET)),
+            image.read_pointer(field(DESCRIPTOR_EVENTS_START_OFFSET)),
+            image.read_pointer(field(DESCRIPTOR_EVENTS_STOP_OFFSET)),
+        ) {
+            (Some(symbol), Some(name), Some(options), Some(start), Some(stop)) if start <= stop => {
+                (symbol, name, options, start, stop)
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "eventheader provider descriptor refers to invalid data",
+                ))
+            }
+        };
+
+        let mut event_addrs = Vec::new();
+        let mut pos = events_start;
+        while pos + pointer_size <= events_stop {
+            match image.read_pointer(pos) {
+                Some(0) | None => {}
+                Some(event_addr) => event_addrs.push(event_addr),
+            }
+            pos += pointer_size;
+        }
+
+        // Same as Provider::register: ignore duplicate pointers.
+        event_addrs.sort_unstable();
+        event_addrs.dedup();
+
+        let mut events = Vec::with_capacity(event_addrs.len());
+        for event_addr in event_addrs {
+            let mut event = ElfEvent::read(image, event_addr)?;
+            event.tracepoint_name = format!(
+                "{}_L{:x}K{:x}{}",
+                name,
+                event.header.level.as_int(),
+                event.keyword,
+                options
+            );
+            events.push(event);
+        }
+
+        events.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then(a.header.level.as_int().cmp(&b.header.level.as_int()))
+                .then(a.keyword.cmp(&b.keyword))
+        });
+
+        return Ok(Self {
+            symbol: String::from(symbol),
+            name: String::from(name),
+            options: String::from(options),
+            events,
+        });
+    }
+
+    /// Returns the provider's symbol, i.e. the `MY_PROVIDER` from
+    /// `define_provider!(MY_PROVIDER, "ProviderName")`.
+    pub fn symbol(&self) -> &str {
+        return &self.symbol;
+    }
+
+    /// Returns the provider's name, e.g. "MyCompany_MyComponent".
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    /// Returns the provider's options, e.g. "" or "Gmygroup".
+    pub fn options(&self) -> &str {
+        return &self.options;
+    }
+
+    /// Returns the provider's events, sorted by name, level, and keyword.
+    pub fn events(&self) -> &[ElfEvent] {
+        return &self.events;
+    }
+}
+
+/// An event defined by `write_event!`, as found in an ELF binary.
+#[derive(Clone, Debug)]
+pub struct ElfEvent {
+    tracepoint_name: String,
+    name: String,
+    header: EventHeader,
+    keyword: u64,
+    metadata: Vec<u8>,
+    big_endian: bool,
+}
+
+impl ElfEvent {
+    /// Reads an `EventHeaderTracepoint`, which has `#[repr(C)]` layout:
+    /// `state: TracepointState` (8 bytes), `header: EventHeader` (8 bytes),
+    /// `keyword: u64`, `metadata_ptr: *const u8`, `metadata_len: usize`.
+    fn read(image: &ElfImage, addr: u64) -> io::Result<Self> {
+        let byte_reader = image.byte_reader();
+        let header_bytes = image.read_bytes(addr + TRACEPOINT_HEADER_OFFSET, 8);
+        let keyword = image.read_u64(addr + TRACEPOINT_KEYWORD_OFFSET);
+        let metadata = read_bytes(image, addr + TRACEPOINT_METADATA_OFFSET, u16::MAX as u64);
+        return match (header_bytes, keyword, metadata) {
+            (Some(header_bytes), Some(keyword), Some(metadata)) => {
+                let header = EventHeader {
+                    flags: HeaderFlags::from_int(header_bytes[0]),
+                    version: header_bytes[1],
+                    id: byte_reader.read_u16(&header_bytes[2..]),
+                    tag: byte_reader.read_u16(&header_bytes[4..]),
+                    opcode: Opcode::from_int(header_bytes[6]),
+                    level: Level::from_int(header_bytes[7]),
+                };
+                let name_len = metadata
+                    .iter()
+                    .position(|&b| b == 0)
+                    .unwrap_or(metadata.len());
+                Ok(Self {
+                    tracepoint_name: String::new(), // Filled in by ElfProvider::read_all.
+                    name: String::from_utf8_lossy(&metadata[..name_len]).into_owned(),
+                    header,
+                    keyword,
+                    metadata: metadata.to_vec(),
+                    big_endian: byte_reader.source_big_endian(),
+                })
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "eventheader tracepoint pointer refers to invalid data",
+            )),
+        };
+    }
+
+    /// Returns the name of the tracepoint that this event would use, e.g.
+    /// "MyCompany_MyComponent_L5K1".
+    pub fn tracepoint_name(&self) -> &str {
+        return &self.tracepoint_name;
+    }
+
+    /// Returns the event's name, e.g. "MyEvent".
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    /// Returns the event's header (contains level, opcode, tag, id, version).
+    pub fn header(&self) -> EventHeader {
+        return self.header;
+    }
+
+    /// Returns the event's keyword (category bits).
+    pub fn keyword(&self) -> u64 {
+        return self.keyword;
+    }
+
+    /// Returns the event's metadata: nul-terminated event name followed by the
+    /// field declarations.
+    pub fn metadata(&self) -> &[u8] {
+        return &self.metadata;
+    }
+
+    /// Returns the bytes of an event with no field values, i.e. the event header
+    /// followed by a metadata extension block. This can be decoded with
+    /// [`EventHeaderEnumeratorContext::enumerate_with_name_and_data`] using
+    /// `tracepoint_name()`.
+    pub fn event_data(&self) -> Vec<u8> {
+        let u16_bytes = |value: u16| {
+            if self.big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+
+        let mut data = Vec::with_capacity(12 + self.metadata.len());
+        data.push(self.header.flags.as_int() | HeaderFlags::Extension.as_int());
+        data.push(self.header.version);
+        data.extend_from_slice(&u16_bytes(self.header.id));
+        data.extend_from_slice(&u16_bytes(self.header.tag));
+        data.push(self.header.opcode.as_int());
+        data.push(self.header.level.as_int());
+        data.extend_from_slice(&u16_bytes(self.metadata.len() as u16));
+        data.extend_from_slice(&u16_bytes(ExtensionKind::Metadata.as_int()));
+        data.extend_from_slice(&self.metadata);
+        return data;
+    }
+
+    /// Decodes the event's metadata using [`EventHeaderEnumeratorContext`] and
+    /// returns the field declarations in metadata order. Struct fields are followed
+    /// by their member fields.
+    pub fn fields(&self) -> Result<Vec<ElfField>, EventHeaderEnumeratorError> {
+        let event_data = self.event_data();
+        let mut context = EventHeaderEnumeratorContext::new();
+        let mut enumerator = context.enumerate_with_name_and_data(
+            &self.tracepoint_name,
+            &event_data,
+            EventHeaderEnumeratorContext::MOVE_NEXT_LIMIT_DEFAULT,
+        )?;
+
+        let mut fields = Vec::new();
+        while enumerator.move_next_metadata() {
+            let item = enumerator.item_info();
+            fields.push(ElfField {
+                name: String::from_utf8_lossy(item.name_bytes()).into_owned(),
+                metadata: item.metadata(),
+                is_array: enumerator.state() == EventHeaderEnumeratorState::ArrayBegin,
+            });
+        }
+
+        return match enumerator.last_error() {
+            EventHeaderEnumeratorError::Success => Ok(fields),
+            error => Err(error),
+        };
+    }
+}
+
+/// A field declaration from an event's metadata.
+#[derive(Clone, Debug)]
+pub struct ElfField {
+    name: String,
+    metadata: PerfItemMetadata,
+    is_array: bool,
+}
+
+impl ElfField {
+    /// Returns the field's name.
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    /// Returns the field's type information: encoding, format (or struct field
+    /// count), and tag.
+    pub fn metadata(&self) -> PerfItemMetadata {
+        return self.metadata;
+    }
+
+    /// Returns true if the field is an array.
+    pub fn is_array(&self) -> bool {
+        return self.is_array;
+    }
+
+    /// For a constant-length array, returns the number of elements.
+    /// For a variable-length array, returns 0. For a non-array, returns 1.
+    pub fn element_count(&self) -> u16 {
+        return self.metadata.element_count();
+    }
+}
+
+/// Reads a pointer followed by a pointer-sized length at the specified address and
+/// returns the referenced bytes, or None if the length is greater than `max_len` or
+/// the bytes are not in a loaded section.
+fn read_bytes<'a>(image: &ElfImage<'a>, addr: u64, max_len: u64) -> Option<&'a [u8]> {
+    let ptr = image.read_pointer(addr)?;
+    let len = image.read_pointer(addr + image.pointer_size())?;
+    return if len == 0 {
+        Some(&[])
+    } else if len <= max_len {
+        image.read_bytes(ptr, len)
+    } else {
+        None
+    };
+}
+
+fn read_str<'a>(image: &ElfImage<'a>, addr: u64) -> Option<&'a str> {
+    return read_bytes(image, addr, EVENTHEADER_NAME_MAX)
+        .and_then(|bytes| str::from_utf8(bytes).ok());
+}
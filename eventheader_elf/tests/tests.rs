@@ -0,0 +1,91 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+#![cfg(target_os = "linux")]
+#![allow(clippy::needless_return)]
+
+use std::env;
+
+use eventheader as eh;
+use eventheader_elf::ElfProvider;
+use eventheader_types::*;
+
+eh::define_provider!(ELF_TEST, "ElfTest_Provider", group_name("elftest"));
+eh::define_provider!(ELF_EMPTY, "ElfTest_Empty");
+
+#[test]
+fn read_current_exe() {
+    // Make sure the events are referenced so the linker keeps them.
+    eh::write_event!(
+        ELF_TEST,
+        "Event1",
+        level(Warning),
+        keyword(0x23),
+        id_version(5, 2),
+        opcode(Send),
+        tag(0x123),
+        u32("Field1", &1u32),
+        str8_json("Field2", "{}", tag(0x456)),
+        struct("Field3", { u8("Nested", &2u8) }),
+        u16_slice("Field4", &[1u16, 2][..]),
+    );
+    eh::write_event!(ELF_TEST, "Event2");
+    ELF_EMPTY.unregister();
+
+    let providers = ElfProvider::read_all_from_file(env::current_exe().unwrap()).unwrap();
+
+    let empty = providers
+        .iter()
+        .find(|p| p.symbol() == "ELF_EMPTY")
+        .unwrap();
+    assert_eq!(empty.name(), "ElfTest_Empty");
+    assert_eq!(empty.options(), "");
+    assert!(empty.events().is_empty());
+
+    let prov = providers.iter().find(|p| p.symbol() == "ELF_TEST").unwrap();
+    assert_eq!(prov.name(), "ElfTest_Provider");
+    assert_eq!(prov.options(), "Gelftest");
+    assert_eq!(prov.events().len(), 2);
+
+    let event1 = &prov.events()[0];
+    assert_eq!(event1.name(), "Event1");
+    assert_eq!(event1.tracepoint_name(), "ElfTest_Provider_L3K23Gelftest");
+    assert_eq!(event1.keyword(), 0x23);
+    let header = event1.header();
+    assert_eq!(header.level, Level::Warning);
+    assert_eq!(header.opcode, Opcode::Send);
+    assert_eq!(header.id, 5);
+    assert_eq!(header.version, 2);
+    assert_eq!(header.tag, 0x123);
+
+    let fields = event1.fields().unwrap();
+    let names: Vec<&str> = fields.iter().map(|f| f.name()).collect();
+    assert_eq!(names, ["Field1", "Field2", "Field3", "Nested", "Field4"]);
+
+    assert_eq!(fields[0].metadata().encoding(), FieldEncoding::Value32);
+    assert!(!fields[0].is_array());
+
+    assert_eq!(
+        fields[1].metadata().encoding(),
+        FieldEncoding::StringLength16Char8
+    );
+    assert_eq!(fields[1].metadata().format(), FieldFormat::StringJson);
+    assert_eq!(fields[1].metadata().field_tag(), 0x456);
+
+    assert_eq!(fields[2].metadata().encoding(), FieldEncoding::Struct);
+    assert_eq!(fields[2].metadata().struct_field_count(), 1);
+
+    assert_eq!(fields[4].metadata().encoding(), FieldEncoding::Value16);
+    assert!(fields[4].is_array());
+    assert_eq!(fields[4].element_count(), 0);
+
+    let event2 = &prov.events()[1];
+    assert_eq!(event2.name(), "Event2");
+    assert_eq!(event2.tracepoint_name(), "ElfTest_Provider_L5K1Gelftest");
+    assert!(event2.fields().unwrap().is_empty());
+}
+
+#[test]
+fn not_elf() {
+    assert!(ElfProvider::read_all(b"not an elf file").is_err());
+}
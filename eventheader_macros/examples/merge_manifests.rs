@@ -0,0 +1,114 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//! Tool that merges the per-crate event manifests written by the eventheader macros
+//! into a single JSON catalog.
+//!
+//! Build with `EVENTHEADER_MANIFEST_DIR` set to generate the per-crate manifests,
+//! then merge them:
+//!
+//! ```text
+//! EVENTHEADER_MANIFEST_DIR=/tmp/eh_manifest cargo build
+//! cargo run -p eventheader_macros --example merge_manifests -- /tmp/eh_manifest catalog.json
+//! ```
+//!
+//! The output is `{ "providers": [ ... ], "events": [ ... ] }`. Events refer to their
+//! provider by `crate` + `provider` (the provider symbol).
+//!
+//! Note that cargo does not track the environment variable, so crates that were
+//! already built will not be re-expanded. Use `cargo clean` before building if you
+//! need a complete manifest.
+
+#![allow(clippy::needless_return)]
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 || args.len() > 3 {
+        eprintln!("Usage: merge_manifests <manifest_dir> [output_file]");
+        process::exit(1);
+    }
+
+    let mut providers = Vec::new();
+    let mut events = Vec::new();
+    if let Err(e) = read_manifests(Path::new(&args[1]), &mut providers, &mut events) {
+        eprintln!("error: failed to read \"{}\": {}", args[1], e);
+        process::exit(1);
+    }
+
+    providers.sort();
+    providers.dedup();
+    events.sort();
+    events.dedup();
+
+    let mut output = String::new();
+    output.push_str("{\n  \"providers\": [");
+    push_entries(&mut output, &providers);
+    output.push_str("],\n  \"events\": [");
+    push_entries(&mut output, &events);
+    output.push_str("]\n}\n");
+
+    if args.len() == 3 {
+        if let Err(e) = fs::write(&args[2], output) {
+            eprintln!("error: failed to write \"{}\": {}", args[2], e);
+            process::exit(1);
+        }
+    } else {
+        print!("{}", output);
+    }
+}
+
+/// Reads `manifest_dir/<crate>/<kind>-<key>.json`.
+fn read_manifests(
+    manifest_dir: &Path,
+    providers: &mut Vec<String>,
+    events: &mut Vec<String>,
+) -> io::Result<()> {
+    for crate_dir in fs::read_dir(manifest_dir)? {
+        let crate_dir = crate_dir?;
+        if !crate_dir.file_type()?.is_dir() {
+            continue;
+        }
+
+        for entry in fs::read_dir(crate_dir.path())? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            if !file_name.ends_with(".json") {
+                continue;
+            }
+
+            let list = if file_name.starts_with("provider-") {
+                &mut *providers
+            } else if file_name.starts_with("event-") {
+                &mut *events
+            } else {
+                continue;
+            };
+
+            let json = fs::read_to_string(entry.path())?;
+            let json = json.trim();
+            if !json.is_empty() {
+                list.push(json.to_string());
+            }
+        }
+    }
+
+    return Ok(());
+}
+
+fn push_entries(output: &mut String, entries: &[String]) {
+    for (i, entry) in entries.iter().enumerate() {
+        output.push_str(if i == 0 { "\n    " } else { ",\n    " });
+        output.push_str(entry);
+    }
+
+    if !entries.is_empty() {
+        output.push_str("\n  ");
+    }
+}
@@ -204,10 +204,10 @@ impl ToMarkdown for FieldOption {
 
         s.push_str("` | ");
 
-        push_enum_value(s, "FieldEncoding", encoding_to_string(self.encoding));
+        push_enum_value(s, "FieldEncoding", self.encoding.as_str());
         if !matches!(self.format, FieldFormat::Default) {
             s.push_str(" + ");
-            push_enum_value(s, "FieldFormat", format_to_string(self.format));
+            push_enum_value(s, "FieldFormat", self.format.as_str());
         }
     }
 }
@@ -237,46 +237,3 @@ fn parse_int(str: &[u8], pos: usize) -> ParseResult {
 
     return ParseResult { end_pos, value };
 }
-
-fn encoding_to_string(value: FieldEncoding) -> &'static str {
-    return match value {
-        FieldEncoding::Invalid => "Invalid",
-        FieldEncoding::Struct => "Struct",
-        FieldEncoding::Value8 => "Value8",
-        FieldEncoding::Value16 => "Value16",
-        FieldEncoding::Value32 => "Value32",
-        FieldEncoding::Value64 => "Value64",
-        FieldEncoding::Value128 => "Value128",
-        FieldEncoding::ZStringChar8 => "ZStringChar8",
-        FieldEncoding::ZStringChar16 => "ZStringChar16",
-        FieldEncoding::ZStringChar32 => "ZStringChar32",
-        FieldEncoding::StringLength16Char8 => "StringLength16Char8",
-        FieldEncoding::StringLength16Char16 => "StringLength16Char16",
-        FieldEncoding::StringLength16Char32 => "StringLength16Char32",
-        FieldEncoding::ValueSize => "ValueSize",
-    };
-}
-
-fn format_to_string(value: FieldFormat) -> &'static str {
-    return match value {
-        FieldFormat::Default => "Default",
-        FieldFormat::UnsignedInt => "UnsignedInt",
-        FieldFormat::SignedInt => "SignedInt",
-        FieldFormat::HexInt => "HexInt",
-        FieldFormat::Errno => "Errno",
-        FieldFormat::Pid => "Pid",
-        FieldFormat::Time => "Time",
-        FieldFormat::Boolean => "Boolean",
-        FieldFormat::Float => "Float",
-        FieldFormat::HexBytes => "HexBytes",
-        FieldFormat::String8 => "String8",
-        FieldFormat::StringUtf => "StringUtf",
-        FieldFormat::StringUtfBom => "StringUtfBom",
-        FieldFormat::StringXml => "StringXml",
-        FieldFormat::StringJson => "StringJson",
-        FieldFormat::Uuid => "Uuid",
-        FieldFormat::Port => "Port",
-        FieldFormat::IPv4 => "IPv4",
-        FieldFormat::IPv6 => "IPv6",
-    };
-}
@@ -27,6 +27,7 @@ impl EnabledGenerator {
     }
 
     pub fn generate(&mut self, enabled: EnabledInfo) -> TokenStream {
+        let provider_symbol = enabled.provider_symbol.clone();
         let provider_symbol_string = enabled.provider_symbol.to_string();
         let provider_symbol_span = enabled.provider_symbol.span();
 
@@ -36,7 +37,7 @@ impl EnabledGenerator {
         const _EH_TAGn: u16 = TAGn;
         static _EH_TRACEPOINT = EventHeaderTracepoint::new(...);
         static _EH_TRACEPOINT_PTR = &_EH_TRACEPOINT;
-        if !_EH_TRACEPOINT.enabled() {
+        if !_EH_TRACEPOINT.enabled_unsuppressed(&PROVIDER) {
             0u32
         } else {
             enabled_tree...
@@ -106,8 +107,8 @@ impl EnabledGenerator {
                             .drain(),
                     )
                     .add_punct(",")
-                    // _EH_KEYWORD,
-                    .add_tokens(enabled.keyword)
+                    // { use MY_PROVIDER::*; KEYWORD },
+                    .add_provider_keywords_block(&provider_symbol, enabled.keyword)
                     .add_punct(",")
                     // &[metadata]
                     .add_punct("&")
@@ -139,11 +140,11 @@ impl EnabledGenerator {
             .add_punct("&")
             .add_ident(EH_TRACEPOINT_STATIC)
             .add_punct(";")
-            // _EH_TRACEPOINT.enabled()
+            // _EH_TRACEPOINT.enabled_unsuppressed(&PROVIDER)
             .add_ident(EH_TRACEPOINT_STATIC)
             .add_punct(".")
-            .add_ident(EH_TRACEPOINT_ENABLED)
-            .add_group_paren([]);
+            .add_ident(EH_TRACEPOINT_ENABLED_UNSUPPRESSED)
+            .add_group_paren(self.tree1.add_punct("&").add_token(provider_symbol).drain());
 
         // Wrap the event in "{...}":
         let enabled_tokens = TokenStream::from(TokenTree::Group(Group::new(
@@ -39,6 +39,25 @@ impl FieldEncoding {
             other => EnumToken::U8(other as u8),
         }
     }
+
+    pub const fn as_str(self) -> &'static str {
+        return match self {
+            FieldEncoding::Invalid => "Invalid",
+            FieldEncoding::Struct => "Struct",
+            FieldEncoding::Value8 => "Value8",
+            FieldEncoding::Value16 => "Value16",
+            FieldEncoding::Value32 => "Value32",
+            FieldEncoding::Value64 => "Value64",
+            FieldEncoding::Value128 => "Value128",
+            FieldEncoding::ZStringChar8 => "ZStringChar8",
+            FieldEncoding::ZStringChar16 => "ZStringChar16",
+            FieldEncoding::ZStringChar32 => "ZStringChar32",
+            FieldEncoding::StringLength16Char8 => "StringLength16Char8",
+            FieldEncoding::StringLength16Char16 => "StringLength16Char16",
+            FieldEncoding::StringLength16Char32 => "StringLength16Char32",
+            FieldEncoding::ValueSize => "ValueSize",
+        };
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -68,4 +87,28 @@ pub enum FieldFormat {
 
 impl FieldFormat {
     pub const ValueMask: u8 = 0x7F;
+
+    pub const fn as_str(self) -> &'static str {
+        return match self {
+            FieldFormat::Default => "Default",
+            FieldFormat::UnsignedInt => "UnsignedInt",
+            FieldFormat::SignedInt => "SignedInt",
+            FieldFormat::HexInt => "HexInt",
+            FieldFormat::Errno => "Errno",
+            FieldFormat::Pid => "Pid",
+            FieldFormat::Time => "Time",
+            FieldFormat::Boolean => "Boolean",
+            FieldFormat::Float => "Float",
+            FieldFormat::HexBytes => "HexBytes",
+            FieldFormat::String8 => "String8",
+            FieldFormat::StringUtf => "StringUtf",
+            FieldFormat::StringUtfBom => "StringUtfBom",
+            FieldFormat::StringXml => "StringXml",
+            FieldFormat::StringJson => "StringJson",
+            FieldFormat::Uuid => "Uuid",
+            FieldFormat::Port => "Port",
+            FieldFormat::IPv4 => "IPv4",
+            FieldFormat::IPv6 => "IPv6",
+        };
+    }
 }
@@ -11,7 +11,7 @@ use crate::ident_builder::IdentBuilder;
 use crate::strings::*;
 use crate::tree::Tree;
 
-use crate::event_info::EventInfo;
+use crate::event_info::{EventInfo, ExtensionInfo};
 
 pub struct EventGenerator {
     /// tokens for declaring the _EH_TAGn constants.
@@ -24,6 +24,8 @@ pub struct EventGenerator {
     func_call_tree: Tree,
     /// tokens in the _eh_lengths = [...] array initializer.
     lengths_init_tree: Tree,
+    /// tokens in the _eh_extensions = [...] array initializer.
+    extensions_init_tree: Tree,
     /// tokens in the EventDataDescriptor &[...] array initializer.
     data_desc_init_tree: Tree,
     /// Code that runs if the provider is enabled.
@@ -52,6 +54,7 @@ impl EventGenerator {
             func_args_tree: Tree::new(span),
             func_call_tree: Tree::new(span),
             lengths_init_tree: Tree::new(span),
+            extensions_init_tree: Tree::new(span),
             data_desc_init_tree: Tree::new(span),
             enabled_tree: Tree::new(span),
             tree1: Tree::new(span),
@@ -65,8 +68,10 @@ impl EventGenerator {
     }
 
     pub fn generate(&mut self, mut event: EventInfo) -> TokenStream {
+        let provider_symbol = event.provider_symbol.clone();
         let provider_symbol_string = event.provider_symbol.to_string();
         let provider_symbol_span = event.provider_symbol.span();
+        let throttled = event.is_throttled();
 
         self.field_count = 0;
         self.lengths_count = 0;
@@ -137,6 +142,13 @@ impl EventGenerator {
             .add_borrowed_option_from_tokens(&mut self.tree1, event.related_id.tokens)
             .pop_span();
 
+        // Add the per-extension stuff:
+
+        let extension_count = event.extensions.len();
+        for (n, extension) in event.extensions.drain(..).enumerate() {
+            self.add_extension(n, n + 1 != extension_count, extension);
+        }
+
         // Add the per-field stuff:
 
         for field in event.fields.drain(..) {
@@ -152,6 +164,27 @@ impl EventGenerator {
         _eh_write(func_call_tree)
         */
 
+        // If the event has extensions:
+        //   let _eh_extensions = [extensions_init_tree...];
+        //   tp.write_eventheader_with_extensions(aid, rid, N, &mut [data_desc_init_tree...]);
+        if extension_count != 0 {
+            self.tree3
+                // let _eh_extensions: [EventHeaderExtension; N] = [...];
+                .add_ident("let")
+                .add_ident(EH_EXTENSIONS_VAR)
+                .add_punct(":")
+                .add_group_square(
+                    self.tree2
+                        .add_path(EVENTHEADEREXTENSION_PATH)
+                        .add_punct(";")
+                        .add_literal(Literal::usize_unsuffixed(extension_count))
+                        .drain(),
+                )
+                .add_punct("=")
+                .add_group_square(self.extensions_init_tree.drain())
+                .add_punct(";");
+        }
+
         self.enabled_tree
             // #[link_section = "_eh_tracepoints_MY_PROVIDER"]
             .add_punct("#")
@@ -214,21 +247,34 @@ impl EventGenerator {
                     .add_punct("=")
                     .add_group_square(self.lengths_init_tree.drain())
                     .add_punct(";")
+                    // let _eh_extensions: [EventHeaderExtension; N] = [...];
+                    .add_tokens(self.tree3.drain())
                     // _eh_tracepoint.write_eventheader(aid, rid, &mut [data...]) as u32
+                    // or write_eventheader_with_extensions(aid, rid, N, &mut [data...]) as u32
                     .add_ident(EH_TRACEPOINT_VAR)
                     .add_punct(".")
-                    .add_ident(EH_TRACEPOINT_WRITE_EVENTHEADER)
-                    .add_group_paren(
+                    .add_ident(if extension_count == 0 {
+                        EH_TRACEPOINT_WRITE_EVENTHEADER
+                    } else {
+                        EH_TRACEPOINT_WRITE_EVENTHEADER_WITH_EXTENSIONS
+                    })
+                    .add_group_paren({
                         self.tree2
                             .add_ident(EH_ACTIVITY_ID_VAR)
                             .add_punct(",")
                             .add_ident(EH_RELATED_ID_VAR)
-                            .add_punct(",")
+                            .add_punct(",");
+                        if extension_count != 0 {
+                            self.tree2
+                                .add_literal(Literal::usize_unsuffixed(extension_count))
+                                .add_punct(",");
+                        }
+                        self.tree2
                             .add_punct("&")
                             .add_ident("mut")
                             .add_group_square(self.data_desc_init_tree.drain())
-                            .drain(),
-                    )
+                            .drain()
+                    })
                     .add_ident("as")
                     .add_path(U32_PATH)
                     .drain(),
@@ -237,13 +283,17 @@ impl EventGenerator {
             .add_ident(EH_WRITE_FUNC)
             .add_group_paren(self.func_call_tree.drain());
 
+        if throttled {
+            self.wrap_enabled_in_throttle(event.rate_limit, event.sample);
+        }
+
         // put it all together:
         /*
         const _EH_KEYWORD = keywords...;
         const _EH_TAGn: u16 = TAGn;
         static _EH_TRACEPOINT = EventHeaderTracepoint::new(...);
         static mut _EH_TRACEPOINT_PTR = &_EH_TRACEPOINT;
-        if !_EH_TRACEPOINT.enabled() {
+        if !_EH_TRACEPOINT.enabled_unsuppressed(&PROVIDER) {
             9u32 // EBADF
         } else {
             enabled_tree...
@@ -259,7 +309,13 @@ impl EventGenerator {
             let keyword = event.keywords.pop().unwrap();
             event_tree
                 .push_span(keyword.context)
-                .add_const_from_tokens(EH_KEYWORD_CONST, U64_PATH, keyword.tokens)
+                .add_const_from_tokens(
+                    EH_KEYWORD_CONST,
+                    U64_PATH,
+                    self.tree1
+                        .add_provider_keywords_block(&event.provider_symbol, keyword.tokens)
+                        .drain(),
+                )
                 .pop_span();
         } else {
             // More-complex output needed in other cases.
@@ -287,7 +343,13 @@ impl EventGenerator {
                 // event_tree += "const _EH_KEYWORDn: u64 = KEYWORDSn;"
                 event_tree
                     .push_span(keyword.context)
-                    .add_const_from_tokens(keyword_n.set_suffix(n), U64_PATH, keyword.tokens)
+                    .add_const_from_tokens(
+                        keyword_n.set_suffix(n),
+                        U64_PATH,
+                        self.tree2
+                            .add_provider_keywords_block(&event.provider_symbol, keyword.tokens)
+                            .drain(),
+                    )
                     .pop_span();
 
                 // tree1 += "| _EH_KEYWORDn"
@@ -367,13 +429,13 @@ impl EventGenerator {
                     .drain(),
             )
             .add_punct(";")
-            // if !_EH_TRACEPOINT.enabled() { 9u32 } // EBADF
+            // if !_EH_TRACEPOINT.enabled_unsuppressed(&PROVIDER) { 9u32 } // EBADF
             .add_ident("if")
             .add_punct("!")
             .add_ident(EH_TRACEPOINT_STATIC)
             .add_punct(".")
-            .add_ident(EH_TRACEPOINT_ENABLED)
-            .add_group_paren([])
+            .add_ident(EH_TRACEPOINT_ENABLED_UNSUPPRESSED)
+            .add_group_paren(self.tree1.add_punct("&").add_token(provider_symbol).drain())
             .add_group_curly(self.tree1.add_literal(Literal::u32_suffixed(9)).drain()) // 9 = EBADF
             // else { enabled_tree... }
             .add_ident("else")
@@ -392,6 +454,143 @@ impl EventGenerator {
         return event_tokens;
     }
 
+    /// Wraps the enabled_tree in a check of a per-call-site EventThrottle:
+    /*
+    static _EH_THROTTLE: EventThrottle = EventThrottle::new(RATE, SAMPLE);
+    match _EH_THROTTLE.check() {
+        None => 11u32, // EAGAIN
+        Some(_eh_suppressed) => { enabled_tree... }
+    }
+    */
+    fn wrap_enabled_in_throttle(&mut self, rate_limit: Expression, sample: Expression) {
+        let rate_limit_tokens = if rate_limit.is_empty() {
+            self.tree2
+                .add_literal(Literal::u32_suffixed(0))
+                .drain()
+                .collect()
+        } else {
+            rate_limit.tokens
+        };
+        let sample_tokens = if sample.is_empty() {
+            self.tree2
+                .add_literal(Literal::u32_suffixed(0))
+                .drain()
+                .collect()
+        } else {
+            sample.tokens
+        };
+
+        self.tree3
+            // static _EH_THROTTLE: EventThrottle = EventThrottle::new(RATE, SAMPLE);
+            .add_ident("static")
+            .add_ident(EH_THROTTLE_STATIC)
+            .add_punct(":")
+            .add_path(EVENTTHROTTLE_PATH)
+            .add_punct("=")
+            .add_path_call(
+                EVENTTHROTTLE_NEW_PATH,
+                self.tree1
+                    .push_span(rate_limit.context)
+                    .add_tokens(rate_limit_tokens)
+                    .pop_span()
+                    .add_punct(",")
+                    .push_span(sample.context)
+                    .add_tokens(sample_tokens)
+                    .pop_span()
+                    .drain(),
+            )
+            .add_punct(";")
+            // match _EH_THROTTLE.check() { ... }
+            .add_ident("match")
+            .add_ident(EH_THROTTLE_STATIC)
+            .add_punct(".")
+            .add_ident(EH_THROTTLE_CHECK)
+            .add_group_paren([])
+            .add_group_curly(
+                self.tree1
+                    // None => 11u32, // Dropped: libc::EAGAIN.
+                    .add_path(OPTION_NONE_PATH)
+                    .add_punct("=>")
+                    .add_literal(Literal::u32_suffixed(11))
+                    .add_punct(",")
+                    // Some(_eh_suppressed) => { enabled_tree... }
+                    .add_path(OPTION_SOME_PATH)
+                    .add_group_paren(self.tree2.add_ident(EH_SUPPRESSED_VAR).drain())
+                    .add_punct("=>")
+                    .add_group_curly(self.enabled_tree.drain())
+                    .drain(),
+            );
+
+        self.enabled_tree.add_tokens(self.tree3.drain());
+    }
+
+    fn add_extension(&mut self, n: usize, chain: bool, extension: ExtensionInfo) {
+        let mut kind_n = IdentBuilder::new(EH_EXTENSION_KIND_CONST);
+        let mut extension_n = IdentBuilder::new(EH_EXTENSION_VAR);
+        kind_n.set_suffix(n);
+        extension_n.set_suffix(n);
+
+        // const _EH_EXTENSION_KINDn: ExtensionKind = KIND;
+        self.tags_tree
+            .push_span(extension.kind.context)
+            .add_const_from_tokens(kind_n.current(), EXTENSIONKIND_PATH, extension.kind.tokens)
+            .pop_span();
+
+        // Prototype: , _eh_extensionN: &[u8]
+        self.func_args_tree
+            .add_punct(",")
+            .add_ident(extension_n.current())
+            .add_punct(":")
+            .add_punct("&")
+            .add_group_square(self.tree1.add_path(U8_PATH).drain());
+
+        // Call site: , AsRef::<[u8]>::as_ref(data_tokens...)
+        self.func_call_tree
+            .add_punct(",")
+            .push_span(extension.data.context) // Use as_ref(...) as a target for error messages.
+            .add_path(ASREF_PATH)
+            .add_punct("::")
+            .add_punct("<")
+            .add_group_square(self.tree1.add_path(U8_PATH).drain())
+            .add_punct(">")
+            .add_punct("::")
+            .add_ident("as_ref")
+            .add_group_paren(extension.data.tokens)
+            .pop_span();
+
+        // extension_header(_EH_EXTENSION_KINDn, _eh_extensionN, chain),
+        self.extensions_init_tree
+            .add_path_call(
+                EXTENSION_HEADER_PATH,
+                self.tree1
+                    .add_ident(kind_n.current())
+                    .add_punct(",")
+                    .add_ident(extension_n.current())
+                    .add_punct(",")
+                    .add_ident(if chain { "true" } else { "false" })
+                    .drain(),
+            )
+            .add_punct(",");
+
+        // EventDataDescriptor::from_value(&_eh_extensions[n]),
+        // EventDataDescriptor::from_slice(_eh_extensionN),
+        self.data_desc_init_tree
+            .add_path_call(
+                DATADESC_FROM_VALUE_PATH,
+                self.tree1
+                    .add_punct("&")
+                    .add_ident(EH_EXTENSIONS_VAR)
+                    .add_group_square(self.tree2.add_literal(Literal::usize_unsuffixed(n)).drain())
+                    .drain(),
+            )
+            .add_punct(",")
+            .add_path_call(
+                DATADESC_FROM_SLICE_PATH,
+                self.tree1.add_ident(extension_n.current()).drain(),
+            )
+            .add_punct(",");
+    }
+
     fn add_field(&mut self, field: FieldInfo) {
         // Metadata
 
@@ -16,6 +16,12 @@ use crate::tree::Tree;
 const METADATA_BYTES_MAX: u16 = u16::MAX; // EventHeader limit
 const STRUCT_FIELDS_MAX: u8 = 127; // EventHeader limit
 
+/// A provider-defined extension block, `extension(KIND, DATA)`.
+pub struct ExtensionInfo {
+    pub kind: Expression,
+    pub data: Expression,
+}
+
 pub struct EventInfo {
     pub provider_symbol: Ident,
     pub name: String,
@@ -27,6 +33,9 @@ pub struct EventInfo {
     pub tag: Expression,
     pub activity_id: Expression,
     pub related_id: Expression,
+    pub rate_limit: Expression,
+    pub sample: Expression,
+    pub extensions: Vec<ExtensionInfo>,
     pub fields: Vec<FieldInfo>,
     pub debug: bool,
 
@@ -50,6 +59,9 @@ impl EventInfo {
             tag: Expression::empty(arg_span),
             activity_id: Expression::empty(arg_span),
             related_id: Expression::empty(arg_span),
+            rate_limit: Expression::empty(arg_span),
+            sample: Expression::empty(arg_span),
+            extensions: Vec::new(),
             fields: Vec::new(),
             debug: false,
             metadata_bytes_used: 1,
@@ -97,6 +109,29 @@ impl EventInfo {
 
         event.parse_event_options(&mut root_parser, false, &mut scratch_tree);
 
+        // If throttled, add a field for the number of suppressed events.
+
+        if event.is_throttled() {
+            let field_option_index = FIELD_OPTIONS
+                .binary_search_by(|o| o.option_name.cmp("u32"))
+                .unwrap();
+            let field = FieldInfo {
+                type_name_span: arg_span,
+                option: &FIELD_OPTIONS[field_option_index],
+                name: String::from(EH_SUPPRESSED_FIELD),
+                value_tokens: scratch_tree
+                    .add_punct("&")
+                    .add_ident(EH_SUPPRESSED_VAR)
+                    .drain()
+                    .collect(),
+                encoding_tokens: TokenStream::new(),
+                format_or_field_count_expr: Expression::empty(arg_span),
+                format_or_field_count_int: FIELD_OPTIONS[field_option_index].format as u8,
+                tag: Expression::empty(arg_span),
+            };
+            event.push_field(root_parser.errors(), field);
+        }
+
         // Set defaults for optional values
 
         // id default: 0
@@ -159,6 +194,11 @@ impl EventInfo {
         };
     }
 
+    /// Returns true if the event uses rate_limit or sample.
+    pub fn is_throttled(&self) -> bool {
+        return !self.rate_limit.is_empty() || !self.sample.is_empty();
+    }
+
     /// Parses options. Returns the number of logical fields added to the event.
     fn parse_event_options(
         &mut self,
@@ -460,6 +500,60 @@ impl EventInfo {
                             option_parser.next_tokens(RequiredLast, "expected Related Id variable"),
                         );
                     }
+                    "extension" if !in_struct => {
+                        let kind = option_parser.next_tokens(
+                            RequiredNotLast,
+                            "expected ExtensionKind value, e.g. ExtensionKind::from_int(0x100)",
+                        );
+                        let data = option_parser.next_tokens(
+                            RequiredLast,
+                            "expected extension data, e.g. &trace_flags[..]",
+                        );
+                        self.extensions.push(ExtensionInfo {
+                            kind: Expression::new(option_ident.span(), kind),
+                            data: Expression::new(option_ident.span(), data),
+                        });
+                    }
+                    "rate_limit" if !in_struct => {
+                        if !self.rate_limit.is_empty() {
+                            errors.add(option_ident.span(), "rate_limit already set");
+                        }
+                        let tokens = option_parser.next_tokens(
+                            RequiredLast,
+                            "expected rate limit, e.g. rate_limit(100 per second)",
+                        );
+                        self.rate_limit = Expression::new(
+                            option_ident.span(),
+                            split_throttle_tokens(
+                                option_parser.errors(),
+                                option_ident.span(),
+                                tokens,
+                                &[],
+                                &["per", "second"],
+                                "expected rate limit, e.g. rate_limit(100 per second)",
+                            ),
+                        );
+                    }
+                    "sample" if !in_struct => {
+                        if !self.sample.is_empty() {
+                            errors.add(option_ident.span(), "sample already set");
+                        }
+                        let tokens = option_parser.next_tokens(
+                            RequiredLast,
+                            "expected sample rate, e.g. sample(1 in 10)",
+                        );
+                        self.sample = Expression::new(
+                            option_ident.span(),
+                            split_throttle_tokens(
+                                option_parser.errors(),
+                                option_ident.span(),
+                                tokens,
+                                &["1", "in"],
+                                &[],
+                                "expected sample rate, e.g. sample(1 in 10)",
+                            ),
+                        );
+                    }
                     _ => {
                         errors.add(option_ident.span(), "unrecognized option");
                         continue;
@@ -476,7 +570,9 @@ impl EventInfo {
             + 1 // name nul-termination
             + if !field.tag.is_empty() {
                 4 // encoding + format + tag
-            } else if field.format_or_field_count_int != 0 {
+            } else if field.format_or_field_count_int != 0
+                || !field.format_or_field_count_expr.is_empty()
+            {
                 2 // encoding + format
             } else {
                 1 // encoding
@@ -499,6 +595,46 @@ impl EventInfo {
     }
 }
 
+/// For `rate_limit(N per second)` and `sample(1 in N)`: verifies that the tokens
+/// start with `prefix` and end with `suffix`, returning the `N` tokens in between.
+/// Returns empty tokens (and adds an error) if the tokens do not match.
+fn split_throttle_tokens(
+    errors: &mut Errors,
+    option_name_span: Span,
+    tokens: TokenStream,
+    prefix: &[&str],
+    suffix: &[&str],
+    error_message: &str,
+) -> TokenStream {
+    let trees: Vec<TokenTree> = tokens.into_iter().collect();
+    let matches = |trees: &[TokenTree], words: &[&str]| {
+        trees.len() == words.len()
+            && trees
+                .iter()
+                .zip(words.iter())
+                .all(|(tree, word)| tree.to_string() == *word)
+    };
+
+    if trees.len() > prefix.len() + suffix.len()
+        && matches(&trees[..prefix.len()], prefix)
+        && matches(&trees[trees.len() - suffix.len()..], suffix)
+    {
+        return trees[prefix.len()..trees.len() - suffix.len()]
+            .iter()
+            .cloned()
+            .collect();
+    }
+
+    errors.add(
+        trees
+            .first()
+            .map(|tree| tree.span())
+            .unwrap_or(option_name_span),
+        error_message,
+    );
+    return TokenStream::new();
+}
+
 fn expected_enum_message(
     enum_name: &str,
     suggested_string_value: &str,
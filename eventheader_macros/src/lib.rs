@@ -7,6 +7,13 @@
 //!
 //! # Changelog
 //!
+//! ## v0.6.0 (TBD)
+//! - Optional export of a JSON event manifest at build time, enabled by
+//!   setting the `EVENTHEADER_MANIFEST_DIR` environment variable.
+//! - `write_event!` supports `rate_limit(N per second)` and `sample(1 in N)`.
+//! - `write_event!` validates event metadata at compile time (struct nesting,
+//!   struct field counts, encodings, and encoding/format combinations).
+//!
 //! ## v0.4.1 (2025-02-28)
 //! - Handle invisible delimiters to support use of eventheader macros from
 //!   within other macros.
@@ -34,7 +41,10 @@ pub fn define_provider(arg_tokens: TokenStream) -> TokenStream {
     let call_site = Span::call_site();
     return match ProviderInfo::try_from_tokens(call_site, arg_tokens) {
         Err(error_tokens) => error_tokens,
-        Ok(prov) => ProviderGenerator::new(call_site).generate(prov),
+        Ok(prov) => match manifest::write_provider(call_site, &prov) {
+            Err(error_tokens) => error_tokens,
+            Ok(()) => ProviderGenerator::new(call_site).generate(prov),
+        },
     };
 }
 
@@ -52,7 +62,12 @@ pub fn write_event(arg_tokens: TokenStream) -> TokenStream {
     let call_site = Span::call_site();
     return match EventInfo::try_from_tokens(call_site, arg_tokens) {
         Err(error_tokens) => error_tokens,
-        Ok(prov) => EventGenerator::new(call_site).generate(prov),
+        Ok(event) => match metadata_validator::validate_event(&event)
+            .and_then(|()| manifest::write_event(call_site, &event))
+        {
+            Err(error_tokens) => error_tokens,
+            Ok(()) => EventGenerator::new(call_site).generate(event),
+        },
     };
 }
 
@@ -67,6 +82,8 @@ mod field_info;
 mod field_option;
 mod field_options;
 mod ident_builder;
+mod manifest;
+mod metadata_validator;
 mod parser;
 mod provider_generator;
 mod provider_info;
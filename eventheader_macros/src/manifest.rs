@@ -0,0 +1,404 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//! Optional build-time export of event schemas.
+//!
+//! If the `EVENTHEADER_MANIFEST_DIR` environment variable is set when the macros
+//! are expanded, each `define_provider!` and `write_event!` writes a one-line JSON
+//! entry describing the provider or event to
+//! `$EVENTHEADER_MANIFEST_DIR/<crate name>/<kind>-<key>.json`. The key is the
+//! provider symbol (for providers) or the provider symbol and event name (for
+//! events), so re-expanding a changed macro invocation overwrites its previous entry.
+//! Entries for events that were renamed or removed are not deleted. The
+//! `merge_manifests` example combines the entries from all crates into a single
+//! catalog.
+
+use proc_macro::*;
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::errors::Errors;
+use crate::event_info::EventInfo;
+use crate::field_option::FieldStrategy;
+use crate::provider_info::ProviderInfo;
+use crate::strings::*;
+
+/// If manifest export is enabled, writes an entry for the provider.
+pub fn write_provider(arg_span: Span, prov: &ProviderInfo) -> Result<(), TokenStream> {
+    return match ManifestDir::from_env() {
+        None => Ok(()),
+        Some(dir) => {
+            let mut json = String::new();
+            json.push_str("{\"kind\":\"provider\",\"crate\":");
+            push_json_string(&mut json, &dir.crate_name);
+            json.push_str(",\"symbol\":");
+            push_json_string(&mut json, &prov.symbol.to_string());
+            json.push_str(",\"name\":");
+            push_json_string(&mut json, &prov.name);
+            json.push_str(",\"group_name\":");
+            push_json_string(&mut json, &prov.group_name);
+            json.push_str(",\"keywords\":{");
+            for (n, (name, value)) in prov.keywords.iter().enumerate() {
+                if n != 0 {
+                    json.push(',');
+                }
+                push_json_string(&mut json, &name.to_string());
+                write!(json, ":{}", value).unwrap();
+            }
+            json.push_str("}}\n");
+
+            let symbol = prov.symbol.to_string();
+            dir.write_entry("provider", &[&symbol], &json)
+                .map_err(|message| {
+                    let mut errors = Errors::new();
+                    errors.add(arg_span, &message);
+                    errors.into_items()
+                })
+        }
+    };
+}
+
+/// If manifest export is enabled, writes an entry for the event.
+pub fn write_event(arg_span: Span, event: &EventInfo) -> Result<(), TokenStream> {
+    return match ManifestDir::from_env() {
+        None => Ok(()),
+        Some(dir) => {
+            let mut json = String::new();
+            json.push_str("{\"kind\":\"event\",\"crate\":");
+            push_json_string(&mut json, &dir.crate_name);
+            json.push_str(",\"provider\":");
+            push_json_string(&mut json, &event.provider_symbol.to_string());
+            json.push_str(",\"name\":");
+            push_json_string(&mut json, &event.name);
+            json.push_str(",\"id\":");
+            push_json_value(&mut json, &event.id_tokens, "");
+            json.push_str(",\"version\":");
+            push_json_value(&mut json, &event.version_tokens, "");
+            json.push_str(",\"level\":");
+            push_json_value(&mut json, &event.level.tokens, "Level");
+            json.push_str(",\"keyword\":");
+            push_json_keyword(&mut json, event);
+            json.push_str(",\"opcode\":");
+            push_json_value(&mut json, &event.opcode_tokens, "Opcode");
+            json.push_str(",\"tag\":");
+            push_json_value(&mut json, &event.tag.tokens, "");
+            json.push_str(",\"fields\":[");
+
+            let mut first = true;
+            for field in &event.fields {
+                if !field.option.strategy.has_metadata() {
+                    continue;
+                }
+
+                if !first {
+                    json.push(',');
+                }
+                first = false;
+
+                json.push_str("{\"name\":");
+                push_json_string(&mut json, &field.name);
+
+                json.push_str(",\"encoding\":");
+                if field.encoding_tokens.is_empty() {
+                    push_json_string(&mut json, field.option.encoding.as_str());
+                } else {
+                    push_json_value(&mut json, &field.encoding_tokens, "FieldEncoding");
+                }
+
+                if matches!(
+                    field.option.strategy,
+                    FieldStrategy::Struct
+                        | FieldStrategy::RawStruct
+                        | FieldStrategy::RawStructSlice
+                ) {
+                    json.push_str(",\"field_count\":");
+                    if field.format_or_field_count_expr.is_empty() {
+                        write!(json, "{}", field.format_or_field_count_int).unwrap();
+                    } else {
+                        push_json_value(
+                            &mut json,
+                            &field.format_or_field_count_expr.tokens,
+                            "FieldFormat",
+                        );
+                    }
+                } else {
+                    json.push_str(",\"format\":");
+                    if field.format_or_field_count_expr.is_empty() {
+                        push_json_string(&mut json, field.option.format.as_str());
+                    } else {
+                        push_json_value(
+                            &mut json,
+                            &field.format_or_field_count_expr.tokens,
+                            "FieldFormat",
+                        );
+                    }
+                }
+
+                json.push_str(",\"tag\":");
+                if field.tag.is_empty() {
+                    json.push('0');
+                } else {
+                    push_json_value(&mut json, &field.tag.tokens, "");
+                }
+
+                json.push_str(",\"array\":");
+                json.push_str(if field.option.strategy.is_slice() {
+                    "true"
+                } else {
+                    "false"
+                });
+                json.push('}');
+            }
+
+            json.push_str("]}\n");
+
+            let provider_symbol = event.provider_symbol.to_string();
+            dir.write_entry("event", &[&provider_symbol, &event.name], &json)
+                .map_err(|message| {
+                    let mut errors = Errors::new();
+                    errors.add(arg_span, &message);
+                    errors.into_expression()
+                })
+        }
+    };
+}
+
+struct ManifestDir {
+    path: PathBuf,
+    crate_name: String,
+}
+
+impl ManifestDir {
+    /// Returns None if manifest export is not enabled.
+    fn from_env() -> Option<Self> {
+        let dir = env::var_os(MANIFEST_DIR_ENV)?;
+        if dir.is_empty() {
+            return None;
+        }
+
+        // Cargo sets CARGO_CRATE_NAME when invoking rustc.
+        let crate_name = env::var("CARGO_CRATE_NAME")
+            .or_else(|_| env::var("CARGO_PKG_NAME"))
+            .unwrap_or_else(|_| String::from("unknown"));
+
+        let mut path = PathBuf::from(dir);
+        path.push(&crate_name);
+        return Some(Self { path, crate_name });
+    }
+
+    /// Writes (or overwrites) the entry for the specified key.
+    fn write_entry(&self, kind: &str, key: &[&str], json: &str) -> Result<(), String> {
+        let mut file_path = self.path.clone();
+        file_path.push(entry_file_name(kind, key));
+
+        return fs::create_dir_all(&self.path)
+            .and_then(|_| fs::write(&file_path, json))
+            .map_err(|e| {
+                format!(
+                    "{}: failed to write \"{}\": {}",
+                    MANIFEST_DIR_ENV,
+                    file_path.display(),
+                    e
+                )
+            });
+    }
+}
+
+/// Returns `<kind>-<key0>-<key1>....json`. Bytes other than ASCII letters, digits,
+/// and `_` are written as `%XX` so that the name is valid on all file systems and
+/// different keys never map to the same file.
+fn entry_file_name(kind: &str, key: &[&str]) -> String {
+    let mut file_name = String::from(kind);
+    for part in key {
+        file_name.push('-');
+        for b in part.bytes() {
+            if b.is_ascii_alphanumeric() || b == b'_' {
+                file_name.push(b as char);
+            } else {
+                write!(file_name, "%{:02X}", b).unwrap();
+            }
+        }
+    }
+    file_name.push_str(".json");
+    return file_name;
+}
+
+/// Keywords are OR'ed together. Writes a number if all keywords are integer
+/// literals, otherwise writes a string with the keyword expressions.
+fn push_json_keyword(json: &mut String, event: &EventInfo) {
+    let mut value = 0u64;
+    let mut text = String::new();
+    let mut all_numeric = true;
+    for keyword in &event.keywords {
+        let keyword_text = expression_text(&keyword.tokens, "");
+        match parse_int(&keyword_text) {
+            Some(keyword_value) => value |= keyword_value,
+            None => all_numeric = false,
+        }
+
+        if !text.is_empty() {
+            text.push_str(" | ");
+        }
+        text.push_str(&keyword_text);
+    }
+
+    if all_numeric {
+        write!(json, "{}", value).unwrap();
+    } else {
+        push_json_string(json, &text);
+    }
+}
+
+/// Writes the value as a JSON number if it is an integer literal, otherwise as a
+/// JSON string containing the expression, e.g. "Verbose" or "MY_CONSTANT".
+fn push_json_value(json: &mut String, tokens: &TokenStream, enum_name: &str) {
+    let text = expression_text(tokens, enum_name);
+    match parse_int(&text) {
+        Some(value) => write!(json, "{}", value).unwrap(),
+        None => push_json_string(json, &text),
+    }
+}
+
+/// Returns the expression as a string, removing the `::eventheader::Enum::` prefix
+/// and `from_int(...)` wrapper added by `filter_enum_tokens`.
+pub fn expression_text(tokens: &TokenStream, enum_name: &str) -> String {
+    let mut text = tokens.to_string();
+    text.retain(|ch| !ch.is_whitespace());
+
+    if !enum_name.is_empty() {
+        let prefix = format!("eventheader::{}::", enum_name);
+        let unprefixed = text.strip_prefix("::").unwrap_or(&text);
+        if let Some(value) = unprefixed.strip_prefix(&prefix) {
+            text = match value
+                .strip_prefix("from_int(")
+                .and_then(|v| v.strip_suffix(')'))
+            {
+                Some(inner) => String::from(inner),
+                None => String::from(value),
+            };
+        }
+    }
+
+    return text;
+}
+
+/// Parses a Rust integer literal, e.g. `123`, `0x1F`, `1_000u32`.
+pub fn parse_int(text: &str) -> Option<u64> {
+    let (radix, digits) = if let Some(digits) = text.strip_prefix("0x") {
+        (16, digits)
+    } else if let Some(digits) = text.strip_prefix("0o") {
+        (8, digits)
+    } else if let Some(digits) = text.strip_prefix("0b") {
+        (2, digits)
+    } else {
+        (10, text)
+    };
+
+    let digits = match digits.find(['u', 'i']) {
+        Some(suffix_pos) => &digits[..suffix_pos],
+        None => digits,
+    };
+
+    let digits: String = digits.chars().filter(|ch| *ch != '_').collect();
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    return u64::from_str_radix(&digits, radix).ok();
+}
+
+fn push_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => write!(json, "\\u{:04x}", ch as u32).unwrap(),
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_file_names() {
+        assert_eq!(
+            entry_file_name("provider", &["MY_PROVIDER"]),
+            "provider-MY_PROVIDER.json"
+        );
+        assert_eq!(
+            entry_file_name("event", &["MY_PROVIDER", "My-Event 1/2"]),
+            "event-MY_PROVIDER-My%2DEvent%201%2F2.json"
+        );
+
+        // The separator is escaped within a key part, so keys cannot collide.
+        assert_ne!(
+            entry_file_name("event", &["A", "B-C"]),
+            entry_file_name("event", &["A-B", "C"])
+        );
+    }
+
+    #[test]
+    fn write_entry_overwrites() {
+        let mut path = env::temp_dir();
+        path.push(format!("eventheader_manifest_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        let dir = ManifestDir {
+            path: path.clone(),
+            crate_name: String::from("test_crate"),
+        };
+
+        dir.write_entry("event", &["P", "E1"], "{\"fields\":[]}\n")
+            .unwrap();
+        dir.write_entry("event", &["P", "E1"], "{\"fields\":[1]}\n")
+            .unwrap();
+        dir.write_entry("event", &["P", "E2"], "{\"fields\":[2]}\n")
+            .unwrap();
+
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&path).unwrap() {
+            let entry = entry.unwrap();
+            entries.push((
+                entry.file_name().into_string().unwrap(),
+                fs::read_to_string(entry.path()).unwrap(),
+            ));
+        }
+        entries.sort();
+        fs::remove_dir_all(&path).unwrap();
+
+        assert_eq!(
+            entries,
+            [
+                (
+                    String::from("event-P-E1.json"),
+                    String::from("{\"fields\":[1]}\n")
+                ),
+                (
+                    String::from("event-P-E2.json"),
+                    String::from("{\"fields\":[2]}\n")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_helpers() {
+        assert_eq!(parse_int("0x1F"), Some(31));
+        assert_eq!(parse_int("1_000u32"), Some(1000));
+        assert_eq!(parse_int("Verbose"), None);
+
+        let mut json = String::new();
+        push_json_string(&mut json, "a\"b\\c\n\u{1}");
+        assert_eq!(json, "\"a\\\"b\\\\c\\n\\u0001\"");
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+//! Compile-time validation of event metadata.
+//!
+//! Walks the fields of a parsed `write_event!` the same way that
+//! `tracepoint_decode::EventHeaderEnumerator` walks the event's metadata and reports
+//! a spanned error for anything the decoder would reject (`NotSupported`,
+//! `StackOverflow`, `InvalidData`), as well as encoding/format combinations that
+//! are not listed as usable in the `FieldEncoding` documentation.
+//!
+//! Only values known at compile time (field types, enum names, and integer
+//! literals) are checked. Fields whose encoding, format, or struct field count is an
+//! arbitrary expression are not checked.
+
+use proc_macro::*;
+
+use crate::errors::Errors;
+use crate::event_info::EventInfo;
+use crate::field_info::FieldInfo;
+use crate::field_option::FieldStrategy;
+use crate::manifest::{expression_text, parse_int};
+
+/// Maximum depth of nested structs (`EventHeaderEnumeratorContext::STRUCT_NEST_LIMIT`).
+const STRUCT_NEST_LIMIT: usize = 8;
+
+/// Maximum number of fields in a struct (7-bit field count).
+const STRUCT_FIELDS_MAX: u8 = 127;
+
+const ENCODING_STRUCT: u8 = 1;
+const ENCODING_KIND_MAX: u8 = 13; // BinaryLength16Char8
+const ENCODING_VALUE_MASK: u8 = 0x1F;
+const ENCODING_CARRAY_FLAG: u8 = 0x20;
+const CHAIN_FLAG: u8 = 0x80;
+
+/// FieldEncoding names and values. ValueSize has the same usable formats as Value64.
+const ENCODINGS: &[(&str, u8)] = &[
+    ("Invalid", 0),
+    ("Struct", 1),
+    ("Value8", 2),
+    ("Value16", 3),
+    ("Value32", 4),
+    ("Value64", 5),
+    ("Value128", 6),
+    ("ZStringChar8", 7),
+    ("ZStringChar16", 8),
+    ("ZStringChar32", 9),
+    ("StringLength16Char8", 10),
+    ("StringLength16Char16", 11),
+    ("StringLength16Char32", 12),
+    ("BinaryLength16Char8", 13),
+    ("ValueSize", 5),
+];
+
+/// FieldFormat names and values.
+const FORMATS: &[(&str, u8)] = &[
+    ("Default", 0),
+    ("UnsignedInt", 1),
+    ("SignedInt", 2),
+    ("HexInt", 3),
+    ("Errno", 4),
+    ("Pid", 5),
+    ("Time", 6),
+    ("Boolean", 7),
+    ("Float", 8),
+    ("HexBytes", 9),
+    ("String8", 10),
+    ("StringUtf", 11),
+    ("StringUtfBom", 12),
+    ("StringXml", 13),
+    ("StringJson", 14),
+    ("Uuid", 15),
+    ("Port", 16),
+    ("IPAddress", 17),
+    ("IPAddressObsolete", 18),
+    ("IPv4", 17),
+    ("IPv6", 18),
+];
+
+/// Largest FieldFormat value recognized by decoders. Larger values are decoded
+/// using the encoding's default format, so they are not checked.
+const FORMAT_KNOWN_MAX: u8 = 18;
+
+/// Returns the usable formats for an encoding kind (from the `FieldEncoding` docs),
+/// or None if any format is usable. `Default` is always usable.
+fn usable_formats(encoding_kind: u8) -> Option<&'static [u8]> {
+    const STRINGS: &[u8] = &[9, 11, 12, 13, 14];
+    return match encoding_kind {
+        2 => Some(&[1, 2, 3, 7, 9, 10]),                 // Value8
+        3 => Some(&[1, 2, 3, 7, 9, 11, 16]),             // Value16
+        4 => Some(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 11, 17]), // Value32
+        5 => Some(&[1, 2, 3, 6, 8, 9]),                  // Value64, ValueSize
+        6 => Some(&[9, 15, 17, 18]),                     // Value128
+        7 => Some(&[9, 10, 11, 12, 13, 14]),             // ZStringChar8
+        8 | 9 | 11 | 12 => Some(STRINGS),                // Char16/Char32 strings
+        _ => None, // Struct, StringLength16Char8, BinaryLength16Char8
+    };
+}
+
+/// Checks the event's metadata against the decoder's rules. Returns the compile
+/// errors (as an expression) if the decoder would reject the event.
+pub fn validate_event(event: &EventInfo) -> Result<(), TokenStream> {
+    let mut errors = Errors::new();
+
+    // Remaining field counts of the structs that contain the current field, or None
+    // if a struct's field count is not known at compile time.
+    let mut struct_stack = Some(Vec::new());
+
+    for field in &event.fields {
+        if !field.option.strategy.has_metadata() {
+            continue;
+        }
+
+        let encoding = field_encoding(field);
+        let format = field_format(field);
+        let encoding_kind = encoding.value.map(|value| value & ENCODING_VALUE_MASK);
+
+        if let Some(encoding_value) = encoding.value {
+            if encoding_kind == Some(0) {
+                errors.add(field.type_name_span, "field encoding must not be Invalid");
+            } else if encoding_value & ENCODING_VALUE_MASK > ENCODING_KIND_MAX {
+                errors.add(
+                    field.type_name_span,
+                    "unrecognized field encoding (decoders do not support this event)",
+                );
+            } else if encoding_value & (ENCODING_CARRAY_FLAG | CHAIN_FLAG) != 0 {
+                errors.add(
+                    field.type_name_span,
+                    "field encoding must not include CArrayFlag or ChainFlag",
+                );
+            }
+        }
+
+        let is_struct = match field.option.strategy {
+            FieldStrategy::Struct | FieldStrategy::RawStruct | FieldStrategy::RawStructSlice => {
+                true
+            }
+            _ => encoding_kind == Some(ENCODING_STRUCT),
+        };
+
+        if let Some(format_value) = format.value {
+            if is_struct {
+                if format_value == 0 {
+                    errors.add(format.span, "struct must have at least 1 field");
+                } else if format_value > STRUCT_FIELDS_MAX {
+                    errors.add(format.span, "too many fields in struct (limit 127)");
+                }
+            } else if format_value & CHAIN_FLAG != 0 {
+                errors.add(format.span, "field format must not include ChainFlag");
+            } else if format_value != 0 && format_value <= FORMAT_KNOWN_MAX {
+                let usable = encoding_kind.and_then(usable_formats);
+                if usable.map_or(false, |usable| !usable.contains(&format_value)) {
+                    errors.add(
+                        format.span,
+                        &format!(
+                            "format {} is not usable with encoding {}",
+                            format.text, encoding.text,
+                        ),
+                    );
+                }
+            }
+        }
+
+        // Track struct nesting. Stop tracking if a struct's field count is unknown.
+        if let Some(stack) = &mut struct_stack {
+            while stack.last() == Some(&0) {
+                stack.pop();
+            }
+
+            if let Some(remaining) = stack.last_mut() {
+                *remaining -= 1;
+            }
+
+            if is_struct {
+                if stack.len() >= STRUCT_NEST_LIMIT {
+                    errors.add(
+                        field.type_name_span,
+                        "structs are nested too deeply (limit 8 levels)",
+                    );
+                    struct_stack = None;
+                } else if let Some(field_count) = format.value {
+                    stack.push(field_count & STRUCT_FIELDS_MAX);
+                } else {
+                    struct_stack = None;
+                }
+            }
+        }
+    }
+
+    return if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.into_expression())
+    };
+}
+
+/// An encoding or format: the text used in error messages, the value (None if not
+/// known at compile time), and the span for error messages.
+struct TypeCode {
+    text: String,
+    value: Option<u8>,
+    span: Span,
+}
+
+fn field_encoding(field: &FieldInfo) -> TypeCode {
+    let text = if field.encoding_tokens.is_empty() {
+        String::from(field.option.encoding.as_str())
+    } else {
+        expression_text(&field.encoding_tokens, "FieldEncoding")
+    };
+
+    return TypeCode {
+        value: const_value(&text, "FieldEncoding", ENCODINGS),
+        text,
+        span: field.type_name_span,
+    };
+}
+
+fn field_format(field: &FieldInfo) -> TypeCode {
+    return if field.format_or_field_count_expr.is_empty() {
+        TypeCode {
+            text: String::from(field.option.format.as_str()),
+            value: Some(field.format_or_field_count_int),
+            span: field.type_name_span,
+        }
+    } else {
+        let text = expression_text(&field.format_or_field_count_expr.tokens, "FieldFormat");
+        TypeCode {
+            value: const_value(&text, "FieldFormat", FORMATS),
+            text,
+            span: field.format_or_field_count_expr.context,
+        }
+    };
+}
+
+/// Returns the value of an enum name (optionally qualified, e.g. `FieldFormat::Float`)
+/// or integer literal, or None if the value is not known at compile time.
+fn const_value(text: &str, enum_name: &str, names: &[(&str, u8)]) -> Option<u8> {
+    let name = match text.rsplit_once("::") {
+        Some((path, name)) if path.ends_with(enum_name) => name,
+        _ => text,
+    };
+
+    return match names.iter().find(|(n, _)| *n == name) {
+        Some((_, value)) => Some(*value),
+        None => parse_int(name).and_then(|value| u8::try_from(value).ok()),
+    };
+}
@@ -39,8 +39,51 @@ impl ProviderGenerator {
             String::from_iter(["G", &provider.group_name])
         };
 
+        // &[("Keyword1", 0x1u64), ("Keyword2", 0x2u64), ...]
+        for (name, value) in &provider.keywords {
+            self.tree2
+                .add_group_paren(
+                    self.tree1
+                        .add_literal(Literal::string(&name.to_string()))
+                        .add_punct(",")
+                        .add_literal(Literal::u64_suffixed(*value))
+                        .drain(),
+                )
+                .add_punct(",");
+        }
+        let keywords_slice: Vec<TokenTree> = self
+            .tree1
+            .add_punct("&")
+            .add_group_square(self.tree2.drain())
+            .drain()
+            .collect();
+
+        // pub const Keyword1: u64 = 0x1u64; ...
+        for (name, value) in &provider.keywords {
+            self.tree1.add_ident("pub").add_const_from_tokens(
+                &name.to_string(),
+                U64_PATH,
+                [Literal::u64_suffixed(*value).into()],
+            );
+        }
+
         let prov_tokens = self
             .prov_tree
+            // #[allow(dead_code, non_snake_case, non_upper_case_globals)]
+            .add_outer_attribute(
+                "allow",
+                self.tree2
+                    .add_ident("dead_code")
+                    .add_punct(",")
+                    .add_ident("non_snake_case")
+                    .add_punct(",")
+                    .add_ident("non_upper_case_globals")
+                    .drain(),
+            )
+            // mod MY_PROVIDER { pub const Keyword1: u64 = 0x1u64; ... }
+            .add_ident("mod")
+            .add_token(provider.symbol.clone())
+            .add_group_curly(self.tree1.drain())
             // #[cfg(not(target_os = "linux"))]
             .add_cfg_not_linux()
             // static MY_PROVIDER: eh::Provider = unsafe { ... };
@@ -62,6 +105,9 @@ impl ProviderGenerator {
                             // b"Ggroupname",
                             .add_literal(Literal::byte_string(options.as_bytes()))
                             .add_punct(",")
+                            // &[("Keyword1", 0x1u64), ...],
+                            .add_tokens(keywords_slice.iter().cloned())
+                            .add_punct(",")
                             // &_start__eh_tracepoints_MY_PROVIDER as *const usize,
                             .add_path_call(NULL_PATH, [])
                             .add_punct(",")
@@ -161,6 +207,78 @@ impl ProviderGenerator {
                     .add_path_call(
                         PROVIDER_NEW_PATH,
                         self.tree2
+                            // b"ProviderName",
+                            .add_literal(Literal::byte_string(provider.name.as_bytes()))
+                            .add_punct(",")
+                            // b"Ggroupname",
+                            .add_literal(Literal::byte_string(options.as_bytes()))
+                            .add_punct(",")
+                            // &[("Keyword1", 0x1u64), ...],
+                            .add_tokens(keywords_slice.iter().cloned())
+                            .add_punct(",")
+                            // &_start__eh_tracepoints_MY_PROVIDER as *const usize,
+                            .add_punct("&")
+                            .add_ident(provider_section_start.split_at(1).1)
+                            .add_ident("as")
+                            .add_punct("*")
+                            .add_ident("const")
+                            .add_path(USIZE_PATH)
+                            .add_punct(",")
+                            // &_stop__eh_tracepoints_MY_PROVIDER as *const usize,
+                            .add_punct("&")
+                            .add_ident(provider_section_stop.split_at(1).1)
+                            .add_ident("as")
+                            .add_punct("*")
+                            .add_ident("const")
+                            .add_path(USIZE_PATH)
+                            .add_punct(",")
+                            .drain(),
+                    )
+                    .drain(),
+            )
+            .add_punct(";")
+            // #[cfg(target_os = "linux")]
+            .add_cfg_linux()
+            // #[link_section = "_eh_providers"]
+            .add_punct("#")
+            .add_group_square(
+                self.tree1
+                    .add_ident("link_section")
+                    .add_punct("=")
+                    .add_literal(Literal::string(PROVIDERS_SECTION))
+                    .drain(),
+            )
+            // #[used]
+            .add_punct("#")
+            .add_group_square(self.tree1.add_ident("used").drain())
+            // #[allow(non_upper_case_globals, static_mut_refs)]
+            .add_outer_attribute(
+                "allow",
+                self.tree1
+                    .add_ident("non_upper_case_globals")
+                    .add_punct(",")
+                    .add_ident("static_mut_refs")
+                    .drain(),
+            )
+            // static _eh_provider_descriptor_MY_PROVIDER: eh::_internal::ProviderDescriptor = ...;
+            .add_ident("static")
+            .add_ident(&String::from_iter([
+                PROVIDER_DESCRIPTOR_VAR_PREFIX,
+                &provider_sym,
+            ]))
+            .add_punct(":")
+            .add_path(PROVIDER_DESCRIPTOR_PATH)
+            .add_punct("=")
+            .add_ident("unsafe")
+            .add_group_curly(
+                self.tree1
+                    // eh::_internal::ProviderDescriptor::new( ... )
+                    .add_path_call(
+                        PROVIDER_DESCRIPTOR_NEW_PATH,
+                        self.tree2
+                            // b"MY_PROVIDER",
+                            .add_literal(Literal::byte_string(provider_sym.as_bytes()))
+                            .add_punct(",")
                             // b"ProviderName",
                             .add_literal(Literal::byte_string(provider.name.as_bytes()))
                             .add_punct(",")
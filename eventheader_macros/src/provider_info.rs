@@ -4,6 +4,7 @@
 use proc_macro::*;
 
 use crate::errors::Errors;
+use crate::manifest::parse_int;
 use crate::parser::{ArgConstraints::*, ArgResult, Parser};
 
 /// Maximum length of a Tracepoint name "ProviderName_Attributes\0" (includes nul).
@@ -11,10 +12,14 @@ const EVENTHEADER_NAME_MAX: usize = 256;
 
 const NAMES_MAX: usize = EVENTHEADER_NAME_MAX - "_LffKffffffffffffffffG".len();
 
+const EXPECTED_KEYWORD: &str =
+    "expected KeywordName = integer literal, e.g. keywords(Networking = 0x2, Storage = 0x4)";
+
 pub struct ProviderInfo {
     pub symbol: Ident,
     pub name: String,
     pub group_name: String,
+    pub keywords: Vec<(Ident, u64)>,
     pub debug: bool,
 }
 
@@ -31,6 +36,7 @@ impl ProviderInfo {
             symbol: Ident::new("x", arg_span),
             name: String::new(),
             group_name: String::new(),
+            keywords: Vec::new(),
             debug: false,
         };
 
@@ -114,6 +120,45 @@ impl ProviderInfo {
                         prov.group_name = id_str;
                     }
                 }
+                "keywords" => {
+                    if !prov.keywords.is_empty() {
+                        errors.add(option_name_ident.span(), "keywords already set");
+                    }
+
+                    let mut constraints = Required;
+                    loop {
+                        let tokens: Vec<TokenTree> = option_args_parser
+                            .next_tokens(constraints, EXPECTED_KEYWORD)
+                            .into_iter()
+                            .collect();
+                        if tokens.is_empty() {
+                            break;
+                        }
+
+                        constraints = Optional;
+                        match parse_keyword(&tokens) {
+                            None => {
+                                option_args_parser
+                                    .errors()
+                                    .add(tokens[0].span(), EXPECTED_KEYWORD);
+                            }
+                            Some((ident, value)) => {
+                                let name = ident.to_string();
+                                if value == 0 {
+                                    option_args_parser
+                                        .errors()
+                                        .add(ident.span(), "keyword value must not be 0");
+                                } else if prov.keywords.iter().any(|(k, _)| k.to_string() == name) {
+                                    option_args_parser
+                                        .errors()
+                                        .add(ident.span(), "keyword name already set");
+                                } else {
+                                    prov.keywords.push((ident, value));
+                                }
+                            }
+                        }
+                    }
+                }
                 _ => {
                     errors.add(
                         option_name_ident.span(),
@@ -130,3 +175,15 @@ impl ProviderInfo {
         };
     }
 }
+
+/// Parses `Name = integer_literal`.
+fn parse_keyword(tokens: &[TokenTree]) -> Option<(Ident, u64)> {
+    return match tokens {
+        [TokenTree::Ident(ident), TokenTree::Punct(eq), TokenTree::Literal(value)]
+            if eq.as_char() == '=' =>
+        {
+            Some((ident.clone(), parse_int(&value.to_string())?))
+        }
+        _ => None,
+    };
+}
@@ -70,24 +70,40 @@ pub const FORMAT_ENUMS: &[&str] = &[
     "Uuid",
 ];
 
+/// Environment variable that enables manifest export (see manifest.rs).
+pub const MANIFEST_DIR_ENV: &str = "EVENTHEADER_MANIFEST_DIR";
+
 pub const EH_KEYWORD_CONST: &str = "_EH_KEYWORD";
 pub const EH_TAG_CONST: &str = "_EH_TAG";
+pub const EH_EXTENSION_KIND_CONST: &str = "_EH_EXTENSION_KIND";
 pub const EH_TRACEPOINT_VAR: &str = "_eh_tracepoint";
 pub const EH_ARG_VAR: &str = "_eh_arg";
 pub const EH_WRITE_FUNC: &str = "_eh_write";
 pub const EH_LENGTHS_VAR: &str = "_eh_lengths";
+pub const EH_EXTENSION_VAR: &str = "_eh_extension";
+pub const EH_EXTENSIONS_VAR: &str = "_eh_extensions";
 pub const EH_ACTIVITY_ID_VAR: &str = "_eh_aid";
 pub const EH_RELATED_ID_VAR: &str = "_eh_rid";
 pub const EH_DUR_VAR: &str = "_eh_dur";
 pub const EH_TRACEPOINT_STATIC: &str = "_EH_TRACEPOINT";
 pub const EH_TRACEPOINT_PTR_STATIC: &str = "_EH_TRACEPOINT_PTR";
+pub const EH_THROTTLE_STATIC: &str = "_EH_THROTTLE";
+pub const EH_SUPPRESSED_VAR: &str = "_eh_suppressed";
+
+/// Name of the field added to throttled events (rate_limit or sample).
+pub const EH_SUPPRESSED_FIELD: &str = "_suppressed";
 
 pub const TRACEPOINTS_SECTION_PREFIX: &str = "_eh_tracepoints_";
 pub const TRACEPOINTS_SECTION_START_PREFIX: &str = "__start__eh_tracepoints_";
 pub const TRACEPOINTS_SECTION_STOP_PREFIX: &str = "__stop__eh_tracepoints_";
 pub const PROVIDER_PTR_VAR_PREFIX: &str = "_eh_define_provider_";
+pub const PROVIDERS_SECTION: &str = "_eh_providers";
+pub const PROVIDER_DESCRIPTOR_VAR_PREFIX: &str = "_eh_provider_descriptor_";
 pub const EH_TRACEPOINT_WRITE_EVENTHEADER: &str = "write_eventheader";
-pub const EH_TRACEPOINT_ENABLED: &str = "enabled";
+pub const EH_TRACEPOINT_WRITE_EVENTHEADER_WITH_EXTENSIONS: &str =
+    "write_eventheader_with_extensions";
+pub const EH_TRACEPOINT_ENABLED_UNSUPPRESSED: &str = "enabled_unsuppressed";
+pub const EH_THROTTLE_CHECK: &str = "check";
 
 pub const BORROW_BORROW_PATH: &[&str] = &["core", "borrow", "Borrow", "borrow"];
 pub const ASREF_PATH: &[&str] = &["core", "convert", "AsRef"];
@@ -121,9 +137,16 @@ pub const OPCODE_INFO_PATH: &[&str] = &["eventheader", "Opcode", "Info"];
 pub const FORMAT_PATH: &[&str] = &["eventheader", "FieldFormat"];
 pub const FORMAT_FROM_INT_PATH: &[&str] = &["eventheader", "FieldFormat", "from_int"];
 pub const GUID_PATH: &[&str] = &["eventheader", "Guid"];
+pub const EXTENSIONKIND_PATH: &[&str] = &["eventheader", "ExtensionKind"];
 pub const PROVIDER_PATH: &[&str] = &["eventheader", "Provider"];
+pub const EVENTTHROTTLE_PATH: &[&str] = &["eventheader", "EventThrottle"];
+pub const EVENTTHROTTLE_NEW_PATH: &[&str] = &["eventheader", "EventThrottle", "new"];
 
 pub const PROVIDER_NEW_PATH: &[&str] = &["eventheader", "_internal", "provider_new"];
+pub const PROVIDER_DESCRIPTOR_PATH: &[&str] =
+    &["eventheader", "_internal", "ProviderDescriptor"];
+pub const PROVIDER_DESCRIPTOR_NEW_PATH: &[&str] =
+    &["eventheader", "_internal", "ProviderDescriptor", "new"];
 pub const EVENTHEADERTRACEPOINT_PATH: &[&str] =
     &["eventheader", "_internal", "EventHeaderTracepoint"];
 pub const EVENTHEADERTRACEPOINT_NEW_PATH: &[&str] =
@@ -131,6 +154,9 @@ pub const EVENTHEADERTRACEPOINT_NEW_PATH: &[&str] =
 pub const TAG_BYTE0_PATH: &[&str] = &["eventheader", "_internal", "tag_byte0"];
 pub const TAG_BYTE1_PATH: &[&str] = &["eventheader", "_internal", "tag_byte1"];
 pub const SLICE_COUNT_PATH: &[&str] = &["eventheader", "_internal", "slice_count"];
+pub const EXTENSION_HEADER_PATH: &[&str] = &["eventheader", "_internal", "extension_header"];
+pub const EVENTHEADEREXTENSION_PATH: &[&str] =
+    &["eventheader", "_internal", "EventHeaderExtension"];
 pub const TIME_FROM_DURATION_AFTER_PATH: &[&str] =
     &["eventheader", "_internal", "time_from_duration_after_1970"];
 pub const TIME_FROM_DURATION_BEFORE_PATH: &[&str] =
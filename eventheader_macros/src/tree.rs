@@ -168,6 +168,27 @@ impl Tree {
             .add_punct(";");
     }
 
+    /// `{ #[allow(unused_imports)] use PROVIDER_SYMBOL::*; tokens }`
+    ///
+    /// Evaluates `tokens` with the provider's named keywords (the constants in the
+    /// `mod PROVIDER_SYMBOL` generated by `define_provider!`) in scope.
+    pub fn add_provider_keywords_block(
+        &mut self,
+        provider_symbol: &Ident,
+        tokens: impl IntoIterator<Item = TokenTree>,
+    ) -> &mut Self {
+        let mut block_tree = Tree::new(self.span);
+        block_tree
+            .add_outer_attribute("allow", [Ident::new("unused_imports", self.span).into()])
+            .add_ident("use")
+            .add_token(provider_symbol.clone())
+            .add_punct("::")
+            .add_punct("*")
+            .add_punct(";")
+            .add_tokens(tokens);
+        return self.add_group_curly(block_tree.drain());
+    }
+
     /// If array_count == 0: `type_path`
     ///
     /// If array_count != 0: `[type_path; array_count]`
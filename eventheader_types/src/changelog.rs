@@ -6,6 +6,27 @@
 #[allow(unused_imports)]
 use crate::*; // For docs
 
+/// # v0.6.0 (TBD)
+/// - New [`Guid::new_v4_from`] (random) and [`Guid::new_v7_from`] (time-ordered,
+///   monotonic within a process) methods that take a [`GuidSource`] for random bytes
+///   and time. New `std` feature with `Guid::new_v4`, `Guid::new_v7`, and
+///   `StdGuidSource`. The v7 methods require a target with 64-bit atomics.
+/// - New [`Guid::new_v5`] method for name-based GUIDs with a caller-provided
+///   namespace, and `Guid::NAMESPACE_DNS`, `NAMESPACE_URL`, `NAMESPACE_OID`, and
+///   `NAMESPACE_X500` constants.
+/// - [`Level`], [`Opcode`], [`FieldEncoding`], [`FieldFormat`], [`ExtensionKind`],
+///   and [`HeaderFlags`] now implement `Display` using canonical names (e.g.
+///   `Warning` instead of `3`) and implement `FromStr` (case-insensitive, with
+///   numeric fallback). [`Guid`] implements `FromStr`. New [`ParseNameError`].
+/// - New `serde` feature that implements `Serialize` and `Deserialize` for these
+///   types and for `Guid` (string form).
+/// - New [`TracepointName`] type for formatting, parsing, and validating
+///   EventHeader tracepoint names (`ProviderName_L{level}K{keyword}{Options}`),
+///   with errors reported as [`TracepointNameError`].
+/// - New [`ExtensionKind::is_custom`] method for identifying provider-defined
+///   extension block kinds.
+pub mod v0_6_0 {}
+
 /// # v0.5.0 (2025-11-26)
 /// - Fix clippy issues for Rust 1.91.
 pub mod v0_5_0 {}
@@ -3,9 +3,11 @@
 
 #![allow(non_upper_case_globals)]
 
-use core::fmt;
 use core::mem::size_of;
 
+use crate::names::impl_names;
+use crate::names::Names;
+
 #[allow(unused_imports)]
 use crate::descriptors::*; // For docs
 
@@ -65,11 +67,18 @@ impl HeaderFlags {
     pub const DefaultWithExtension: Self = Self(Self::Default.0 | Self::Extension.0);
 }
 
-impl fmt::Display for HeaderFlags {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        return self.0.fmt(f);
-    }
-}
+const HEADER_FLAGS_NAMES: Names = Names {
+    type_name: "HeaderFlags",
+    value_mask: 0,
+    values: &[("None", 0)],
+    flags: &[
+        ("Pointer64", 0x01),
+        ("LittleEndian", 0x02),
+        ("Extension", 0x04),
+    ],
+};
+
+impl_names!(HeaderFlags, u8, HEADER_FLAGS_NAMES);
 
 impl From<u8> for HeaderFlags {
     fn from(val: u8) -> Self {
@@ -84,6 +93,10 @@ impl From<HeaderFlags> for u8 {
 }
 
 /// The type of data contined in an [`EventHeaderExtension`] block.
+///
+/// Kinds other than `Invalid`, `Metadata`, and `ActivityId` (up to `ValueMask`) can
+/// be used for provider-defined extension blocks, e.g. trace flags or a tenant id.
+/// Decoders skip extension blocks with kinds that they do not recognize.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct ExtensionKind(u16);
@@ -104,6 +117,13 @@ impl ExtensionKind {
         return (self.0 & flag.0) != 0;
     }
 
+    /// Returns true if this kind can be used for a provider-defined extension block,
+    /// i.e. it is not `Invalid`, `Metadata`, or `ActivityId` and does not have the
+    /// `ChainFlag` bit set.
+    pub const fn is_custom(self) -> bool {
+        return self.0 > Self::ActivityId.0 && self.0 <= Self::ValueMask;
+    }
+
     /// Invalid extension kind.
     pub const Invalid: Self = Self(0);
 
@@ -164,11 +184,14 @@ impl ExtensionKind {
     pub const ValueMask: u16 = 0x7FFF;
 }
 
-impl fmt::Display for ExtensionKind {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        return self.0.fmt(f);
-    }
-}
+const EXTENSION_KIND_NAMES: Names = Names {
+    type_name: "ExtensionKind",
+    value_mask: ExtensionKind::ValueMask as u32,
+    values: &[("Invalid", 0), ("Metadata", 1), ("ActivityId", 2)],
+    flags: &[("ChainFlag", ExtensionKind::ChainFlag as u32)],
+};
+
+impl_names!(ExtensionKind, u16, EXTENSION_KIND_NAMES);
 
 impl From<u16> for ExtensionKind {
     fn from(val: u16) -> Self {
@@ -395,11 +418,34 @@ impl FieldEncoding {
     pub const ChainFlag: u8 = 0x80;
 }
 
-impl fmt::Display for FieldEncoding {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        return self.0.fmt(f);
-    }
-}
+const FIELD_ENCODING_NAMES: Names = Names {
+    type_name: "FieldEncoding",
+    value_mask: FieldEncoding::ValueMask as u32,
+    values: &[
+        ("Invalid", 0),
+        ("Struct", 1),
+        ("Value8", 2),
+        ("Value16", 3),
+        ("Value32", 4),
+        ("Value64", 5),
+        ("Value128", 6),
+        ("ZStringChar8", 7),
+        ("ZStringChar16", 8),
+        ("ZStringChar32", 9),
+        ("StringLength16Char8", 10),
+        ("StringLength16Char16", 11),
+        ("StringLength16Char32", 12),
+        ("BinaryLength16Char8", 13),
+        ("ValueSize", FieldEncoding::ValueSize.0 as u32),
+    ],
+    flags: &[
+        ("CArrayFlag", FieldEncoding::CArrayFlag as u32),
+        ("VArrayFlag", FieldEncoding::VArrayFlag as u32),
+        ("ChainFlag", FieldEncoding::ChainFlag as u32),
+    ],
+};
+
+impl_names!(FieldEncoding, u8, FIELD_ENCODING_NAMES);
 
 impl From<u8> for FieldEncoding {
     fn from(val: u8) -> Self {
@@ -517,11 +563,36 @@ impl FieldFormat {
     pub const ChainFlag: u8 = 0x80;
 }
 
-impl fmt::Display for FieldFormat {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        return self.0.fmt(f);
-    }
-}
+const FIELD_FORMAT_NAMES: Names = Names {
+    type_name: "FieldFormat",
+    value_mask: FieldFormat::ValueMask as u32,
+    values: &[
+        ("Default", 0),
+        ("UnsignedInt", 1),
+        ("SignedInt", 2),
+        ("HexInt", 3),
+        ("Errno", 4),
+        ("Pid", 5),
+        ("Time", 6),
+        ("Boolean", 7),
+        ("Float", 8),
+        ("HexBytes", 9),
+        ("String8", 10),
+        ("StringUtf", 11),
+        ("StringUtfBom", 12),
+        ("StringXml", 13),
+        ("StringJson", 14),
+        ("Uuid", 15),
+        ("Port", 16),
+        ("IPAddress", 17),
+        ("IPAddressObsolete", 18),
+        ("IPv4", 17),
+        ("IPv6", 18),
+    ],
+    flags: &[("ChainFlag", FieldFormat::ChainFlag as u32)],
+};
+
+impl_names!(FieldFormat, u8, FIELD_FORMAT_NAMES);
 
 impl From<u8> for FieldFormat {
     fn from(val: u8) -> Self {
@@ -572,11 +643,21 @@ impl Level {
     pub const Verbose: Level = Level(5);
 }
 
-impl fmt::Display for Level {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        return self.0.fmt(f);
-    }
-}
+const LEVEL_NAMES: Names = Names {
+    type_name: "Level",
+    value_mask: 0xFF,
+    values: &[
+        ("Invalid", 0),
+        ("CriticalError", 1),
+        ("Error", 2),
+        ("Warning", 3),
+        ("Informational", 4),
+        ("Verbose", 5),
+    ],
+    flags: &[],
+};
+
+impl_names!(Level, u8, LEVEL_NAMES);
 
 impl From<u8> for Level {
     fn from(val: u8) -> Self {
@@ -715,11 +796,41 @@ impl Opcode {
     pub const ReservedOpcode255: Opcode = Opcode(255);
 }
 
-impl fmt::Display for Opcode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        return self.0.fmt(f);
-    }
-}
+const OPCODE_NAMES: Names = Names {
+    type_name: "Opcode",
+    value_mask: 0xFF,
+    values: &[
+        ("Info", 0),
+        ("ActivityStart", 1),
+        ("ActivityStop", 2),
+        ("CollectionStart", 3),
+        ("CollectionStop", 4),
+        ("Extension", 5),
+        ("Reply", 6),
+        ("Resume", 7),
+        ("Suspend", 8),
+        ("Send", 9),
+        ("Receive", 240),
+        ("ReservedOpcode241", 241),
+        ("ReservedOpcode242", 242),
+        ("ReservedOpcode243", 243),
+        ("ReservedOpcode244", 244),
+        ("ReservedOpcode245", 245),
+        ("ReservedOpcode246", 246),
+        ("ReservedOpcode247", 247),
+        ("ReservedOpcode248", 248),
+        ("ReservedOpcode249", 249),
+        ("ReservedOpcode250", 250),
+        ("ReservedOpcode251", 251),
+        ("ReservedOpcode252", 252),
+        ("ReservedOpcode253", 253),
+        ("ReservedOpcode254", 254),
+        ("ReservedOpcode255", 255),
+    ],
+    flags: &[],
+};
+
+impl_names!(Opcode, u8, OPCODE_NAMES);
 
 impl From<u8> for Opcode {
     fn from(val: u8) -> Self {
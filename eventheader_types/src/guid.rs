@@ -4,7 +4,15 @@
 use core::borrow;
 use core::convert::TryInto;
 use core::fmt;
+use core::str;
 use core::str::from_utf8;
+use core::sync::atomic;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+#[cfg(feature = "std")]
+use core::sync::atomic::AtomicUsize;
+
+use crate::ParseNameError;
 
 /// [UUID](https://en.wikipedia.org/wiki/Universally_unique_identifier)
 /// with big-endian in-memory representation.
@@ -18,6 +26,22 @@ impl Guid {
         return Self([0; 16]);
     }
 
+    /// Namespace for [`Guid::new_v5`] names that are fully-qualified domain names
+    /// (RFC 9562): `6ba7b810-9dad-11d1-80b4-00c04fd430c8`.
+    pub const NAMESPACE_DNS: Guid = Guid::from_u128(&0x6ba7b810_9dad_11d1_80b4_00c04fd430c8);
+
+    /// Namespace for [`Guid::new_v5`] names that are URLs (RFC 9562):
+    /// `6ba7b811-9dad-11d1-80b4-00c04fd430c8`.
+    pub const NAMESPACE_URL: Guid = Guid::from_u128(&0x6ba7b811_9dad_11d1_80b4_00c04fd430c8);
+
+    /// Namespace for [`Guid::new_v5`] names that are ISO OIDs (RFC 9562):
+    /// `6ba7b812-9dad-11d1-80b4-00c04fd430c8`.
+    pub const NAMESPACE_OID: Guid = Guid::from_u128(&0x6ba7b812_9dad_11d1_80b4_00c04fd430c8);
+
+    /// Namespace for [`Guid::new_v5`] names that are X.500 DNs (RFC 9562):
+    /// `6ba7b814-9dad-11d1-80b4-00c04fd430c8`.
+    pub const NAMESPACE_X500: Guid = Guid::from_u128(&0x6ba7b814_9dad_11d1_80b4_00c04fd430c8);
+
     /// Returns a GUID generated from a case-insensitive hash of the specified trace
     /// provider name. The hash uses the same algorithm as many Windows tracing tools
     /// and APIs. Given the same name, it will always generate the same GUID.
@@ -47,6 +71,108 @@ impl Guid {
         return Guid::from_bytes_le(v[0..16].try_into().unwrap());
     }
 
+    /// Returns a name-based version 5 GUID (RFC 9562): the SHA-1 hash of the
+    /// `namespace` GUID followed by the bytes of `name`. Given the same namespace
+    /// and name, it will always generate the same GUID.
+    ///
+    /// Unlike [`Guid::from_name`], the name is hashed as-is (case-sensitive) and the
+    /// namespace is chosen by the caller, e.g. [`Guid::NAMESPACE_DNS`] or a
+    /// component-specific GUID.
+    /// ```
+    /// # use eventheader_types::Guid;
+    /// assert_eq!(
+    ///    Guid::new_v5(&Guid::NAMESPACE_DNS, b"www.example.com"),
+    ///    Guid::from_u128(&0x2ed6657d_e927_568b_95e1_2665a8aea6a2));
+    /// ```
+    pub fn new_v5(namespace: &Guid, name: &[u8]) -> Self {
+        let mut hasher = Sha1NonSecret::new();
+        hasher.write(&namespace.0);
+        hasher.write(name);
+        let v = hasher.finish();
+        return Self::with_version(v[0..16].try_into().unwrap(), 5);
+    }
+
+    /// Returns a random (version 4) GUID using [`StdGuidSource`].
+    /// Requires the `std` feature.
+    ///
+    /// The random bits are not suitable for security purposes. For no_std use, or to
+    /// use a different random number generator, see [`Guid::new_v4_from`].
+    #[cfg(feature = "std")]
+    pub fn new_v4() -> Self {
+        return Self::new_v4_from(&StdGuidSource);
+    }
+
+    /// Returns a random (version 4) GUID using random bytes from `source`.
+    pub fn new_v4_from<S: GuidSource + ?Sized>(source: &S) -> Self {
+        let mut v = [0u8; 16];
+        source.fill_random(&mut v);
+        return Self::with_version(v, 4);
+    }
+
+    /// Returns a time-ordered (version 7) GUID using [`StdGuidSource`].
+    /// Requires the `std` feature and a target with 64-bit atomics.
+    ///
+    /// See [`Guid::new_v7_from`] for details.
+    #[cfg(all(feature = "std", target_has_atomic = "64"))]
+    pub fn new_v7() -> Self {
+        return Self::new_v7_from(&StdGuidSource);
+    }
+
+    /// Returns a time-ordered (version 7) GUID using the time and random bytes from
+    /// `source`.
+    ///
+    /// The GUID contains a 48-bit Unix timestamp in milliseconds, a 12-bit counter,
+    /// and 62 random bits (RFC 9562, fixed-length dedicated counter method). GUIDs
+    /// generated by `new_v7` and `new_v7_from` are strictly increasing within a
+    /// process (as compared by `Ord`), even if they are generated in the same
+    /// millisecond or if the clock goes backwards: if the timestamp would not
+    /// increase, the counter is incremented instead, and if the counter overflows, the
+    /// timestamp is advanced by 1 millisecond.
+    ///
+    /// Requires a target with 64-bit atomics (`target_has_atomic = "64"`).
+    #[cfg(target_has_atomic = "64")]
+    pub fn new_v7_from<S: GuidSource + ?Sized>(source: &S) -> Self {
+        // Timestamp (48 bits) and counter (12 bits) of the most recent v7 GUID.
+        static LAST: AtomicU64 = AtomicU64::new(0);
+
+        let mut v = [0u8; 16];
+        source.fill_random(&mut v);
+
+        let now = (source.unix_time_ms() & 0xFFFF_FFFF_FFFF) << 12;
+        let mut last = LAST.load(atomic::Ordering::Relaxed);
+        let next = loop {
+            // In a new millisecond, start the counter at a random value in its lower
+            // half to leave room for increments.
+            let next = if now > last {
+                now | (u16::from_be_bytes([v[6], v[7]]) & 0x7FF) as u64
+            } else {
+                last + 1
+            };
+
+            match LAST.compare_exchange_weak(
+                last,
+                next,
+                atomic::Ordering::Relaxed,
+                atomic::Ordering::Relaxed,
+            ) {
+                Ok(_) => break next,
+                Err(current) => last = current,
+            }
+        };
+
+        // 48-bit timestamp, 4-bit version (set below), 12-bit counter.
+        let timestamp_counter = ((next >> 12) << 16) | (next & 0xFFF);
+        v[0..8].copy_from_slice(&timestamp_counter.to_be_bytes());
+        return Self::with_version(v, 7);
+    }
+
+    /// Sets the version and variant bits of a big-endian GUID.
+    const fn with_version(mut bytes_be: [u8; 16], version: u8) -> Self {
+        bytes_be[6] = (bytes_be[6] & 0x0F) | (version << 4);
+        bytes_be[8] = (bytes_be[8] & 0x3F) | 0x80;
+        return Self(bytes_be);
+    }
+
     /// Creates a GUID from field values.
     /// ```
     /// # use eventheader_types::Guid;
@@ -328,6 +454,46 @@ impl fmt::Display for Guid {
     }
 }
 
+impl str::FromStr for Guid {
+    type Err = ParseNameError;
+
+    /// Parses a GUID string in any of the forms accepted by [`Guid::try_parse`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Self::try_parse(s).ok_or(ParseNameError::new("Guid"));
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Guid {
+    /// Serializes as a string, e.g. `"a3a2a1a0-b1b0-c1c0-d7d6-d5d4d3d2d1d0"`.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(from_utf8(&self.to_utf8_bytes()).unwrap());
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Guid {
+    /// Deserializes from a string in any of the forms accepted by [`Guid::try_parse`].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Guid;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                return f.write_str("a GUID string");
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Guid, E> {
+                return Guid::try_parse(v)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self));
+            }
+        }
+
+        return deserializer.deserialize_str(Visitor);
+    }
+}
+
 impl borrow::Borrow<[u8; 16]> for Guid {
     /// Returns this implementation's in-memory byte representation.
     fn borrow(&self) -> &[u8; 16] {
@@ -335,6 +501,54 @@ impl borrow::Borrow<[u8; 16]> for Guid {
     }
 }
 
+/// Source of random bytes and time for [`Guid::new_v4_from`] and
+/// [`Guid::new_v7_from`]. Implement this trait to generate GUIDs in a no_std
+/// environment or with a specific random number generator. With the `std` feature,
+/// `StdGuidSource` is available (and used by `Guid::new_v4` and `Guid::new_v7`).
+pub trait GuidSource {
+    /// Fills `bytes` with random bytes.
+    fn fill_random(&self, bytes: &mut [u8]);
+
+    /// Returns the current time, in milliseconds since 1970-01-01 00:00:00 UTC.
+    fn unix_time_ms(&self) -> u64;
+}
+
+/// The default [`GuidSource`], using `std::time::SystemTime` for the time and
+/// randomly-keyed `std::collections::hash_map::RandomState` hashes for the random
+/// bytes. Requires the `std` feature.
+///
+/// The random bytes are unpredictable enough for unique identifiers, but they are
+/// not suitable for security purposes.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdGuidSource;
+
+#[cfg(feature = "std")]
+impl GuidSource for StdGuidSource {
+    fn fill_random(&self, bytes: &mut [u8]) {
+        use std::collections::hash_map::RandomState;
+        use std::hash::BuildHasher;
+        use std::hash::Hasher;
+
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        for chunk in bytes.chunks_mut(8) {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_usize(COUNTER.fetch_add(1, atomic::Ordering::Relaxed));
+            hasher.write_u64(self.unix_time_ms());
+            let value = hasher.finish().to_ne_bytes();
+            chunk.copy_from_slice(&value[..chunk.len()]);
+        }
+    }
+
+    fn unix_time_ms(&self) -> u64 {
+        return match std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+            Ok(duration) => duration.as_millis() as u64,
+            Err(_) => 0,
+        };
+    }
+}
+
 struct GuidParseState<'a> {
     input: &'a [u8],
     pos: usize,
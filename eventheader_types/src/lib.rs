@@ -22,6 +22,14 @@
 //!   [tracepoint-collect](https://github.com/microsoft/LinuxTracepoints/blob/main/libtracepoint-control-cpp/tools/tracepoint-collect.cpp)
 //!   tool.
 //!
+//! The [`Level`], [`Opcode`], [`FieldEncoding`], [`FieldFormat`], [`ExtensionKind`],
+//! and [`HeaderFlags`] types implement `Display` and `FromStr` using their canonical
+//! names, e.g. `"Warning"` or `"HexInt"`. Parsing is case-insensitive and also
+//! accepts numeric values (decimal or `0x` hex). Values with flags use `'|'`, e.g.
+//! `"Value32|VArrayFlag"` or `"Pointer64|LittleEndian"`. With the `serde` feature
+//! enabled, these types and [`Guid`] implement `Serialize` and `Deserialize` (as
+//! strings for human-readable formats).
+//!
 //! # EventHeader Technical Details
 //!
 //! `EventHeader` is a tracing convention layered on top of Linux Tracepoints.
@@ -104,6 +112,7 @@
 //!   `ProviderName + '_' + 'L' + eventLevel + 'K' + eventKeyword + [Options]`
 //!   or `printf("%s_L%xK%lx%s", providerName, eventLevel, eventKeyword, options)`,
 //!   e.g. `"MyProvider_L3K2a"` or `"OtherProvider_L5K1fGperf"`.
+//!   Use [`TracepointName`] to format, parse, and validate tracepoint names.
 //!
 //! Event level is a uint8 value 1..255 indicating event severity, formatted as
 //! lowercase hexadecimal, e.g. `printf("L%x", eventLevel)`. The defined level values
@@ -180,9 +189,20 @@ pub use enums::HeaderFlags;
 pub use enums::Level;
 pub use enums::Opcode;
 pub use guid::Guid;
+pub use guid::GuidSource;
+#[cfg(feature = "std")]
+pub use guid::StdGuidSource;
+pub use names::ParseNameError;
+pub use tracepoint_name::TracepointName;
+pub use tracepoint_name::TracepointNameError;
 
 pub mod changelog;
 
 mod descriptors;
 mod enums;
 mod guid;
+mod names;
+mod tracepoint_name;
+
+#[cfg(feature = "std")]
+extern crate std;
@@ -0,0 +1,192 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use core::fmt;
+
+/// Error returned when parsing a [`Level`](crate::Level), [`Opcode`](crate::Opcode),
+/// [`FieldEncoding`](crate::FieldEncoding), [`FieldFormat`](crate::FieldFormat),
+/// [`ExtensionKind`](crate::ExtensionKind), [`HeaderFlags`](crate::HeaderFlags), or
+/// [`Guid`](crate::Guid) from a string fails.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ParseNameError {
+    type_name: &'static str,
+}
+
+impl ParseNameError {
+    pub(crate) const fn new(type_name: &'static str) -> Self {
+        return Self { type_name };
+    }
+
+    /// Returns the name of the type that could not be parsed, e.g. `"Level"`.
+    pub const fn type_name(&self) -> &'static str {
+        return self.type_name;
+    }
+}
+
+impl fmt::Display for ParseNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "invalid {} value", self.type_name);
+    }
+}
+
+/// Canonical names for the values and flags of an enum-like type.
+pub(crate) struct Names {
+    pub type_name: &'static str,
+
+    /// Bits of the value that hold an enumerated value. The other bits are flags.
+    pub value_mask: u32,
+
+    /// Names of enumerated values. The first name for a value is canonical.
+    pub values: &'static [(&'static str, u32)],
+
+    /// Names of flag bits.
+    pub flags: &'static [(&'static str, u32)],
+}
+
+impl Names {
+    /// Writes the value as `Name`, `Name|Flag|Flag`, or a decimal number if the
+    /// value has no name. Unnamed flag bits are written in hex, e.g. `Name|0x40`.
+    pub fn fmt(&self, value: u32, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let base = value & self.value_mask;
+        let mut rest = value & !self.value_mask;
+        let mut separator = "";
+
+        if self.value_mask != 0 || rest == 0 {
+            match self.values.iter().find(|(_, v)| *v == base) {
+                Some((name, _)) => f.write_str(name)?,
+                None => write!(f, "{}", base)?,
+            }
+            separator = "|";
+        }
+
+        for &(name, bit) in self.flags {
+            if rest & bit != 0 {
+                f.write_str(separator)?;
+                f.write_str(name)?;
+                separator = "|";
+                rest &= !bit;
+            }
+        }
+
+        if rest != 0 {
+            write!(f, "{}{:#x}", separator, rest)?;
+        }
+
+        return Ok(());
+    }
+
+    /// Parses a string written by `fmt`. Names are case-insensitive. Each
+    /// `'|'`-separated part may be a value name, a flag name, a decimal number, or a
+    /// `0x`-prefixed hex number. Returns an error if the result exceeds `max`.
+    pub fn parse(&self, s: &str, max: u32) -> Result<u32, ParseNameError> {
+        let error = ParseNameError::new(self.type_name);
+        let mut result = 0u32;
+        for part in s.split('|') {
+            let part = part.trim();
+            let value = if let Some((_, v)) = self
+                .values
+                .iter()
+                .chain(self.flags)
+                .find(|(name, _)| name.eq_ignore_ascii_case(part))
+            {
+                *v
+            } else if let Some(hex) = part.strip_prefix("0x").or_else(|| part.strip_prefix("0X")) {
+                u32::from_str_radix(hex, 16).map_err(|_| error)?
+            } else {
+                part.parse::<u32>().map_err(|_| error)?
+            };
+            result |= value;
+        }
+
+        return if result <= max {
+            Ok(result)
+        } else {
+            Err(error)
+        };
+    }
+}
+
+/// Implements `Display` and `FromStr` (and, with the `serde` feature, `Serialize` and
+/// `Deserialize`) for an enum-like newtype using its `Names` table.
+macro_rules! impl_names {
+    ($type:ident, $int:ident, $names:expr) => {
+        impl core::fmt::Display for $type {
+            /// Writes the canonical name of the value, e.g. `Warning`, or the
+            /// numeric value if it has no name.
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                return $names.fmt(self.0 as u32, f);
+            }
+        }
+
+        impl core::str::FromStr for $type {
+            type Err = $crate::ParseNameError;
+
+            /// Parses a canonical name (case-insensitive) or a numeric value.
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                return Ok(Self($names.parse(s, $int::MAX as u32)? as $int));
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $type {
+            /// Serializes as the canonical name for human-readable formats (e.g.
+            /// JSON), otherwise as the numeric value.
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                return if serializer.is_human_readable() {
+                    serializer.collect_str(self)
+                } else {
+                    serde::Serialize::serialize(&self.0, serializer)
+                };
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $type {
+            /// Deserializes from a name or numeric string (see `FromStr`) or from a
+            /// number.
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+
+                impl<'de> serde::de::Visitor<'de> for Visitor {
+                    type Value = $type;
+
+                    fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                        return write!(f, "a {} name or number", stringify!($type));
+                    }
+
+                    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<$type, E> {
+                        return match $int::try_from(v) {
+                            Ok(v) => Ok($type(v)),
+                            Err(_) => {
+                                Err(E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+                            }
+                        };
+                    }
+
+                    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<$type, E> {
+                        return match $int::try_from(v) {
+                            Ok(v) => Ok($type(v)),
+                            Err(_) => {
+                                Err(E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+                            }
+                        };
+                    }
+
+                    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<$type, E> {
+                        return v
+                            .parse()
+                            .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self));
+                    }
+                }
+
+                return if deserializer.is_human_readable() {
+                    deserializer.deserialize_any(Visitor)
+                } else {
+                    <$int as serde::Deserialize>::deserialize(deserializer).map($type)
+                };
+            }
+        }
+    };
+}
+
+pub(crate) use impl_names;
@@ -0,0 +1,362 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+use core::fmt;
+
+use crate::Level;
+
+/// The reason that a tracepoint name, provider name, or group name is invalid.
+/// Returned by the validation and parsing methods of [`TracepointName`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TracepointNameError {
+    /// The provider name is empty or contains `'\0'`, `' '`, or `':'`.
+    InvalidProviderName,
+
+    /// The group name contains a character other than an ASCII digit or a lowercase
+    /// ASCII letter.
+    InvalidGroupName,
+
+    /// The options are not a sequence of uppercase ASCII letters, each followed by 0
+    /// or more ASCII digits or lowercase ASCII letters, or are not sorted in
+    /// alphabetical order.
+    InvalidOptions,
+
+    /// The options contain an option type other than `G` (group name). Decoders
+    /// ignore unknown options, but producers should not generate them.
+    UnknownOption(u8),
+
+    /// The tracepoint name is 256 or more bytes long, or the provider name + group
+    /// name are 234 or more bytes long.
+    NameTooLong,
+
+    /// The tracepoint name does not contain `_L` followed by a level, formatted as 1
+    /// or 2 lowercase hex digits.
+    InvalidLevel,
+
+    /// The level is not followed by `K` and a keyword, formatted as 1 to 16
+    /// lowercase hex digits.
+    InvalidKeyword,
+}
+
+impl fmt::Display for TracepointNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Self::InvalidProviderName => f.write_str(
+                "provider name must be non-empty and must not contain '\\0', ' ', or ':'",
+            ),
+            Self::InvalidGroupName => f.write_str("group name must contain only 0..9 and a..z"),
+            Self::InvalidOptions => {
+                f.write_str("options must be sorted and must match ([A-Z][0-9a-z]*)*")
+            }
+            Self::UnknownOption(ch) => write!(f, "unknown option '{}'", *ch as char),
+            Self::NameTooLong => f.write_str("tracepoint name is too long"),
+            Self::InvalidLevel => f.write_str("tracepoint name has no valid level attribute"),
+            Self::InvalidKeyword => f.write_str("tracepoint name has no valid keyword attribute"),
+        };
+    }
+}
+
+/// The parts of an EventHeader tracepoint name,
+/// `ProviderName_L{level}K{keyword}{Options}`, e.g. `"MyProvider_L3K2a"` or
+/// `"OtherProvider_L5K1fGperf"`.
+///
+/// - Use [`TracepointName::parse`] to split a tracepoint name into its parts, e.g. when
+///   decoding an event.
+/// - Use [`TracepointName::new`] to validate the parts of a tracepoint name, e.g. when
+///   registering a tracepoint, then use `Display` to format the name.
+/// - Use [`TracepointName::validate_provider`] to validate a provider name and group
+///   name before any tracepoints are registered.
+///
+/// See the "Tracepoint Names" section of the [crate documentation](crate) for the
+/// naming rules.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TracepointName<'a> {
+    provider_name: &'a str,
+    level: Level,
+    keyword: u64,
+    options: &'a str,
+}
+
+impl<'a> TracepointName<'a> {
+    /// Tracepoint names must be less than 256 bytes long.
+    pub const NAME_MAX: usize = 256;
+
+    /// The provider name + group name of a provider must be less than 234 bytes long
+    /// so that its tracepoint names are less than 256 bytes for any level and keyword.
+    pub const PROVIDER_NAMES_MAX: usize = Self::NAME_MAX - "_LffKffffffffffffffffG".len();
+
+    /// Returns a `TracepointName` with the specified parts after validating them
+    /// using the rules for producers:
+    ///
+    /// - `provider_name` must be non-empty and must not contain `'\0'`, `' '`, or `':'`.
+    /// - `options` must be `""` or `"G"` + group name, where the group name contains
+    ///   only ASCII digits and lowercase ASCII letters.
+    /// - The formatted name must be less than 256 bytes long.
+    pub fn new(
+        provider_name: &'a str,
+        level: Level,
+        keyword: u64,
+        options: &'a str,
+    ) -> Result<Self, TracepointNameError> {
+        validate_provider_name(provider_name)?;
+        validate_options(options)?;
+
+        let options_bytes = options.as_bytes();
+        let mut pos = 0;
+        while pos < options_bytes.len() {
+            let ch = options_bytes[pos];
+            if ch != b'G' {
+                return Err(TracepointNameError::UnknownOption(ch));
+            }
+
+            pos += 1 + option_value_len(&options_bytes[pos + 1..]);
+        }
+
+        let name = Self::new_unchecked(provider_name, level, keyword, options);
+        if name.formatted_len() >= Self::NAME_MAX {
+            return Err(TracepointNameError::NameTooLong);
+        }
+
+        return Ok(name);
+    }
+
+    /// Returns a `TracepointName` with the specified parts without validating them.
+    /// Use this when the parts are known to be valid, e.g. when they were validated
+    /// by [`TracepointName::validate_provider`]. If the parts are not valid, the
+    /// formatted name will not be usable as a tracepoint name.
+    pub const fn new_unchecked(
+        provider_name: &'a str,
+        level: Level,
+        keyword: u64,
+        options: &'a str,
+    ) -> Self {
+        return Self {
+            provider_name,
+            level,
+            keyword,
+            options,
+        };
+    }
+
+    /// Splits a tracepoint name into its parts, e.g. `"MyProvider_L3K2aGperf"` into
+    /// provider name `"MyProvider"`, level 3, keyword 0x2a, and options `"Gperf"`.
+    ///
+    /// Uses the rules for decoders: the name must be less than 256 bytes, the
+    /// provider name must be valid, the level and keyword must be present, and the
+    /// options must be well-formed. Unknown options are allowed.
+    pub fn parse(tracepoint_name: &'a str) -> Result<Self, TracepointNameError> {
+        if tracepoint_name.len() >= Self::NAME_MAX {
+            return Err(TracepointNameError::NameTooLong);
+        }
+
+        let underscore_pos = match tracepoint_name.rfind('_') {
+            Some(pos) => pos,
+            None => return Err(TracepointNameError::InvalidLevel),
+        };
+
+        let provider_name = &tracepoint_name[..underscore_pos];
+        let attribs = &tracepoint_name.as_bytes()[underscore_pos + 1..];
+
+        if attribs.first() != Some(&b'L') {
+            return Err(TracepointNameError::InvalidLevel);
+        }
+
+        let (level, level_len) = lowercase_hex_to_int(&attribs[1..]);
+        if level_len == 0 || level_len > 2 {
+            return Err(TracepointNameError::InvalidLevel);
+        }
+
+        let keyword_pos = 1 + level_len;
+        if attribs.get(keyword_pos) != Some(&b'K') {
+            return Err(TracepointNameError::InvalidKeyword);
+        }
+
+        let (keyword, keyword_len) = lowercase_hex_to_int(&attribs[keyword_pos + 1..]);
+        if keyword_len == 0 || keyword_len > 16 {
+            return Err(TracepointNameError::InvalidKeyword);
+        }
+
+        validate_provider_name(provider_name)?;
+
+        // Attributes are ASCII, so this is a char boundary.
+        let options = &tracepoint_name[underscore_pos + 2 + keyword_pos + keyword_len..];
+        validate_options(options)?;
+
+        return Ok(Self {
+            provider_name,
+            level: Level::from_int(level as u8),
+            keyword,
+            options,
+        });
+    }
+
+    /// Validates a provider's name and group name (`""` for no group) using the
+    /// rules for producers. The provider name must be non-empty and must not contain
+    /// `'\0'`, `' '`, or `':'`, the group name must contain only ASCII digits and
+    /// lowercase ASCII letters, and `provider_name.len() + group_name.len()` must be
+    /// less than 234.
+    pub fn validate_provider(
+        provider_name: &str,
+        group_name: &str,
+    ) -> Result<(), TracepointNameError> {
+        validate_provider_name(provider_name)?;
+
+        if !Self::is_valid_option_value(group_name) {
+            return Err(TracepointNameError::InvalidGroupName);
+        }
+
+        if provider_name.len() + group_name.len() >= Self::PROVIDER_NAMES_MAX {
+            return Err(TracepointNameError::NameTooLong);
+        }
+
+        return Ok(());
+    }
+
+    /// Returns true if the specified string is a valid option value, e.g. a valid
+    /// group name. A valid option value contains only ASCII digits and lowercase
+    /// ASCII letters.
+    pub const fn is_valid_option_value(value: &str) -> bool {
+        let value_bytes = value.as_bytes();
+        let mut i = 0;
+        while i < value_bytes.len() {
+            if !is_option_value_char(value_bytes[i]) {
+                return false;
+            }
+            i += 1;
+        }
+
+        return true;
+    }
+
+    /// Returns the provider name, e.g. `"MyProvider"`.
+    pub const fn provider_name(&self) -> &'a str {
+        return self.provider_name;
+    }
+
+    /// Returns the level, e.g. `Level::Warning`.
+    pub const fn level(&self) -> Level {
+        return self.level;
+    }
+
+    /// Returns the keyword (category bits), e.g. `0x2a`.
+    pub const fn keyword(&self) -> u64 {
+        return self.keyword;
+    }
+
+    /// Returns the options, e.g. `""` or `"Gperf"`.
+    pub const fn options(&self) -> &'a str {
+        return self.options;
+    }
+
+    /// Returns the value of the first option with the specified type (an uppercase
+    /// ASCII letter), or `None` if there is no such option. For example, for options
+    /// `"Gperf"`, `option(b'G')` returns `Some("perf")`.
+    pub fn option(&self, option_type: u8) -> Option<&'a str> {
+        let options_bytes = self.options.as_bytes();
+        let mut pos = 0;
+        while pos < options_bytes.len() {
+            let value_start = pos + 1;
+            let value_end = value_start + option_value_len(&options_bytes[value_start..]);
+            if options_bytes[pos] == option_type {
+                return self.options.get(value_start..value_end);
+            }
+
+            pos = value_end;
+        }
+
+        return None;
+    }
+
+    /// Returns the group name (the value of the `G` option), or `""` if there is no
+    /// group name.
+    pub fn group_name(&self) -> &'a str {
+        return self.option(b'G').unwrap_or("");
+    }
+
+    /// Returns the length of the formatted name, e.g. 16 for `"MyProvider_L3K2a"`.
+    pub fn formatted_len(&self) -> usize {
+        return self.provider_name.len()
+            + "_LK".len()
+            + hex_len(self.level.as_int() as u64)
+            + hex_len(self.keyword)
+            + self.options.len();
+    }
+}
+
+impl fmt::Display for TracepointName<'_> {
+    /// Writes the tracepoint name, e.g. `MyProvider_L3K2aGperf`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(
+            f,
+            "{}_L{:x}K{:x}{}",
+            self.provider_name,
+            self.level.as_int(),
+            self.keyword,
+            self.options
+        );
+    }
+}
+
+const fn is_option_value_char(ch: u8) -> bool {
+    return ch.is_ascii_digit() || ch.is_ascii_lowercase();
+}
+
+fn option_value_len(bytes: &[u8]) -> usize {
+    return bytes
+        .iter()
+        .position(|&ch| !is_option_value_char(ch))
+        .unwrap_or(bytes.len());
+}
+
+fn validate_provider_name(provider_name: &str) -> Result<(), TracepointNameError> {
+    return if provider_name.is_empty() || provider_name.contains(['\0', ' ', ':']) {
+        Err(TracepointNameError::InvalidProviderName)
+    } else {
+        Ok(())
+    };
+}
+
+/// Options must be `([A-Z][0-9a-z]*)*`, sorted by option type.
+fn validate_options(options: &str) -> Result<(), TracepointNameError> {
+    let options_bytes = options.as_bytes();
+    let mut prev_type = 0;
+    let mut pos = 0;
+    while pos < options_bytes.len() {
+        let ch = options_bytes[pos];
+        if !ch.is_ascii_uppercase() || ch < prev_type {
+            return Err(TracepointNameError::InvalidOptions);
+        }
+
+        prev_type = ch;
+        pos += 1 + option_value_len(&options_bytes[pos + 1..]);
+    }
+
+    return Ok(());
+}
+
+/// Returns (value, digit_count). Stops at the first non-lowercase-hex character.
+/// The value is only meaningful if digit_count <= 16.
+fn lowercase_hex_to_int(bytes: &[u8]) -> (u64, usize) {
+    let mut value: u64 = 0;
+    let mut len = 0;
+    for &ch in bytes {
+        let nibble = match ch {
+            b'0'..=b'9' => ch - b'0',
+            b'a'..=b'f' => ch - b'a' + 10,
+            _ => break,
+        };
+
+        value = (value << 4) | nibble as u64;
+        len += 1;
+    }
+
+    return (value, len);
+}
+
+const fn hex_len(value: u64) -> usize {
+    return if value == 0 {
+        1
+    } else {
+        (64 - value.leading_zeros() as usize + 3) / 4
+    };
+}
@@ -109,3 +109,273 @@ fn guid() {
         assert_eq!(g, Guid::from_u128(&v.as_u128()));
     }
 }
+
+#[test]
+fn guid_generation() {
+    use eht::Guid;
+    use eht::GuidSource;
+    use std::cell::Cell;
+
+    struct TestSource {
+        time: Cell<u64>,
+        random: u8,
+    }
+
+    impl GuidSource for TestSource {
+        fn fill_random(&self, bytes: &mut [u8]) {
+            bytes.fill(self.random);
+        }
+
+        fn unix_time_ms(&self) -> u64 {
+            self.time.get()
+        }
+    }
+
+    fn version(guid: &Guid) -> u8 {
+        guid.to_bytes_be()[6] >> 4
+    }
+
+    fn variant(guid: &Guid) -> u8 {
+        guid.to_bytes_be()[8] >> 6
+    }
+
+    // v5 matches the RFC 9562 test vector and the uuid crate.
+    let v5 = Guid::new_v5(&Guid::NAMESPACE_DNS, b"www.example.com");
+    assert_eq!(v5, Guid::from_u128(&0x2ed6657d_e927_568b_95e1_2665a8aea6a2));
+    assert_eq!(
+        Guid::NAMESPACE_URL.to_bytes_be(),
+        *uuid::Uuid::NAMESPACE_URL.as_bytes()
+    );
+    assert_ne!(Guid::new_v5(&Guid::NAMESPACE_URL, b"www.example.com"), v5);
+
+    let source = TestSource {
+        time: Cell::new(0),
+        random: 0xFF,
+    };
+    let v4 = Guid::new_v4_from(&source);
+    assert_eq!(v4, Guid::from_u128(&0xffffffff_ffff_4fff_bfff_ffffffffffff));
+    assert_eq!((version(&v4), variant(&v4)), (4, 2));
+
+    // v7 GUIDs are strictly increasing within the process.
+    #[cfg(feature = "std")]
+    {
+        let a = Guid::new_v4();
+        assert_ne!(a, Guid::new_v4());
+        assert_eq!((version(&a), variant(&a)), (4, 2));
+
+        #[cfg(target_has_atomic = "64")]
+        {
+            let mut prev = Guid::new_v7();
+            for _ in 0..1000 {
+                let next = Guid::new_v7();
+                assert!(next > prev);
+                assert_eq!((version(&next), variant(&next)), (7, 2));
+                prev = next;
+            }
+        }
+    }
+
+    // v7: timestamp in the first 48 bits, then a counter that starts in its lower
+    // half and increments within the same millisecond (or if time goes backwards).
+    #[cfg(target_has_atomic = "64")]
+    {
+        source.time.set(0xf000_0000_0000); // Later than any real clock.
+        let a = Guid::new_v7_from(&source);
+        assert_eq!(a, Guid::from_u128(&0xf0000000_0000_77ff_bfff_ffffffffffff));
+        assert_eq!((version(&a), variant(&a)), (7, 2));
+        let b = Guid::new_v7_from(&source);
+        assert_eq!(b, Guid::from_u128(&0xf0000000_0000_7800_bfff_ffffffffffff));
+        source.time.set(1);
+        let c = Guid::new_v7_from(&source);
+        assert_eq!(c, Guid::from_u128(&0xf0000000_0000_7801_bfff_ffffffffffff));
+        assert!(a < b && b < c);
+    }
+}
+
+#[test]
+fn names() {
+    use eht::*;
+    use std::string::ToString;
+
+    assert_eq!(Level::Warning.to_string(), "Warning");
+    assert_eq!(Level::from_int(9).to_string(), "9");
+    assert_eq!("warning".parse(), Ok(Level::Warning));
+    assert_eq!("VERBOSE".parse(), Ok(Level::Verbose));
+    assert_eq!("9".parse(), Ok(Level::from_int(9)));
+    assert_eq!("0x10".parse(), Ok(Level::from_int(16)));
+    assert_eq!(
+        "256".parse::<Level>().unwrap_err().to_string(),
+        "invalid Level value"
+    );
+    assert_eq!("".parse::<Level>().unwrap_err().type_name(), "Level");
+    assert!("Warn".parse::<Level>().is_err());
+
+    assert_eq!(Opcode::ActivityStart.to_string(), "ActivityStart");
+    assert_eq!(Opcode::from_int(100).to_string(), "100");
+    assert_eq!("activitystop".parse(), Ok(Opcode::ActivityStop));
+    assert_eq!("ReservedOpcode255".parse(), Ok(Opcode::ReservedOpcode255));
+
+    assert_eq!(FieldFormat::HexInt.to_string(), "HexInt");
+    assert_eq!(FieldFormat::IPv4.to_string(), "IPAddress");
+    assert_eq!("ipv6".parse(), Ok(FieldFormat::IPAddressObsolete));
+    assert_eq!(
+        FieldFormat::from_int(FieldFormat::Uuid.as_int() | FieldFormat::ChainFlag).to_string(),
+        "Uuid|ChainFlag"
+    );
+
+    let varray32 =
+        FieldEncoding::from_int(FieldEncoding::Value32.as_int() | FieldEncoding::VArrayFlag);
+    assert_eq!(varray32.to_string(), "Value32|VArrayFlag");
+    assert_eq!("value32 | varrayflag".parse(), Ok(varray32));
+    assert_eq!(FieldEncoding::from_int(0x1F).to_string(), "31");
+    assert_eq!("valuesize".parse(), Ok(FieldEncoding::ValueSize));
+
+    assert_eq!(ExtensionKind::Metadata.to_string(), "Metadata");
+    assert_eq!(
+        ExtensionKind::from_int(0x8002).to_string(),
+        "ActivityId|ChainFlag"
+    );
+    assert_eq!("0x8001".parse(), Ok(ExtensionKind::from_int(0x8001)));
+    assert!(!ExtensionKind::ActivityId.is_custom());
+    assert!(ExtensionKind::from_int(3).is_custom());
+    assert!(ExtensionKind::from_int(0x7FFF).is_custom());
+    assert!(!ExtensionKind::from_int(0x8003).is_custom());
+
+    assert_eq!(HeaderFlags::None.to_string(), "None");
+    assert_eq!(
+        HeaderFlags::from_int(0x07).to_string(),
+        "Pointer64|LittleEndian|Extension"
+    );
+    assert_eq!(HeaderFlags::from_int(0x12).to_string(), "LittleEndian|0x10");
+    assert_eq!("littleendian|0x10".parse(), Ok(HeaderFlags::from_int(0x12)));
+    assert_eq!("none".parse(), Ok(HeaderFlags::None));
+
+    let guid = Guid::from_u128(&0xa3a2a1a0_b1b0_c1c0_d7d6_d5d4d3d2d1d0);
+    assert_eq!("{a3a2a1a0-b1b0-c1c0-d7d6-d5d4d3d2d1d0}".parse(), Ok(guid));
+    assert_eq!("a3a2a1a0".parse::<Guid>().unwrap_err().type_name(), "Guid");
+
+    // Every named value round-trips.
+    for value in 0..=255u8 {
+        let level = Level::from_int(value);
+        assert_eq!(level.to_string().parse(), Ok(level));
+        let opcode = Opcode::from_int(value);
+        assert_eq!(opcode.to_string().parse(), Ok(opcode));
+        let encoding = FieldEncoding::from_int(value);
+        assert_eq!(encoding.to_string().parse(), Ok(encoding));
+        let format = FieldFormat::from_int(value);
+        assert_eq!(format.to_string().parse(), Ok(format));
+        let flags = HeaderFlags::from_int(value);
+        assert_eq!(flags.to_string().parse(), Ok(flags));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn names_serde() {
+    use eht::*;
+
+    let guid = Guid::from_u128(&0xa3a2a1a0_b1b0_c1c0_d7d6_d5d4d3d2d1d0);
+    assert_eq!(
+        serde_json::to_string(&(Level::Warning, FieldFormat::HexInt, guid)).unwrap(),
+        r#"["Warning","HexInt","a3a2a1a0-b1b0-c1c0-d7d6-d5d4d3d2d1d0"]"#
+    );
+    assert_eq!(
+        serde_json::from_str::<(Level, Level, Opcode, FieldEncoding, HeaderFlags, Guid)>(
+            r#"["warning", 5, "0x1", "Value32|CArrayFlag", "Pointer64", "a3a2a1a0b1b0c1c0d7d6d5d4d3d2d1d0"]"#
+        )
+        .unwrap(),
+        (
+            Level::Warning,
+            Level::Verbose,
+            Opcode::ActivityStart,
+            FieldEncoding::from_int(FieldEncoding::Value32.as_int() | FieldEncoding::CArrayFlag),
+            HeaderFlags::Pointer64,
+            guid
+        )
+    );
+    assert!(serde_json::from_str::<Level>("256").is_err());
+    assert!(serde_json::from_str::<Level>(r#""Bogus""#).is_err());
+    assert!(serde_json::from_str::<Guid>(r#""bogus""#).is_err());
+    assert_eq!(
+        serde_json::from_str::<ExtensionKind>("32770").unwrap(),
+        ExtensionKind::from_int(0x8002)
+    );
+}
+
+#[test]
+fn tracepoint_name() {
+    use eht::TracepointName as TN;
+    use eht::TracepointNameError as E;
+    use std::string::ToString;
+
+    let name = TN::parse("MyCompany_MyComponent_L5K1fGmygroup").unwrap();
+    assert_eq!(name.provider_name(), "MyCompany_MyComponent");
+    assert_eq!(name.level(), eht::Level::Verbose);
+    assert_eq!(name.keyword(), 0x1f);
+    assert_eq!(name.options(), "Gmygroup");
+    assert_eq!(name.group_name(), "mygroup");
+    assert_eq!(name.option(b'X'), None);
+    assert_eq!(name.to_string(), "MyCompany_MyComponent_L5K1fGmygroup");
+    assert_eq!(name.formatted_len(), 35);
+
+    let name = TN::parse("P_L3K0AbGcZ").unwrap();
+    assert_eq!(name.options(), "AbGcZ");
+    assert_eq!(name.option(b'A'), Some("b"));
+    assert_eq!(name.option(b'Z'), Some(""));
+    assert_eq!(name.group_name(), "c");
+
+    assert_eq!(TN::parse("P"), Err(E::InvalidLevel));
+    assert_eq!(TN::parse("P_K1"), Err(E::InvalidLevel));
+    assert_eq!(TN::parse("P_L1"), Err(E::InvalidKeyword));
+    assert_eq!(TN::parse("_L1K1"), Err(E::InvalidProviderName));
+    assert_eq!(TN::parse("P Q_L1K1"), Err(E::InvalidProviderName));
+    assert_eq!(TN::parse("P_L1K1g"), Err(E::InvalidOptions));
+    assert_eq!(TN::parse("P_L1K1GaAb"), Err(E::InvalidOptions));
+    assert_eq!(TN::parse(&"P".repeat(251)), Err(E::InvalidLevel));
+    assert_eq!(
+        TN::parse(&(("P".repeat(251)) + "_L1K1")),
+        Err(E::NameTooLong)
+    );
+
+    let level = eht::Level::Warning;
+    assert_eq!(
+        TN::new("P", level, 0x2a, "").unwrap().to_string(),
+        "P_L3K2a"
+    );
+    assert_eq!(TN::new("P", level, 0, "Gabc").unwrap().group_name(), "abc");
+    assert_eq!(TN::new("", level, 0, ""), Err(E::InvalidProviderName));
+    assert_eq!(TN::new("P:Q", level, 0, ""), Err(E::InvalidProviderName));
+    assert_eq!(TN::new("P", level, 0, "GAbc"), Err(E::InvalidOptions));
+    assert_eq!(TN::new("P", level, 0, "Aa"), Err(E::UnknownOption(b'A')));
+    assert!(TN::new(&"P".repeat(250), level, 0xf, "").is_ok());
+    assert_eq!(
+        TN::new(&"P".repeat(250), level, 0x10, ""),
+        Err(E::NameTooLong)
+    );
+
+    assert_eq!(TN::validate_provider("P", "mygroup"), Ok(()));
+    assert_eq!(
+        TN::validate_provider("P", "MyGroup"),
+        Err(E::InvalidGroupName)
+    );
+    assert_eq!(TN::validate_provider(&"P".repeat(230), "abc"), Ok(()));
+    assert_eq!(
+        TN::validate_provider(&"P".repeat(230), "abcd"),
+        Err(E::NameTooLong)
+    );
+    assert!(TN::is_valid_option_value("abc123"));
+    assert!(!TN::is_valid_option_value("abc_123"));
+    assert_eq!(E::UnknownOption(b'A').to_string(), "unknown option 'A'");
+
+    // Round-trip every level and a range of keywords.
+    for level in 0..=255u8 {
+        let keyword = (level as u64) << (level % 57);
+        let text = TN::new("P", eht::Level::from_int(level), keyword, "Gg")
+            .unwrap()
+            .to_string();
+        let name = TN::parse(&text).unwrap();
+        assert_eq!(name.level().as_int(), level);
+        assert_eq!(name.keyword(), keyword);
+        assert_eq!(name.formatted_len(), text.len());
+    }
+}
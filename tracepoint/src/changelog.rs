@@ -6,6 +6,13 @@
 #[allow(unused_imports)]
 use crate::*; // For docs
 
+/// # v0.6.0 (TBD)
+/// - `TracepointState` now uses `#[repr(C)]` layout.
+/// - New `TracepointState::register_local` and `TracepointState::is_local` for
+///   in-process tracepoints that are not connected to the kernel.
+/// - New `EventDataDescriptor::as_bytes`.
+pub mod v0_6_0 {}
+
 /// # v0.5.0 (2025-11-26)
 /// - Fix clippy issues for Rust 1.91.
 pub mod v0_5_0 {}
@@ -34,6 +34,15 @@ impl<'a> EventDataDescriptor<'a> {
         return self.size == 0;
     }
 
+    /// Returns the bytes referenced by this descriptor.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        return if self.size == 0 {
+            &[]
+        } else {
+            unsafe { core::slice::from_raw_parts(self.ptr as *const u8, self.size) }
+        };
+    }
+
     /// Returns an EventDataDescriptor initialized with the specified ptr and size.
     ///
     /// # Safety
@@ -279,6 +279,9 @@ impl Drop for UserEventsDataFile {
 }
 
 /// Low-level API: Represents a tracepoint registration.
+///
+/// Uses `#[repr(C)]` layout (two `u32` values) so that the state's size is stable.
+#[repr(C)]
 pub struct TracepointState {
     /// The kernel will update this variable with tracepoint enable/disable state.
     /// It will be 0 if tracepoint is disabled, nonzero if tracepoint is enabled.
@@ -294,7 +297,8 @@ pub struct TracepointState {
 impl TracepointState {
     const UNREGISTERED_WRITE_INDEX: u32 = u32::MAX;
     const BUSY_WRITE_INDEX: u32 = u32::MAX - 1;
-    const HIGHEST_VALID_WRITE_INDEX: u32 = u32::MAX - 2;
+    const LOCAL_WRITE_INDEX: u32 = u32::MAX - 2;
+    const HIGHEST_VALID_WRITE_INDEX: u32 = u32::MAX - 3;
 
     #[cfg(all(target_os = "linux", feature = "user_events"))]
     const IOC_WRITE: ffi::c_ulong = 1;
@@ -365,6 +369,10 @@ impl TracepointState {
             Self::UNREGISTERED_WRITE_INDEX => {
                 error = 116; // EALREADY: Already unregistered. No action needed.
             }
+            Self::LOCAL_WRITE_INDEX => {
+                error = 0;
+                self.enable_status.store(0, Ordering::Relaxed);
+            }
             _ => {
                 #[cfg(not(all(target_os = "linux", feature = "user_events")))]
                 {
@@ -412,6 +420,38 @@ impl TracepointState {
         return error;
     }
 
+    /// Advanced: Registers this tracepoint as a local (in-process) tracepoint, e.g.
+    /// for testing code that writes events.
+    ///
+    /// Requires: this `TracepointState` is not currently registered.
+    ///
+    /// A local tracepoint is not connected to the kernel. It is enabled until it is
+    /// unregistered, but `write` and `write_with_headers` do nothing and return
+    /// `EBADF`. Use [`TracepointState::is_local`] to detect a local tracepoint and
+    /// handle its events in-process.
+    pub fn register_local(self: Pin<&Self>) {
+        let old_write_index = self
+            .write_index
+            .swap(Self::BUSY_WRITE_INDEX, Ordering::Relaxed);
+        assert!(
+            old_write_index == Self::UNREGISTERED_WRITE_INDEX,
+            "register of active tracepoint (already-registered or being-unregistered)"
+        );
+
+        self.enable_status.store(1, Ordering::Relaxed);
+
+        let old_write_index = self
+            .write_index
+            .swap(Self::LOCAL_WRITE_INDEX, Ordering::Relaxed);
+        debug_assert!(old_write_index == Self::BUSY_WRITE_INDEX);
+    }
+
+    /// Returns true if this tracepoint was registered via
+    /// [`TracepointState::register_local`] and has not been unregistered.
+    pub fn is_local(&self) -> bool {
+        return self.write_index.load(Ordering::Relaxed) == Self::LOCAL_WRITE_INDEX;
+    }
+
     /// Registers this tracepoint.
     ///
     /// Requires: this `TracepointState` is not currently registered.
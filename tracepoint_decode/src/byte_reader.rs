@@ -188,11 +188,11 @@ mod tests {
             !TARGET_BIG_ENDIAN,
             PerfByteReader::SWAP_ENDIAN.source_big_endian()
         );
-        assert_eq!(false, PerfByteReader::new(false).source_big_endian());
-        assert_eq!(true, PerfByteReader::new(true).source_big_endian());
+        assert!(!PerfByteReader::new(false).source_big_endian());
+        assert!(PerfByteReader::new(true).source_big_endian());
 
-        assert_eq!(false, PerfByteReader::KEEP_ENDIAN.byte_swap_needed());
-        assert_eq!(true, PerfByteReader::SWAP_ENDIAN.byte_swap_needed());
+        assert!(!PerfByteReader::KEEP_ENDIAN.byte_swap_needed());
+        assert!(PerfByteReader::SWAP_ENDIAN.byte_swap_needed());
         assert_eq!(
             TARGET_BIG_ENDIAN,
             PerfByteReader::new(false).byte_swap_needed()
@@ -6,6 +6,20 @@
 #[allow(unused_imports)]
 use crate::*; // For docs
 
+/// # v0.6.0 (TBD)
+/// - New `EventHeaderEventInfo::tracepoint_name_parts` method, returning the
+///   provider name, level, keyword, and options as an
+///   `eventheader_types::TracepointName`.
+/// - New [`EventSchema`], [`FieldSchema`], and [`FieldArrayKind`] types: an owned
+///   model of an event's name, tag, and fields that can be decoded from and encoded
+///   to the EventHeader metadata extension. New
+///   [`EventHeaderEventInfo::metadata`] method.
+/// - New [`EventHeaderEventInfo::extensions`] method that returns an
+///   [`EventHeaderExtensionIter`] over the event's header extension blocks (kind,
+///   chain flag, and data). New [`PerfMetaOptions::Extensions`] flag that adds
+///   provider-defined extension blocks to `json_meta_display` output as hex.
+pub mod v0_6_0 {}
+
 /// # v0.5.0 (2025-11-26)
 /// - Fix clippy issues for Rust 1.91.
 pub mod v0_5_0 {}
@@ -306,8 +306,8 @@ mod tests {
                 }
 
                 let mut str = string::String::new();
-                for j in 0..len {
-                    str.push(char::from_u32(buf4[j] as u32).unwrap());
+                for ch in buf4.iter().take(len) {
+                    str.push(char::from_u32(*ch as u32).unwrap());
                 }
                 check(&str, &buf4[..len]);
             }
@@ -348,7 +348,7 @@ mod tests {
 
             actual.clear();
             let mut actual_writer = filters::WriteFilter::new(&mut actual);
-            write_utf16_to::<false, _>(&input_big_endian, &mut actual_writer).unwrap();
+            write_utf16_to::<false, _>(input_big_endian, &mut actual_writer).unwrap();
             assert_eq!(expected, actual);
         }
 
@@ -387,7 +387,7 @@ mod tests {
 
             actual.clear();
             let mut actual_writer = filters::WriteFilter::new(&mut actual);
-            write_utf32_to::<false, _>(&input_big_endian, &mut actual_writer).unwrap();
+            write_utf32_to::<false, _>(input_big_endian, &mut actual_writer).unwrap();
             assert_eq!(expected, actual);
         }
 
@@ -483,6 +483,28 @@ impl<'inf> EventHeaderJsonMetaDisplay<'inf> {
             })?;
         }
 
+        if self.meta_options.has_flag(PerfMetaOptions::Extensions) {
+            let mut extensions = self
+                .eh_event_info
+                .extensions()
+                .filter(|ext| ext.kind().is_custom())
+                .peekable();
+            if extensions.peek().is_some() {
+                any_written = true;
+                json.write_property_name_json_safe("extensions")?;
+                json.write_array_begin()?;
+                for ext in extensions {
+                    json.write_object_begin()?;
+                    json.write_property_name_json_safe("kind")?;
+                    json.write_value(|w| w.write_json_hex32(ext.kind().as_int() as u32))?;
+                    json.write_property_name_json_safe("data")?;
+                    json.write_value_quoted(|w| w.write_hexbytes(ext.data()))?;
+                    json.write_object_end()?;
+                }
+                json.write_array_end()?;
+            }
+        }
+
         return Ok(any_written);
     }
 }
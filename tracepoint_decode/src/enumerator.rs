@@ -210,8 +210,10 @@ pub struct EventHeaderEventInfo<'nam, 'dat> {
     event_data: &'dat [u8],
     name_start: u32,
     name_len: u32,
+    meta_len: u32,
     activity_id_start: u32,
     activity_id_len: u8,
+    data_start: u32,
     header: EventHeader,
     keyword: u64,
 }
@@ -271,6 +273,8 @@ impl<'nam, 'dat> EventHeaderEventInfo<'nam, 'dat> {
     /// - `"relatedActivity": "12345678-1234-1234-1234-1234567890AB"` (omitted if not present)
     /// - `"options": "Gmygroup"` (omitted if not present, off by default)
     /// - `"flags": "0x7"` (omitted if zero, off by default)
+    /// - `"extensions": [ { "kind": "0x3", "data": "01 02" } ]` (provider-defined
+    ///   extension blocks, omitted if none, off by default)
     pub fn json_meta_display<'inf>(
         &'inf self,
         sample_event_info: Option<&'inf PerfSampleEventInfo>,
@@ -296,6 +300,15 @@ impl<'nam, 'dat> EventHeaderEventInfo<'nam, 'dat> {
         return &self.event_data[start..end];
     }
 
+    /// Returns the content of the event's metadata extension, i.e. the nul-terminated
+    /// event name followed by the field metadata. Use [`EventSchema::decode`](crate::EventSchema::decode) to
+    /// decode it.
+    pub fn metadata(&self) -> &'dat [u8] {
+        let start = self.name_start as usize;
+        let end = start + self.meta_len as usize;
+        return &self.event_data[start..end];
+    }
+
     /// Returns a formatter for the the event's name. The formatter tries to interpret
     /// the field name as UTF-8, but falls back to Latin1 for any invalid UTF-8 sequences.
     pub fn name_display(&self) -> display::Utf8WithLatin1FallbackDisplay<'dat> {
@@ -316,6 +329,23 @@ impl<'nam, 'dat> EventHeaderEventInfo<'nam, 'dat> {
         return self.activity_id_len;
     }
 
+    /// Returns an iterator over the event's header extension blocks, in the order
+    /// they appear in the event. This includes the `Metadata` and `ActivityId`
+    /// blocks as well as any provider-defined blocks.
+    pub fn extensions(&self) -> EventHeaderExtensionIter<'dat> {
+        let start = if self.header.flags.has_flag(HeaderFlags::Extension) {
+            mem::size_of::<EventHeader>()
+        } else {
+            self.data_start as usize
+        };
+        return EventHeaderExtensionIter {
+            data: &self.event_data[start..self.data_start as usize],
+            byte_reader: PerfByteReader::new(
+                !self.header.flags.has_flag(HeaderFlags::LittleEndian),
+            ),
+        };
+    }
+
     /// Returns the event header (contains level, opcode, tag, id, version).
     pub fn header(&self) -> EventHeader {
         return self.header;
@@ -326,6 +356,21 @@ impl<'nam, 'dat> EventHeaderEventInfo<'nam, 'dat> {
         return self.keyword;
     }
 
+    /// Returns the parts of `tracepoint_name` (provider name, level, keyword, options).
+    ///
+    /// The parts are extracted using the same rules as [`EventHeaderEventInfo::provider_name`]
+    /// and [`EventHeaderEventInfo::options`]. The decoder accepts some names that
+    /// [`TracepointName::parse`] rejects (e.g. options that are not sorted), so the
+    /// result might not be a valid name for a new tracepoint.
+    pub fn tracepoint_name_parts(&self) -> TracepointName<'nam> {
+        return TracepointName::new_unchecked(
+            self.provider_name(),
+            self.header.level,
+            self.keyword,
+            self.options(),
+        );
+    }
+
     /// Returns the provider name (extracted from `tracepoint_name`).
     pub fn provider_name(&self) -> &'nam str {
         let result = if let Some(underscore_pos) = self.tracepoint_name.rfind('_') {
@@ -392,6 +437,64 @@ impl<'nam, 'dat> EventHeaderEventInfo<'nam, 'dat> {
     }
 }
 
+/// An EventHeader header extension block, returned by [`EventHeaderExtensionIter`].
+#[derive(Clone, Copy, Debug)]
+pub struct EventHeaderExtensionInfo<'dat> {
+    kind: ExtensionKind,
+    data: &'dat [u8],
+}
+
+impl<'dat> EventHeaderExtensionInfo<'dat> {
+    /// Returns the block's kind, without the chain flag, e.g. `Metadata` or a
+    /// provider-defined kind.
+    pub fn kind(&self) -> ExtensionKind {
+        return ExtensionKind::from_int(self.kind.as_int() & ExtensionKind::ValueMask);
+    }
+
+    /// Returns true if the block's chain flag is set, i.e. if another extension block
+    /// follows this one.
+    pub fn chain(&self) -> bool {
+        return self
+            .kind
+            .has_flag(ExtensionKind::from_int(ExtensionKind::ChainFlag));
+    }
+
+    /// Returns the block's data (not including the block's header).
+    pub fn data(&self) -> &'dat [u8] {
+        return self.data;
+    }
+}
+
+/// Iterator over the header extension blocks of an EventHeader event. Returned by
+/// [`EventHeaderEventInfo::extensions`].
+#[derive(Clone, Debug)]
+pub struct EventHeaderExtensionIter<'dat> {
+    data: &'dat [u8],
+    byte_reader: PerfByteReader,
+}
+
+impl<'dat> Iterator for EventHeaderExtensionIter<'dat> {
+    type Item = EventHeaderExtensionInfo<'dat>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The blocks were validated by enumerate, so they should be well-formed.
+        const HEADER_SIZE: usize = mem::size_of::<EventHeaderExtension>();
+        if self.data.len() < HEADER_SIZE {
+            return None;
+        }
+
+        let size = self.byte_reader.read_u16(self.data) as usize;
+        let kind = ExtensionKind::from_int(self.byte_reader.read_u16(&self.data[2..]));
+        let end = (HEADER_SIZE + size).min(self.data.len());
+        let item = EventHeaderExtensionInfo {
+            kind,
+            data: &self.data[HEADER_SIZE..end],
+        };
+        self.data = &self.data[end..];
+        return Some(item);
+    }
+}
+
 /// Provides access to the name and value of an EventHeader event item. An item is a
 /// field of the event or an element of an array field of the event. This struct is
 /// returned by the `item_info()` method of [`EventHeaderEnumerator`].
@@ -526,8 +629,10 @@ impl<'ctx, 'nam, 'dat> EventHeaderEnumerator<'ctx, 'nam, 'dat> {
             tracepoint_name: self.tracepoint_name,
             name_start: self.context.meta_start,
             name_len: self.context.event_name_len as u32,
+            meta_len: self.context.meta_end - self.context.meta_start,
             activity_id_start: self.context.activity_id_start,
             activity_id_len: self.context.activity_id_len,
+            data_start: self.context.data_start,
             header: self.context.header,
             keyword: self.context.keyword,
         };
@@ -850,18 +955,15 @@ impl EventHeaderEnumeratorContext {
         event_data: &'dat [u8],
         move_next_limit: u32,
     ) -> Result<EventHeaderEnumerator<'ctx, 'nam, 'dat>, EventHeaderEnumeratorError> {
-        const EVENT_HEADER_TRACEPOINT_NAME_MAX: usize = 256;
-
         const KNOWN_FLAGS: u8 = HeaderFlags::Pointer64.as_int()
             | HeaderFlags::LittleEndian.as_int()
             | HeaderFlags::Extension.as_int();
 
         let mut event_pos = 0;
-        let tp_name_bytes = tracepoint_name.as_bytes();
 
         if event_data.len() < mem::size_of::<EventHeader>()
             || event_data.len() >= 0x80000000
-            || tp_name_bytes.len() >= EVENT_HEADER_TRACEPOINT_NAME_MAX
+            || tracepoint_name.len() >= TracepointName::NAME_MAX
         {
             // Event has no header or tracepoint_name too long.
             return Err(EventHeaderEnumeratorError::InvalidParameter);
@@ -890,7 +992,10 @@ impl EventHeaderEnumeratorContext {
         }
 
         // Validate Tracepoint name (e.g. "ProviderName_L1K2..."), extract keyword.
+        // This is intentionally more lenient than TracepointName::parse so that
+        // events from older or non-conforming producers can still be decoded.
 
+        let tp_name_bytes = tracepoint_name.as_bytes();
         let mut attrib_pos = tp_name_bytes.len();
         loop {
             if attrib_pos == 0 {
@@ -1941,6 +2046,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lenient_names() {
+        // Flags = Pointer64 | LittleEndian | Extension, level = 1, then a Metadata
+        // extension block containing only the event name "E".
+        let event_data = [7, 0, 0, 0, 0, 0, 0, 1, 2, 0, 1, 0, b'E', 0];
+        let mut ctx = EventHeaderEnumeratorContext::new();
+
+        // Names that TracepointName::parse rejects but the decoder accepts.
+        for (name, provider_name, options) in [
+            ("P_L1K1HfooGbar", "P", "HfooGbar"),
+            ("P_L001K1", "P", ""),
+            ("P_L1K01234567890abcdef0", "P", ""),
+            ("P-Q.R_L1K1", "P-Q.R", ""),
+        ] {
+            let info = ctx
+                .enumerate_with_name_and_data(name, &event_data, 100)
+                .unwrap()
+                .event_info();
+            assert_eq!(info.provider_name(), provider_name, "{}", name);
+            assert_eq!(info.options(), options, "{}", name);
+            assert_eq!(info.tracepoint_name_parts().options(), options, "{}", name);
+        }
+
+        // Names without Level or Keyword, or with invalid attributes, are rejected.
+        for name in ["P_K1", "P_L1", "P_L2K1", "P_L1K1hfoo", "P_L1K1G_", "PL1K1"] {
+            assert_eq!(
+                ctx.enumerate_with_name_and_data(name, &event_data, 100)
+                    .err(),
+                Some(EventHeaderEnumeratorError::NotSupported),
+                "{}",
+                name
+            );
+        }
+    }
+
     fn strnlen(bytes: &[u8]) -> usize {
         let mut len = 0;
         while len < bytes.len() && bytes[len] != 0 {
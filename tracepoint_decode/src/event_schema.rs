@@ -0,0 +1,615 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT license.
+
+extern crate alloc;
+
+use alloc::string;
+use alloc::string::ToString;
+use alloc::vec;
+
+use crate::*;
+use eventheader_types::*;
+
+/// The array kind of a [`FieldSchema`], i.e. whether the field is a single value, an
+/// array with a length fixed in the metadata, or an array with a length stored in
+/// the event payload.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum FieldArrayKind {
+    /// Not an array (neither `CArrayFlag` nor `VArrayFlag`).
+    #[default]
+    Scalar,
+
+    /// Array whose element count is stored in the metadata (`CArrayFlag`). The count
+    /// must not be 0.
+    Constant(u16),
+
+    /// Array whose element count is stored in the payload before the elements
+    /// (`VArrayFlag`).
+    Variable,
+}
+
+/// Owned description of one field of an [`EventSchema`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FieldSchema {
+    /// The field's name. May include attributes, e.g. `"Name;attrib=value"`.
+    pub name: string::String,
+
+    /// The field's encoding, without flags (the array kind is stored in `array`).
+    pub encoding: FieldEncoding,
+
+    /// The field's format, without flags. Ignored for [`FieldEncoding::Struct`]
+    /// fields (the struct's field count is `fields.len()`).
+    pub format: FieldFormat,
+
+    /// The field's tag, or 0 for no tag.
+    pub tag: u16,
+
+    /// Whether the field is a scalar, a constant-length array, or a
+    /// variable-length array.
+    pub array: FieldArrayKind,
+
+    /// For [`FieldEncoding::Struct`] fields, the struct's fields (1 to 127 of them).
+    /// Empty for other fields.
+    pub fields: vec::Vec<FieldSchema>,
+}
+
+impl FieldSchema {
+    /// Returns a scalar, untagged field with the specified name, encoding, and format.
+    pub fn new(name: &str, encoding: FieldEncoding, format: FieldFormat) -> Self {
+        return Self {
+            name: name.to_string(),
+            encoding,
+            format,
+            ..Self::default()
+        };
+    }
+
+    /// Returns a scalar, untagged struct field containing the specified fields.
+    pub fn new_struct(name: &str, fields: vec::Vec<FieldSchema>) -> Self {
+        return Self {
+            name: name.to_string(),
+            encoding: FieldEncoding::Struct,
+            fields,
+            ..Self::default()
+        };
+    }
+
+    /// Returns this field with the specified tag.
+    pub fn with_tag(mut self, tag: u16) -> Self {
+        self.tag = tag;
+        return self;
+    }
+
+    /// Returns this field with the specified array kind.
+    pub fn with_array(mut self, array: FieldArrayKind) -> Self {
+        self.array = array;
+        return self;
+    }
+}
+
+/// Owned description of an EventHeader event's schema: the event name, tag, and
+/// fields, i.e. the information stored in the event's metadata extension (plus the
+/// tag from the event's header).
+///
+/// Use [`EventSchema::decode`] or [`EventSchema::from_event_info`] to get the schema
+/// of an event, e.g. to compare schemas or to generate documentation. Use
+/// [`EventSchema::encode`] to get the metadata bytes for the schema, e.g. to write
+/// events with `EventProvider::write_encoded`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EventSchema {
+    /// The event's name. May include attributes, e.g. `"Name;attrib=value"`.
+    pub name: string::String,
+
+    /// The event's tag (stored in the event header, not in the metadata), or 0 for no
+    /// tag.
+    pub tag: u16,
+
+    /// The event's top-level fields.
+    pub fields: vec::Vec<FieldSchema>,
+}
+
+impl EventSchema {
+    /// Returns an untagged schema with the specified name and no fields.
+    pub fn new(name: &str) -> Self {
+        return Self {
+            name: name.to_string(),
+            ..Self::default()
+        };
+    }
+
+    /// Decodes the schema from the content of an event's metadata extension. The
+    /// returned schema's tag is 0.
+    ///
+    /// - `metadata` is the metadata extension's data, starting with the nul-terminated
+    ///   event name, e.g. from [`EventHeaderEventInfo::metadata`].
+    /// - `byte_reader` specifies the byte order of the event (used for field tags and
+    ///   array lengths).
+    ///
+    /// Returns [`EventHeaderEnumeratorError::InvalidData`] if the metadata is
+    /// truncated or has a struct with no fields or a constant-length array with no
+    /// elements, [`EventHeaderEnumeratorError::NotSupported`] if a field has both
+    /// `CArrayFlag` and `VArrayFlag`, or [`EventHeaderEnumeratorError::StackOverflow`]
+    /// if structs are nested more than 8 deep.
+    pub fn decode(
+        metadata: &[u8],
+        byte_reader: PerfByteReader,
+    ) -> Result<Self, EventHeaderEnumeratorError> {
+        let name_len = match metadata.iter().position(|&b| b == 0) {
+            Some(len) => len,
+            None => return Err(EventHeaderEnumeratorError::InvalidData),
+        };
+
+        let mut decoder = SchemaDecoder {
+            metadata,
+            pos: name_len + 1,
+            byte_reader,
+        };
+        let mut fields = vec::Vec::new();
+        decoder.decode_fields(&mut fields, usize::MAX, 0)?;
+
+        return Ok(Self {
+            name: bytes_to_string(&metadata[..name_len]),
+            tag: 0,
+            fields,
+        });
+    }
+
+    /// Decodes the schema of the event described by `event_info`, including the tag
+    /// from its header.
+    pub fn from_event_info(
+        event_info: &EventHeaderEventInfo,
+    ) -> Result<Self, EventHeaderEnumeratorError> {
+        let header = event_info.header();
+        let byte_reader = PerfByteReader::new(!header.flags.has_flag(HeaderFlags::LittleEndian));
+        let mut schema = Self::decode(event_info.metadata(), byte_reader)?;
+        schema.tag = header.tag;
+        return Ok(schema);
+    }
+
+    /// Returns the content of the metadata extension for this schema, i.e. the
+    /// nul-terminated event name followed by the encoded fields. Multi-byte values are
+    /// written in the byte order specified by `byte_reader`. The tag is not included
+    /// (it belongs in the event header).
+    ///
+    /// Returns [`EventHeaderEnumeratorError::InvalidParameter`] if a name contains
+    /// `'\0'`, an encoding has flags, a format has `ChainFlag`, a struct field does
+    /// not have 1 to 127 fields, a non-struct field has fields, or a constant-length
+    /// array has a length of 0. Returns [`EventHeaderEnumeratorError::StackOverflow`]
+    /// if structs are nested more than 8 deep.
+    pub fn encode(
+        &self,
+        byte_reader: PerfByteReader,
+    ) -> Result<vec::Vec<u8>, EventHeaderEnumeratorError> {
+        let mut metadata = vec::Vec::new();
+        self.encode_to(&mut metadata, byte_reader)?;
+        return Ok(metadata);
+    }
+
+    /// Appends the content of the metadata extension for this schema to `metadata`.
+    /// Same as [`EventSchema::encode`], but reuses an existing buffer. If an error is
+    /// returned, `metadata` is unchanged.
+    pub fn encode_to(
+        &self,
+        metadata: &mut vec::Vec<u8>,
+        byte_reader: PerfByteReader,
+    ) -> Result<(), EventHeaderEnumeratorError> {
+        let old_len = metadata.len();
+        let result = encode_name(metadata, &self.name)
+            .and_then(|_| encode_fields(metadata, &self.fields, byte_reader, 0));
+        if result.is_err() {
+            metadata.truncate(old_len);
+        }
+
+        return result;
+    }
+}
+
+struct SchemaDecoder<'dat> {
+    metadata: &'dat [u8],
+    pos: usize,
+    byte_reader: PerfByteReader,
+}
+
+impl SchemaDecoder<'_> {
+    /// Decodes up to `count` fields, stopping early at end of metadata (which is
+    /// legal, matching the behavior of `EventHeaderEnumerator`).
+    fn decode_fields(
+        &mut self,
+        fields: &mut vec::Vec<FieldSchema>,
+        count: usize,
+        depth: u8,
+    ) -> Result<(), EventHeaderEnumeratorError> {
+        let mut remaining = count;
+        while remaining != 0 && self.pos != self.metadata.len() {
+            fields.push(self.decode_field(depth)?);
+            remaining -= 1;
+        }
+
+        return Ok(());
+    }
+
+    fn decode_field(&mut self, depth: u8) -> Result<FieldSchema, EventHeaderEnumeratorError> {
+        let metadata = self.metadata;
+        let name_start = self.pos;
+        let name_len = match metadata[name_start..].iter().position(|&b| b == 0) {
+            // Need nul + encoding.
+            Some(len) if metadata.len() - name_start - len >= 2 => len,
+            _ => return Err(EventHeaderEnumeratorError::InvalidData),
+        };
+        self.pos = name_start + name_len + 1;
+
+        let encoding = FieldEncoding::from_int(metadata[self.pos]);
+        self.pos += 1;
+
+        let mut format = FieldFormat::Default;
+        let mut tag = 0;
+        if encoding.has_chain_flag() {
+            format = FieldFormat::from_int(*metadata.get(self.pos).ok_or(
+                EventHeaderEnumeratorError::InvalidData, // Missing format.
+            )?);
+            self.pos += 1;
+            if format.has_chain_flag() {
+                tag = self.read_u16()?;
+            }
+        }
+
+        let array = if !encoding.is_array() {
+            FieldArrayKind::Scalar
+        } else if !encoding.is_constant_length_array() {
+            FieldArrayKind::Variable
+        } else if !encoding.is_variable_length_array() {
+            match self.read_u16()? {
+                0 => return Err(EventHeaderEnumeratorError::InvalidData),
+                count => FieldArrayKind::Constant(count),
+            }
+        } else {
+            // Both CArrayFlag and VArrayFlag are set (reserved encoding).
+            return Err(EventHeaderEnumeratorError::NotSupported);
+        };
+
+        let mut field = FieldSchema {
+            name: bytes_to_string(&metadata[name_start..name_start + name_len]),
+            encoding: encoding.without_flags(),
+            format: format.without_flags(),
+            tag,
+            array,
+            fields: vec::Vec::new(),
+        };
+
+        if field.encoding == FieldEncoding::Struct {
+            let count = field.format.as_int();
+            if count == 0 {
+                // Struct must have at least 1 field.
+                return Err(EventHeaderEnumeratorError::InvalidData);
+            } else if depth == EventHeaderEnumeratorContext::STRUCT_NEST_LIMIT {
+                return Err(EventHeaderEnumeratorError::StackOverflow);
+            }
+
+            field.format = FieldFormat::Default;
+            self.decode_fields(&mut field.fields, count as usize, depth + 1)?;
+        }
+
+        return Ok(field);
+    }
+
+    fn read_u16(&mut self) -> Result<u16, EventHeaderEnumeratorError> {
+        if self.metadata.len() - self.pos < 2 {
+            return Err(EventHeaderEnumeratorError::InvalidData);
+        }
+
+        let value = self.byte_reader.read_u16(&self.metadata[self.pos..]);
+        self.pos += 2;
+        return Ok(value);
+    }
+}
+
+fn bytes_to_string(bytes: &[u8]) -> string::String {
+    return display::Utf8WithLatin1FallbackDisplay::new(bytes).to_string();
+}
+
+fn write_u16(metadata: &mut vec::Vec<u8>, value: u16, byte_reader: PerfByteReader) {
+    metadata.extend_from_slice(&byte_reader.fix_u16(value).to_ne_bytes());
+}
+
+fn encode_name(metadata: &mut vec::Vec<u8>, name: &str) -> Result<(), EventHeaderEnumeratorError> {
+    if name.contains('\0') {
+        return Err(EventHeaderEnumeratorError::InvalidParameter);
+    }
+
+    metadata.extend_from_slice(name.as_bytes());
+    metadata.push(0);
+    return Ok(());
+}
+
+fn encode_fields(
+    metadata: &mut vec::Vec<u8>,
+    fields: &[FieldSchema],
+    byte_reader: PerfByteReader,
+    depth: u8,
+) -> Result<(), EventHeaderEnumeratorError> {
+    for field in fields {
+        if field.encoding.as_int() & FieldEncoding::FlagMask != 0 || field.format.has_chain_flag() {
+            return Err(EventHeaderEnumeratorError::InvalidParameter);
+        }
+
+        let is_struct = field.encoding == FieldEncoding::Struct;
+        let format = if is_struct {
+            if field.fields.is_empty() || field.fields.len() > FieldFormat::ValueMask as usize {
+                return Err(EventHeaderEnumeratorError::InvalidParameter);
+            } else if depth == EventHeaderEnumeratorContext::STRUCT_NEST_LIMIT {
+                return Err(EventHeaderEnumeratorError::StackOverflow);
+            }
+
+            field.fields.len() as u8
+        } else if !field.fields.is_empty() {
+            return Err(EventHeaderEnumeratorError::InvalidParameter);
+        } else {
+            field.format.as_int()
+        };
+
+        let encoding = match field.array {
+            FieldArrayKind::Scalar => field.encoding.as_int(),
+            FieldArrayKind::Constant(0) => {
+                return Err(EventHeaderEnumeratorError::InvalidParameter)
+            }
+            FieldArrayKind::Constant(_) => field.encoding.as_int() | FieldEncoding::CArrayFlag,
+            FieldArrayKind::Variable => field.encoding.as_int() | FieldEncoding::VArrayFlag,
+        };
+
+        encode_name(metadata, &field.name)?;
+
+        // Same layout as EventBuilder: format only if needed, tag only if non-zero.
+        if field.tag != 0 {
+            metadata.push(encoding | FieldEncoding::ChainFlag);
+            metadata.push(format | FieldFormat::ChainFlag);
+            write_u16(metadata, field.tag, byte_reader);
+        } else if format != 0 {
+            metadata.push(encoding | FieldEncoding::ChainFlag);
+            metadata.push(format);
+        } else {
+            metadata.push(encoding);
+        }
+
+        if let FieldArrayKind::Constant(count) = field.array {
+            write_u16(metadata, count, byte_reader);
+        }
+
+        if is_struct {
+            encode_fields(metadata, &field.fields, byte_reader, depth + 1)?;
+        }
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::vec::Vec;
+
+    use super::*;
+
+    const LE: PerfByteReader = PerfByteReader::new(false);
+    const BE: PerfByteReader = PerfByteReader::new(true);
+
+    #[test]
+    fn encode_decode() {
+        let schema = EventSchema {
+            name: "Event;a=b".into(),
+            tag: 0x1234,
+            fields: vec![
+                FieldSchema::new("u8", FieldEncoding::Value8, FieldFormat::Default),
+                FieldSchema::new("hex", FieldEncoding::Value32, FieldFormat::HexInt)
+                    .with_tag(0x102),
+                FieldSchema::new("arr", FieldEncoding::Value16, FieldFormat::Default)
+                    .with_array(FieldArrayKind::Constant(0x304)),
+                FieldSchema::new_struct(
+                    "s",
+                    vec![
+                        FieldSchema::new("z", FieldEncoding::ZStringChar8, FieldFormat::Default)
+                            .with_array(FieldArrayKind::Variable),
+                        FieldSchema::new_struct(
+                            "inner",
+                            vec![FieldSchema::new(
+                                "b",
+                                FieldEncoding::BinaryLength16Char8,
+                                FieldFormat::Default,
+                            )],
+                        ),
+                    ],
+                )
+                .with_tag(0x506),
+            ],
+        };
+
+        let le = schema.encode(LE).unwrap();
+        assert_eq!(
+            le,
+            b"Event;a=b\0\
+            u8\0\x02\
+            hex\0\x84\x83\x02\x01\
+            arr\0\x23\x04\x03\
+            s\0\x81\x82\x06\x05\
+            z\0\x47\
+            inner\0\x81\x01\
+            b\0\x0d"
+        );
+
+        let be = schema.encode(BE).unwrap();
+        assert_ne!(le, be);
+
+        let decoded = EventSchema::decode(&le, LE).unwrap();
+        assert_eq!(decoded.tag, 0);
+        assert_eq!(
+            decoded,
+            EventSchema {
+                tag: 0,
+                ..schema.clone()
+            }
+        );
+        assert_eq!(EventSchema::decode(&be, BE).unwrap(), decoded);
+
+        // encode_to appends.
+        let mut buf = vec![0xff];
+        schema.encode_to(&mut buf, LE).unwrap();
+        assert_eq!(&buf[1..], &le[..]);
+
+        // Struct count larger than remaining fields is legal.
+        assert_eq!(
+            EventSchema::decode(b"E\0s\0\x81\x05x\0\x02", LE)
+                .unwrap()
+                .fields[0]
+                .fields
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use EventHeaderEnumeratorError::*;
+
+        let decode = |meta: &[u8]| EventSchema::decode(meta, LE).map(|_| ());
+        assert_eq!(decode(b"E"), Err(InvalidData));
+        assert_eq!(decode(b"E\0x"), Err(InvalidData));
+        assert_eq!(decode(b"E\0x\0"), Err(InvalidData));
+        assert_eq!(decode(b"E\0x\0\x82"), Err(InvalidData));
+        assert_eq!(decode(b"E\0x\0\x82\x80\x01"), Err(InvalidData));
+        assert_eq!(decode(b"E\0x\0\x22\x01"), Err(InvalidData));
+        assert_eq!(decode(b"E\0x\0\x22\0\0"), Err(InvalidData));
+        assert_eq!(decode(b"E\0x\0\x62"), Err(NotSupported));
+        assert_eq!(decode(b"E\0s\0\x01"), Err(InvalidData));
+        assert_eq!(decode(b"E\0"), Ok(()));
+
+        let mut nested = Vec::new();
+        nested.extend_from_slice(b"E\0");
+        for _ in 0..8 {
+            nested.extend_from_slice(b"s\0\x81\x01");
+        }
+        nested.extend_from_slice(b"x\0\x02");
+        assert_eq!(decode(&nested), Ok(()));
+        nested.truncate(nested.len() - 3);
+        nested.extend_from_slice(b"s\0\x81\x01x\0\x02");
+        assert_eq!(decode(&nested), Err(StackOverflow));
+
+        let encode = |field: FieldSchema| {
+            let mut schema = EventSchema::new("E");
+            schema.fields.push(field);
+            return schema.encode(LE).map(|_| ());
+        };
+        let u8_field = || FieldSchema::new("x", FieldEncoding::Value8, FieldFormat::Default);
+        assert_eq!(encode(u8_field()), Ok(()));
+        assert_eq!(EventSchema::new("E\0").encode(LE), Err(InvalidParameter));
+        assert_eq!(
+            encode(FieldSchema::new(
+                "x\0",
+                FieldEncoding::Value8,
+                FieldFormat::Default
+            )),
+            Err(InvalidParameter)
+        );
+        assert_eq!(
+            encode(FieldSchema::new(
+                "x",
+                FieldEncoding::Value8.with_flags(FieldEncoding::CArrayFlag),
+                FieldFormat::Default
+            )),
+            Err(InvalidParameter)
+        );
+        assert_eq!(
+            encode(u8_field().with_array(FieldArrayKind::Constant(0))),
+            Err(InvalidParameter)
+        );
+        assert_eq!(
+            encode(FieldSchema::new_struct("s", Vec::new())),
+            Err(InvalidParameter)
+        );
+        assert_eq!(
+            encode(FieldSchema::new_struct("s", vec![u8_field(); 128])),
+            Err(InvalidParameter)
+        );
+        assert_eq!(
+            encode(FieldSchema::new_struct("s", vec![u8_field(); 127])),
+            Ok(())
+        );
+        assert_eq!(
+            encode(FieldSchema {
+                fields: vec![u8_field()],
+                ..u8_field()
+            }),
+            Err(InvalidParameter)
+        );
+
+        let mut field = u8_field();
+        for _ in 0..8 {
+            field = FieldSchema::new_struct("s", vec![field]);
+        }
+        assert_eq!(encode(field.clone()), Ok(()));
+        assert_eq!(
+            encode(FieldSchema::new_struct("s", vec![field])),
+            Err(StackOverflow)
+        );
+
+        // Failed encode_to leaves the buffer unchanged.
+        let mut buf = vec![1, 2, 3];
+        let mut schema = EventSchema::new("E");
+        schema.fields.push(u8_field());
+        schema.fields.push(FieldSchema::new_struct("s", Vec::new()));
+        assert_eq!(schema.encode_to(&mut buf, LE), Err(InvalidParameter));
+        assert_eq!(buf, [1, 2, 3]);
+    }
+
+    /// Decodes the schema of each event in EventHeaderInterceptorLE64.dat and checks
+    /// that it survives an encode + decode round trip.
+    #[test]
+    fn round_trip_dat() {
+        let mut dat_path = env::current_dir().unwrap();
+        dat_path.push("test_data");
+        dat_path.push("EventHeaderInterceptorLE64.dat");
+
+        let mut ctx = EventHeaderEnumeratorContext::new();
+        let dat_vec = fs::read(dat_path).unwrap();
+        let dat_bytes = &dat_vec[..];
+        let mut dat_pos = 0;
+        let mut count = 0;
+        while dat_pos < dat_bytes.len() {
+            let size =
+                u32::from_le_bytes(dat_bytes[dat_pos..dat_pos + 4].try_into().unwrap()) as usize;
+            let name_pos = dat_pos + 4;
+            dat_pos += size;
+
+            let name_len = dat_bytes[name_pos..dat_pos]
+                .iter()
+                .position(|&b| b == 0)
+                .unwrap();
+            let tracepoint_name =
+                std::str::from_utf8(&dat_bytes[name_pos..name_pos + name_len]).unwrap();
+            let event_data = &dat_bytes[name_pos + name_len + 1..dat_pos];
+            if let Ok(e) = ctx.enumerate_with_name_and_data(
+                tracepoint_name,
+                event_data,
+                EventHeaderEnumeratorContext::MOVE_NEXT_LIMIT_DEFAULT,
+            ) {
+                let info = e.event_info();
+                let schema = match EventSchema::from_event_info(&info) {
+                    Ok(schema) => schema,
+                    Err(_) => continue, // Intentionally-malformed test events.
+                };
+
+                assert_eq!(schema.tag, info.header().tag);
+                assert_eq!(schema.name.as_bytes(), info.name_bytes());
+                // Metadata is not always byte-identical after a round trip (e.g. a
+                // struct's field count may exceed the number of fields present), so
+                // compare the decoded schemas.
+                let metadata = schema.encode(LE).unwrap();
+                let mut decoded = EventSchema::decode(&metadata, LE).unwrap();
+                decoded.tag = schema.tag;
+                assert_eq!(decoded, schema);
+                count += 1;
+            }
+        }
+
+        assert!(count > 100, "count = {}", count);
+    }
+}
@@ -14,8 +14,14 @@ pub use enumerator::EventHeaderEnumeratorContext;
 pub use enumerator::EventHeaderEnumeratorError;
 pub use enumerator::EventHeaderEnumeratorState;
 pub use enumerator::EventHeaderEventInfo;
+pub use enumerator::EventHeaderExtensionInfo;
+pub use enumerator::EventHeaderExtensionIter;
 pub use enumerator::EventHeaderItemInfo;
 
+pub use event_schema::EventSchema;
+pub use event_schema::FieldArrayKind;
+pub use event_schema::FieldSchema;
+
 pub use perf_abi::PerfEventAttr;
 pub use perf_abi::PerfEventAttrOptions;
 pub use perf_abi::PerfEventAttrReadFormat;
@@ -54,6 +60,7 @@ pub mod display;
 mod byte_reader;
 mod charconv;
 mod enumerator;
+mod event_schema;
 mod filters;
 mod perf_abi;
 mod perf_event_data;
@@ -284,6 +284,10 @@ impl PerfMetaOptions {
     /// Not used by `json_meta_display`.
     pub const Common: Self = Self(0x100000);
 
+    /// eventheader extension blocks other than metadata and activity ID (array of
+    /// objects with hexadecimal `"kind"` and hex-bytes `"data"`, omitted if none).
+    pub const Extensions: Self = Self(0x200000);
+
     /// Include N..RelatedActivity.
     pub const Default: Self = Self(0xffff);
 
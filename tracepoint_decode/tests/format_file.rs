@@ -1,11 +1,11 @@
+#![allow(clippy::needless_return)]
+
 use std::env;
 use std::fs;
 use std::io;
 use std::io::Read;
 use std::io::Write;
 
-use zip;
-
 use tracepoint_decode as td;
 
 #[test]
@@ -29,7 +29,7 @@ fn format_rewrite() -> io::Result<()> {
         let zip_filename = zip_file
             .name()
             .split(&['/', '\\'])
-            .last()
+            .next_back()
             .unwrap_or("")
             .to_string();
         let mut zip_filename_parts = zip_filename.split(' ');
@@ -39,7 +39,7 @@ fn format_rewrite() -> io::Result<()> {
         zip_file.read_to_string(&mut input_str)?;
 
         // Parse format file.
-        let format = td::PerfEventFormat::parse(false, &system_name, &input_str).unwrap();
+        let format = td::PerfEventFormat::parse(false, system_name, &input_str).unwrap();
         assert_eq!(format.system_name(), system_name);
         assert_eq!(format.name(), event_name);
 
@@ -8,7 +8,6 @@ use core::fmt;
 use std::env;
 use std::fs;
 use std::io;
-use std::io::IsTerminal;
 use std::process;
 
 use tracepoint_decode as td;
@@ -101,17 +100,13 @@ fn main() -> process::ExitCode {
         return process::ExitCode::FAILURE;
     }
 
-    let is_tty;
+    // Write a BOM only when writing to a file (stdout may be a terminal).
+    let write_bom = !output_name.is_empty();
     let mut output: Box<dyn io::Write> = if output_name.is_empty() {
-        let stdout = io::stdout();
-        is_tty = stdout.is_terminal();
-        Box::new(stdout)
+        Box::new(io::stdout())
     } else {
         match fs::File::create(&output_name) {
-            Ok(output) => {
-                is_tty = output.is_terminal();
-                Box::new(output)
-            }
+            Ok(output) => Box::new(output),
             Err(e) => {
                 eprintln!(
                     "error: failed to open output file \"{}\": {}",
@@ -122,7 +117,7 @@ fn main() -> process::ExitCode {
         }
     };
 
-    match write_json(&mut output, &input_names, is_tty) {
+    match write_json(&mut output, &input_names, write_bom) {
         Err(e) => {
             eprintln!("error: {}", e);
             process::ExitCode::FAILURE
@@ -134,7 +129,7 @@ fn main() -> process::ExitCode {
 fn write_json(
     json_out: &mut Box<dyn io::Write>,
     input_names: &[String],
-    is_tty: bool,
+    write_bom: bool,
 ) -> io::Result<process::ExitCode> {
     let mut exit_code = process::ExitCode::SUCCESS;
     let mut comma;
@@ -143,7 +138,7 @@ fn write_json(
     // JSON: {
     // Start of JSON.
     // Include a BOM if the output is not a TTY.
-    writeln!(json_out, "{}", if is_tty { "{" } else { "\u{FEFF}{" })?;
+    writeln!(json_out, "{}", if write_bom { "\u{FEFF}{" } else { "{" })?;
     comma = false;
 
     // A reader can be reused for multiple files.
@@ -1034,20 +1034,20 @@ impl DataFileReader {
         // Successfully read the basic event data.
         // Check for any special cases based on the type.
         match event_header.ty {
-            PerfEventHeaderType::HeaderAttr => {
-                if event_data_len >= PerfEventAttrSize::Ver0.0 as u16 {
-                    let attr_size = byte_reader
-                        .read_u32(&self.buffers[cb][event_data_pos + PerfEventAttr::SIZE_OFFSET..]);
-                    if attr_size >= PerfEventAttrSize::Ver0.0 && attr_size < event_data_len as u32 {
-                        let attr_size_capped = cmp::min(attr_size, PERF_EVENT_ATTR_SIZE as u32);
-                        self.attrs.add_attr(
-                            byte_reader,
-                            &self.buffers[cb]
-                                [event_data_pos..event_data_pos + attr_size_capped as usize],
-                            b"",
-                            &self.buffers[cb][event_data_pos + attr_size as usize..],
-                        );
-                    }
+            PerfEventHeaderType::HeaderAttr
+                if event_data_len >= PerfEventAttrSize::Ver0.0 as u16 =>
+            {
+                let attr_size = byte_reader
+                    .read_u32(&self.buffers[cb][event_data_pos + PerfEventAttr::SIZE_OFFSET..]);
+                if attr_size >= PerfEventAttrSize::Ver0.0 && attr_size < event_data_len as u32 {
+                    let attr_size_capped = cmp::min(attr_size, PERF_EVENT_ATTR_SIZE as u32);
+                    self.attrs.add_attr(
+                        byte_reader,
+                        &self.buffers[cb]
+                            [event_data_pos..event_data_pos + attr_size_capped as usize],
+                        b"",
+                        &self.buffers[cb][event_data_pos + attr_size as usize..],
+                    );
                 }
             }
             PerfEventHeaderType::HeaderTracingData => {
@@ -1110,26 +1110,24 @@ impl DataFileReader {
                     }
                 }
             }
-            PerfEventHeaderType::HeaderFeature => {
-                if event_data_len >= U64_SIZE as u16 {
-                    let index64 = byte_reader.read_u64(&self.buffers[cb][event_data_pos..]);
-                    if index64 < self.headers.len() as u64 {
-                        Self::set_header(
-                            &mut self.headers[index64 as usize],
-                            &self.buffers[cb][event_data_pos + mem::size_of::<u64>()..],
-                        );
-                        match PerfHeaderIndex(index64 as u8) {
-                            PerfHeaderIndex::ClockId => self.parse_header_clockid(),
-                            PerfHeaderIndex::ClockData => self.parse_header_clock_data(),
-                            _ => (),
-                        }
+            PerfEventHeaderType::HeaderFeature if event_data_len >= U64_SIZE as u16 => {
+                let index64 = byte_reader.read_u64(&self.buffers[cb][event_data_pos..]);
+                if index64 < self.headers.len() as u64 {
+                    Self::set_header(
+                        &mut self.headers[index64 as usize],
+                        &self.buffers[cb][event_data_pos + mem::size_of::<u64>()..],
+                    );
+                    match PerfHeaderIndex(index64 as u8) {
+                        PerfHeaderIndex::ClockId => self.parse_header_clockid(),
+                        PerfHeaderIndex::ClockData => self.parse_header_clock_data(),
+                        _ => (),
                     }
                 }
             }
-            PerfEventHeaderType::FinishedInit => {
-                if !self.headers[PerfHeaderIndex::EventDesc.0 as usize].is_empty() {
-                    self.parse_header_event_desc();
-                }
+            PerfEventHeaderType::FinishedInit
+                if !self.headers[PerfHeaderIndex::EventDesc.0 as usize].is_empty() =>
+            {
+                self.parse_header_event_desc();
             }
             _ => (),
         }